@@ -0,0 +1,53 @@
+//! Compares how long `Graph::new`, `Graph::fast` and `ParallelReader` take
+//! to parse a large document. Run with `cargo bench`.
+extern crate chelone;
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use chelone::{Graph, ParallelReader};
+
+const RUNS: u32 = 5;
+
+fn document(subjects: usize) -> String {
+    let mut source = String::from(concat!(
+        "@prefix : <http://example.org/> .\n",
+        "@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n",
+    ));
+
+    for i in 0..subjects {
+        writeln!(source, ":s{} a :Thing ;", i).unwrap();
+        writeln!(source, "    :name \"Subject number {}\"@en ;", i).unwrap();
+        writeln!(source, "    :value {}, {}.5, \"{}\"^^xsd:integer ;", i, i, i).unwrap();
+        writeln!(source, "    <http://example.org/list> ( :a :b [ :c :d ] ) .").unwrap();
+    }
+
+    source
+}
+
+fn time<F: FnMut()>(name: &str, mut parse: F) {
+    let mut total = Duration::default();
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        parse();
+        total += start.elapsed();
+    }
+
+    println!("{:<20} {:>10.2?}", name, total / RUNS);
+}
+
+fn main() {
+    let source = document(20_000);
+    println!("Parsing {} bytes, {} runs each", source.len(), RUNS);
+
+    time("pest parse", || { Graph::new(&source).unwrap().parse().unwrap(); });
+    time("fast parse", || { Graph::fast(&source).unwrap().parse().unwrap(); });
+    time("pest triple_refs", || {
+        Graph::new(&source).unwrap().triple_refs().for_each(|triple| { triple.unwrap(); });
+    });
+    time("fast triple_refs", || {
+        Graph::fast(&source).unwrap().triple_refs().for_each(|triple| { triple.unwrap(); });
+    });
+    time("parallel parse", || { ParallelReader::turtle(source.as_bytes()).parse().unwrap(); });
+}
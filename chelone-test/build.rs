@@ -9,13 +9,26 @@ use std::path::Path;
 use std::error::Error;
 
 use chelone::{Graph, Triples, TripleSearcher};
-use chelone::literal::Literal;
 use chelone::object::Object;
-use chelone::subject::Subject;
 use chelone::iri::Iri;
-use url::Url;
 
-const BASE_URL: &str = "http://www.w3.org/2013/TurtleTests/";
+const TURTLE_BASE_URL: &str = "http://www.w3.org/2013/TurtleTests/";
+const NTRIPLES_BASE_URL: &str = "http://www.w3.org/2013/N-TriplesTests/";
+const NQUADS_BASE_URL: &str = "http://www.w3.org/2013/N-QuadsTests/";
+const TRIG_BASE_URL: &str = "http://www.w3.org/2013/TriGTests/";
+const RDFXML_BASE_URL: &str = "http://www.w3.org/2013/RDFXMLTests/";
+const TURTLE_STAR_BASE_URL: &str = "https://w3c.github.io/rdf-star/tests/turtle/";
+const RDF12_BASE_URL: &str = "https://w3c.github.io/rdf-tests/rdf/rdf12/";
+const N3_BASE_URL: &str = "https://w3c.github.io/N3/tests/N3Tests/";
+const JSONLD_BASE_URL: &str = "https://w3c.github.io/json-ld-api/tests/";
+
+/// The parsers every Turtle test is also read with, each checked against
+/// `Graph::new` by the `check_*` function of the same name in a module of
+/// its own, so a failure names the parser that disagreed.
+const TURTLE_CHECKS: &[&str] = &[
+    "syntax_tree", "parse_with", "turtle_reader", "lenient_reader", "fast", "parallel_reader",
+    "located_triples", "triple_refs", "trig",
+];
 
 macro_rules! urls {
     ($($name:ident: $url:expr);+) => {
@@ -37,14 +50,36 @@ macro_rules! wrapped {
 urls! {
     rdf:  "http://www.w3.org/1999/02/22-rdf-syntax-ns";
     rdft: "http://www.w3.org/ns/rdftest";
-    mf:   "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest"
+    mf:   "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest";
+    jld:  "https://w3c.github.io/json-ld-api/tests/vocab"
 }
 
 fn main() {
     let out_dir = env::var_os("OUT_DIR").expect("can't get OUT_DIR");
+    let mut output = String::new();
+
+    output += &suite("turtle", "tests/data/manifest.ttl", TURTLE_BASE_URL);
+    output += &suite("ntriples", "tests/ntriples/manifest.ttl", NTRIPLES_BASE_URL);
+    output += &suite("nquads", "tests/nquads/manifest.ttl", NQUADS_BASE_URL);
+    output += &suite("trig", "tests/trig/manifest.ttl", TRIG_BASE_URL);
+    output += &suite("rdfxml", "tests/rdfxml/manifest.ttl", RDFXML_BASE_URL);
+    output += &suite("turtle_star", "tests/turtle-star/manifest.ttl", TURTLE_STAR_BASE_URL);
+    output += &suite("rdf12", "tests/rdf12/manifest.ttl", RDF12_BASE_URL);
+    output += &suite("n3", "tests/n3/manifest.ttl", N3_BASE_URL);
+    output += &suite("jsonld", "tests/jsonld/manifest.ttl", JSONLD_BASE_URL);
+
+    File::create(Path::new(&out_dir).join("tests.rs"))
+        .expect("Couldn't create tests.rs.")
+        .write_all(output.as_bytes())
+        .expect("Couldn't write to tests.rs");
+}
+
+/// Generates a module named `module` with a test for every entry in the
+/// manifest at `manifest`.
+fn suite(module: &str, manifest: &str, base_url: &str) -> String {
     let url = {
         use std::fs;
-        let mut path = fs::canonicalize("tests/data/manifest.ttl")
+        let mut path = fs::canonicalize(manifest)
             .expect("Couldn't find manifest file.")
             .into_os_string()
             .into_string()
@@ -61,13 +96,13 @@ fn main() {
     let mf_action = Iri::parse(mf!("action")).unwrap();
     let mf_result = Iri::parse(mf!("result")).unwrap();
     let rdf_type = Iri::parse(rdf!("type")).unwrap();
-    let triples = read_to_graph("tests/data/manifest.ttl", &url)
+    let triples = read_to_graph(manifest, &url)
         .expect("Couldn't read manifest into graph.");
     let mut entries = Vec::new();
-    let mut output = String::new();
+    let mut cross_checked = Vec::new();
+    let mut output = format!("mod {} {{\n    use super::*;\n", module);
 
     let mf_entries = Iri::parse(mf!("entries")).unwrap();
-    eprintln!("{:?}", mf_entries);
 
     let mut last_node =  TripleSearcher::new()
             .predicate(&mf_entries)
@@ -122,26 +157,302 @@ fn main() {
             .object
             .to_string();
 
+        let path = &file[8..file.len() - 1];
+        let base = format!("{}{}", base_url, &path[path.rfind('/').unwrap() + 1..]);
+
+        match &*rdf_type.to_string() {
+            wrapped!(rdft, "TestTurtlePositiveSyntax") |
+            wrapped!(rdft, "TestTurtleNegativeSyntax") |
+            wrapped!(rdft, "TestTurtleNegativeEval") |
+            wrapped!(rdft, "TestTurtleEval") => {
+                cross_checked.push((name.clone(), path.to_owned(), base.clone()))
+            }
+            _ => {}
+        }
+
         output += &match &*rdf_type.to_string() {
             wrapped!(rdft, "TestTurtlePositiveSyntax") => format!(r#"
                 #[test]
+                #[allow(non_snake_case)]
                 fn {name}() {{
-                    read_to_triples("{file}", "{base}");
+                    read_to_triples("{file}", "{base}").unwrap();
                 }}
             "#, name = name,
-                file = &file[8..file.len() - 1],
-                base = BASE_URL),
-            wrapped!(rdft, "TestTurtleNegativeEval") |
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestNTriplesPositiveSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    read_to_ntriples("{file}").unwrap();
+                }}
+            "#, name = name,
+                file = path),
+            wrapped!(rdft, "TestNQuadsPositiveSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    read_to_nquads("{file}").unwrap();
+                }}
+            "#, name = name,
+                file = path),
+            wrapped!(rdft, "TestTrigPositiveSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    read_to_trig("{file}", "{base}").unwrap();
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestN3PositiveSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    read_to_n3("{file}", "{base}").unwrap();
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestN3NegativeSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_syntax_error(read_to_n3("{file}", "{base}"));
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestN3Eval") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let document = read_to_n3("{result}", "{base}").unwrap();
+                        let mut result = document.formula.triples();
+                        let mut expected = read_to_ntriples("{expected}").unwrap();
+
+                        assert_eq!(result.len(), document.formula.statements.len());
+                        if !result.is_isomorphic(&mut expected) {{
+                            compare(result, expected);
+                        }}
+                    }}"##,
+                    name = name,
+                    result = path,
+                    expected = &expected[8..expected.len() - 1],
+                    base = base)
+            },
+            wrapped!(rdft, "TestTurtleNegativeEval") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_eval_error(read_to_triples("{file}", "{base}"));
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
             wrapped!(rdft, "TestTurtleNegativeSyntax") => format!(r#"
                 #[test]
-                #[should_panic]
                 #[allow(non_snake_case)]
                 fn {name}() {{
-                    read_to_triples("{file}", "{base}");
+                    assert_syntax_error(read_to_triples("{file}", "{base}"));
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestNTriplesNegativeSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_syntax_error(read_to_ntriples("{file}"));
+                }}
+            "#, name = name,
+                file = path),
+            wrapped!(rdft, "TestNQuadsNegativeSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_syntax_error(read_to_nquads("{file}"));
                 }}
             "#, name = name,
-                file = &file[8..file.len() - 1],
-                base = BASE_URL),
+                file = path),
+            wrapped!(rdft, "TestTrigNegativeSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_syntax_error(read_to_trig("{file}", "{base}"));
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestTrigEval") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let result = read_to_trig("{result}", "{base}").unwrap();
+                        let expected = read_to_nquads("{expected}").unwrap();
+
+                        assert_same_dataset(result, expected);
+                    }}"##,
+                    name = name,
+                    result = path,
+                    expected = &expected[8..expected.len() - 1],
+                    base = base)
+            },
+            wrapped!(rdft, "TestXMLNegativeSyntax") => format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    assert_syntax_error(read_to_rdfxml("{file}", "{base}"));
+                }}
+            "#, name = name,
+                file = path,
+                base = base),
+            wrapped!(rdft, "TestXMLEval") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let mut result = read_to_rdfxml("{result}", "{base}").unwrap();
+                        let mut expected = read_to_ntriples("{expected}").unwrap();
+
+                        if !result.is_isomorphic(&mut expected) {{
+                            compare(result, expected);
+                        }}
+                    }}"##,
+                    name = name,
+                    result = path,
+                    expected = &expected[8..expected.len() - 1],
+                    base = base)
+            },
+            wrapped!(jld, "ToRDFTest") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let result = read_to_jsonld("{result}", "{base}").unwrap();
+                        let expected = read_to_nquads("{expected}").unwrap();
+
+                        assert_same_dataset(result, expected);
+                    }}"##,
+                    name = name,
+                    result = path,
+                    expected = &expected[8..expected.len() - 1],
+                    base = base)
+            },
+            wrapped!(jld, "ExpandTest") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let result = expand_jsonld("{result}", "{base}").unwrap();
+                        let expected = fs::read_to_string("{expected}").unwrap();
+
+                        assert_eq!(result, serde_json::from_str::<serde_json::Value>(&expected).unwrap());
+                    }}"##,
+                    name = name,
+                    result = path,
+                    expected = &expected[8..expected.len() - 1],
+                    base = base)
+            },
+            wrapped!(jld, "FromRDFTest") |
+            wrapped!(jld, "CompactTest") => {
+                let expected = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object
+                    .to_string();
+                let option = |name| {
+                    let option = TripleSearcher::new()
+                        .subject(&entry)
+                        .predicate(&Iri::parse(&format!("{}#{}", jld!(), name)).unwrap())
+                        .execute(&triples)
+                        .unwrap_or_else(|| panic!("Couldn't find jld:{}.", name))
+                        .object
+                        .to_string();
+
+                    option[8..option.len() - 1].to_owned()
+                };
+                let result = match &*rdf_type.to_string() {
+                    wrapped!(jld, "FromRDFTest") => format!("from_rdf_jsonld(\"{}\")", path),
+                    _ => format!("compact_jsonld(\"{}\", \"{}\", \"{}\")", path, base, option("context")),
+                };
+
+                format!(r##"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        let result = {result};
+                        let expected = fs::read_to_string("{expected}").unwrap();
+
+                        assert_eq!(result, serde_json::from_str::<serde_json::Value>(&expected).unwrap());
+                    }}"##,
+                    name = name,
+                    result = result,
+                    expected = &expected[8..expected.len() - 1])
+            },
+            wrapped!(jld, "NegativeEvaluationTest") => {
+                let object = TripleSearcher::new()
+                    .subject(&entry)
+                    .predicate(&mf_result)
+                    .execute(&triples)
+                    .expect("Couldn't find mf:result.")
+                    .object;
+
+                format!(r#"
+                    #[test]
+                    #[allow(non_snake_case)]
+                    fn {name}() {{
+                        assert_json_ld_error(read_to_jsonld("{file}", "{base}"), "{code}");
+                    }}
+                "#, name = name,
+                    file = path,
+                    base = base,
+                    code = unwrap_to!(object => Object::Literal).value)
+            },
             wrapped!(rdft, "TestTurtleEval") => {
                 let expected = TripleSearcher::new()
                     .subject(&entry)
@@ -155,35 +466,54 @@ fn main() {
                     #[test]
                     #[allow(non_snake_case)]
                     fn {name}() {{
-                        let mut result = read_to_triples("{result}", "{base}");
-                        let mut expected = read_to_triples("{expected}", "{base}");
+                        let mut result = read_to_triples("{result}", "{base}").unwrap();
+                        let mut expected = read_to_ntriples("{expected}").unwrap();
 
                         if !result.is_isomorphic(&mut expected) {{
                             compare(result, expected);
                         }}
                     }}"##,
                     name = name,
-                    result = &file[8..file.len() - 1],
+                    result = path,
                     expected = &expected[8..expected.len() - 1],
-                    base = BASE_URL)
+                    base = base)
             },
 
             _ => String::new(),
         };
     }
 
-    File::create(Path::new(&out_dir).join("tests.rs"))
-        .expect("Couldn't create tests.rs.")
-        .write_all(output.as_bytes())
-        .expect("Couldn't write to tests.rs");
+    if !cross_checked.is_empty() {
+        for check in TURTLE_CHECKS {
+            output += &format!("\n    mod {} {{\n        use super::*;\n", check);
+
+            for (name, path, base) in &cross_checked {
+                output += &format!(r#"
+                #[test]
+                #[allow(non_snake_case)]
+                fn {name}() {{
+                    check_{check}("{file}", "{base}");
+                }}
+            "#, name = name,
+                    check = check,
+                    file = path,
+                    base = base);
+            }
+
+            output += "    }\n";
+        }
+    }
+
+    output += "}\n";
+    output
 }
 
-fn read_to_graph(path: &str, base: &str) -> Result<Triples, Box<Error>> {
+fn read_to_graph(path: &str, base: &str) -> Result<Triples, Box<dyn Error>> {
     use std::fs;
 
     let input = fs::read_to_string(path)?;
-    let mut graph = Graph::new(&input).unwrap_or_else(|e| panic!("{}", e));
+    let mut graph = Graph::new(&input)?;
 
     graph.set_base(Iri::parse(base)?);
-    Ok(graph.parse())
+    Ok(graph.parse()?)
 }
@@ -0,0 +1,119 @@
+extern crate chelone;
+
+use std::borrow::Cow;
+
+use chelone::borrowed::{ObjectRef, SubjectRef};
+use chelone::labeller::CounterLabeller;
+use chelone::{Error, Graph, Iri, ParserOptions, TripleRef};
+
+fn parse(source: &str) -> Vec<TripleRef<'_>> {
+    Graph::new(source).unwrap().triple_refs().collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn iris_are_borrowed() {
+    let triples = parse(r"<http://a/s> <http://a/p> <http://a/\u0041> .");
+
+    match triples[0].subject {
+        SubjectRef::Iri(ref iri) => {
+            assert!(iri.is_borrowed());
+            assert_eq!(iri.as_str(), "http://a/s");
+        }
+        ref subject => panic!("Expected an IRI, got: {:?}", subject),
+    }
+
+    match triples[0].object {
+        ObjectRef::Iri(ref iri) => {
+            assert!(!iri.is_borrowed());
+            assert_eq!(iri.as_str(), "http://a/A");
+        }
+        ref object => panic!("Expected an IRI, got: {:?}", object),
+    }
+}
+
+#[test]
+fn prefixed_and_relative_iris_are_owned() {
+    let triples = parse("@base <http://a/> .\n@prefix : <http://b/> .\n:s <p> <http://c/o> .");
+
+    assert_eq!(triples[0].to_string(), "<http://b/s> <http://a/p> <http://c/o> .");
+    assert!(!triples[0].predicate.is_borrowed());
+
+    match triples[0].object {
+        ObjectRef::Iri(ref iri) => assert!(iri.is_borrowed()),
+        ref object => panic!("Expected an IRI, got: {:?}", object),
+    }
+}
+
+#[test]
+fn literals_are_borrowed() {
+    let source = r#"<http://a/s> <http://a/p> "a", 'b'@en-GB, """c
+d""", "e\tf", 12, true ."#;
+    let triples = parse(source);
+    let literals = triples.iter()
+        .map(|triple| match triple.object {
+            ObjectRef::Literal(ref literal) => literal.clone(),
+            ref object => panic!("Expected a literal, got: {:?}", object),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(literals.iter().map(|l| &*l.value).collect::<Vec<_>>(),
+               ["a", "b", "c\nd", "e\tf", "12", "true"]);
+    assert_eq!(literals.iter().map(|l| matches!(l.value, Cow::Borrowed(_))).collect::<Vec<_>>(),
+               [true, true, true, false, true, true]);
+    assert!(matches!(literals[1].language_tag, Some(Cow::Borrowed("en-GB"))));
+    assert_eq!(literals[4].iri.as_str(), "http://www.w3.org/2001/XMLSchema#integer");
+}
+
+#[test]
+fn to_owned_matches_parse() {
+    let source = r#"
+        @prefix : <http://example.org/> .
+        :a :b ( 1 "x"@ar--rtl [ :c << :d :e _:f >> ] ) ;
+            :g "h"^^:i {| :j :k |} .
+    "#;
+
+    let mut graph = Graph::new(source).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+    let owned = graph.parse().unwrap();
+
+    let mut graph = Graph::new(source).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+    let borrowed = graph.triple_refs()
+        .map(|triple| triple.unwrap().to_owned().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(owned.0, borrowed);
+}
+
+#[test]
+fn iris_are_only_checked_by_to_owned() {
+    let source = "<http://a/s> <http://a/p> <http://[a/o> .";
+
+    match Graph::new(source).unwrap().parse() {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "http://[a/o"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+
+    let triples = parse(source);
+    assert_eq!(triples[0].object.to_string(), "<http://[a/o>");
+    assert!(triples[0].to_owned().is_err());
+}
+
+#[test]
+fn options_still_apply() {
+    let options = ParserOptions::new().keep_relative_iris(true);
+    let triples = Graph::with_options("<s> <http://a/p> <o> .", options).unwrap()
+        .triple_refs()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert!(matches!(triples[0].subject, SubjectRef::RelativeIri(_)));
+
+    let resolved = triples[0].to_owned().unwrap().resolve(&Iri::parse("http://a/").unwrap()).unwrap();
+    assert_eq!(resolved.to_string(), "<http://a/s> <http://a/p> <http://a/o> .");
+
+    match Graph::new("<s> <http://a/p> <o> .").unwrap().triple_refs().next() {
+        Some(Err(Error::InvalidIri { iri, .. })) => assert_eq!(iri, "s"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+}
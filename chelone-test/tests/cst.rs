@@ -0,0 +1,149 @@
+extern crate chelone;
+
+use chelone::cst::{Element, NodeKind, TokenKind};
+use chelone::{Error, Graph, Position, SyntaxTree};
+
+const SOURCE: &str = concat!(
+    "\u{FEFF}# A document\r\n",
+    "@prefix ex: <http://example.org/> .\n",
+    "PREFIX other: <http://other.org/>\n\n",
+    "ex:a ex:b ( 1 2.5 -3e4 ) , [ ] ;   # Trailing\n",
+    "    a other:c ;\n",
+    "    ex:d [ex:e \"f\"@en, '''g'''^^ex:h] {| ex:i true |} .\n",
+    "<< ex:j ex:k _:l >> ex:m other:n .\n",
+    "# The end",
+);
+
+#[test]
+fn prints_the_source_back() {
+    let tree = SyntaxTree::new(SOURCE).unwrap();
+
+    assert_eq!(tree.to_string(), SOURCE);
+    assert_eq!(tree.root().kind, NodeKind::TurtleDoc);
+    assert!(tree.parse().unwrap().is_isomorphic(&mut Graph::new(SOURCE).unwrap().parse().unwrap()));
+}
+
+#[test]
+fn nodes_mirror_the_grammar() {
+    let tree = SyntaxTree::new(SOURCE).unwrap();
+    let kinds = tree.root().nodes().map(|node| node.kind).collect::<Vec<_>>();
+    let comments = tree.root().tokens()
+        .filter(|token| token.kind == TokenKind::Comment)
+        .map(|token| token.text.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(kinds, [NodeKind::Statement; 4]);
+    assert_eq!(comments, ["# A document", "# The end"]);
+
+    let statement = tree.root().nodes().nth(2).unwrap();
+    let triples = statement.nodes().next().unwrap();
+    let kinds = triples.nodes().map(|node| node.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [NodeKind::Subject, NodeKind::PredicateObjectList]);
+
+    let predicates = triples.nodes().nth(1).unwrap();
+    let children = predicates.children.iter()
+        .map(|child| match *child {
+            Element::Node(ref node) => format!("{:?}", node.kind),
+            Element::Token(ref token) => format!("{:?} {:?}", token.kind, token.text),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(children, [
+        "Verb",
+        "Whitespace \" \"",
+        "ObjectList",
+        "Whitespace \" \"",
+        "Punctuation \";\"",
+        "Whitespace \"   \"",
+        "Comment \"# Trailing\"",
+        "Whitespace \"\\n    \"",
+        "Verb",
+        "Whitespace \" \"",
+        "ObjectList",
+        "Whitespace \" \"",
+        "Punctuation \";\"",
+        "Whitespace \"\\n    \"",
+        "Verb",
+        "Whitespace \" \"",
+        "ObjectList",
+    ]);
+}
+
+#[test]
+fn add_triple() {
+    let mut tree = SyntaxTree::new(SOURCE).unwrap();
+    tree.add_triple("ex:a", "ex:d", "ex:o").unwrap();
+    tree.add_triple("ex:a", "ex:p", "( ex:q )").unwrap();
+    tree.add_triple("<< ex:j ex:k _:l >>", "a", "ex:r").unwrap();
+    tree.add_triple("ex:s", "ex:t", "\"u\"").unwrap();
+
+    let expected = SOURCE
+        .replace("{| ex:i true |} .", "{| ex:i true |}, ex:o ;\n    ex:p ( ex:q ) .")
+        .replace("other:n .", "other:n ; a ex:r .")
+        .replace("# The end", "# The end\nex:s ex:t \"u\" .\n");
+
+    assert_eq!(tree.to_string(), expected);
+    assert_eq!(tree.parse().unwrap().len(), Graph::new(SOURCE).unwrap().parse().unwrap().len() + 6);
+
+    let mut tree = SyntaxTree::new("").unwrap();
+    tree.add_triple("<http://a/s>", "<http://a/p>", "1").unwrap();
+    assert_eq!(tree.to_string(), "<http://a/s> <http://a/p> 1 .\n");
+}
+
+#[test]
+fn add_triple_checks_its_terms() {
+    let mut tree = SyntaxTree::new(SOURCE).unwrap();
+
+    match tree.add_triple("ex:a", "ex:b", "ex:c ; ex:d ex:e") {
+        Err(Error::Syntax { message, position }) => {
+            assert_eq!(message, "expected an object");
+            assert_eq!(position, Position::new(1, 5));
+        }
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+    assert!(tree.add_triple("\"a\"", "ex:b", "ex:c").is_err());
+    assert!(tree.add_triple("ex:a", "_:b", "ex:c").is_err());
+    assert_eq!(tree.to_string(), SOURCE);
+}
+
+#[test]
+fn rename_prefix() {
+    let mut tree = SyntaxTree::new(SOURCE).unwrap();
+    tree.rename_prefix("ex", "example").unwrap();
+
+    assert_eq!(tree.to_string(), SOURCE.replace("ex:", "example:"));
+    assert!(tree.parse().unwrap().is_isomorphic(&mut Graph::new(SOURCE).unwrap().parse().unwrap()));
+
+    match tree.rename_prefix("example", "other") {
+        Err(Error::DuplicatePrefix { prefix, position }) => {
+            assert_eq!(prefix, "other");
+            assert_eq!(position, Position::new(3, 1));
+        }
+        result => panic!("Expected a duplicate prefix, got: {:?}", result),
+    }
+    assert!(tree.rename_prefix("example", "not a prefix").is_err());
+
+    tree.rename_prefix("other", "").unwrap();
+    assert!(tree.to_string().contains("PREFIX : <http://other.org/>\n\n"));
+    assert!(tree.to_string().contains("    a :c ;"));
+}
+
+#[test]
+fn edits_through_nodes() {
+    let mut tree = SyntaxTree::new("<http://a/s> <http://a/p> \"o\" . # Comment\n").unwrap();
+    let statement = tree.root_mut().children[0].as_node_mut().unwrap();
+    statement.children.retain(|child| match *child {
+        Element::Token(ref token) => token.kind != TokenKind::Whitespace,
+        Element::Node(_) => true,
+    });
+
+    assert_eq!(tree.to_string(), "<http://a/s> <http://a/p> \"o\". # Comment\n");
+    assert_eq!(tree.parse().unwrap().len(), 1);
+}
+
+#[test]
+fn syntax_errors() {
+    match SyntaxTree::new("<http://a/s> <http://a/p> .") {
+        Err(Error::Syntax { position, .. }) => assert_eq!(position, Position::new(1, 27)),
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+}
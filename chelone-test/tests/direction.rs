@@ -0,0 +1,85 @@
+extern crate chelone;
+#[macro_use] extern crate serde_json;
+
+use chelone::literal::{Direction, Literal};
+use chelone::object::Object;
+use chelone::{
+    Dataset, Graph, JsonLdReader, JsonLdWriter, RdfXmlReader, RdfXmlWriter, TriGWriter,
+    Triples,
+};
+
+fn turtle(input: &str) -> Triples {
+    Graph::new(input).unwrap().parse().unwrap()
+}
+
+fn literal(triples: &Triples) -> &Literal {
+    match triples[0].object {
+        Object::Literal(ref literal) => literal,
+        ref object => panic!("Expected a literal, got: {:?}", object),
+    }
+}
+
+#[test]
+fn parses_direction() {
+    let triples = turtle(r#"<http://a> <http://b> "مرحبا"@ar-EG--rtl ."#);
+    let literal = literal(&triples);
+
+    assert_eq!(literal.language_tag.as_ref().unwrap(), "ar-EG");
+    assert_eq!(literal.direction, Some(Direction::Rtl));
+    assert_eq!(
+        literal.iri.to_string(),
+        "<http://www.w3.org/1999/02/22-rdf-syntax-ns#dirLangString>"
+    );
+    assert_eq!(literal.to_string(), r#""مرحبا"@ar-EG--rtl"#);
+}
+
+#[test]
+fn trig_writer_writes_direction() {
+    let dataset = turtle(r#"<http://a/s> <http://a/p> "Hello"@en--ltr ."#)
+        .into_iter()
+        .map(Into::into)
+        .collect::<Dataset>();
+
+    assert_eq!(
+        TriGWriter::new(&dataset).to_string(),
+        "<http://a/s> <http://a/p> \"Hello\"@en--ltr .\n"
+    );
+}
+
+#[test]
+fn rdfxml_round_trip() {
+    let mut triples = turtle(r#"
+        <http://example.org/s> <http://example.org/p> "Hello"@en--ltr, "Hello"@en, "שלום"@he--rtl .
+    "#);
+    let output = RdfXmlWriter::new(&triples).to_xml().unwrap();
+    let mut reread = RdfXmlReader::new(output.as_bytes()).parse().unwrap();
+
+    assert!(output.contains(r#"xmlns:its="http://www.w3.org/2005/11/its""#));
+    assert!(output.contains(r#"its:version="2.0""#));
+    assert!(output.contains(r#"xml:lang="he" its:dir="rtl""#));
+    assert!(triples.is_isomorphic(&mut reread));
+}
+
+#[test]
+fn jsonld_round_trip() {
+    let mut triples = turtle(r#"<http://example.org/s> <http://example.org/p> "Hello"@en--ltr ."#);
+    let value = JsonLdWriter::new(&triples).to_value().unwrap();
+    let output = value.to_string();
+    let mut reread = JsonLdReader::new(output.as_bytes()).parse().unwrap();
+
+    assert_eq!(value["http://example.org/p"], json!({
+        "@value": "Hello",
+        "@language": "en",
+        "@direction": "ltr"
+    }));
+    assert!(triples.is_isomorphic(&mut reread));
+}
+
+#[test]
+fn new_directional() {
+    let literal = Literal::new_directional(String::from("x"), String::from("fr"), Direction::Rtl);
+
+    assert_eq!(literal.to_string(), r#""x"@fr--rtl"#);
+    assert_eq!(Direction::parse("ltr"), Some(Direction::Ltr));
+    assert_eq!(Direction::parse("LTR"), None);
+}
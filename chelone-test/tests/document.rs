@@ -0,0 +1,65 @@
+extern crate chelone;
+
+use chelone::{Graph, Iri};
+
+fn iri(iri: &str) -> Iri {
+    Iri::parse(iri).unwrap()
+}
+
+#[test]
+fn declarations_in_order() {
+    let document = Graph::new(r#"
+        @prefix ex: <http://example.org/> .
+        @base <http://example.org/base/> .
+        PREFIX : <a/>
+        ex:a :b <c> .
+        @prefix ex: <http://example.com/> .
+        BASE <http://example.com/base/>
+        ex:a :b <c> .
+    "#).unwrap().parse_document().unwrap();
+
+    assert_eq!(document.prefixs, vec![
+        (String::from("ex"), iri("http://example.org/")),
+        (String::from(""), iri("http://example.org/base/a/")),
+        (String::from("ex"), iri("http://example.com/")),
+    ]);
+    assert_eq!(document.bases, vec![iri("http://example.org/base/"), iri("http://example.com/base/")]);
+    assert_eq!(document.base(), Some(&iri("http://example.com/base/")));
+    assert_eq!(document.prefix_map()["ex"], iri("http://example.com/"));
+    assert_eq!(document.triples.len(), 2);
+    assert_eq!(document.triples[1].subject.to_string(), "<http://example.com/a>");
+    assert_eq!(document.triples[1].object.to_string(), "<http://example.com/base/c>");
+}
+
+#[test]
+fn seeded_prefixs() {
+    let mut graph = Graph::new(r#"
+        ex:a ex:b ex:c .
+        @prefix ex: <http://example.com/> .
+        ex:a ex:b ex:c .
+    "#).unwrap();
+    graph.set_prefixs(vec![(String::from("ex"), iri("http://example.org/"))]);
+
+    let document = graph.parse_document().unwrap();
+
+    // Only declarations that were written are part of the document.
+    assert_eq!(document.prefixs, vec![(String::from("ex"), iri("http://example.com/"))]);
+    assert_eq!(document.triples[0].subject.to_string(), "<http://example.org/a>");
+    assert_eq!(document.triples[1].subject.to_string(), "<http://example.com/a>");
+}
+
+#[test]
+fn no_declarations() {
+    let document = Graph::new("<http://a/> <http://b/> <http://c/> .").unwrap()
+        .parse_document()
+        .unwrap();
+
+    assert!(document.prefixs.is_empty());
+    assert_eq!(document.base(), None);
+    assert_eq!(document.triples.len(), 1);
+}
+
+#[test]
+fn errors() {
+    assert!(Graph::new("ex:a ex:b ex:c .").unwrap().parse_document().is_err());
+}
@@ -0,0 +1,127 @@
+extern crate chelone;
+
+use std::borrow::Cow;
+
+use chelone::borrowed::{ObjectRef, SubjectRef};
+use chelone::labeller::CounterLabeller;
+use chelone::limits::Limit;
+use chelone::{Error, Graph, Limits, ParserOptions, Policy, Position};
+
+const PREFIX: &str = "@prefix : <http://example.org/> .\n";
+
+/// Both parsers label blank nodes the same way, so they can be compared
+/// label for label.
+fn labelled(mut graph: Graph) -> Graph {
+    graph.set_labeller(CounterLabeller::new("b"));
+    graph
+}
+
+fn same_as_pest(source: &str) {
+    let expected = labelled(Graph::new(source).unwrap()).parse().unwrap();
+    let actual = labelled(Graph::fast(source).unwrap()).parse().unwrap();
+    assert_eq!(expected.0, actual.0);
+
+    let expected = labelled(Graph::new(source).unwrap()).located_triples().collect::<Result<Vec<_>, _>>().unwrap();
+    let actual = labelled(Graph::fast(source).unwrap()).located_triples().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(expected, actual);
+}
+
+fn syntax_error(source: &str) -> Position {
+    match Graph::fast(source).and_then(Graph::parse) {
+        Err(Error::Syntax { position, .. }) => position,
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+}
+
+#[test]
+fn nested_structures() {
+    same_as_pest(&format!(
+        "{}:a :b ( 1 ( ) [ :c ( 2.5 -3e4 ) ; :d [ ] ] ), _:e ;; :f [ :g :h ] .\n[ :i :j ] .\n( :k ) :l :m .",
+        PREFIX
+    ));
+}
+
+#[test]
+fn annotations_and_quoted_triples() {
+    same_as_pest(&format!(
+        "{}:a :b :c {{| :d [ :e << << :f :g :h >> :i _:j >> ] |}}, :k {{| :l :m |}} .\n<< :n a :o >> :p true .",
+        PREFIX
+    ));
+}
+
+#[test]
+fn terms() {
+    same_as_pest(concat!(
+        "PREFIX ex: <http://example.org/>\nbase <http://example.com/>\n",
+        "ex:a.b ex:c\\-d <e>, \"f\\tg\", 'h'@en-GB, \"\"\"i\n\"j\"\"\"^^ex:k, ex:%41, ex:, 'l'@ar--rtl .\n",
+        "# A comment\nex:prefix ex:base false, .5, +1. # Another\n",
+    ));
+}
+
+#[test]
+fn keywords_as_prefixes() {
+    same_as_pest("@prefix PREFIX: <http://a/> .\n@prefix base: <http://b/> .\nPREFIX:a base:b PREFIX:c .");
+}
+
+#[test]
+fn triples_are_borrowed() {
+    let source = r#"<http://a/s> <http://a/p> "a", "b\tc", 1 ."#;
+    let triples = Graph::fast(source).unwrap().triple_refs().collect::<Result<Vec<_>, _>>().unwrap();
+    let borrowed = triples.iter()
+        .map(|triple| match triple.object {
+            ObjectRef::Literal(ref literal) => matches!(literal.value, Cow::Borrowed(_)),
+            ref object => panic!("Expected a literal, got: {:?}", object),
+        })
+        .collect::<Vec<_>>();
+
+    match triples[0].subject {
+        SubjectRef::Iri(ref iri) => assert!(iri.is_borrowed()),
+        ref subject => panic!("Expected an IRI, got: {:?}", subject),
+    }
+    assert_eq!(borrowed, [true, false, true]);
+}
+
+#[test]
+fn options_apply() {
+    let source = "@prefix a: <http://a/> .\n@prefix a: <http://b/> .\na:b a:c \"d\"@a-b .";
+    let options = ParserOptions::new().duplicate_prefixes(Policy::Warn).invalid_language_tags(Policy::Warn);
+
+    let expected = Graph::with_options(source, options.clone()).unwrap().parse_document().unwrap();
+    let actual = Graph::fast_with_options(source, options).unwrap().parse_document().unwrap();
+
+    assert_eq!(expected.triples.0, actual.triples.0);
+    assert_eq!(expected.warnings, actual.warnings);
+    assert_eq!(actual.warnings.len(), 2);
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    for &(open, close) in &[("[ :p ", " ]"), ("( ", " )"), ("<< :s :p ", " >>")] {
+        let source = format!("{}:a :b {}1{} .\n", PREFIX, open.repeat(100_000), close.repeat(100_000));
+
+        match Graph::fast(&source).and_then(Graph::parse) {
+            Err(Error::LimitExceeded { limit, position, .. }) => {
+                assert_eq!(limit, Limit::Depth);
+                assert_eq!(position, Position::new(2, 7 + 128 * open.len()));
+            }
+            result => panic!("Expected a limit error, got: {:?}", result),
+        }
+    }
+
+    let limits = Limits { max_depth: 3, ..Limits::default() };
+    let options = ParserOptions::new().limits(limits);
+    let source = format!("{}:a :b ( ( ( 1 ) ) ) .", PREFIX);
+    assert!(Graph::fast_with_options(&source, options.clone()).unwrap().parse().is_ok());
+
+    let source = format!("{}:a :b ( ( ( [ ] ) ) ) .", PREFIX);
+    assert!(Graph::fast_with_options(&source, options).unwrap().parse().is_err());
+}
+
+#[test]
+fn syntax_errors() {
+    assert_eq!(syntax_error("<http://a/s> <http://a/p> ."), Position::new(1, 27));
+    assert_eq!(syntax_error("<http://a/s> <http://a/p> <http://a/o>"), Position::new(1, 39));
+    assert_eq!(syntax_error("<http://a/s> <http://a/p> \"o ."), Position::new(1, 31));
+    assert_eq!(syntax_error("<http://a/s> <http://a/p> ( <http://a/o> ."), Position::new(1, 42));
+    assert_eq!(syntax_error("@prefix a <http://a/> ."), Position::new(1, 10));
+}
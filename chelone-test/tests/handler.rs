@@ -0,0 +1,120 @@
+extern crate chelone;
+
+use chelone::labeller::CounterLabeller;
+use chelone::{Error, Graph, Iri, ParserOptions, Policy, Position, Triple, TurtleHandler};
+
+#[derive(Debug, Default)]
+struct Events(Vec<String>);
+
+impl TurtleHandler for Events {
+    fn on_prefix(&mut self, prefix: &str, iri: &Iri) {
+        self.0.push(format!("prefix {} {}", prefix, iri));
+    }
+
+    fn on_base(&mut self, iri: &Iri) {
+        self.0.push(format!("base {}", iri));
+    }
+
+    fn on_triple(&mut self, triple: Triple) {
+        self.0.push(format!("triple {}", triple));
+    }
+
+    fn on_comment(&mut self, comment: &str, position: Position) {
+        self.0.push(format!("comment {:?} {}:{}", comment, position.line, position.column));
+    }
+
+    fn on_warning(&mut self, warning: &Error) {
+        self.0.push(format!("warning {}", warning.position().unwrap().line));
+    }
+
+    fn on_error(&mut self, error: &Error) {
+        self.0.push(format!("error {}", error.position().unwrap().line));
+    }
+}
+
+/// The events from both parsers, checking they're the same.
+fn parse_with(source: &str, options: ParserOptions) -> (Vec<String>, Result<(), Error>) {
+    let mut expected = Events::default();
+    let result = Graph::with_options(source, options.clone()).unwrap().parse_with(&mut expected);
+    let mut actual = Events::default();
+    let fast = Graph::fast_with_options(source, options).unwrap().parse_with(&mut actual);
+
+    assert_eq!(expected.0, actual.0);
+    assert_eq!(result, fast);
+    (actual.0, result)
+}
+
+#[test]
+fn events_are_in_document_order() {
+    let source = concat!(
+        "# Start\r\n@prefix ex: <http://a/#> . # After a prefix\n",
+        "BASE <http://b/>\n",
+        "ex:s ex:p <o#x>, \"# Not a comment\", '''#\n# Nor this''' ; # Inside\n",
+        "  ex:q ex:\\#r .\n",
+        "# End",
+    );
+    let (events, result) = parse_with(source, ParserOptions::new());
+
+    assert!(result.is_ok());
+    assert_eq!(events, [
+        "comment \" Start\" 1:1",
+        "prefix ex <http://a/#>",
+        "comment \" After a prefix\" 2:28",
+        "base <http://b/>",
+        "comment \" Inside\" 5:17",
+        "triple <http://a/#s> <http://a/#p> <http://b/o#x> .",
+        "triple <http://a/#s> <http://a/#p> \"# Not a comment\"^^<http://www.w3.org/2001/XMLSchema#string> .",
+        "triple <http://a/#s> <http://a/#p> \"#\\n# Nor this\"^^<http://www.w3.org/2001/XMLSchema#string> .",
+        "triple <http://a/#s> <http://a/#q> <http://a/##r> .",
+        "comment \" End\" 7:1",
+    ]);
+}
+
+#[test]
+fn triples_are_the_same_as_parse() {
+    let source = "@prefix : <http://a/> .\n:a :b ( 1 [ :c _:d ] ), << :e :f :g >> {| :h :i |} .\n_:d :j :k .";
+    let mut triples = Vec::new();
+
+    {
+        struct Collect<'a>(&'a mut Vec<Triple>);
+
+        impl<'a> TurtleHandler for Collect<'a> {
+            fn on_triple(&mut self, triple: Triple) {
+                self.0.push(triple);
+            }
+        }
+
+        let mut graph = Graph::new(source).unwrap();
+        graph.set_labeller(CounterLabeller::new("b"));
+        graph.parse_with(&mut Collect(&mut triples)).unwrap();
+    }
+
+    let mut graph = Graph::new(source).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+    assert_eq!(triples, graph.parse().unwrap().0);
+}
+
+#[test]
+fn warnings_and_errors() {
+    let source = "@prefix a: <http://a/> .\n@prefix a: <http://b/> .\na:s a:p \"x\"@a-b .\na:s b:p a:o .";
+    let options = ParserOptions::new().duplicate_prefixes(Policy::Warn).invalid_language_tags(Policy::Warn);
+    let (events, result) = parse_with(source, options);
+
+    assert!(result.is_err());
+    assert_eq!(events, [
+        "prefix a <http://a/>",
+        "prefix a <http://b/>",
+        "warning 2",
+        "warning 3",
+        "triple <http://b/s> <http://b/p> \"x\"@a-b .",
+        "error 4",
+    ]);
+
+    let (events, result) = parse_with("<http://a/s> <http://a/p> ex:o .", ParserOptions::new());
+
+    match result {
+        Err(Error::UndefinedPrefix { prefix, .. }) => assert_eq!(prefix, "ex"),
+        result => panic!("Expected an undefined prefix, got: {:?}", result),
+    }
+    assert_eq!(events, ["error 1"]);
+}
@@ -0,0 +1,275 @@
+extern crate chelone;
+#[macro_use] extern crate serde_json;
+
+use std::env;
+use std::fs;
+
+use chelone::{
+    DirectoryLoader, DocumentLoader, Error, Graph, GraphName, Iri, JsonLdReader, JsonLdWriter, Position, Triples,
+};
+
+#[test]
+fn streams_quads() {
+    let input = r#"{
+        "@context": { "@vocab": "http://example.org/" },
+        "@id": "http://example.org/g",
+        "@graph": [{ "@id": "http://example.org/s", "p": "v" }]
+    }"#;
+    let quads = JsonLdReader::new(input.as_bytes())
+        .collect::<Result<Vec<_>, Error>>()
+        .unwrap();
+
+    assert_eq!(quads.len(), 1);
+    assert_eq!(quads[0].graph_name,
+               Some(GraphName::Iri(Iri::parse("http://example.org/g").unwrap())));
+}
+
+#[test]
+fn parse_merges_named_graphs() {
+    let input = r#"{
+        "@id": "http://example.org/g",
+        "@graph": [{ "@id": "http://example.org/s", "http://example.org/p": "v" }]
+    }"#;
+
+    assert_eq!(JsonLdReader::new(input.as_bytes()).parse().unwrap().len(), 1);
+}
+
+#[test]
+fn closure_loader() {
+    let input = r#"{ "@context": "http://example.org/context", "name": "n" }"#;
+    let mut reader = JsonLdReader::new(input.as_bytes());
+    reader.set_loader(|iri: &Iri| {
+        assert_eq!(iri.as_str(), "http://example.org/context");
+        Ok(String::from(r#"{ "@context": { "name": "http://schema.org/name" } }"#))
+    });
+
+    let triples = reader.parse().unwrap();
+
+    assert_eq!(triples[0].predicate.as_str(), "http://schema.org/name");
+}
+
+#[test]
+fn remote_context_without_loader() {
+    let input = r#"{ "@context": "http://example.org/context", "name": "n" }"#;
+
+    match JsonLdReader::new(input.as_bytes()).parse() {
+        Err(Error::JsonLd { code, message, .. }) => {
+            assert_eq!(code, "loading remote context failed");
+            assert_eq!(message, "http://example.org/context");
+        }
+        result => panic!("Expected a JSON-LD error, got: {:?}", result),
+    }
+}
+
+#[test]
+fn directory_loader_stays_in_its_directory() {
+    let root = env::temp_dir().join(format!("chelone-loader-{}", std::process::id()));
+    let directory = root.join("contexts");
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("context.jsonld"), "{}").unwrap();
+    let secret = root.join("secret.jsonld");
+    fs::write(&secret, "{}").unwrap();
+
+    let loader = DirectoryLoader::new(Iri::parse("http://example.org/").unwrap(), &directory);
+    let load = |iri: &str| loader.load(&Iri::parse(iri).unwrap());
+
+    assert_eq!(load("http://example.org/context.jsonld").unwrap(), "{}");
+    // The IRI's path is `//tmp/...`, which would join to an absolute path.
+    match load(&format!("http://example.org/{}", secret.display())) {
+        Err(Error::JsonLd { code, .. }) => assert_eq!(code, "loading document failed"),
+        result => panic!("Expected the secret not to be loaded, got: {:?}", result),
+    }
+
+    match load("http://example.org/missing.jsonld") {
+        Err(Error::Io { position, .. }) => assert_eq!(position, Position::new(1, 1)),
+        result => panic!("Expected an IO error, got: {:?}", result),
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn syntax_error_position() {
+    let input = "{\n  \"@id\": \"http://example.org/s\",\n  oops\n}";
+    let error = JsonLdReader::new(input.as_bytes()).parse().unwrap_err();
+
+    match error {
+        Error::Syntax { position, .. } => assert_eq!((position.line, position.column), (3, 3)),
+        error => panic!("Expected a syntax error, got: {:?}", error),
+    }
+}
+
+#[test]
+fn error_display() {
+    let input = r#"{ "@id": 1 }"#;
+    let error = JsonLdReader::new(input.as_bytes()).parse().unwrap_err();
+
+    assert_eq!(error.to_string(), "invalid @id value: 1");
+    assert_eq!(error.position(), None);
+}
+
+fn turtle(input: &str) -> Triples {
+    Graph::new(input).unwrap().triples().collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn writer_uses_graph_prefixs() {
+    let graph = Graph::new(r#"
+        @prefix : <http://schema.org/> .
+        @prefix ex: <http://example.org/> .
+        ex:s :name "n" ; :knows ex:o .
+    "#).unwrap();
+    let mut parsed = graph.triples();
+    let triples = parsed.by_ref().collect::<Result<Triples, Error>>().unwrap();
+    let value = JsonLdWriter::new(&triples).prefixs(parsed.prefixs().clone()).to_value().unwrap();
+
+    assert_eq!(value, json!({
+        "@context": { "@vocab": "http://schema.org/", "ex": "http://example.org/" },
+        "@id": "ex:s",
+        "knows": { "@id": "ex:o" },
+        "name": "n"
+    }));
+}
+
+#[test]
+fn writer_native_types() {
+    let triples = turtle(r#"
+        <http://example.org/s> <http://example.org/p> 1, 2.5E0, true, "01"^^<http://www.w3.org/2001/XMLSchema#boolean> .
+    "#);
+    let value = JsonLdWriter::new(&triples)
+        .prefix("", Iri::parse("http://example.org/").unwrap())
+        .native_types(true)
+        .to_value()
+        .unwrap();
+
+    assert_eq!(value["p"], json!([
+        1,
+        2.5,
+        true,
+        { "@type": "http://www.w3.org/2001/XMLSchema#boolean", "@value": "01" }
+    ]));
+}
+
+#[test]
+fn writer_remote_context() {
+    let triples = turtle(r#"<http://example.org/s> <http://schema.org/name> "n" ."#);
+    let loader = |iri: &Iri| {
+        assert_eq!(iri.as_str(), "http://example.org/context");
+        Ok(String::from(r#"{ "@context": { "name": "http://schema.org/name" } }"#))
+    };
+    let value = JsonLdWriter::new(&triples)
+        .context(json!("http://example.org/context"))
+        .loader(&loader)
+        .to_value()
+        .unwrap();
+
+    assert_eq!(value, json!({
+        "@context": "http://example.org/context",
+        "@id": "http://example.org/s",
+        "name": "n"
+    }));
+}
+
+/// Frames `turtle`'s triples, with `ex:` as the frame's `@vocab`.
+fn frame(turtle_source: &str, mut frame: serde_json::Value) -> serde_json::Value {
+    let source = format!("@prefix ex: <http://example.org/> .\n{}", turtle_source);
+    let triples = turtle(&source);
+    frame["@context"] = json!({ "@vocab": "http://example.org/" });
+
+    let mut value = JsonLdWriter::new(&triples).frame(frame).to_value().unwrap();
+    value.as_object_mut().unwrap().remove("@context");
+    value
+}
+
+#[test]
+fn writer_frame_embeds_nodes() {
+    let value = frame(r#"
+        ex:lib a ex:Library ; ex:contains ex:book .
+        ex:book a ex:Book ; ex:title "T" ; ex:chapter ex:ch .
+        ex:ch ex:title "C" .
+    "#, json!({ "@type": "Library" }));
+
+    assert_eq!(value, json!({
+        "@id": "http://example.org/lib",
+        "@type": "Library",
+        "contains": {
+            "@id": "http://example.org/book",
+            "@type": "Book",
+            "chapter": { "@id": "http://example.org/ch", "title": "C" },
+            "title": "T"
+        }
+    }));
+
+    // A node is only embedded the first time, which also breaks cycles.
+    let value = frame(r#"
+        ex:s a ex:T ; ex:a ex:o ; ex:b ex:o .
+        ex:o ex:name "O" .
+    "#, json!({ "@type": "T" }));
+
+    assert_eq!(value, json!({
+        "@id": "http://example.org/s",
+        "@type": "T",
+        "a": { "@id": "http://example.org/o", "name": "O" },
+        "b": { "@id": "http://example.org/o" }
+    }));
+
+    let value = frame("ex:a a ex:T ; ex:knows ex:b .\nex:b ex:knows ex:a .", json!({ "@type": "T" }));
+
+    assert_eq!(value["knows"], json!({
+        "@id": "http://example.org/b",
+        "knows": { "@id": "http://example.org/a" }
+    }));
+}
+
+#[test]
+fn writer_frame_flags() {
+    let source = r#"ex:s a ex:T ; ex:p "v" ; ex:q "w" ."#;
+
+    assert_eq!(frame(source, json!({ "@type": "T", "@explicit": true, "p": {} })), json!({
+        "@id": "http://example.org/s",
+        "@type": "T",
+        "p": "v"
+    }));
+
+    assert_eq!(frame(source, json!({ "@type": "T", "missing": { "@default": "n/a" }, "absent": {} })), json!({
+        "@id": "http://example.org/s",
+        "@type": "T",
+        "absent": null,
+        "missing": "n/a",
+        "p": "v",
+        "q": "w"
+    }));
+
+    assert_eq!(frame("ex:s a ex:T .", json!({ "@type": "T", "@omitDefault": true, "p": {} })), json!({
+        "@id": "http://example.org/s",
+        "@type": "T"
+    }));
+
+    // An empty object matches any node with the property.
+    assert_eq!(frame(r#"ex:a ex:name "A" . ex:b ex:other "B" ."#, json!({ "name": {} })), json!({
+        "@id": "http://example.org/a",
+        "name": "A"
+    }));
+}
+
+#[test]
+fn writer_empty_graph() {
+    assert_eq!(JsonLdWriter::new(&turtle("")).to_json_string().unwrap(), "{}");
+}
+
+#[test]
+fn writer_invalid_json_literal() {
+    let triples = turtle(r#"
+        <http://example.org/s> <http://example.org/p> "{"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON> .
+    "#);
+
+    match JsonLdWriter::new(&triples).to_value() {
+        Err(Error::JsonLd { code, .. }) => assert_eq!(code, "invalid JSON literal"),
+        result => panic!("Expected a JSON-LD error, got: {:?}", result),
+    }
+
+    match JsonLdWriter::new(&triples).to_json_string() {
+        Err(Error::JsonLd { code, .. }) => assert_eq!(code, "invalid JSON literal"),
+        result => panic!("Expected a JSON-LD error, got: {:?}", result),
+    }
+}
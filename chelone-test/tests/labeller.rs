@@ -0,0 +1,183 @@
+extern crate chelone;
+
+use std::collections::HashSet;
+
+use chelone::labeller::{CounterLabeller, HashLabeller, RandomLabeller};
+use chelone::{
+    Graph, JsonLdReader, LenientReader, N3Reader, NQuadsReader, NTriplesReader, ParallelReader,
+    RdfXmlReader, Triples, TurtleReader,
+};
+
+fn labels(triples: &Triples) -> HashSet<String> {
+    triples.iter()
+        .flat_map(|triple| vec![triple.subject.to_string(), triple.object.to_string()])
+        .filter(|term| term.starts_with("_:"))
+        .collect()
+}
+
+#[test]
+fn generated_labels_dont_collide() {
+    let triples = Graph::new("_:b1 <http://example.org/p> [] . _:b2 <http://example.org/p> _:b1 .")
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    assert_ne!(triples[0].subject.to_string(), triples[0].object.to_string());
+    assert_eq!(triples[0].subject.to_string(), triples[1].object.to_string());
+    assert_eq!(labels(&triples).len(), 3);
+}
+
+#[test]
+fn repeated_labels_are_retried() {
+    let mut count = 0;
+    let mut graph = Graph::new("_:a <http://example.org/p> _:b, [] .").unwrap();
+    graph.set_labeller(move |_: Option<&str>| {
+        count += 1;
+        format!("x{}", count / 2)
+    });
+    let triples = graph.parse().unwrap();
+
+    assert_eq!(labels(&triples).len(), 3);
+}
+
+#[test]
+fn parses_are_scoped() {
+    let source = "_:a <http://example.org/p> [] .";
+
+    let mut first = Graph::new(source).unwrap();
+    first.set_labeller(CounterLabeller::new("one"));
+    let mut second = Graph::new(source).unwrap();
+    second.set_labeller(CounterLabeller::new("two"));
+
+    let first = labels(&first.parse().unwrap());
+    let second = labels(&second.parse().unwrap());
+    assert!(first.is_disjoint(&second));
+
+    let mut first = Graph::new(source).unwrap();
+    first.set_labeller(RandomLabeller::new());
+    let mut second = Graph::new(source).unwrap();
+    second.set_labeller(RandomLabeller::new());
+
+    let first = labels(&first.parse().unwrap());
+    let second = labels(&second.parse().unwrap());
+    assert!(first.is_disjoint(&second));
+}
+
+#[test]
+fn default_labels_are_unique_per_parse() {
+    let turtle = "_:a <http://example.org/p> [] .";
+    let parse = || labels(&Graph::new(turtle).unwrap().parse().unwrap());
+    assert!(parse().is_disjoint(&parse()));
+
+    let parse = || labels(&Graph::fast(turtle).unwrap().parse().unwrap());
+    assert!(parse().is_disjoint(&parse()));
+
+    let ntriples = "_:a <http://example.org/p> _:b .\n";
+    let parse = || labels(&NTriplesReader::new(ntriples.as_bytes()).parse().unwrap());
+    assert!(parse().is_disjoint(&parse()));
+
+    let parse = || labels(&ParallelReader::ntriples(ntriples.as_bytes()).parse().unwrap());
+    assert!(parse().is_disjoint(&parse()));
+
+    let nquads = "_:a <http://example.org/p> _:b _:g .\n";
+    let parse = || {
+        let dataset = NQuadsReader::new(nquads.as_bytes()).parse().unwrap();
+        let graph = dataset.named_graphs.keys().next().unwrap().to_string();
+        let mut labels = labels(dataset.named_graphs.values().next().unwrap());
+        labels.insert(graph);
+        labels
+    };
+    let first = parse();
+    assert_eq!(first.len(), 3);
+    assert!(first.is_disjoint(&parse()));
+
+    let jsonld = r#"{ "@id": "_:a", "http://example.org/p": { "@id": "_:b" } }"#;
+    let parse = || labels(&JsonLdReader::new(jsonld.as_bytes()).parse().unwrap());
+    assert!(parse().is_disjoint(&parse()));
+}
+
+#[test]
+fn line_based_readers_take_labellers() {
+    let ntriples = "_:a <http://example.org/p> _:b .\n_:b <http://example.org/p> _:a .\n";
+    let mut reader = NTriplesReader::new(ntriples.as_bytes());
+    reader.set_labeller(CounterLabeller::new("t"));
+    let triples = reader.parse().unwrap();
+    assert_eq!(triples[1].to_string(), "_:t2 <http://example.org/p> _:t1 .");
+
+    let mut reader = ParallelReader::ntriples(ntriples.as_bytes());
+    reader.set_labeller(CounterLabeller::new("t"));
+    reader.set_chunk_size(1);
+    assert_eq!(reader.parse().unwrap().0, triples.0);
+
+    let mut reader = NQuadsReader::new("_:a <http://example.org/p> _:b _:a .\n".as_bytes());
+    reader.set_labeller(CounterLabeller::new("q"));
+    let quad = reader.next().unwrap().unwrap();
+    assert_eq!(quad.to_string(), "_:q1 <http://example.org/p> _:q2 _:q1 .");
+
+    let jsonld = r#"{ "@id": "_:a", "http://example.org/p": { "@id": "_:a" } }"#;
+    let mut reader = JsonLdReader::new(jsonld.as_bytes());
+    reader.set_labeller(CounterLabeller::new("j"));
+    let triples = reader.parse().unwrap();
+    assert_eq!(triples[0].to_string(), "_:j1 <http://example.org/p> _:j1 .");
+}
+
+#[test]
+fn hash_labels_follow_content() {
+    let parse = |source: &str| {
+        let mut graph = Graph::new(source).unwrap();
+        graph.set_labeller(HashLabeller::new(source));
+        labels(&graph.parse().unwrap())
+    };
+
+    let source = "_:a <http://example.org/p> [] .";
+    let other = "_:a <http://example.org/q> [] .";
+
+    assert_eq!(parse(source), parse(source));
+    assert!(parse(source).is_disjoint(&parse(other)));
+}
+
+#[test]
+fn closure_labeller() {
+    let mut graph = Graph::new("_:a <http://example.org/p> [] .").unwrap();
+    graph.set_labeller(|label: Option<&str>| match label {
+        Some(label) => format!("doc-{}", label),
+        None => String::from("anon"),
+    });
+    let triples = graph.parse().unwrap();
+
+    assert_eq!(triples[0].to_string(), "_:doc-a <http://example.org/p> _:anon .");
+}
+
+#[test]
+fn readers_take_labellers() {
+    let source = "_:a <http://example.org/p> _:a .\n_:b <http://example.org/p> [] .";
+
+    let mut reader = TurtleReader::new(source.as_bytes());
+    reader.set_labeller(CounterLabeller::new("t"));
+    let triples = reader.parse().unwrap();
+    assert_eq!(triples[0].to_string(), "_:t1 <http://example.org/p> _:t1 .");
+    assert_eq!(triples[1].to_string(), "_:t2 <http://example.org/p> _:t3 .");
+
+    let mut reader = LenientReader::new(source);
+    reader.set_labeller(CounterLabeller::new("l"));
+    let (triples, _) = reader.parse();
+    assert_eq!(triples[1].to_string(), "_:l2 <http://example.org/p> _:l3 .");
+
+    let mut reader = N3Reader::new(source).unwrap();
+    reader.set_labeller(CounterLabeller::new("n"));
+    let document = reader.parse().unwrap();
+    assert_eq!(document.formula.triples()[0].to_string(), "_:n1 <http://example.org/p> _:n1 .");
+
+    let xml = r#"
+        <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                 xmlns:ex="http://example.org/">
+          <rdf:Description rdf:nodeID="b1">
+            <ex:p rdf:parseType="Resource"><ex:q rdf:nodeID="b1"/></ex:p>
+          </rdf:Description>
+        </rdf:RDF>
+    "#;
+    let mut reader = RdfXmlReader::new(xml.as_bytes());
+    reader.set_labeller(CounterLabeller::new("x"));
+    let triples = reader.parse().unwrap();
+    assert_eq!(labels(&triples).len(), 2);
+}
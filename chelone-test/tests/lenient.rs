@@ -0,0 +1,103 @@
+extern crate chelone;
+
+use chelone::labeller::CounterLabeller;
+use chelone::{Error, Graph, LenientReader, Position};
+
+#[test]
+fn valid_document() {
+    let input = r#"
+        @prefix : <http://example.org/> .
+        :a :b ( 1 2 ), [ :c "d" ] .
+        :e :f :g .
+    "#;
+    let mut reader = LenientReader::new(input);
+    reader.set_labeller(CounterLabeller::new("b"));
+    let (triples, diagnostics) = reader.parse();
+    let mut graph = Graph::new(input).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+
+    assert!(diagnostics.is_empty());
+    assert_eq!(triples.0, graph.parse().unwrap().0);
+}
+
+#[test]
+fn skips_dots_in_iris_and_strings() {
+    let (triples, diagnostics) = LenientReader::new(r#"
+@prefix : <http://example.org/> .
+:a :b :c :d "x. y" <http://example.org/a.b> :e.f 1.5 .
+:g :h :i .
+"#).parse();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(triples[0].to_string(), "<http://example.org/g> <http://example.org/h> <http://example.org/i> .");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].start, Position::new(3, 1));
+    assert_eq!(diagnostics[0].end, Position::new(3, 55));
+}
+
+#[test]
+fn unterminated_string() {
+    let (triples, diagnostics) = LenientReader::new(r#"
+<http://a/> <http://b/> "unterminated .
+<http://c/> <http://d/> "e" .
+<http://f/> <http://g/> <http://h/> .
+"#).parse();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].end, Position::new(3, 30));
+}
+
+#[test]
+fn semantic_errors_skip_one_statement() {
+    let (triples, diagnostics) = LenientReader::new(r#"
+@prefix : <http://example.org/> .
+:a :b :c, ex:d, :e .
+:f :g [ :h :i ] .
+"#).parse();
+
+    assert_eq!(triples.len(), 2);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].start, Position::new(3, 1));
+    assert_eq!(diagnostics[0].end, Position::new(3, 21));
+    match diagnostics[0].error {
+        Error::UndefinedPrefix { ref prefix, position } => {
+            assert_eq!(prefix, "ex");
+            assert_eq!(position, Position::new(3, 11));
+        }
+        ref error => panic!("Expected an undefined prefix, got: {:?}", error),
+    }
+}
+
+#[test]
+fn later_directives_still_apply() {
+    let mut reader = LenientReader::new(r#"
+@prefix : <http://example.org/> .
+:a :b .
+@prefix ex: <http://example.com/> .
+ex:a [] [] .
+ex:c ex:d [] .
+"#);
+    reader.set_labeller(CounterLabeller::new("b"));
+    let (triples, diagnostics) = reader.parse();
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(triples.len(), 1);
+    assert_eq!(triples[0].to_string(), "<http://example.com/c> <http://example.com/d> _:b1 .");
+}
+
+#[test]
+fn missing_final_dot() {
+    let (triples, diagnostics) = LenientReader::new("<http://a/> <http://b/> <http://c/> .\n<http://d/> <http://e/>").parse();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].end, Position::new(2, 24));
+}
+
+#[test]
+fn diagnostic_display() {
+    let (_, diagnostics) = LenientReader::new("<http://a/> <http://b/> ex:c .").parse();
+
+    assert_eq!(diagnostics[0].to_string(), "1:25: undefined prefix `ex:` (skipped 1:1 to 1:31)");
+}
@@ -0,0 +1,155 @@
+extern crate chelone;
+
+use chelone::limits::Limit;
+use chelone::{Error, Graph, LenientReader, Limits, N3Reader, Position, TurtleReader};
+
+const PREFIX: &str = "@prefix : <http://example.org/> .\n";
+
+fn limit(result: Result<impl Sized, Error>) -> (Limit, Position) {
+    match result {
+        Err(Error::LimitExceeded { limit, position, .. }) => (limit, position),
+        Err(error) => panic!("Expected a limit error, got: {}", error),
+        Ok(_) => panic!("Expected a limit error"),
+    }
+}
+
+fn nested(open: &str, close: &str, depth: usize) -> String {
+    format!("{}:a :b {}1{} .\n", PREFIX, open.repeat(depth), close.repeat(depth))
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    for &(open, close) in &[("[ :p ", " ]"), ("( ", " )"), ("<< :s :p ", " >>")] {
+        let source = nested(open, close, 100_000);
+
+        assert_eq!(limit(Graph::new(&source)), (Limit::Depth, Position::new(2, 7 + 128 * open.len())));
+        assert_eq!(limit(Graph::trig(&source)).0, Limit::Depth);
+        assert_eq!(limit(N3Reader::new(&source)).0, Limit::Depth);
+        assert_eq!(limit(TurtleReader::new(source.as_bytes()).parse()).0, Limit::Depth);
+    }
+}
+
+#[test]
+fn nesting_within_the_limit() {
+    let source = nested("[ :p ", " ]", 128);
+    assert_eq!(Graph::new(&source).unwrap().parse().unwrap().len(), 129);
+
+    let source = nested("( ", " )", 128);
+    assert_eq!(Graph::new(&source).unwrap().parse().unwrap().len(), 257);
+
+    let limits = Limits { max_depth: 3, ..Limits::default() };
+    let source = nested("( ", " )", 3);
+    assert!(Graph::with_limits(&source, limits).is_ok());
+    let source = nested("( ", " )", 4);
+    assert_eq!(limit(Graph::with_limits(&source, limits)).0, Limit::Depth);
+}
+
+#[test]
+fn nested_quoted_triples() {
+    let triples = Graph::new(&format!(
+        "{}<< << :a :b :c >> :d << :e :f << :g :h :i >> >> >> :j :k .",
+        PREFIX
+    )).unwrap().parse().unwrap();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(triples[0].to_string(), concat!(
+        "<< << <http://example.org/a> <http://example.org/b> <http://example.org/c> >> ",
+        "<http://example.org/d> << <http://example.org/e> <http://example.org/f> ",
+        "<< <http://example.org/g> <http://example.org/h> <http://example.org/i> >> >> >> ",
+        "<http://example.org/j> <http://example.org/k> ."
+    ));
+}
+
+#[test]
+fn lenient_skips_deep_statements() {
+    let mut source = nested("[ :p ", " ]", 1_000);
+    source.push_str(":c :d :e .\n");
+
+    let mut reader = LenientReader::new(&source);
+    reader.set_limits(Limits { max_depth: 10, ..Limits::default() });
+    let (triples, diagnostics) = reader.parse();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].error.position(), Some(Position::new(2, 7 + 10 * 5)));
+    assert_eq!(diagnostics[0].end.line, 2);
+}
+
+#[test]
+fn literal_length() {
+    let limits = Limits { max_literal_length: 5, ..Limits::default() };
+    let parse = |literal: &str| {
+        Graph::with_limits(&format!("{}:a :b {} .", PREFIX, literal), limits)
+            .and_then(Graph::parse)
+    };
+
+    assert!(parse(r#""hello""#).is_ok());
+    assert!(parse(r#""hello"@en"#).is_ok());
+    assert_eq!(limit(parse(r#""hello!""#)), (Limit::LiteralLength, Position::new(2, 7)));
+    assert_eq!(limit(parse(r#"'''hello
+!'''"#)).0, Limit::LiteralLength);
+}
+
+#[test]
+fn iri_length() {
+    let limits = Limits { max_iri_length: 20, ..Limits::default() };
+    let parse = |source: &str| Graph::with_limits(source, limits).and_then(Graph::parse);
+
+    assert!(parse("<http://a/s> <http://a/p> <http://a/o> .").is_ok());
+    assert_eq!(
+        limit(parse("<http://a/s> <http://a/p> <http://a/longer-object> .")),
+        (Limit::IriLength, Position::new(1, 27))
+    );
+    assert_eq!(
+        limit(parse("@prefix a: <http://a/> . a:s a:p a:longer-object .")),
+        (Limit::IriLength, Position::new(1, 34))
+    );
+    assert_eq!(
+        limit(parse("@base <http://a/b/c/d/e/f/> . <s> <p> <long> .")),
+        (Limit::IriLength, Position::new(1, 39))
+    );
+}
+
+#[test]
+fn triple_count() {
+    let limits = Limits { max_triples: 3, ..Limits::default() };
+    let source = format!("{}:a :b :c, :d .\n:e :f ( :g ) .", PREFIX);
+
+    // The triples of the statement that goes over the limit are dropped.
+    let triples = Graph::with_limits(&source, limits).unwrap().triples();
+    assert_eq!(triples.take_while(Result::is_ok).count(), 2);
+
+    assert_eq!(limit(Graph::with_limits(&source, limits).unwrap().parse()), (Limit::Triples, Position::new(3, 7)));
+
+    let mut reader = TurtleReader::new(source.as_bytes());
+    reader.set_limits(limits);
+    assert_eq!(limit(reader.parse()).0, Limit::Triples);
+
+    let n3 = N3Reader::with_limits(&source, limits).unwrap();
+    assert_eq!(limit(n3.parse()).0, Limit::Triples);
+}
+
+#[test]
+fn prefix_count() {
+    let limits = Limits { max_prefixes: 2, ..Limits::default() };
+    let source = r#"
+        @prefix a: <http://a/> .
+        PREFIX b: <http://b/>
+        @prefix a: <http://c/> .
+        @prefix c: <http://d/> .
+    "#;
+
+    assert_eq!(
+        limit(Graph::with_limits(source, limits).unwrap().parse()),
+        (Limit::Prefixes, Position::new(5, 17))
+    );
+}
+
+#[test]
+fn display() {
+    let error = limit(Graph::with_limits("( ( ) )", Limits { max_depth: 1, ..Limits::default() }));
+    assert_eq!(error.0.to_string(), "nesting depth");
+
+    let error = Graph::with_limits("( ( ) )", Limits { max_depth: 1, ..Limits::default() }).unwrap_err();
+    assert_eq!(error.to_string(), "1:3: nesting depth is over the limit of 1");
+}
@@ -0,0 +1,83 @@
+extern crate chelone;
+
+use chelone::{Graph, Located, Position, Triple};
+
+fn located(source: &str) -> Vec<Located<Triple>> {
+    Graph::new(source).unwrap().located_triples().collect::<Result<_, _>>().unwrap()
+}
+
+fn text<'a>(source: &'a str, triple: &Located<Triple>) -> [&'a str; 3] {
+    [
+        &source[triple.subject.bytes.clone()],
+        &source[triple.predicate.bytes.clone()],
+        &source[triple.object.bytes.clone()],
+    ]
+}
+
+#[test]
+fn terms() {
+    let source = "@prefix : <http://example.org/> .\n:s a :C ;\n  :p \"é\", 1 .\n";
+    let triples = located(source);
+
+    assert_eq!(text(source, &triples[0]), [":s", "a", ":C"]);
+    assert_eq!(text(source, &triples[1]), [":s", ":p", "\"é\""]);
+    assert_eq!(text(source, &triples[2]), [":s", ":p", "1"]);
+    assert_eq!(triples[2].object.start, Position::new(3, 11));
+    assert_eq!(triples[2].object.end, Position::new(3, 12));
+    assert_eq!(triples[1].predicate.end, Position::new(3, 5));
+}
+
+#[test]
+fn blank_node_property_lists() {
+    let source = "@prefix : <http://example.org/> .\n:s :p [ :q :o ] .";
+    let triples = located(source);
+
+    assert_eq!(text(source, &triples[0]), ["[ :q :o ]", ":q", ":o"]);
+    assert_eq!(text(source, &triples[1]), [":s", ":p", "[ :q :o ]"]);
+}
+
+#[test]
+fn collections() {
+    let source = "@prefix : <http://example.org/> .\n:s :p ( :a :b ) .";
+    let triples = located(source);
+
+    assert_eq!(triples.len(), 5);
+    assert_eq!(text(source, &triples[0]), ["( :a :b )", "( :a :b )", ":a"]);
+    assert_eq!(text(source, &triples[1]), ["( :a :b )", "( :a :b )", "( :a :b )"]);
+    assert_eq!(text(source, &triples[2]), ["( :a :b )", "( :a :b )", ":b"]);
+    assert_eq!(text(source, &triples[4]), [":s", ":p", "( :a :b )"]);
+}
+
+#[test]
+fn annotations() {
+    let source = "@prefix : <http://example.org/> .\n:s :p :o {| :q :r |} ; :t :u .";
+    let triples = located(source);
+
+    assert_eq!(text(source, &triples[0]), [":s", ":p", ":o"]);
+    assert_eq!(text(source, &triples[1]), ["{| :q :r |}", ":q", ":r"]);
+    assert_eq!(text(source, &triples[2]), [":s", ":t", ":u"]);
+}
+
+#[test]
+fn trig() {
+    let source = "@prefix : <http://example.org/> .\nGRAPH :g { :a :b :c }\n:d :e :f .";
+    let triples = Graph::trig(source).unwrap()
+        .located_triples()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(text(source, &triples[0]), [":a", ":b", ":c"]);
+    assert_eq!(text(source, &triples[1]), [":d", ":e", ":f"]);
+    assert_eq!(triples[1].subject.start, Position::new(3, 1));
+}
+
+#[test]
+fn errors() {
+    let mut triples = Graph::new("<http://a/> <http://b/> <http://c/> . <http://a/> <http://b/> ex:c .")
+        .unwrap()
+        .located_triples();
+
+    assert!(triples.next().unwrap().is_ok());
+    assert!(triples.next().unwrap().is_err());
+    assert!(triples.next().is_none());
+}
@@ -0,0 +1,98 @@
+extern crate chelone;
+
+use chelone::n3::{Formula, Statement, Term, Variable};
+use chelone::{Error, Graph, Iri, N3Document, N3Reader};
+
+fn n3(input: &str) -> N3Document {
+    N3Reader::new(input).unwrap().parse().unwrap()
+}
+
+fn iri(iri: &str) -> Term {
+    Term::Iri(Iri::parse(iri).unwrap())
+}
+
+fn formula(term: &Term) -> &Formula {
+    match *term {
+        Term::Formula(ref formula) => formula,
+        ref term => panic!("Expected a formula, got: {}", term),
+    }
+}
+
+#[test]
+fn rule() {
+    let document = n3(r#"
+        @prefix : <http://example.org/> .
+        { ?x :parent ?y } => { ?y :child ?x } .
+    "#);
+
+    assert_eq!(document.formula.statements.len(), 1);
+
+    let rule = match document.formula.statements[0] {
+        Statement::N3(ref rule) => rule,
+        ref statement => panic!("Expected a rule, got: {}", statement),
+    };
+    let premise = formula(&rule.subject);
+
+    assert_eq!(rule.predicate, iri("http://www.w3.org/2000/10/swap/log#implies"));
+    assert_eq!(formula(&rule.object).statements.len(), 1);
+
+    match premise.statements[0] {
+        Statement::N3(ref triple) => {
+            assert_eq!(triple.subject, Term::Variable(Variable(String::from("x"))));
+            assert_eq!(triple.predicate, iri("http://example.org/parent"));
+        }
+        ref statement => panic!("Expected variables, got: {}", statement),
+    }
+}
+
+#[test]
+fn declarations_belong_to_their_formula() {
+    let document = n3(r#"
+        @prefix : <http://example.org/> .
+        @forSome :a .
+        { @forAll :x, :y . :x :p :y } => {} .
+    "#);
+
+    assert_eq!(document.formula.existentials, vec![Iri::parse("http://example.org/a").unwrap()]);
+    assert!(document.formula.universals.is_empty());
+
+    let rule = match document.formula.statements[0] {
+        Statement::N3(ref rule) => rule,
+        ref statement => panic!("Expected a rule, got: {}", statement),
+    };
+
+    assert_eq!(formula(&rule.subject).universals.len(), 2);
+    assert_eq!(formula(&rule.subject).triples().len(), 1);
+    assert!(formula(&rule.object).is_empty());
+}
+
+#[test]
+fn display() {
+    let document = n3(r#"
+        @prefix : <http://example.org/> .
+        @forAll :x .
+        :a :b "c" .
+        { :x :p ?y } => {} .
+    "#);
+
+    assert_eq!(document.to_string(), r#"@forAll <http://example.org/x> .
+<http://example.org/a> <http://example.org/b> "c"^^<http://www.w3.org/2001/XMLSchema#string> .
+{ <http://example.org/x> <http://example.org/p> ?y . } <http://www.w3.org/2000/10/swap/log#implies> {} .
+"#);
+    assert_eq!(n3(&document.to_string()).formula, document.formula);
+}
+
+#[test]
+fn prefixs() {
+    let document = n3("@prefix ex: <http://example.org/> . ex:a ex:b ex:c .");
+
+    assert_eq!(document.prefixs["ex"], Iri::parse("http://example.org/").unwrap());
+}
+
+#[test]
+fn turtle_rejects_n3() {
+    match Graph::new("{ <http://a> <http://b> <http://c> } => {} .").unwrap().parse() {
+        Err(Error::Syntax { .. }) => {}
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+}
@@ -0,0 +1,39 @@
+extern crate chelone;
+
+use chelone::{Error, GraphName, Iri, NQuadsReader};
+
+#[test]
+fn default_and_named_graphs() {
+    let input = "<http://a.example/s> <http://a.example/p> \"o\" .\n\
+                 <http://a.example/s> <http://a.example/p> \"o\" <http://a.example/g> .\n\
+                 <http://a.example/s> <http://a.example/p> _:o _:g .\n";
+    let dataset = NQuadsReader::new(input.as_bytes()).parse().unwrap();
+    let name = GraphName::Iri(Iri::parse("http://a.example/g").unwrap());
+
+    assert_eq!(dataset.len(), 3);
+    assert_eq!(dataset.default_graph.len(), 1);
+    assert_eq!(dataset.graph(Some(&name)).unwrap().len(), 1);
+    assert_eq!(dataset.named_graphs.len(), 2);
+}
+
+#[test]
+fn escaped_literals_round_trip() {
+    let input = "<http://a.example/s> <http://a.example/p> \"a \\\"quoted\\\" \\\\ line\\n\\r\" <http://a.example/g> .\n\
+                 <http://a.example/s> <http://a.example/p> \"chat\"@fr .\n";
+    let dataset = NQuadsReader::new(input.as_bytes()).parse().unwrap();
+    let written = dataset.to_string();
+    let reread = NQuadsReader::new(written.as_bytes()).parse().unwrap();
+
+    assert_eq!(dataset.quads().collect::<Vec<_>>(), reread.quads().collect::<Vec<_>>());
+}
+
+#[test]
+fn error_position() {
+    let input = "<http://a.example/s> <http://a.example/p> <http://a.example/o> .\n\
+                 <http://a.example/s> <http://a.example/p> <http://a.example/o> \"g\" .\n";
+
+    match NQuadsReader::new(input.as_bytes()).parse() {
+        Err(Error::Syntax { position, .. }) => assert_eq!(position.line, 2),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
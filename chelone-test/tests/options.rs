@@ -0,0 +1,169 @@
+extern crate chelone;
+
+use chelone::{Document, Error, Graph, Iri, ParserOptions, Policy, Position, TurtleReader};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+fn parse(source: &str, options: ParserOptions) -> Result<Document, Error> {
+    Graph::with_options(source, options)?.parse_document()
+}
+
+#[test]
+fn defaults_are_unchanged() {
+    let source = r#"
+        @prefix : <http://example.org/> .
+        @prefix : <http://example.com/> .
+        :a :b "x"@a-b, "y"^^<http://www.w3.org/2001/XMLSchema#integer> .
+    "#;
+
+    let document = parse(source, ParserOptions::new()).unwrap();
+    assert_eq!(document.triples.len(), 2);
+    assert!(document.warnings.is_empty());
+
+    match parse("<a> <b> <c> .", ParserOptions::new()) {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "a"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+
+    match parse("ex:a ex:b ex:c .", ParserOptions::new()) {
+        Err(Error::UndefinedPrefix { prefix, .. }) => assert_eq!(prefix, "ex"),
+        result => panic!("Expected an undefined prefix, got: {:?}", result),
+    }
+}
+
+#[test]
+fn strict() {
+    match parse("@prefix a: <http://a/> .\n@prefix a: <http://b/> .", ParserOptions::strict()) {
+        Err(Error::DuplicatePrefix { prefix, position }) => {
+            assert_eq!(prefix, "a");
+            assert_eq!(position, Position::new(2, 9));
+        }
+        result => panic!("Expected a duplicate prefix, got: {:?}", result),
+    }
+
+    match parse(r#"<http://a/s> <http://a/p> "x"@en-a ."#, ParserOptions::strict()) {
+        Err(Error::InvalidLanguageTag { tag, position }) => {
+            assert_eq!(tag, "en-a");
+            assert_eq!(position, Position::new(1, 27));
+        }
+        result => panic!("Expected an invalid language tag, got: {:?}", result),
+    }
+
+    let source = format!(r#"<http://a/s> <http://a/p> "300"^^<{}byte> ."#, XSD);
+    match parse(&source, ParserOptions::strict()) {
+        Err(Error::IllTypedLiteral { value, datatype, .. }) => {
+            assert_eq!(value, "300");
+            assert_eq!(datatype, format!("{}byte", XSD));
+        }
+        result => panic!("Expected an ill-typed literal, got: {:?}", result),
+    }
+}
+
+#[test]
+fn lenient() {
+    let source = r#"@prefix a: <http://a/> .
+@prefix a: <http://a/> .
+<s> ex:p "x"@en-a, "x"^^<http://www.w3.org/2001/XMLSchema#boolean> .
+"#;
+    let document = parse(source, ParserOptions::lenient()).unwrap();
+
+    assert_eq!(document.triples[0].to_string(), r#"<file:///s> <ex:p> "x"@en-a ."#);
+    assert_eq!(document.warnings, vec![
+        Error::DuplicatePrefix { prefix: String::from("a"), position: Position::new(2, 9) },
+        Error::InvalidIri { iri: String::from("s"), position: Position::new(3, 1) },
+        Error::UndefinedPrefix { prefix: String::from("ex"), position: Position::new(3, 5) },
+        Error::InvalidLanguageTag { tag: String::from("en-a"), position: Position::new(3, 10) },
+        Error::IllTypedLiteral {
+            value: String::from("x"),
+            datatype: String::from("http://www.w3.org/2001/XMLSchema#boolean"),
+            position: Position::new(3, 20),
+        },
+    ]);
+}
+
+#[test]
+fn policies_are_separate() {
+    let options = ParserOptions::strict()
+        .relative_iris(Policy::Allow)
+        .unknown_prefixes(Policy::Warn);
+    let document = parse("<s> ex:p <o> .", options.clone()).unwrap();
+
+    assert_eq!(document.warnings.len(), 1);
+    assert!(parse(r#"<s> <p> "x"@en-a ."#, options).is_err());
+
+    // The empty prefix can't be read as an IRI.
+    assert!(parse(":a :b :c .", ParserOptions::lenient()).is_err());
+}
+
+#[test]
+fn base() {
+    let options = ParserOptions::strict().base(Iri::parse("http://example.org/").unwrap());
+    let document = parse("<s> <p> <o> .", options).unwrap();
+
+    assert_eq!(document.triples[0].subject.to_string(), "<http://example.org/s>");
+}
+
+#[test]
+fn language_tags() {
+    let valid = [
+        "en", "en-US", "zh-Hant-TW", "sl-rozaj-biske", "de-CH-1901", "zh-yue-HK",
+        "en-a-bbb-x-a-ccc", "x-whatever", "i-klingon", "es-419", "ar--rtl",
+    ];
+    let invalid = ["a", "toolongtag", "en-a", "en-US-x", "de-419-DE", "en-abcdefghi"];
+
+    for tag in valid.iter() {
+        let source = format!(r#"<http://a/s> <http://a/p> "x"@{} ."#, tag);
+        assert!(parse(&source, ParserOptions::strict()).is_ok(), "{} is valid", tag);
+    }
+
+    for tag in invalid.iter() {
+        let source = format!(r#"<http://a/s> <http://a/p> "x"@{} ."#, tag);
+        assert!(parse(&source, ParserOptions::strict()).is_err(), "{} is invalid", tag);
+    }
+}
+
+#[test]
+fn literal_types() {
+    let valid = [
+        ("-5", "integer"), ("99999999999999999999999999999999999999999", "integer"),
+        ("1.5", "decimal"), (".5", "decimal"), ("-1.", "decimal"),
+        ("1e5", "double"), ("-.5E-3", "float"), ("INF", "double"), ("NaN", "float"),
+        ("1", "boolean"), ("false", "boolean"), ("127", "byte"), ("0", "nonNegativeInteger"),
+        ("-1", "negativeInteger"), ("18446744073709551615", "unsignedLong"), ("x", "string"),
+        ("anything", "date"),
+    ];
+    let invalid = [
+        ("a", "integer"), ("1.0", "integer"), ("", "integer"), ("+", "integer"),
+        ("1.5.5", "decimal"), (".", "decimal"), ("1e", "double"), ("inf", "double"),
+        ("yes", "boolean"), ("128", "byte"), ("-1", "nonNegativeInteger"), ("0", "positiveInteger"),
+        ("-99999999999999999999999999999999999999999", "long"),
+    ];
+
+    for &(value, datatype) in valid.iter() {
+        let source = format!(r#"<http://a/s> <http://a/p> "{}"^^<{}{}> ."#, value, XSD, datatype);
+        assert!(parse(&source, ParserOptions::strict()).is_ok(), "{} is a {}", value, datatype);
+    }
+
+    for &(value, datatype) in invalid.iter() {
+        let source = format!(r#"<http://a/s> <http://a/p> "{}"^^<{}{}> ."#, value, XSD, datatype);
+        assert!(parse(&source, ParserOptions::strict()).is_err(), "{} isn't a {}", value, datatype);
+    }
+}
+
+#[test]
+fn reader() {
+    let source = "@prefix a: <http://a/> .\n\n<http://a/s> <http://a/p> 1 .\n@prefix a: <http://b/> .";
+
+    let mut reader = TurtleReader::new(source.as_bytes());
+    reader.set_options(ParserOptions::lenient());
+    let triples = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(triples.len(), 1);
+    assert_eq!(reader.warnings(), &[
+        Error::DuplicatePrefix { prefix: String::from("a"), position: Position::new(4, 9) },
+    ][..]);
+
+    let mut reader = TurtleReader::new(source.as_bytes());
+    reader.set_options(ParserOptions::strict());
+    assert!(reader.parse().is_err());
+}
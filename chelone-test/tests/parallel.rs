@@ -0,0 +1,186 @@
+extern crate chelone;
+
+use chelone::labeller::CounterLabeller;
+use chelone::limits::Limit;
+use chelone::object::Object;
+use chelone::subject::Subject;
+use chelone::{
+    Error, Graph, Limits, NTriplesReader, ParallelReader, ParserOptions, Policy, Position, Triple,
+    Triples,
+};
+
+const PREFIX: &str = "@prefix : <http://example.org/> .\n";
+
+fn parallel(source: &str, chunk_size: usize) -> Result<Triples, Error> {
+    let mut reader = ParallelReader::turtle(source.as_bytes());
+    reader.set_chunk_size(chunk_size);
+    reader.set_threads(4);
+    reader.parse()
+}
+
+fn statements(count: usize) -> String {
+    let mut source = String::from(PREFIX);
+
+    for i in 0..count {
+        source += &format!(":s{} :p \"{}\", {}.5 ; :q <http://example.org/o{}> .\n", i, i, i, i);
+    }
+
+    source
+}
+
+#[test]
+fn same_triples_in_the_same_order() {
+    let source = statements(500);
+    let expected = Graph::new(&source).unwrap().parse().unwrap();
+
+    for &chunk_size in &[1, 100, 4096, 1 << 20] {
+        assert_eq!(expected.0, parallel(&source, chunk_size).unwrap().0);
+    }
+}
+
+#[test]
+fn blank_nodes_are_consistent_across_chunks() {
+    let source = format!("{}:a :b _:x, [] .\n:c :d ( 1 2 ) .\n:e :f _:x, [ :g _:y ] .\n_:y :h :i .", PREFIX);
+    let triples = parallel(&source, 1).unwrap();
+
+    assert!(triples.clone().is_isomorphic(&mut Graph::new(&source).unwrap().parse().unwrap()));
+
+    let object = |i: usize| triples[i].object.clone();
+    assert_eq!(object(0), object(7));
+    assert_ne!(object(1), object(9));
+    assert_eq!(triples[8].object, Object::BlankNode(match triples[10].subject {
+        Subject::BlankNode(ref node) => node.clone(),
+        ref subject => panic!("Expected a blank node, got: {:?}", subject),
+    }));
+}
+
+#[test]
+fn labeller_is_used_for_every_chunk() {
+    let mut reader = ParallelReader::turtle("_:a <http://a/p> [] .\n_:a <http://a/q> [] .".as_bytes());
+    reader.set_chunk_size(1);
+    let mut count = 0;
+    reader.set_labeller(move |label: Option<&str>| {
+        count += 1;
+        format!("n{}", label.map_or(count.to_string(), str::to_owned))
+    });
+    let triples = reader.parse().unwrap();
+
+    assert_eq!(triples.to_string(), "_:na <http://a/p> _:n2 .\n_:na <http://a/q> _:n3 .\n");
+}
+
+#[test]
+fn directives_apply_to_later_chunks() {
+    let source = concat!(
+        "@prefix : <http://a/> .\n:s :p :o .\n",
+        "@prefix : <http://b/> .\n:s :p :o .\n",
+        "<http://c/s> <http://c/p> <http://c/o> .PREFIX ex: <http://d/>\nex:s ex:p ex:o .\n",
+        "BASE <http://e/>\n<s> <p> <o> .\n",
+        "ex:s ex:p ex:o .\n",
+    );
+
+    let expected = Graph::new(source).unwrap().parse().unwrap();
+
+    assert_eq!(expected.len(), 6);
+    assert_eq!(expected.0, parallel(source, 1).unwrap().0);
+}
+
+#[test]
+fn triples_before_an_error_are_kept() {
+    let source = format!("{}:a :b :c .\n:d :e :f .\n:g :h .\n:i :j :k .", PREFIX);
+    let mut triples = Vec::new();
+    let mut reader = ParallelReader::turtle(source.as_bytes());
+    reader.set_chunk_size(1);
+
+    let error = reader.parse_into(&mut |triple: Triple| triples.push(triple)).unwrap_err();
+    let expected = Graph::fast(&source).and_then(Graph::parse).unwrap_err();
+
+    assert_eq!(triples.len(), 2);
+    assert_eq!(error.position(), expected.position());
+    assert_eq!(error.position(), Some(Position::new(4, 7)));
+}
+
+#[test]
+fn errors_are_in_document_order() {
+    let mut source = statements(200);
+    source += "ex:a ex:b ex:c .\n";
+    source += &statements(200)[PREFIX.len()..];
+    source += ":a :b \"c .\n";
+
+    match parallel(&source, 64) {
+        Err(Error::UndefinedPrefix { prefix, position }) => {
+            assert_eq!(prefix, "ex");
+            assert_eq!(position, Position::new(202, 1));
+        }
+        result => panic!("Expected an undefined prefix, got: {:?}", result.map(|t| t.len())),
+    }
+}
+
+#[test]
+fn limits_and_warnings() {
+    let source = statements(50);
+    let limits = Limits { max_triples: 101, ..Limits::default() };
+
+    let mut reader = ParallelReader::turtle(source.as_bytes());
+    reader.set_chunk_size(100);
+    reader.set_limits(limits);
+    let error = reader.parse().unwrap_err();
+    let expected = Graph::fast_with_options(&source, ParserOptions::new().limits(limits))
+        .and_then(Graph::parse)
+        .unwrap_err();
+
+    match error {
+        Error::LimitExceeded { limit, .. } => assert_eq!(limit, Limit::Triples),
+        error => panic!("Expected a limit error, got: {:?}", error),
+    }
+    assert_eq!(error.position(), expected.position());
+
+    let source = "@prefix a: <http://a/> .\n<http://a/s> <http://a/p> \"x\"@a-b .\n@prefix a: <http://b/> .";
+    let options = ParserOptions::new().invalid_language_tags(Policy::Warn).duplicate_prefixes(Policy::Warn);
+    let mut reader = ParallelReader::turtle(source.as_bytes());
+    reader.set_chunk_size(1);
+    reader.set_options(options.clone());
+    reader.parse_into(&mut |_| {}).unwrap();
+
+    let expected = Graph::fast_with_options(source, options).unwrap().parse_document().unwrap();
+    assert_eq!(reader.warnings(), &expected.warnings[..]);
+    assert_eq!(reader.warnings().len(), 2);
+}
+
+#[test]
+fn ntriples() {
+    let mut source = String::from("\u{FEFF}");
+
+    for i in 0..300 {
+        source += &format!("<http://a/s{}> <http://a/p> _:b{} .\r\n# comment\n", i, i % 7);
+    }
+
+    let mut reader = NTriplesReader::new(source.as_bytes());
+    reader.set_labeller(CounterLabeller::new("b"));
+    let expected = reader.parse().unwrap();
+    let mut reader = ParallelReader::ntriples(source.as_bytes());
+    reader.set_labeller(CounterLabeller::new("b"));
+    reader.set_chunk_size(100);
+
+    assert_eq!(expected.0, reader.parse().unwrap().0);
+
+    source += "<http://a/s> <http://a/p> .\n";
+
+    match ParallelReader::ntriples(source.as_bytes()).parse() {
+        Err(Error::Syntax { position, .. }) => assert_eq!(position.line, 601),
+        result => panic!("Expected a syntax error, got: {:?}", result.map(|t| t.len())),
+    }
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut source = statements(10).into_bytes();
+    source.extend_from_slice(b":a :b \"\xFF\" .\n");
+
+    let mut reader = ParallelReader::turtle(source.as_slice());
+    reader.set_chunk_size(1);
+
+    match reader.parse() {
+        Err(Error::InvalidUtf8 { position }) => assert_eq!(position, Position::new(12, 8)),
+        result => panic!("Expected invalid UTF-8, got: {:?}", result.map(|t| t.len())),
+    }
+}
@@ -0,0 +1,179 @@
+extern crate chelone;
+
+use chelone::{Error, Graph, Iri, RdfXmlReader, RdfXmlWriter};
+use chelone::object::Object;
+
+#[test]
+fn language_and_base() {
+    let input = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                          xmlns:eg="http://example.org/"
+                          xml:base="http://example.org/dir/" xml:lang="en">
+                     <rdf:Description rdf:about="a">
+                       <eg:p>hello</eg:p>
+                       <eg:q xml:lang="">bare</eg:q>
+                     </rdf:Description>
+                   </rdf:RDF>"#;
+    let triples = RdfXmlReader::new(input.as_bytes()).parse().unwrap();
+
+    assert_eq!(triples.len(), 2);
+    assert_eq!(triples[0].subject.to_string(), "<http://example.org/dir/a>");
+    assert_eq!(triples[0].object.to_string(), "\"hello\"@en");
+    assert_eq!(triples[1].object.to_string(),
+               "\"bare\"^^<http://www.w3.org/2001/XMLSchema#string>");
+}
+
+#[test]
+fn streams_triples() {
+    let input = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                          xmlns:eg="http://example.org/">
+                     <rdf:Description rdf:about="http://example.org/a">
+                       <eg:p rdf:resource="http://example.org/b"/>
+                     </rdf:Description>
+                     <rdf:Description rdf:about="http://example.org/c">
+                       <eg:p rdf:nodeID="x"/>
+                     </rdf:Description>
+                   </rdf:RDF>"#;
+    let mut reader = RdfXmlReader::new(input.as_bytes());
+    reader.set_base(Iri::parse("http://example.org/").unwrap());
+
+    let first = reader.next().unwrap().unwrap();
+    assert_eq!(first.object, Object::Iri(Iri::parse("http://example.org/b").unwrap()));
+
+    let second = reader.next().unwrap().unwrap();
+    assert!(second.object.is_blank_node());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn error_position() {
+    let input = "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+                 <rdf:Description rdf:about=\"http://example.org/a\" rdf:ID=\"a\"/>\n\
+                 </rdf:RDF>\n";
+
+    let mut reader = RdfXmlReader::new(input.as_bytes());
+    reader.set_base(Iri::parse("http://example.org/").unwrap());
+
+    match reader.parse() {
+        Err(Error::Syntax { position, .. }) => assert_eq!(position.line, 2),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
+
+#[test]
+fn writes_escaped_literals() {
+    let triples = Graph::new(r#"
+        @prefix : <http://example.org/> .
+        :a :p "<b> & \"c\"", "d"@en-GB, "1"^^<http://example.org/type?a=1&b=2> .
+    "#).unwrap().parse().unwrap();
+    let xml = RdfXmlWriter::new(&triples).to_xml().unwrap();
+
+    assert!(xml.contains("<ns0:p>&lt;b&gt; &amp; \"c\"</ns0:p>"), "{}", xml);
+    assert!(xml.contains("<ns0:p xml:lang=\"en-GB\">d</ns0:p>"), "{}", xml);
+    assert!(xml.contains("rdf:datatype=\"http://example.org/type?a=1&amp;b=2\""), "{}", xml);
+
+    let mut reread = RdfXmlReader::new(xml.as_bytes()).parse().unwrap();
+    assert!(triples.clone().is_isomorphic(&mut reread));
+}
+
+#[test]
+fn shared_blank_nodes_use_node_ids() {
+    let triples = Graph::new(r#"
+        @prefix : <http://example.org/> .
+        :a :p _:shared, [ :q _:loop ] .
+        :b :p _:shared .
+        _:shared :q "x" .
+        _:loop :q _:loop .
+        _:x :q _:y .
+        _:y :q _:x .
+    "#).unwrap().parse().unwrap();
+    let xml = RdfXmlWriter::new(&triples).prefix("", Iri::parse("http://example.org/").unwrap())
+        .to_xml()
+        .unwrap();
+
+    assert_eq!(xml.matches("rdf:nodeID=\"").count(), 8, "{}", xml);
+
+    let mut reread = RdfXmlReader::new(xml.as_bytes()).parse().unwrap();
+    assert!(triples.clone().is_isomorphic(&mut reread));
+}
+
+#[test]
+fn unwritable_predicate() {
+    let triples = Graph::new("<http://example.org/a> <http://example.org/1> 1 .")
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    match RdfXmlWriter::new(&triples).to_xml() {
+        Err(Error::Unwritable { triple, index, .. }) => {
+            assert_eq!(triple, "<http://example.org/a> <http://example.org/1> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .");
+            assert_eq!(index, 0);
+        }
+        other => panic!("expected an unwritable triple, got {:?}", other),
+    }
+}
+
+#[test]
+fn xml_literals_are_only_written_as_xml_when_well_formed() {
+    let triples = Graph::new(r#"
+        @prefix : <http://example.org/> .
+        @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+        :a :p "<a"^^rdf:XMLLiteral, "<b xmlns=\"http://example.org/\">c &amp; d</b>"^^rdf:XMLLiteral .
+        :a :q "<b>not canonical</b >"^^rdf:XMLLiteral .
+    "#).unwrap().parse().unwrap();
+    let xml = RdfXmlWriter::new(&triples).to_xml().unwrap();
+
+    assert!(xml.contains("<ns0:p rdf:datatype=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral\">&lt;a</ns0:p>"), "{}", xml);
+    assert!(xml.contains("<ns0:p rdf:parseType=\"Literal\"><b xmlns=\"http://example.org/\">c &amp; d</b></ns0:p>"), "{}", xml);
+    assert!(xml.contains("&lt;b&gt;not canonical&lt;/b &gt;"), "{}", xml);
+
+    let mut reread = RdfXmlReader::new(xml.as_bytes()).parse().unwrap();
+    assert!(triples.clone().is_isomorphic(&mut reread), "{}", xml);
+}
+
+#[test]
+fn characters_xml_forbids_are_an_error() {
+    let triples = Graph::new(r#"
+        <http://example.org/a> <http://example.org/p> "ok\t\r\n" .
+        <http://example.org/a> <http://example.org/p> "not \u0001 ok" .
+    "#).unwrap().parse().unwrap();
+
+    match RdfXmlWriter::new(&triples).to_xml() {
+        Err(Error::Unwritable { message, index, .. }) => {
+            assert_eq!(message, "U+0001 isn't allowed in XML 1.0");
+            assert_eq!(index, 1);
+        }
+        other => panic!("expected an unwritable triple, got {:?}", other),
+    }
+
+    let triples = Graph::new(r#"<http://example.org/a> <http://example.org/p> "ok\t\r\n�" ."#)
+        .unwrap()
+        .parse()
+        .unwrap();
+    let xml = RdfXmlWriter::new(&triples).to_xml().unwrap();
+    let mut reread = RdfXmlReader::new(xml.as_bytes()).parse().unwrap();
+    assert!(triples.clone().is_isomorphic(&mut reread), "{}", xml);
+}
+
+#[test]
+fn prefixes_xml_can_not_declare_are_replaced() {
+    let triples = Graph::new(r#"
+        <http://example.org/a> <http://example.org/p> "x"@ar--rtl .
+        <http://example.org/a> <http://example.com/q> <http://example.net/b> .
+    "#).unwrap().parse().unwrap();
+    let xml = RdfXmlWriter::new(&triples)
+        .prefix("its", Iri::parse("http://example.org/").unwrap())
+        .prefix("a b", Iri::parse("http://example.com/").unwrap())
+        .prefix("xmlns", Iri::parse("http://example.net/").unwrap())
+        .to_xml()
+        .unwrap();
+
+    assert_eq!(xml.matches("xmlns:its=").count(), 1, "{}", xml);
+    assert!(xml.contains("xmlns:its=\"http://example.org/\""), "{}", xml);
+    assert!(xml.contains("xmlns:ns0=\"http://www.w3.org/2005/11/its\""), "{}", xml);
+    assert!(xml.contains("<its:p xml:lang=\"ar\" ns0:dir=\"rtl\">x</its:p>"), "{}", xml);
+    assert!(!xml.contains("a b"), "{}", xml);
+    assert!(!xml.contains("xmlns:xmlns"), "{}", xml);
+
+    let mut reread = RdfXmlReader::new(xml.as_bytes()).parse().unwrap();
+    assert!(triples.clone().is_isomorphic(&mut reread), "{}", xml);
+}
@@ -0,0 +1,75 @@
+extern crate chelone;
+
+use std::io::BufReader;
+
+use chelone::labeller::CounterLabeller;
+use chelone::{Error, Graph, Position, TurtleReader};
+
+#[test]
+fn statements_spanning_reads() {
+    let mut input = String::from("@prefix : <http://example.org/> .\n");
+
+    for i in 0..2000 {
+        input += &format!(":s{} :p \"\"\"a long\nstring\"\"\", ( 1\n 2 ) ;\n    :q [ :r {} ] .\n", i, i);
+    }
+
+    let mut graph = Graph::new(&input).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+    let expected = graph.parse().unwrap();
+    let mut reader = TurtleReader::new(BufReader::with_capacity(16, input.as_bytes()));
+    reader.set_labeller(CounterLabeller::new("b"));
+    let triples = reader.parse().unwrap();
+
+    assert_eq!(expected.0, triples.0);
+}
+
+#[test]
+fn byte_order_mark() {
+    let input = "\u{FEFF}<http://a.example/s> <http://a.example/p> <http://a.example/o> .";
+
+    assert_eq!(TurtleReader::new(input.as_bytes()).parse().unwrap().len(), 1);
+    assert_eq!(Graph::new(input).unwrap().parse().unwrap().len(), 1);
+}
+
+#[test]
+fn invalid_utf8() {
+    let input = b"<http://a.example/s> <http://a.example/p> \"a\" .\n\
+                  <http://a.example/s> <http://a.example/p> \"\xC3\xA9\xFF\" .\n";
+
+    match TurtleReader::new(&input[..]).parse() {
+        Err(Error::InvalidUtf8 { position }) => {
+            assert_eq!(position, Position::new(2, 45));
+        }
+        result => panic!("Expected invalid UTF-8, got: {:?}", result),
+    }
+}
+
+#[test]
+fn error_position_after_earlier_statements() {
+    let input = "<http://a.example/s> <http://a.example/p> 1 . \
+                 <http://a.example/s> <http://a.example/p> 2 .\n\
+                 \n  <http://a.example/s> ex:p 3 .\n";
+
+    let error = TurtleReader::new(input.as_bytes()).parse().unwrap_err();
+
+    assert_eq!(error, Graph::new(input).unwrap().parse().unwrap_err());
+    assert_eq!(error.position(), Some(Position::new(3, 24)));
+}
+
+#[test]
+fn graph_yields_triples_before_a_later_syntax_error() {
+    let input = "@prefix : <http://example.org/> .\n:a :b :c .\n:d :e :f, .\n";
+    let mut triples = Graph::new(input).unwrap().triples();
+
+    assert_eq!(triples.next().unwrap().unwrap().to_string(),
+               "<http://example.org/a> <http://example.org/b> <http://example.org/c> .");
+
+    match triples.next() {
+        Some(Err(error @ Error::Syntax { .. })) => {
+            assert_eq!(error, TurtleReader::new(input.as_bytes()).parse().unwrap_err());
+            assert_eq!(error.position().unwrap().line, 3);
+        }
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+    assert!(triples.next().is_none());
+}
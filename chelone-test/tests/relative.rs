@@ -0,0 +1,111 @@
+extern crate chelone;
+
+use chelone::object::Object;
+use chelone::subject::Subject;
+use chelone::{Dataset, Error, Graph, Iri, ParserOptions, Quad, RelativeIri, TriGWriter, Triples};
+
+fn keep() -> ParserOptions {
+    ParserOptions::new().keep_relative_iris(true)
+}
+
+fn parse(source: &str, options: ParserOptions) -> Result<Triples, Error> {
+    Graph::with_options(source, options)?.parse()
+}
+
+fn relative(iri: &str) -> RelativeIri {
+    RelativeIri(String::from(iri))
+}
+
+#[test]
+fn kept_as_relative_iris() {
+    let source = "<foo> <http://a/p> <#bar> .\n<http://a/s> <http://a/p> <http://a/o> .";
+    let triples = parse(source, keep()).unwrap();
+
+    assert_eq!(triples[0].subject, Subject::RelativeIri(relative("foo")));
+    assert_eq!(triples[0].object, Object::RelativeIri(relative("#bar")));
+    assert_eq!(triples[1].object, Object::Iri(Iri::parse("http://a/o").unwrap()));
+    assert!(triples[0].is_relative());
+    assert!(!triples[1].is_relative());
+    assert_eq!(triples[0].to_string(), "<foo> <http://a/p> <#bar> .");
+}
+
+#[test]
+fn quoted_triples() {
+    let triples = parse("<< <s> <http://a/p> <o> >> <http://a/q> <r> .", keep()).unwrap();
+
+    assert!(triples[0].is_relative());
+    assert_eq!(triples[0].to_string(), "<< <s> <http://a/p> <o> >> <http://a/q> <r> .");
+
+    let base = Iri::parse("http://example.org/dir/").unwrap();
+    let resolved = triples.resolve(&base).unwrap();
+
+    assert!(!resolved[0].is_relative());
+    assert_eq!(resolved[0].to_string(),
+               "<< <http://example.org/dir/s> <http://a/p> <http://example.org/dir/o> >> \
+                <http://a/q> <http://example.org/dir/r> .");
+}
+
+#[test]
+fn resolved_when_there_is_a_base() {
+    let source = "@base <http://example.org/> .\n<foo> <http://a/p> <bar> .";
+    let triples = parse(source, keep()).unwrap();
+    assert!(!triples[0].is_relative());
+    assert_eq!(triples[0].subject, Subject::Iri(Iri::parse("http://example.org/foo").unwrap()));
+
+    let options = keep().base(Iri::parse("http://example.com/").unwrap());
+    let triples = parse("<foo> <http://a/p> <bar> .", options).unwrap();
+    assert!(!triples[0].is_relative());
+}
+
+#[test]
+fn predicates_follow_the_policy() {
+    match parse("<foo> <p> <bar> .", keep()) {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "p"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+
+    match parse(r#"<foo> <http://a/p> "1"^^<int> ."#, keep()) {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "int"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+
+    match parse("<foo> <http://a/p> <bar> .", ParserOptions::new()) {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "foo"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+}
+
+#[test]
+fn graph_names_are_not_kept() {
+    let graph = Graph::trig_with_options("<g> { <s> <http://a/p> <o> . }", keep()).unwrap();
+
+    match graph.parse_dataset() {
+        Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "g"),
+        result => panic!("Expected an invalid IRI, got: {:?}", result),
+    }
+
+    let graph = Graph::trig_with_options("<s> <http://a/p> <o> .", keep()).unwrap();
+    let dataset = graph.parse_dataset().unwrap();
+
+    assert!(dataset.graph(None).unwrap()[0].is_relative());
+}
+
+#[test]
+fn resolved_when_writing() {
+    let triples = parse("<foo> <http://a/p> <bar> .", keep()).unwrap();
+    let mut dataset = Dataset::new();
+
+    for triple in triples {
+        dataset.insert(Quad::new(triple.subject, triple.predicate, triple.object, None));
+    }
+
+    assert_eq!(TriGWriter::new(&dataset).to_string(), "<foo> <http://a/p> <bar> .\n");
+
+    let base = Iri::parse("http://example.org/").unwrap();
+    let trig = TriGWriter::new(&dataset)
+        .prefix("ex", base.clone())
+        .base(base)
+        .to_string();
+
+    assert!(trig.ends_with("ex:foo <http://a/p> ex:bar .\n"), "{}", trig);
+}
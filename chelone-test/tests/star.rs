@@ -0,0 +1,88 @@
+extern crate chelone;
+
+use chelone::labeller::CounterLabeller;
+use chelone::{
+    Dataset, Error, Graph, Iri, JsonLdWriter, NTriplesReader, RdfXmlWriter, TriGWriter, Triples,
+};
+
+fn turtle(input: &str) -> Triples {
+    Graph::new(input).unwrap().parse().unwrap()
+}
+
+#[test]
+fn quoted_blank_nodes_are_isomorphic() {
+    let mut a = turtle(r#"
+        @prefix : <http://example.org/> .
+        _:a :p :o .
+        << _:a :p :o >> :q << _:x :r "v" >> .
+    "#);
+    let mut b = turtle(r#"
+        @prefix : <http://example.org/> .
+        _:b :p :o .
+        << _:b :p :o >> :q << _:y :r "v" >> .
+    "#);
+
+    assert!(a.is_isomorphic(&mut b));
+}
+
+#[test]
+fn different_quoted_triples_are_not_isomorphic() {
+    let mut a = turtle("<< <http://a> <http://b> <http://c> >> <http://q> 1 .");
+    let mut b = turtle("<< <http://a> <http://b> <http://d> >> <http://q> 1 .");
+
+    assert!(!a.is_isomorphic(&mut b));
+}
+
+#[test]
+fn display_is_ntriples_star() {
+    let mut graph = Graph::new(r#"
+        @prefix : <http://example.org/> .
+        :s :p [ :q 1 ] {| :r << :a :b "c"@en >> |} .
+    "#).unwrap();
+    graph.set_labeller(CounterLabeller::new("b"));
+    let triples = graph.parse().unwrap();
+    let output = triples.to_string();
+    let mut reread = NTriplesReader::new(output.as_bytes()).parse().unwrap();
+
+    assert!(output.contains("<< <http://example.org/s> <http://example.org/p> _:b1 >>"));
+    assert!(triples.clone().is_isomorphic(&mut reread));
+}
+
+#[test]
+fn trig_writer_writes_quoted_triples() {
+    let triples = turtle(r#"
+        @prefix : <http://example.org/> .
+        :s :p :o {| :source << :a a :C >> |} .
+    "#);
+    let dataset = triples.into_iter().map(Into::into).collect::<Dataset>();
+    let output = TriGWriter::new(&dataset)
+        .prefix("", Iri::parse("http://example.org/").unwrap())
+        .to_string();
+
+    assert_eq!(output, "@prefix : <http://example.org/> .
+
+:s :p :o .
+
+<< :s :p :o >> :source << :a a :C >> .
+");
+}
+
+#[test]
+fn rdfxml_writer_rejects_quoted_triples() {
+    let triples = turtle("<http://a> <http://example.org/p> << <http://a> <http://b> <http://c> >> .");
+
+    match RdfXmlWriter::new(&triples).to_xml() {
+        Err(Error::Unwritable { message, .. }) => assert_eq!(message, "RDF/XML has no syntax for quoted triples"),
+        other => panic!("Expected an unwritable triple, got: {:?}", other),
+    }
+}
+
+#[test]
+fn jsonld_writer_rejects_quoted_triples() {
+    let triples = turtle("<< <http://a> <http://b> <http://c> >> <http://q> 1 .");
+
+    match JsonLdWriter::new(&triples).to_value() {
+        Err(Error::JsonLd { code, .. }) => assert_eq!(code, "invalid embedded node"),
+        result => panic!("Expected a JSON-LD error, got: {:?}", result),
+    }
+}
@@ -1,16 +1,405 @@
 extern crate chelone;
+extern crate serde_json;
 
+use std::fmt::Debug;
 use std::fs;
+use std::mem;
 
-use chelone::{Graph, Triples, Iri};
+use chelone::labeller::CounterLabeller;
+use chelone::{
+    Dataset, DirectoryLoader, Error, Graph, GraphName, JsonLdReader, JsonLdWriter, LenientReader,
+    N3Document, N3Reader, NQuadsReader, NTriplesReader, ParallelReader,
+    RdfXmlReader, RdfXmlWriter, SyntaxTree, TriGWriter, Triples, TurtleReader, Iri,
+};
 
 include!(concat!(env!("OUT_DIR"), "/tests.rs"));
 
-fn read_to_triples(path: &'static str, base: &'static str) -> Triples {
+/// Keeps the labels blank nodes have in the document, so documents that
+/// were written out can be compared label for label.
+fn document_labels() -> impl FnMut(Option<&str>) -> String {
+    let mut generated = 0;
+
+    move |label: Option<&str>| match label {
+        Some(label) => label.to_owned(),
+        None => {
+            generated += 1;
+            format!("generated{}", generated)
+        }
+    }
+}
+
+/// Sets `graph` up the same way for the reference parse and every
+/// cross-check, so their triples can be compared label for label.
+fn prepare<'a>(graph: Result<Graph<'a>, Error>, base: &str) -> Result<Graph<'a>, Error> {
+    graph.map(|mut graph| {
+        graph.set_base(Iri::parse(base).unwrap());
+        graph.set_labeller(CounterLabeller::new("b"));
+        graph
+    })
+}
+
+/// Parses a Turtle test with `Graph::new`, which the other parsers are
+/// checked against by the `check_*` functions.
+fn read_to_triples(path: &'static str, base: &'static str)
+    -> Result<Triples, Error>
+{
+    let input = fs::read_to_string(path).unwrap();
+
+    prepare(Graph::new(&input), base).and_then(Graph::parse)
+}
+
+fn check_syntax_tree(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+
+    match SyntaxTree::new(&input) {
+        Ok(tree) => assert_eq!(tree.to_string(), input, "SyntaxTree printed a different document"),
+        Err(error) => assert!(read_to_triples(path, base).is_err(), "SyntaxTree: {:?}", error),
+    }
+}
+
+fn check_parse_with(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let result = read_to_triples(path, base);
+    let mut handled = Triples::default();
+    let handled_result = prepare(Graph::new(&input), base)
+        .and_then(|graph| graph.parse_with(&mut handled));
+
+    match (&result, handled_result) {
+        (Ok(a), Ok(())) => assert_eq!(a.0, handled.0, "Graph::parse_with gave different triples"),
+        (Err(a), Err(b)) => assert_eq!(*a, b),
+        (a, b) => panic!("Graph: {:?}\nGraph::parse_with: {:?}", a, b),
+    }
+}
+
+fn check_turtle_reader(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let result = read_to_triples(path, base);
+    let mut reader = TurtleReader::new(input.as_bytes());
+    reader.set_base(Iri::parse(base).unwrap());
+    reader.set_labeller(CounterLabeller::new("b"));
+    let streamed = reader.parse();
+
+    match (&result, &streamed) {
+        (Ok(a), Ok(b)) => assert_eq!(a.0, b.0, "TurtleReader parsed different triples"),
+        // pest's messages differ as the reader parses a statement at a time.
+        (Err(a), Err(b)) if mem::discriminant(a) == mem::discriminant(b) &&
+                            a.position() == b.position() => {}
+        _ => panic!("Graph: {:?}\nTurtleReader: {:?}", result, streamed),
+    }
+}
+
+fn check_lenient_reader(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let result = read_to_triples(path, base);
+    let mut lenient = LenientReader::new(&input);
+    lenient.set_base(Iri::parse(base).unwrap());
+    lenient.set_labeller(CounterLabeller::new("b"));
+    let (recovered, diagnostics) = lenient.parse();
+
+    match (&result, diagnostics.first()) {
+        (Ok(triples), None) => assert_eq!(triples.0, recovered.0, "LenientReader parsed different triples"),
+        (Err(a), Some(b)) if mem::discriminant(a) == mem::discriminant(&b.error) &&
+                             a.position() == b.error.position() => {}
+        _ => panic!("Graph: {:?}\nLenientReader: {:?}", result, diagnostics),
+    }
+}
+
+fn check_fast(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let result = read_to_triples(path, base);
+    let fast = prepare(Graph::fast(&input), base).and_then(Graph::parse);
+
+    match (&result, &fast) {
+        (Ok(a), Ok(b)) => assert_eq!(a.0, b.0, "Graph::fast parsed different triples"),
+        // pest checks the syntax before anything else, while the fast
+        // parser stops at the first error of any kind.
+        (Err(a @ Error::Syntax { .. }), Err(b)) if b.position() <= a.position() => {}
+        (Err(Error::Syntax { .. }), Err(Error::Syntax { .. })) => {}
+        (Err(a), Err(b)) if mem::discriminant(a) == mem::discriminant(b) &&
+                            a.position() == b.position() => {}
+        _ => panic!("Graph: {:?}\nGraph::fast: {:?}", result, fast),
+    }
+}
+
+fn check_parallel_reader(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    // The chunks are parsed by the fast parser, so they stop at the same
+    // errors it does.
+    let fast = prepare(Graph::fast(&input), base).and_then(Graph::parse);
+
+    // Splitting after every statement puts the most strain on the chunking.
+    let mut parallel = ParallelReader::turtle(input.as_bytes());
+    parallel.set_base(Iri::parse(base).unwrap());
+    parallel.set_chunk_size(1);
+    let parallel = parallel.parse();
+
+    match (&fast, &parallel) {
+        (Ok(a), Ok(b)) => {
+            assert!(a.clone().is_isomorphic(&mut b.clone()), "ParallelReader parsed different triples")
+        }
+        (Err(a), Err(b)) if mem::discriminant(a) == mem::discriminant(b) &&
+                            a.position() == b.position() => {}
+        _ => panic!("Graph::fast: {:?}\nParallelReader: {:?}", fast, parallel),
+    }
+}
+
+fn check_located_triples(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let triples = match read_to_triples(path, base) {
+        Ok(triples) => triples,
+        Err(_) => return,
+    };
+
+    let located = prepare(Graph::new(&input), base).unwrap()
+        .located_triples()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(triples.0, located.iter().map(|t| t.value.clone()).collect::<Vec<_>>(),
+               "located_triples parsed different triples");
+    assert!(located.iter().all(|t| t.object.bytes.start < t.object.bytes.end &&
+                                   t.object.bytes.end <= input.len()),
+            "located_triples gave object bytes outside the input");
+
+    let fast = prepare(Graph::fast(&input), base).unwrap()
+        .located_triples()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(located, fast, "Graph::fast located triples differently");
+}
+
+/// Borrowed triples are the same once they're converted.
+fn check_triple_refs(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+    let triples = match read_to_triples(path, base) {
+        Ok(triples) => triples,
+        Err(_) => return,
+    };
+
+    let borrowed = prepare(Graph::new(&input), base).unwrap()
+        .triple_refs()
+        .map(|triple| triple.unwrap().to_owned().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(triples.0, borrowed, "triple_refs parsed different triples");
+
+    let borrowed = prepare(Graph::fast(&input), base).unwrap()
+        .triple_refs()
+        .map(|triple| triple.unwrap().to_owned().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(triples.0, borrowed, "Graph::fast's triple_refs parsed different triples");
+}
+
+/// Every Turtle document is also a TriG document.
+fn check_trig(path: &'static str, base: &'static str) {
+    let input = fs::read_to_string(path).unwrap();
+
+    if let Ok(triples) = read_to_triples(path, base) {
+        let trig = prepare(Graph::trig(&input), base).and_then(Graph::parse).unwrap();
+
+        assert_eq!(triples.0, trig.0, "TriG parsed different triples");
+    }
+}
+
+fn read_to_ntriples(path: &'static str) -> Result<Triples, Error> {
+    let input = fs::read(path).unwrap();
+    let mut reader = NTriplesReader::new(input.as_slice());
+    reader.set_labeller(document_labels());
+    let result = reader.parse();
+
+    let mut parallel = ParallelReader::ntriples(input.as_slice());
+    parallel.set_labeller(document_labels());
+    parallel.set_chunk_size(1);
+
+    match (&result, &parallel.parse()) {
+        (Ok(a), Ok(b)) => assert_eq!(a.0, b.0, "ParallelReader parsed different triples"),
+        (Err(a), Err(b)) if mem::discriminant(a) == mem::discriminant(b) &&
+                            a.position() == b.position() => {}
+        (a, b) => panic!("NTriplesReader: {:?}\nParallelReader: {:?}", a, b),
+    }
+
+    result
+}
+
+fn read_to_nquads(path: &'static str) -> Result<Dataset, Error> {
+    let input = fs::read(path).unwrap();
+    let mut reader = NQuadsReader::new(input.as_slice());
+    reader.set_labeller(document_labels());
+    let dataset = reader.parse()?;
+
+    let written = dataset.to_string();
+    let mut reader = NQuadsReader::new(written.as_bytes());
+    reader.set_labeller(document_labels());
+    let reread = reader.parse()
+        .unwrap_or_else(|e| panic!("Couldn't reread written N-Quads: {}\n{}", e, written));
+
+    assert_eq!(dataset.quads().collect::<Vec<_>>(), reread.quads().collect::<Vec<_>>());
+
+    Ok(dataset)
+}
+
+fn read_to_trig(path: &'static str, base: &'static str) -> Result<Dataset, Error> {
     let input = fs::read_to_string(path).unwrap();
-    let mut graph = Graph::new(&input).unwrap_or_else(|e| panic!("{}", e));
+    let mut graph = Graph::trig(&input)?;
     graph.set_base(Iri::parse(base).unwrap());
-    graph.parse()
+
+    let mut quads = graph.quads();
+    let dataset = quads.by_ref().collect::<Result<Dataset, Error>>()?;
+    let written = TriGWriter::new(&dataset).prefixs(quads.prefixs().clone()).to_string();
+    let reread = Graph::trig(&written)
+        .and_then(|mut graph| {
+            graph.set_labeller(document_labels());
+            graph.parse_dataset()
+        })
+        .unwrap_or_else(|e| panic!("Couldn't reread written TriG: {}\n{}", e, written));
+
+    // The writer groups triples by subject, so they may come back in a
+    // different order.
+    let mut expected = dataset.quads().collect::<Vec<_>>();
+    let mut actual = reread.quads().collect::<Vec<_>>();
+    expected.sort();
+    actual.sort();
+
+    assert_eq!(expected, actual);
+
+    Ok(dataset)
+}
+
+fn read_to_rdfxml(path: &'static str, base: &'static str) -> Result<Triples, Error> {
+    let mut reader = RdfXmlReader::new(fs::File::open(path).unwrap());
+    reader.set_base(Iri::parse(base).unwrap());
+
+    let triples = reader.parse()?;
+    let written = RdfXmlWriter::new(&triples).to_xml().unwrap();
+    let mut reread = RdfXmlReader::new(written.as_bytes()).parse()
+        .unwrap_or_else(|e| panic!("Couldn't reread written RDF/XML: {}\n{}", e, written));
+
+    assert!(triples.clone().is_isomorphic(&mut reread), "Reread different triples:\n{}", written);
+
+    Ok(triples)
+}
+
+fn read_to_n3(path: &'static str, base: &'static str) -> Result<N3Document, Error> {
+    let input = fs::read_to_string(path).unwrap();
+    let mut reader = N3Reader::new(&input)?;
+    reader.set_base(Iri::parse(base).unwrap());
+
+    let document = reader.parse()?;
+    let written = document.to_string();
+    let reread = N3Reader::new(&written)
+        .and_then(|mut reader| {
+            reader.set_labeller(document_labels());
+            reader.parse()
+        })
+        .unwrap_or_else(|e| panic!("Couldn't reread written N3: {}\n{}", e, written));
+
+    assert_eq!(document.formula, reread.formula);
+
+    Ok(document)
+}
+
+fn jsonld_reader(path: &'static str, base: &'static str) -> JsonLdReader<fs::File> {
+    let directory = &base[..base.rfind('/').unwrap() + 1];
+    let mut reader = JsonLdReader::new(fs::File::open(path).unwrap());
+    reader.set_base(Iri::parse(base).unwrap());
+    reader.set_loader(DirectoryLoader::new(Iri::parse(directory).unwrap(), "tests/jsonld"));
+
+    reader
+}
+
+fn read_to_jsonld(path: &'static str, base: &'static str) -> Result<Dataset, Error> {
+    jsonld_reader(path, base).parse_dataset()
+}
+
+fn expand_jsonld(path: &'static str, base: &'static str) -> Result<serde_json::Value, Error> {
+    jsonld_reader(path, base).expand()
+}
+
+fn read_json(path: &str) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn from_rdf_jsonld(path: &'static str) -> serde_json::Value {
+    let triples = read_to_nquads(path).unwrap().default_graph;
+
+    write_jsonld(&triples, JsonLdWriter::new(&triples), None)
+}
+
+fn compact_jsonld(path: &'static str, base: &'static str, context: &'static str)
+    -> serde_json::Value
+{
+    let triples = jsonld_reader(path, base).parse().unwrap();
+    let context = read_json(context)["@context"].take();
+
+    write_jsonld(&triples, JsonLdWriter::new(&triples).context(context), Some(base))
+}
+
+/// Writes `triples` with `writer`, checking that reading the output back
+/// gives the same graph.
+fn write_jsonld(triples: &Triples, writer: JsonLdWriter, base: Option<&str>)
+    -> serde_json::Value
+{
+    let output = writer.to_json_string().unwrap();
+    let mut reader = JsonLdReader::new(output.as_bytes());
+
+    if let Some(base) = base {
+        reader.set_base(Iri::parse(base).unwrap());
+    }
+
+    let mut result = reader.parse().unwrap();
+
+    if !result.is_isomorphic(&mut triples.clone()) {
+        compare(result, triples.clone());
+    }
+
+    serde_json::from_str(&output).unwrap()
+}
+
+fn assert_same_dataset(mut result: Dataset, mut expected: Dataset) {
+    if !result.default_graph.is_isomorphic(&mut expected.default_graph) {
+        compare(result.default_graph, expected.default_graph);
+    }
+
+    assert_eq!(result.named_graphs.len(), expected.named_graphs.len());
+
+    for (name, expected) in &mut expected.named_graphs {
+        if let GraphName::Iri(_) = *name {
+            let result = result.named_graphs.get_mut(name)
+                .unwrap_or_else(|| panic!("Missing graph {}", name));
+
+            if !result.is_isomorphic(expected) {
+                compare(result.clone(), expected.clone());
+            }
+        }
+    }
+}
+
+fn assert_syntax_error<T: Debug>(result: Result<T, Error>) {
+    match result {
+        Err(Error::Syntax { .. }) |
+        Err(Error::UndefinedPrefix { .. }) |
+        Err(Error::InvalidIri { .. }) |
+        Err(Error::InvalidEscape { .. }) => {}
+        result => panic!("Expected a syntax error, got: {:?}", result),
+    }
+}
+
+fn assert_json_ld_error<T: Debug>(result: Result<T, Error>, expected: &str) {
+    match result {
+        Err(Error::JsonLd { code, .. }) if code == expected => {}
+        result => panic!("Expected a {:?} error, got: {:?}", expected, result),
+    }
+}
+
+fn assert_eval_error(result: Result<Triples, Error>) {
+    // Some bad IRIs are already rejected by the grammar.
+    match result {
+        Err(Error::InvalidIri { .. }) |
+        Err(Error::Syntax { .. }) => {}
+        result => panic!("Expected an invalid IRI error, got: {:?}", result),
+    }
 }
 
 fn compare(a: Triples, b: Triples) -> ! {
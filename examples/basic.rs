@@ -19,7 +19,7 @@ const TURTLE: &str = r##"
 
 fn main() {
     let graph = Graph::new(TURTLE).unwrap_or_else(|e| panic!("{}", e));
-    let triples = graph.parse();
+    let triples = graph.parse().unwrap_or_else(|e| panic!("{}", e));
 
     println!("{}", triples);
 }
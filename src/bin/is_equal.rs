@@ -3,7 +3,7 @@ extern crate url;
 
 use std::env;
 
-use chelone::{Graph, Triples, Iri};
+use chelone::{TurtleReader, Triples, Iri};
 
 fn main() {
     let url = Iri::parse("https://www.w3.org/2013/TurtleTests/").unwrap();
@@ -23,17 +23,10 @@ fn main() {
 
 fn read_to_graph(path: &str, base: Iri) -> Triples {
     use std::fs::File;
-    use std::io::Read;
+    use std::io::BufReader;
 
-    let mut input = String::new();
-
-    File::open(path)
-        .unwrap()
-        .read_to_string(&mut input)
-        .unwrap();
-
-    let mut graph = Graph::new(&input).unwrap_or_else(|e| panic!("{}", e));
-    graph.set_base(base);
-    graph.parse()
+    let mut reader = TurtleReader::new(BufReader::new(File::open(path).unwrap()));
+    reader.set_base(base);
+    reader.parse().unwrap_or_else(|e| panic!("{}", e))
 }
 
@@ -0,0 +1,350 @@
+//! Terms that borrow from the source they were parsed from.
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use iri::{BlankNode, Iri, RelativeIri};
+use literal::{fmt_string, split_tag, Direction, Literal};
+use object::Object;
+use subject::Subject;
+use triple::Triple;
+
+/// An IRI that borrows its text from the source where it can, as made by
+/// `Graph::triple_refs`.
+///
+/// Unlike an `Iri` it's only checked against Turtle's grammar, so `to_owned`
+/// can still find that it's malformed.
+#[derive(Clone)]
+pub struct IriRef<'a>(Repr<'a>);
+
+#[derive(Clone)]
+enum Repr<'a> {
+    Text(Cow<'a, str>),
+    /// An IRI that has already been parsed, which is kept so it doesn't have
+    /// to be parsed again.
+    Parsed(Iri),
+}
+
+impl<'a> IriRef<'a> {
+    /// Creates an `IriRef` from its text, without the wrapping `<>`.
+    pub fn new<S: Into<Cow<'a, str>>>(iri: S) -> Self {
+        IriRef(Repr::Text(iri.into()))
+    }
+
+    /// The text of the IRI, without the wrapping `<>`.
+    pub fn as_str(&self) -> &str {
+        match self.0 {
+            Repr::Text(ref text) => text,
+            Repr::Parsed(ref iri) => iri.as_str(),
+        }
+    }
+
+    /// Whether the text is borrowed rather than owned.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Repr::Text(Cow::Borrowed(_)))
+    }
+
+    /// Parses the IRI into an `Iri`.
+    pub fn to_owned(&self) -> Result<Iri, url::ParseError> {
+        match self.0 {
+            Repr::Text(ref text) => Iri::parse(text),
+            Repr::Parsed(ref iri) => Ok(iri.clone()),
+        }
+    }
+
+    /// Like `to_owned`, but an IRI that was already parsed is moved rather
+    /// than cloned.
+    pub fn into_owned(self) -> Result<Iri, url::ParseError> {
+        match self.0 {
+            Repr::Text(text) => Iri::parse(&text),
+            Repr::Parsed(iri) => Ok(iri),
+        }
+    }
+}
+
+impl<'a> From<Iri> for IriRef<'a> {
+    fn from(iri: Iri) -> Self {
+        IriRef(Repr::Parsed(iri))
+    }
+}
+
+impl<'a> PartialEq for IriRef<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'a> Eq for IriRef<'a> {}
+
+impl<'a> PartialOrd for IriRef<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for IriRef<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<'a> Hash for IriRef<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<'a> fmt::Debug for IriRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IriRef").field(&self.as_str()).finish()
+    }
+}
+
+impl<'a> fmt::Display for IriRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}>", self.as_str())
+    }
+}
+
+/// A `Literal` that borrows its value and language tag from the source
+/// where it can.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LiteralRef<'a> {
+    /// The value of the literal, after unescaping.
+    pub value: Cow<'a, str>,
+    /// The language tag of the string.
+    pub language_tag: Option<Cow<'a, str>>,
+    /// The base direction of the string, which is only set along with a
+    /// language tag.
+    pub direction: Option<Direction>,
+    /// The iri type.
+    pub iri: IriRef<'a>,
+}
+
+impl<'a> LiteralRef<'a> {
+    /// Creates a literal with the datatype `iri`, or `xsd:string` if it's
+    /// `None`.
+    pub(crate) fn new(value: Cow<'a, str>, iri: Option<IriRef<'a>>) -> Self {
+        LiteralRef {
+            value,
+            language_tag: None,
+            direction: None,
+            iri: iri.unwrap_or_else(|| xsd_ref!("string")),
+        }
+    }
+
+    /// Creates a literal from a tag as written after `@`. See
+    /// `Literal::new_tagged`.
+    pub(crate) fn new_tagged(value: Cow<'a, str>, tag: &'a str) -> Self {
+        let (language, direction) = split_tag(tag);
+
+        LiteralRef {
+            value,
+            language_tag: Some(Cow::Borrowed(language)),
+            direction,
+            iri: match direction {
+                Some(_) => rdf_ref!("dirLangString"),
+                None => rdf_ref!("langString"),
+            },
+        }
+    }
+
+    /// Converts the literal into a `Literal`, parsing its datatype.
+    pub fn to_owned(&self) -> Result<Literal, url::ParseError> {
+        self.clone().into_owned()
+    }
+
+    /// Like `to_owned`, but reuses the value and language tag if they're
+    /// already owned.
+    pub fn into_owned(self) -> Result<Literal, url::ParseError> {
+        Ok(Literal {
+            value: self.value.into_owned(),
+            language_tag: self.language_tag.map(Cow::into_owned),
+            direction: self.direction,
+            iri: self.iri.into_owned()?,
+        })
+    }
+}
+
+/// Formats the literal as it would be written in N-Triples.
+impl<'a> fmt::Display for LiteralRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_string(f, &self.value)?;
+
+        match (&self.language_tag, self.direction) {
+            (Some(tag), Some(direction)) => write!(f, "@{}--{}", tag, direction),
+            (Some(tag), None) => write!(f, "@{}", tag),
+            (None, _) => write!(f, "^^{}", self.iri),
+        }
+    }
+}
+
+/// A `Subject` that borrows from the source where it can.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SubjectRef<'a> {
+    /// An IRI
+    Iri(IriRef<'a>),
+    /// A blank node
+    BlankNode(BlankNode),
+    /// A quoted triple, written `<< s p o >>`.
+    Triple(Box<TripleRef<'a>>),
+    /// A relative IRI that hasn't been resolved.
+    RelativeIri(RelativeIri),
+}
+
+impl<'a> SubjectRef<'a> {
+    /// Converts the subject into a `Subject`, parsing its IRIs.
+    pub fn to_owned(&self) -> Result<Subject, url::ParseError> {
+        self.clone().into_owned()
+    }
+
+    /// Like `to_owned`, but moves what's already owned.
+    pub fn into_owned(self) -> Result<Subject, url::ParseError> {
+        Ok(match self {
+            SubjectRef::Iri(iri) => Subject::Iri(iri.into_owned()?),
+            SubjectRef::BlankNode(node) => Subject::BlankNode(node),
+            SubjectRef::Triple(triple) => Subject::from(triple.into_owned()?),
+            SubjectRef::RelativeIri(relative) => Subject::RelativeIri(relative),
+        })
+    }
+}
+
+impl<'a> From<TripleRef<'a>> for SubjectRef<'a> {
+    fn from(triple: TripleRef<'a>) -> Self {
+        SubjectRef::Triple(Box::new(triple))
+    }
+}
+
+impl<'a> fmt::Display for SubjectRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SubjectRef::Iri(ref iri) => iri.fmt(f),
+            SubjectRef::BlankNode(ref node) => node.fmt(f),
+            SubjectRef::Triple(ref triple) => triple.fmt_quoted(f),
+            SubjectRef::RelativeIri(ref relative) => relative.fmt(f),
+        }
+    }
+}
+
+/// An `Object` that borrows from the source where it can.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectRef<'a> {
+    /// An Iri
+    Iri(IriRef<'a>),
+    /// A blank node
+    BlankNode(BlankNode),
+    /// A literal
+    Literal(LiteralRef<'a>),
+    /// A quoted triple, written `<< s p o >>`.
+    Triple(Box<TripleRef<'a>>),
+    /// A relative IRI that hasn't been resolved.
+    RelativeIri(RelativeIri),
+}
+
+impl<'a> ObjectRef<'a> {
+    /// Converts the object into an `Object`, parsing its IRIs.
+    pub fn to_owned(&self) -> Result<Object, url::ParseError> {
+        self.clone().into_owned()
+    }
+
+    /// Like `to_owned`, but moves what's already owned.
+    pub fn into_owned(self) -> Result<Object, url::ParseError> {
+        Ok(match self {
+            ObjectRef::Iri(iri) => Object::Iri(iri.into_owned()?),
+            ObjectRef::BlankNode(node) => Object::BlankNode(node),
+            ObjectRef::Literal(literal) => Object::Literal(literal.into_owned()?),
+            ObjectRef::Triple(triple) => Object::from(triple.into_owned()?),
+            ObjectRef::RelativeIri(relative) => Object::RelativeIri(relative),
+        })
+    }
+
+    /// Converts the object to a subject.
+    /// # Panics
+    /// If the object is an `ObjectRef::Literal`.
+    pub(crate) fn into_subject(self) -> SubjectRef<'a> {
+        match self {
+            ObjectRef::Iri(iri) => SubjectRef::Iri(iri),
+            ObjectRef::BlankNode(node) => SubjectRef::BlankNode(node),
+            ObjectRef::Triple(triple) => SubjectRef::Triple(triple),
+            ObjectRef::RelativeIri(relative) => SubjectRef::RelativeIri(relative),
+            ObjectRef::Literal(_) => panic!("Tried to convert literal to subject"),
+        }
+    }
+}
+
+impl<'a> From<TripleRef<'a>> for ObjectRef<'a> {
+    fn from(triple: TripleRef<'a>) -> Self {
+        ObjectRef::Triple(Box::new(triple))
+    }
+}
+
+impl<'a> fmt::Display for ObjectRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ObjectRef::Iri(ref iri) => iri.fmt(f),
+            ObjectRef::BlankNode(ref node) => node.fmt(f),
+            ObjectRef::Literal(ref literal) => literal.fmt(f),
+            ObjectRef::Triple(ref triple) => triple.fmt_quoted(f),
+            ObjectRef::RelativeIri(ref relative) => relative.fmt(f),
+        }
+    }
+}
+
+/// A `Triple` that borrows from the source where it can, made by
+/// `Graph::triple_refs`. Its IRIs and literals borrow their text unless it
+/// had to be changed, such as by unescaping, resolving a relative IRI, or
+/// expanding a prefixed name.
+///
+/// ```
+/// use chelone::Graph;
+///
+/// let graph = Graph::new(r#"<http://a/s> <http://a/p> "o", "\"q\"" ."#).unwrap();
+/// let triples = graph.triple_refs().collect::<Result<Vec<_>, _>>().unwrap();
+///
+/// assert!(triples[0].predicate.is_borrowed());
+/// assert_eq!(triples[1].to_string(), r#"<http://a/s> <http://a/p> "\"q\""^^<http://www.w3.org/2001/XMLSchema#string> ."#);
+///
+/// let owned = triples[0].to_owned().unwrap();
+/// assert_eq!(owned.to_string(), r#"<http://a/s> <http://a/p> "o"^^<http://www.w3.org/2001/XMLSchema#string> ."#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TripleRef<'a> {
+    /// The subject of the triple.
+    pub subject: SubjectRef<'a>,
+    /// The predicate of the triple.
+    pub predicate: IriRef<'a>,
+    /// The object of the triple.
+    pub object: ObjectRef<'a>,
+}
+
+impl<'a> TripleRef<'a> {
+    /// Instantiates a new TripleRef.
+    pub fn new(subject: SubjectRef<'a>, predicate: IriRef<'a>, object: ObjectRef<'a>) -> Self {
+        TripleRef { subject, predicate, object }
+    }
+
+    /// Converts the triple into a `Triple`, parsing its IRIs. Fails if one
+    /// of them isn't a valid IRI.
+    pub fn to_owned(&self) -> Result<Triple, url::ParseError> {
+        self.clone().into_owned()
+    }
+
+    /// Like `to_owned`, but moves what's already owned.
+    pub fn into_owned(self) -> Result<Triple, url::ParseError> {
+        Ok(Triple::new(self.subject.into_owned()?,
+                       self.predicate.into_owned()?,
+                       self.object.into_owned()?))
+    }
+
+    /// Writes the triple as a quoted triple term.
+    fn fmt_quoted(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<< {} {} {} >>", self.subject, self.predicate, self.object)
+    }
+}
+
+impl<'a> fmt::Display for TripleRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} .", self.subject, self.predicate, self.object)
+    }
+}
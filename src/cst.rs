@@ -0,0 +1,611 @@
+//! A lossless concrete syntax tree of a Turtle document, for tools that
+//! edit documents while keeping their layout and comments.
+use std::fmt;
+
+use pest::iterators::Pair;
+use pest::Parser;
+
+use error::{Error, Position};
+use limits::Limits;
+use parser::{Rule, TurtleParser};
+use triple::Triples;
+use {Graph, BYTE_ORDER_MARK};
+
+/// A Turtle document as a tree of nodes and tokens that keeps everything
+/// in the source, including whitespace and comments, so it prints back to
+/// exactly the text it was parsed from. The tree can be edited, either with
+/// helpers like `add_triple` and `rename_prefix` or through its nodes, and
+/// then printed or lowered to `Triples`.
+///
+/// ```
+/// use chelone::SyntaxTree;
+///
+/// let source = "@prefix ex: <http://example.org/> .\n\nex:a ex:b ex:c ; # First\n    ex:d ex:e .\n";
+/// let mut tree = SyntaxTree::new(source).unwrap();
+/// assert_eq!(tree.to_string(), source);
+///
+/// tree.add_triple("ex:a", "ex:f", "\"g\"").unwrap();
+/// tree.rename_prefix("ex", "x").unwrap();
+///
+/// assert_eq!(
+///     tree.to_string(),
+///     "@prefix x: <http://example.org/> .\n\nx:a x:b x:c ; # First\n    x:d x:e ;\n    x:f \"g\" .\n"
+/// );
+/// assert_eq!(tree.parse().unwrap().len(), 3);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxTree {
+    /// Whether the source started with a byte order mark, which is kept
+    /// outside of the tree.
+    byte_order_mark: bool,
+    root: Node,
+}
+
+/// A node of a `SyntaxTree`, for one of the rules of the Turtle grammar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    /// The grammar rule the node is for.
+    pub kind: NodeKind,
+    /// The nodes and tokens the node is made of, in the order they're
+    /// written.
+    pub children: Vec<Element>,
+}
+
+/// A child of a `Node`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Element {
+    /// A node for a nested grammar rule.
+    Node(Node),
+    /// A token of the source.
+    Token(Token),
+}
+
+/// A token of a `SyntaxTree`, the text of which is kept as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    /// What the token is.
+    pub kind: TokenKind,
+    /// The text of the token.
+    pub text: String,
+}
+
+/// The grammar rules that nodes are made for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// The whole document, `turtleDoc`.
+    TurtleDoc,
+    /// A directive or triples followed by a `.`, `statement`.
+    Statement,
+    /// A prefix or base declaration, `directive`.
+    Directive,
+    /// `@prefix p: <iri> .`, `prefixID`.
+    PrefixId,
+    /// `@base <iri> .`, `base`.
+    Base,
+    /// `PREFIX p: <iri>`, `sparqlPrefix`.
+    SparqlPrefix,
+    /// `BASE <iri>`, `sparqlBase`.
+    SparqlBase,
+    /// A subject and its predicates, `triples`.
+    Triples,
+    /// Predicates and their objects separated by `;`, `predicateObjectList`.
+    PredicateObjectList,
+    /// Objects separated by `,`, `objectList`.
+    ObjectList,
+    /// A predicate or `a`, `verb`.
+    Verb,
+    /// `subject`.
+    Subject,
+    /// `object`.
+    Object,
+    /// `literal`.
+    Literal,
+    /// A string with an optional language tag or datatype, `RDFLiteral`.
+    RdfLiteral,
+    /// `NumericLiteral`.
+    NumericLiteral,
+    /// `BooleanLiteral`.
+    BooleanLiteral,
+    /// `[ ... ]` with predicates, `blankNodePropertyList`.
+    BlankNodePropertyList,
+    /// `( ... )`, `collection`.
+    Collection,
+    /// `<< ... >>`, `quotedTriple`.
+    QuotedTriple,
+    /// The subject of a quoted triple, `qtSubject`.
+    QtSubject,
+    /// The object of a quoted triple, `qtObject`.
+    QtObject,
+    /// `{| ... |}`, `annotation`.
+    Annotation,
+    /// `iri`.
+    Iri,
+    /// `PrefixedName`.
+    PrefixedName,
+    /// `BlankNode`.
+    BlankNode,
+    /// `[ ]`, `ANON`.
+    Anon,
+}
+
+/// The kinds of token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A run of spaces, tabs and line breaks.
+    Whitespace,
+    /// A `#` and the rest of its line, without the line break.
+    Comment,
+    /// A word of the grammar, such as `@prefix`, `PREFIX`, `a` or `true`.
+    Keyword,
+    /// Punctuation such as `.`, `;`, `[` or `<<`.
+    Punctuation,
+    /// An IRI in angle brackets, `IRIREF`.
+    IriRef,
+    /// A prefixed name, or the prefix of a declaration, such as `ex:a` or
+    /// `ex:`.
+    PrefixedName,
+    /// A labelled blank node, `BLANK_NODE_LABEL`.
+    BlankNodeLabel,
+    /// A quoted string in any of its forms, `STRING`.
+    String,
+    /// A language tag with its `@`, `LANGTAG`.
+    LangTag,
+    /// `INTEGER`.
+    Integer,
+    /// `DECIMAL`.
+    Decimal,
+    /// `DOUBLE`.
+    Double,
+}
+
+impl SyntaxTree {
+    /// Parses the turtle source into a tree. Only its syntax is checked, so
+    /// a document with undefined prefixes or invalid IRIs still has a tree.
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let text = source.trim_start_matches(BYTE_ORDER_MARK);
+        Limits::default().check_depth(text)?;
+
+        let pair = TurtleParser::parse(Rule::turtleDoc, text)
+            .map_err(Error::from_pest)?
+            .next()
+            .expect("a document has a turtleDoc");
+
+        Ok(SyntaxTree {
+            byte_order_mark: text.len() != source.len(),
+            root: node(pair, text),
+        })
+    }
+
+    /// The `TurtleDoc` node at the root of the tree.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// The root of the tree, to edit it. Edits aren't checked, so a tree
+    /// that's been edited could print as a document that doesn't parse.
+    pub fn root_mut(&mut self) -> &mut Node {
+        &mut self.root
+    }
+
+    /// Lowers the tree to the triples of the document it prints as. To
+    /// parse it with a base or with `ParserOptions`, give the printed
+    /// document to a `Graph`.
+    pub fn parse(&self) -> Result<Triples, Error> {
+        Graph::new(&self.to_string())?.parse()
+    }
+
+    /// Adds a triple, with each term written as it would be in the
+    /// document. If a subject is written the same way at the start of a
+    /// statement, the triple is added to its first statement, after the
+    /// objects of `predicate` if it has any and as a new predicate
+    /// otherwise. If not, it's added as a new statement at the end.
+    pub fn add_triple(&mut self, subject: &str, predicate: &str, object: &str) -> Result<(), Error> {
+        let subject = fragment(Rule::subject, subject, "a subject")?;
+        let verb = fragment(Rule::verb, predicate, "a predicate")?;
+        let object = fragment(Rule::object, object, "an object")?;
+
+        let written = subject.to_string();
+        let block = self.root.children.iter_mut()
+            .filter_map(Element::as_node_mut)
+            .filter_map(|statement| statement.child_mut(NodeKind::Triples))
+            .find(|triples| triples.nodes().next().is_some_and(|first| {
+                first.kind == NodeKind::Subject && first.to_string() == written
+            }));
+
+        let triples = match block {
+            Some(triples) => triples,
+            None => {
+                self.root.push_statement(subject, verb, object);
+                return Ok(())
+            }
+        };
+
+        let predicates = triples.child_mut(NodeKind::PredicateObjectList)
+            .expect("triples with a subject have predicates");
+        let written = verb.to_string();
+        let existing = predicates.children.iter()
+            .position(|child| child.as_node().is_some_and(|node| node.kind == NodeKind::Verb && node.to_string() == written));
+
+        match existing {
+            Some(index) => {
+                let objects = predicates.children[index..].iter_mut()
+                    .filter_map(Element::as_node_mut)
+                    .find(|node| node.kind == NodeKind::ObjectList)
+                    .expect("a verb is followed by its objects");
+
+                objects.push_token(TokenKind::Punctuation, ",");
+                objects.push_token(TokenKind::Whitespace, " ");
+                objects.children.push(Element::Node(object));
+            }
+            None => {
+                // Indent the new predicate like the second one, if there is.
+                let indent = predicates.children.iter()
+                    .enumerate()
+                    .filter(|&(_, child)| child.as_node().is_some_and(|node| node.kind == NodeKind::Verb))
+                    .nth(1)
+                    .and_then(|(index, _)| predicates.children[index - 1].as_token())
+                    .filter(|token| token.kind == TokenKind::Whitespace)
+                    .map_or_else(|| String::from(" "), |token| token.text.clone());
+
+                predicates.push_token(TokenKind::Whitespace, " ");
+                predicates.push_token(TokenKind::Punctuation, ";");
+                predicates.push_token(TokenKind::Whitespace, &indent);
+                predicates.children.push(Element::Node(verb));
+                predicates.push_token(TokenKind::Whitespace, " ");
+                predicates.children.push(Element::Node(Node::new(NodeKind::ObjectList, vec![Element::Node(object)])));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renames the prefix `from` to `to` in its declarations and in every
+    /// prefixed name that uses it. The prefixes are written without their
+    /// `:`. Renaming to a prefix that's already declared is an error, as
+    /// the two would be merged.
+    pub fn rename_prefix(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let name = format!("{}:", to);
+        fragment(Rule::PNAME_NS, &name, "a prefix")?;
+
+        if from == to {
+            return Ok(())
+        }
+
+        let mut offset = 0;
+        let mut duplicate = None;
+        self.root.declarations(&mut offset, &mut |prefix, offset| {
+            if prefix == to && duplicate.is_none() {
+                duplicate = Some(offset);
+            }
+        });
+
+        if let Some(offset) = duplicate {
+            let text = self.root.to_string();
+
+            return Err(Error::DuplicatePrefix {
+                prefix: to.to_owned(),
+                position: Position::new(1, 1).after(&text[..offset]),
+            })
+        }
+
+        self.root.for_each_token(&mut |token| {
+            if token.kind == TokenKind::PrefixedName && token.prefix() == from {
+                token.text = format!("{}{}", to, &token.text[from.len()..]);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.byte_order_mark {
+            write!(f, "{}", BYTE_ORDER_MARK)?;
+        }
+
+        write!(f, "{}", self.root)
+    }
+}
+
+impl Node {
+    /// Creates a new `Node` of `kind` made of `children`.
+    pub fn new(kind: NodeKind, children: Vec<Element>) -> Self {
+        Node { kind, children }
+    }
+
+    /// The nodes among the node's children.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter_map(Element::as_node)
+    }
+
+    /// The tokens among the node's children.
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.children.iter().filter_map(Element::as_token)
+    }
+
+    fn child_mut(&mut self, kind: NodeKind) -> Option<&mut Node> {
+        self.children.iter_mut()
+            .filter_map(Element::as_node_mut)
+            .find(|node| node.kind == kind)
+    }
+
+    fn push_token(&mut self, kind: TokenKind, text: &str) {
+        self.children.push(Element::Token(Token { kind, text: text.to_owned() }));
+    }
+
+    /// Adds `subject verb object .` on a line of its own at the end of a
+    /// document.
+    fn push_statement(&mut self, subject: Node, verb: Node, object: Node) {
+        let text = self.to_string();
+
+        if !text.is_empty() && !text.ends_with('\n') {
+            self.push_token(TokenKind::Whitespace, "\n");
+        }
+
+        let space = || Element::Token(Token { kind: TokenKind::Whitespace, text: String::from(" ") });
+        let objects = Node::new(NodeKind::ObjectList, vec![Element::Node(object)]);
+        let predicates = Node::new(NodeKind::PredicateObjectList, vec![
+            Element::Node(verb),
+            space(),
+            Element::Node(objects),
+        ]);
+        let triples = Node::new(NodeKind::Triples, vec![Element::Node(subject), space(), Element::Node(predicates)]);
+        let statement = Node::new(NodeKind::Statement, vec![
+            Element::Node(triples),
+            space(),
+            Element::Token(Token { kind: TokenKind::Punctuation, text: String::from(".") }),
+        ]);
+
+        self.children.push(Element::Node(statement));
+        self.push_token(TokenKind::Whitespace, "\n");
+    }
+
+    fn for_each_token<F: FnMut(&mut Token)>(&mut self, f: &mut F) {
+        for child in &mut self.children {
+            match *child {
+                Element::Node(ref mut node) => node.for_each_token(f),
+                Element::Token(ref mut token) => f(token),
+            }
+        }
+    }
+
+    /// Calls `f` with the prefix and offset of each prefix declaration,
+    /// where `offset` is how far into the document the node starts.
+    fn declarations<F: FnMut(&str, usize)>(&self, offset: &mut usize, f: &mut F) {
+        let declaration = self.kind == NodeKind::PrefixId || self.kind == NodeKind::SparqlPrefix;
+
+        if declaration {
+            if let Some(token) = self.tokens().find(|token| token.kind == TokenKind::PrefixedName) {
+                f(token.prefix(), *offset);
+            }
+        }
+
+        for child in &self.children {
+            match *child {
+                Element::Node(ref node) => node.declarations(offset, f),
+                Element::Token(ref token) => *offset += token.text.len(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Element {
+    /// The node, if the element is one.
+    pub fn as_node(&self) -> Option<&Node> {
+        match *self {
+            Element::Node(ref node) => Some(node),
+            Element::Token(_) => None,
+        }
+    }
+
+    /// The node, if the element is one, to edit it.
+    pub fn as_node_mut(&mut self) -> Option<&mut Node> {
+        match *self {
+            Element::Node(ref mut node) => Some(node),
+            Element::Token(_) => None,
+        }
+    }
+
+    /// The token, if the element is one.
+    pub fn as_token(&self) -> Option<&Token> {
+        match *self {
+            Element::Node(_) => None,
+            Element::Token(ref token) => Some(token),
+        }
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Element::Node(ref node) => write!(f, "{}", node),
+            Element::Token(ref token) => write!(f, "{}", token),
+        }
+    }
+}
+
+impl Token {
+    /// Whether the token is whitespace or a comment.
+    pub fn is_trivia(&self) -> bool {
+        self.kind == TokenKind::Whitespace || self.kind == TokenKind::Comment
+    }
+
+    /// The prefix of a prefixed name, without its `:`.
+    fn prefix(&self) -> &str {
+        self.text.split(':').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parses the whole of `text` as `rule`, such as a term for `add_triple`.
+fn fragment(rule: Rule, text: &str, what: &str) -> Result<Node, Error> {
+    let expected = |end: usize| Error::Syntax {
+        message: format!("expected {}", what),
+        position: Position::new(1, 1).after(&text[..end]),
+    };
+    let pair = TurtleParser::parse(rule, text)
+        .map_err(Error::from_pest)?
+        .next()
+        .ok_or_else(|| expected(0))?;
+
+    if pair.as_span().end() != text.len() {
+        return Err(expected(pair.as_span().end()))
+    }
+
+    match element(pair, text) {
+        Element::Node(node) => Ok(node),
+        Element::Token(token) => Ok(Node::new(NodeKind::PrefixedName, vec![Element::Token(token)])),
+    }
+}
+
+fn node(pair: Pair<Rule>, source: &str) -> Node {
+    match element(pair, source) {
+        Element::Node(node) => node,
+        Element::Token(_) => unreachable!("documents are nodes"),
+    }
+}
+
+/// The element for `pair`, with the text between its children split into
+/// tokens.
+fn element(pair: Pair<Rule>, source: &str) -> Element {
+    let token = |kind| Element::Token(Token { kind, text: pair.as_str().to_owned() });
+
+    let kind = match pair.as_rule() {
+        Rule::IRIREF => return token(TokenKind::IriRef),
+        Rule::PNAME_NS | Rule::PNAME_LN => return token(TokenKind::PrefixedName),
+        Rule::BLANK_NODE_LABEL => return token(TokenKind::BlankNodeLabel),
+        Rule::STRING => return token(TokenKind::String),
+        Rule::LANGTAG => return token(TokenKind::LangTag),
+        Rule::INTEGER => return token(TokenKind::Integer),
+        Rule::DECIMAL => return token(TokenKind::Decimal),
+        Rule::DOUBLE => return token(TokenKind::Double),
+        Rule::turtleDoc => NodeKind::TurtleDoc,
+        Rule::statement => NodeKind::Statement,
+        Rule::directive => NodeKind::Directive,
+        Rule::prefixID => NodeKind::PrefixId,
+        Rule::base => NodeKind::Base,
+        Rule::sparqlPrefix => NodeKind::SparqlPrefix,
+        Rule::sparqlBase => NodeKind::SparqlBase,
+        Rule::triples => NodeKind::Triples,
+        Rule::predicateObjectList => NodeKind::PredicateObjectList,
+        Rule::objectList => NodeKind::ObjectList,
+        Rule::verb => NodeKind::Verb,
+        Rule::subject => NodeKind::Subject,
+        Rule::object => NodeKind::Object,
+        Rule::literal => NodeKind::Literal,
+        Rule::RDFLiteral => NodeKind::RdfLiteral,
+        Rule::NumericLiteral => NodeKind::NumericLiteral,
+        Rule::BooleanLiteral => NodeKind::BooleanLiteral,
+        Rule::blankNodePropertyList => NodeKind::BlankNodePropertyList,
+        Rule::collection => NodeKind::Collection,
+        Rule::quotedTriple => NodeKind::QuotedTriple,
+        Rule::qtSubject => NodeKind::QtSubject,
+        Rule::qtObject => NodeKind::QtObject,
+        Rule::annotation => NodeKind::Annotation,
+        Rule::iri => NodeKind::Iri,
+        Rule::PrefixedName => NodeKind::PrefixedName,
+        Rule::BlankNode => NodeKind::BlankNode,
+        Rule::ANON => NodeKind::Anon,
+        rule => unreachable!("{:?} isn't in a Turtle document", rule),
+    };
+
+    let span = pair.as_span();
+    let mut children = Vec::new();
+    let mut offset = span.start();
+
+    for child in pair.into_inner().filter(|child| child.as_rule() != Rule::EOI) {
+        let start = child.as_span().start();
+        let end = child.as_span().end();
+
+        push_tokens(&source[offset..start], &mut children);
+        push_hoisted(element(child, source), &mut children);
+        offset = end;
+    }
+
+    push_tokens(&source[offset..span.end()], &mut children);
+
+    Element::Node(Node::new(kind, children))
+}
+
+/// Pushes `child`, moving any whitespace and comments at its start or end
+/// out into `children`, as pest's spans can take in the whitespace after
+/// them. Every node then starts and ends with a token of its own, so
+/// anything added to the end of a node goes before the trivia after it.
+fn push_hoisted(mut child: Element, children: &mut Vec<Element>) {
+    let node = match child {
+        Element::Node(ref mut node) => node,
+        Element::Token(_) => return children.push(child),
+    };
+    let trivia = |child: &Element| child.as_token().is_some_and(Token::is_trivia);
+    let leading = node.children.iter().take_while(|child| trivia(child)).count();
+    let trailing = node.children[leading..].iter().rev().take_while(|child| trivia(child)).count();
+    let after = node.children.split_off(node.children.len() - trailing);
+
+    for token in node.children.drain(..leading) {
+        push(token, children);
+    }
+
+    children.push(child);
+
+    for token in after {
+        push(token, children);
+    }
+}
+
+/// Pushes `element`, joining it onto the whitespace before it if they're
+/// both whitespace.
+fn push(element: Element, children: &mut Vec<Element>) {
+    if let (Some(&mut Element::Token(ref mut last)), Element::Token(ref token)) = (children.last_mut(), &element) {
+        if last.kind == TokenKind::Whitespace && token.kind == TokenKind::Whitespace {
+            last.text.push_str(&token.text);
+            return
+        }
+    }
+
+    children.push(element);
+}
+
+/// Splits the text between the children of a node, which is only ever
+/// whitespace, comments, keywords and punctuation, into tokens.
+fn push_tokens(text: &str, children: &mut Vec<Element>) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let (kind, end) = match bytes[i] {
+            b' ' | b'\t' | b'\r' | b'\n' => (TokenKind::Whitespace, run_end(bytes, i, |b| b" \t\r\n".contains(&b))),
+            b'#' => (TokenKind::Comment, run_end(bytes, i, |b| b != b'\r' && b != b'\n')),
+            b'@' => (TokenKind::Keyword, run_end(bytes, i + 1, |b| b.is_ascii_alphabetic())),
+            b if b.is_ascii_alphabetic() => (TokenKind::Keyword, run_end(bytes, i, |b| b.is_ascii_alphabetic())),
+            _ if [&b"<<"[..], b">>", b"{|", b"|}", b"^^"].iter().any(|p| bytes[i..].starts_with(p)) => {
+                (TokenKind::Punctuation, i + 2)
+            }
+            _ => (TokenKind::Punctuation, i + text[i..].chars().next().map_or(1, char::len_utf8)),
+        };
+
+        push(Element::Token(Token { kind, text: text[i..end].to_owned() }), children);
+        i = end;
+    }
+}
+
+/// The offset of the first byte at or after `i` that isn't matched by
+/// `matches`.
+fn run_end<F: Fn(u8) -> bool>(bytes: &[u8], i: usize, matches: F) -> usize {
+    bytes[i..].iter().position(|&b| !matches(b)).map_or(bytes.len(), |length| i + length)
+}
@@ -0,0 +1,48 @@
+//! Output of `Graph::parse_document`
+use std::collections::HashMap;
+
+use error::Error;
+use iri::Iri;
+use triple::Triples;
+
+/// A parsed document: its triples along with the directives that were used
+/// to write them, so they can be written back out the same way.
+///
+/// ```
+/// use chelone::{Graph, TriGWriter};
+///
+/// let document = Graph::new(r#"
+///     @prefix ex: <http://example.org/> .
+///     ex:a ex:b ex:c .
+/// "#).unwrap().parse_document().unwrap();
+///
+/// let dataset = document.triples.into_iter().map(Into::into).collect();
+/// let output = TriGWriter::new(&dataset).prefixs(document.prefixs).to_string();
+///
+/// assert!(output.contains("ex:a ex:b ex:c ."));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Document {
+    /// Every prefix declaration, in the order they were written. A prefix
+    /// that was declared more than once appears once for each declaration.
+    pub prefixs: Vec<(String, Iri)>,
+    /// Every base declaration, in the order they were written.
+    pub bases: Vec<Iri>,
+    /// The triples of the document.
+    pub triples: Triples,
+    /// The problems that were accepted because of the `ParserOptions`.
+    pub warnings: Vec<Error>,
+}
+
+impl Document {
+    /// The prefixes in scope at the end of the document, without their
+    /// trailing `:`.
+    pub fn prefix_map(&self) -> HashMap<String, Iri> {
+        self.prefixs.iter().cloned().collect()
+    }
+
+    /// The base in effect at the end of the document, if one was declared.
+    pub fn base(&self) -> Option<&Iri> {
+        self.bases.last()
+    }
+}
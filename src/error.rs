@@ -0,0 +1,306 @@
+//! Errors produced while parsing a document.
+use std::error;
+use std::fmt;
+use std::io;
+
+use pest;
+use pest::RuleType;
+
+use limits::Limit;
+
+/// A location in the source document. Both `line` and `column` start at `1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    /// The line of the location.
+    pub line: usize,
+    /// The column of the location, counted in characters.
+    pub column: usize,
+}
+
+impl Position {
+    /// Creates a new `Position`.
+    pub fn new(line: usize, column: usize) -> Self {
+        Position { line, column }
+    }
+
+    pub(crate) fn from_pest(position: &pest::Position) -> Self {
+        let (line, column) = position.line_col();
+
+        Position { line, column }
+    }
+
+    /// Converts a position inside a chunk of the document into a position
+    /// in the whole document, given where the chunk starts.
+    pub(crate) fn relative_to(self, start: Position) -> Self {
+        if self.line == 1 {
+            Position::new(start.line, start.column + self.column - 1)
+        } else {
+            Position::new(start.line + self.line - 1, self.column)
+        }
+    }
+}
+
+impl Position {
+    /// The position just after `text`, if `text` starts at this position.
+    pub(crate) fn after(self, text: &str) -> Self {
+        match text.rfind('\n') {
+            Some(newline) => {
+                let lines = text.matches('\n').count();
+                let column = text[newline + 1..].chars().count() + 1;
+
+                Position::new(self.line + lines, column)
+            }
+
+            None => Position::new(self.line, self.column + text.chars().count()),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// An error encountered while parsing a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The document does not match the grammar.
+    Syntax {
+        /// A description of what the parser expected.
+        message: String,
+        /// Where the parser stopped.
+        position: Position,
+    },
+    /// A prefixed name uses a prefix that was never declared.
+    UndefinedPrefix {
+        /// The prefix without the trailing `:`.
+        prefix: String,
+        /// Where the prefixed name starts.
+        position: Position,
+    },
+    /// An IRI is malformed or is relative with no base to resolve it against.
+    InvalidIri {
+        /// The IRI as written, after unescaping.
+        iri: String,
+        /// Where the IRI starts.
+        position: Position,
+    },
+    /// A prefix is declared more than once.
+    DuplicatePrefix {
+        /// The prefix without the trailing `:`.
+        prefix: String,
+        /// Where the second declaration starts.
+        position: Position,
+    },
+    /// A language tag is not well-formed.
+    InvalidLanguageTag {
+        /// The language tag as written, without the `@`.
+        tag: String,
+        /// Where the literal starts.
+        position: Position,
+    },
+    /// A literal's value is not valid for its datatype.
+    IllTypedLiteral {
+        /// The value of the literal.
+        value: String,
+        /// The IRI of the literal's datatype.
+        datatype: String,
+        /// Where the literal starts.
+        position: Position,
+    },
+    /// An escape sequence does not encode a valid character.
+    InvalidEscape {
+        /// The escape sequence as written.
+        escape: String,
+        /// Where the escape sequence starts.
+        position: Position,
+    },
+    /// The input is not valid UTF-8.
+    InvalidUtf8 {
+        /// Where the first invalid byte is.
+        position: Position,
+    },
+    /// Reading the input failed.
+    Io {
+        /// The kind of I/O error.
+        kind: io::ErrorKind,
+        /// The I/O error's description.
+        message: String,
+        /// How far the input had been read.
+        position: Position,
+    },
+    /// A JSON-LD document breaks one of JSON-LD's rules. It has no position
+    /// as JSON-LD is processed after the JSON has been parsed.
+    JsonLd {
+        /// The JSON-LD error code, such as `invalid @id value`.
+        code: &'static str,
+        /// The value the error is about.
+        message: String,
+    },
+    /// The document goes over one of the parser's `Limits`.
+    LimitExceeded {
+        /// The limit that was exceeded.
+        limit: Limit,
+        /// The value of the limit.
+        max: usize,
+        /// Where the limit was exceeded.
+        position: Position,
+    },
+    /// A triple can't be written in the output format. It has no position
+    /// as it isn't about a source document.
+    Unwritable {
+        /// The triple, in N-Triples.
+        triple: String,
+        /// Why it can't be written.
+        message: String,
+        /// The index of the triple in the `Triples` being written.
+        index: usize,
+    },
+    /// The parse tree did not have the shape the parser expected. This is a
+    /// bug in chelone rather than in the document.
+    Unexpected {
+        /// What was found instead.
+        found: String,
+        /// Where it was found.
+        position: Position,
+    },
+}
+
+impl Error {
+    /// The position in the source document where the error occurred, or
+    /// `None` if the error isn't about a place in a source document.
+    pub fn position(&self) -> Option<Position> {
+        let position = match *self {
+            Error::Syntax { position, .. } |
+            Error::UndefinedPrefix { position, .. } |
+            Error::InvalidIri { position, .. } |
+            Error::DuplicatePrefix { position, .. } |
+            Error::InvalidLanguageTag { position, .. } |
+            Error::IllTypedLiteral { position, .. } |
+            Error::InvalidEscape { position, .. } |
+            Error::InvalidUtf8 { position } |
+            Error::Io { position, .. } |
+            Error::LimitExceeded { position, .. } |
+            Error::Unexpected { position, .. } => position,
+            Error::JsonLd { .. } | Error::Unwritable { .. } => return None,
+        };
+
+        Some(position)
+    }
+
+    fn position_mut(&mut self) -> Option<&mut Position> {
+        let position = match *self {
+            Error::Syntax { ref mut position, .. } |
+            Error::UndefinedPrefix { ref mut position, .. } |
+            Error::InvalidIri { ref mut position, .. } |
+            Error::DuplicatePrefix { ref mut position, .. } |
+            Error::InvalidLanguageTag { ref mut position, .. } |
+            Error::IllTypedLiteral { ref mut position, .. } |
+            Error::InvalidEscape { ref mut position, .. } |
+            Error::InvalidUtf8 { ref mut position } |
+            Error::Io { ref mut position, .. } |
+            Error::LimitExceeded { ref mut position, .. } |
+            Error::Unexpected { ref mut position, .. } => position,
+            Error::JsonLd { .. } | Error::Unwritable { .. } => return None,
+        };
+
+        Some(position)
+    }
+
+    /// Moves an error from a chunk of the document to the whole document,
+    /// see `Position::relative_to`.
+    pub(crate) fn relative_to(mut self, start: Position) -> Self {
+        if let Some(position) = self.position_mut() {
+            *position = position.relative_to(start);
+        }
+
+        self
+    }
+
+    pub(crate) fn from_io(error: &io::Error, position: Position) -> Self {
+        Error::Io { kind: error.kind(), message: error.to_string(), position }
+    }
+
+    pub(crate) fn json_ld<S: Into<String>>(code: &'static str, message: S) -> Self {
+        Error::JsonLd { code, message: message.into() }
+    }
+
+    pub(crate) fn from_pest<R: RuleType>(error: pest::error::Error<R>) -> Self {
+        use pest::error::LineColLocation;
+
+        let position = match error.line_col {
+            LineColLocation::Pos((line, column)) |
+            LineColLocation::Span((line, column), _) => {
+                Position::new(line, column)
+            }
+        };
+
+        Error::Syntax { message: error.variant.message().into_owned(), position }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Syntax { ref message, position } => {
+                write!(f, "{}: syntax error: {}", position, message)
+            }
+            Error::UndefinedPrefix { ref prefix, position } => {
+                write!(f, "{}: undefined prefix `{}:`", position, prefix)
+            }
+            Error::InvalidIri { ref iri, position } => {
+                write!(f, "{}: invalid IRI `{}`", position, iri)
+            }
+            Error::DuplicatePrefix { ref prefix, position } => {
+                write!(f, "{}: prefix `{}:` is declared more than once", position, prefix)
+            }
+            Error::InvalidLanguageTag { ref tag, position } => {
+                write!(f, "{}: invalid language tag `{}`", position, tag)
+            }
+            Error::IllTypedLiteral { ref value, ref datatype, position } => {
+                write!(f, "{}: `{}` is not a valid <{}>", position, value, datatype)
+            }
+            Error::InvalidEscape { ref escape, position } => {
+                write!(f, "{}: invalid escape sequence `{}`", position, escape)
+            }
+            Error::InvalidUtf8 { position } => {
+                write!(f, "{}: invalid UTF-8", position)
+            }
+            Error::Io { ref message, position, .. } => {
+                write!(f, "{}: {}", position, message)
+            }
+            Error::JsonLd { code, ref message } if message.is_empty() => write!(f, "{}", code),
+            Error::JsonLd { code, ref message } => write!(f, "{}: {}", code, message),
+            Error::LimitExceeded { limit, max, position } => {
+                write!(f, "{}: {} is over the limit of {}", position, limit, max)
+            }
+            Error::Unwritable { ref triple, ref message, index } => {
+                write!(f, "triple {}: can't write `{}`: {}", index, triple, message)
+            }
+            Error::Unexpected { ref found, position } => {
+                write!(f, "{}: unexpected {}", position, found)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A problem that a lenient parse recovered from by skipping part of the
+/// document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Why the text was skipped.
+    pub error: Error,
+    /// Where the skipped text starts.
+    pub start: Position,
+    /// Where parsing resumed, just after the skipped text.
+    pub end: Position,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (skipped {} to {})", self.error, self.start, self.end)
+    }
+}
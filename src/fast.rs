@@ -0,0 +1,1056 @@
+//! The hand-written Turtle parser behind `Graph::fast`. It reads terms
+//! straight from the source in a single pass, rather than walking the pairs
+//! of a parse tree that pest has built, and shares the rest of `Graph`'s
+//! state with the walker, so both give the same triples, blank nodes,
+//! warnings and spans. Like the walker, nested structures are kept on a
+//! stack of frames rather than parsed recursively.
+use std::borrow::Cow;
+use std::char;
+
+use borrowed::{LiteralRef, ObjectRef, SubjectRef};
+use error::Error;
+use iri::{BlankNode, Iri};
+use {Graph, IriRef, TripleRef};
+
+/// The end of the span of a structure that hasn't been closed yet, which is
+/// filled in once it has.
+const OPEN: usize = usize::MAX;
+
+/// Where the hand-written parser is in the source.
+#[derive(Debug, Default)]
+pub(crate) struct Cursor {
+    /// The offset of the next byte to read.
+    offset: usize,
+    /// How many brackets are open, to check against `Limits::max_depth`.
+    depth: usize,
+}
+
+impl Cursor {
+    /// The offset of the next byte to read.
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// A nested structure that's partly parsed, like the walker's frames.
+/// `spans` is how many spans were pending when the structure was opened.
+#[derive(Debug)]
+enum Frame {
+    /// A predicate object list, after its first objects if `more` is set.
+    Predicates { more: bool },
+    /// An object list, after one of its objects.
+    Objects,
+    /// An object starting at `start` that's emitted once its value has been
+    /// parsed.
+    Object { start: usize },
+    /// A collection starting at `start`, whose last node so far is `node`.
+    Collection { head: BlankNode, node: Option<BlankNode>, start: usize, spans: usize },
+    /// The predicates of the `[ ... ]` for `node` starting at `start`.
+    PropertyList { node: BlankNode, start: usize, spans: usize },
+    /// The predicates of an annotation block starting at `start`.
+    Annotation { start: usize, spans: usize },
+}
+
+impl<'a> Graph<'a> {
+    /// Skips whitespace and comments, returning whether the end of the
+    /// source has been reached.
+    pub(crate) fn at_end(&mut self) -> bool {
+        self.skip_space();
+
+        self.cursor.offset == self.source.len()
+    }
+
+    /// Reads the directive or triples that start at the cursor.
+    pub(crate) fn read_statement(&mut self) -> Result<(), Error> {
+        self.cursor.depth = 0;
+
+        match self.peek() {
+            Some(b'@') => self.read_directive(),
+            Some(b'P') | Some(b'p') | Some(b'B') | Some(b'b') if self.read_sparql_directive()? => Ok(()),
+            _ => self.read_triples(),
+        }
+    }
+
+    fn read_directive(&mut self) -> Result<(), Error> {
+        if self.eat("@prefix") {
+            self.read_prefix()?;
+        } else if self.eat("@base") {
+            self.skip_space();
+            let base = self.read_iriref()?;
+            self.declare_base(base);
+        } else {
+            return Err(self.expected("`@prefix` or `@base`"))
+        }
+
+        self.expect(".")
+    }
+
+    /// Reads a `PREFIX` or `BASE` directive, returning whether there was
+    /// one. As with pest, a keyword that isn't followed by the rest of a
+    /// directive is read as the start of triples instead.
+    fn read_sparql_directive(&mut self) -> Result<bool, Error> {
+        let start = self.cursor.offset;
+
+        if self.eat_ignore_case("PREFIX") {
+            self.skip_space();
+            let colon = self.prefix_end(self.cursor.offset);
+
+            if self.byte(colon) == Some(b':') && self.iriref_follows(colon + 1) {
+                self.read_prefix()?;
+                return Ok(true)
+            }
+        } else if self.eat_ignore_case("BASE") && self.iriref_follows(self.cursor.offset) {
+            self.skip_space();
+            let base = self.read_iriref()?;
+            self.declare_base(base);
+            return Ok(true)
+        }
+
+        self.cursor.offset = start;
+        Ok(false)
+    }
+
+    /// Reads the prefix and IRI of a prefix declaration.
+    fn read_prefix(&mut self) -> Result<(), Error> {
+        self.skip_space();
+
+        let start = self.cursor.offset;
+        let colon = self.prefix_end(start);
+
+        if self.byte(colon) != Some(b':') {
+            return Err(self.expected_at(colon, "`:`"))
+        }
+
+        self.cursor.offset = colon + 1;
+        self.skip_space();
+
+        let value = self.read_iriref()?;
+        let key = self.source[start..colon].to_owned();
+
+        self.declare_prefix(key, value, start)
+    }
+
+    fn read_triples(&mut self) -> Result<(), Error> {
+        let start = self.cursor.offset;
+
+        // `[ ... ]` is the only subject that doesn't need predicates.
+        let (subject, predicates) = match self.peek() {
+            Some(b'[') if !self.is_anon() => (self.read_node()?, false),
+            Some(b'(') => (self.read_node()?, true),
+            Some(b'<') if self.starts_with("<<") => {
+                (SubjectRef::from(self.read_quoted_triple()?), true)
+            }
+            _ => match self.read_term(false)? {
+                Some(term) => (term.into_subject(), true),
+                None => return Err(self.expected("a subject")),
+            },
+        };
+
+        self.subject = Some(subject);
+        self.subject_span = start..self.cursor.offset;
+        self.skip_space();
+
+        if predicates || self.starts_verb() {
+            self.read_frames(vec![Frame::Predicates { more: false }], None)?;
+        }
+
+        self.expect(".")
+    }
+
+    /// Reads a collection or `[ ... ]` that's used as a subject.
+    fn read_node(&mut self) -> Result<SubjectRef<'a>, Error> {
+        let mut frames = Vec::new();
+        let value = self.begin_node(&mut frames)?;
+
+        match self.read_frames(frames, value)? {
+            Some(node) => Ok(node.into_subject()),
+            None => Err(self.expected("a subject")),
+        }
+    }
+
+    /// Reads until every frame in `frames` is finished, returning the value
+    /// of the outermost one. `value` is the value of the structure that was
+    /// read last, which is given to the frame on top of the stack.
+    fn read_frames(&mut self, mut frames: Vec<Frame>, mut value: Option<ObjectRef<'a>>)
+        -> Result<Option<ObjectRef<'a>>, Error>
+    {
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Predicates { more } => {
+                    if more {
+                        // `;` can be repeated, and doesn't have to be
+                        // followed by another predicate.
+                        let mut separated = false;
+
+                        while self.eat(";") {
+                            separated = true;
+                        }
+
+                        self.skip_space();
+
+                        if !separated || !self.starts_verb() {
+                            continue
+                        }
+                    }
+
+                    self.read_verb()?;
+                    frames.push(Frame::Predicates { more: true });
+                    frames.push(Frame::Objects);
+                    value = self.begin_object(&mut frames)?;
+                }
+
+                Frame::Objects => match value.take() {
+                    Some(object) if self.eat("{|") => {
+                        let start = self.cursor.offset - 2;
+                        frames.push(Frame::Objects);
+                        self.begin_annotation(object, start)?;
+                        frames.push(Frame::Annotation { start, spans: self.pending_spans.len() });
+                        frames.push(Frame::Predicates { more: false });
+                    }
+
+                    _ if self.eat(",") => {
+                        frames.push(Frame::Objects);
+                        value = self.begin_object(&mut frames)?;
+                    }
+
+                    _ => {}
+                },
+
+                Frame::Object { start } => {
+                    let object = match value.take() {
+                        Some(object) => object,
+                        None => return Err(self.expected("an object")),
+                    };
+
+                    self.emit_triple(object.clone(), start..self.cursor.offset)?;
+                    value = Some(object);
+                }
+
+                Frame::Collection { head, node: None, start, spans } => {
+                    self.subject = Some(SubjectRef::BlankNode(head.clone()));
+                    self.predicate = Some(rdf_ref!("first"));
+                    frames.push(Frame::Collection { node: Some(head.clone()), head, start, spans });
+                    value = self.begin_object(&mut frames)?;
+                }
+
+                Frame::Collection { head, node: Some(node), start, spans } => {
+                    self.subject = Some(SubjectRef::BlankNode(node));
+                    self.predicate = Some(rdf_ref!("rest"));
+
+                    if self.eat(")") {
+                        self.emit_triple(ObjectRef::Iri(rdf_ref!("nil")), start..OPEN)?;
+                        self.close(start, spans);
+                        value = Some(ObjectRef::BlankNode(head));
+                    } else {
+                        let new_node = self.generate_new_blank_node();
+                        self.emit_triple(ObjectRef::BlankNode(new_node.clone()), start..OPEN)?;
+
+                        self.subject = Some(SubjectRef::BlankNode(new_node.clone()));
+                        self.predicate = Some(rdf_ref!("first"));
+                        frames.push(Frame::Collection { head, node: Some(new_node), start, spans });
+                        value = self.begin_object(&mut frames)?;
+                    }
+                }
+
+                Frame::PropertyList { node, start, spans } => {
+                    self.expect("]")?;
+                    self.close(start, spans);
+                    value = Some(ObjectRef::BlankNode(node));
+                }
+
+                Frame::Annotation { start, spans } => {
+                    self.expect("|}")?;
+                    self.close(start, spans);
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Starts reading the next object. An object that's a single term is
+    /// emitted and returned straight away, otherwise the frames for it are
+    /// pushed and it's emitted once they're finished.
+    fn begin_object(&mut self, frames: &mut Vec<Frame>) -> Result<Option<ObjectRef<'a>>, Error> {
+        self.skip_space();
+        let start = self.cursor.offset;
+
+        let object = match self.peek() {
+            Some(b'(') | Some(b'[') if !self.is_anon() => {
+                frames.push(Frame::Object { start });
+                return self.begin_node(frames)
+            }
+            Some(b'<') if self.starts_with("<<") => ObjectRef::from(self.read_quoted_triple()?),
+            _ => match self.read_term(true)? {
+                Some(object) => object,
+                None => return Err(self.expected("an object")),
+            },
+        };
+
+        self.emit_triple(object.clone(), start..self.cursor.offset)?;
+
+        Ok(Some(object))
+    }
+
+    /// Starts reading the collection or `[ ... ]` at the cursor. Until it's
+    /// closed, its span is left open in the spans of the triples inside it.
+    /// An empty collection is `rdf:nil`, which is returned straight away.
+    fn begin_node(&mut self, frames: &mut Vec<Frame>) -> Result<Option<ObjectRef<'a>>, Error> {
+        let start = self.cursor.offset;
+        let spans = self.pending_spans.len();
+        self.open()?;
+
+        if self.peek() == Some(b'(') {
+            self.cursor.offset += 1;
+
+            if self.eat(")") {
+                self.cursor.depth -= 1;
+                return Ok(Some(ObjectRef::Iri(rdf_ref!("nil"))))
+            }
+
+            self.save_subject();
+            self.save_predicate();
+            self.subject_span = start..OPEN;
+            self.predicate_span = start..OPEN;
+
+            let head = self.generate_new_blank_node();
+            frames.push(Frame::Collection { head, node: None, start, spans });
+        } else {
+            self.cursor.offset += 1;
+            let node = self.generate_new_blank_node();
+
+            self.save_subject();
+            self.subject = Some(SubjectRef::BlankNode(node.clone()));
+            self.subject_span = start..OPEN;
+            self.save_predicate();
+
+            frames.push(Frame::PropertyList { node, start, spans });
+            frames.push(Frame::Predicates { more: false });
+        }
+
+        Ok(None)
+    }
+
+    /// Starts reading an annotation block at `start`, whose triples have
+    /// the triple that was just emitted with `object` as their subject.
+    fn begin_annotation(&mut self, object: ObjectRef<'a>, start: usize) -> Result<(), Error> {
+        self.cursor.offset = start;
+        self.open()?;
+        self.cursor.offset += 2;
+
+        let triple = match (&self.subject, &self.predicate) {
+            (Some(subject), Some(predicate)) => TripleRef::new(subject.clone(), predicate.clone(), object),
+            _ => return Err(self.expected("a triple")),
+        };
+
+        self.save_subject();
+        self.save_predicate();
+        self.subject = Some(SubjectRef::from(triple));
+        self.subject_span = start..OPEN;
+
+        Ok(())
+    }
+
+    /// Reads a quoted triple. Unlike the other terms, it doesn't assert any
+    /// triples.
+    fn read_quoted_triple(&mut self) -> Result<TripleRef<'a>, Error> {
+        // The quoted triples that contain the one being read, each waiting
+        // for either its subject, or its object after its subject and
+        // predicate.
+        let mut outer: Vec<Option<(SubjectRef<'a>, IriRef<'a>)>> = Vec::new();
+
+        'triple: loop {
+            self.open()?;
+            self.cursor.offset += 2;
+            self.skip_space();
+
+            if self.starts_with("<<") {
+                outer.push(None);
+                continue 'triple
+            }
+
+            let mut subject = match self.read_term(false)? {
+                Some(subject) => subject.into_subject(),
+                None => return Err(self.expected("a subject")),
+            };
+
+            loop {
+                self.skip_space();
+                let predicate = self.read_predicate()?;
+                self.skip_space();
+
+                if self.starts_with("<<") {
+                    outer.push(Some((subject, predicate)));
+                    continue 'triple
+                }
+
+                let object = match self.read_term(true)? {
+                    Some(object) => object,
+                    None => return Err(self.expected("an object")),
+                };
+
+                let mut triple = TripleRef::new(subject, predicate, object);
+                self.close_quoted()?;
+
+                // Finish the quoted triples that this one is the object of,
+                // until one is found that it's the subject of.
+                subject = loop {
+                    match outer.pop() {
+                        None => return Ok(triple),
+                        Some(None) => break SubjectRef::from(triple),
+                        Some(Some((outer_subject, outer_predicate))) => {
+                            triple = TripleRef::new(outer_subject, outer_predicate, ObjectRef::from(triple));
+                            self.close_quoted()?;
+                        }
+                    }
+                };
+            }
+        }
+    }
+
+    fn close_quoted(&mut self) -> Result<(), Error> {
+        self.expect(">>")?;
+        self.cursor.depth -= 1;
+
+        Ok(())
+    }
+
+    /// Reads a term that's a single token: an IRI, a blank node, or, if
+    /// `literal` is set, a literal. Returns `None` if there isn't one at the
+    /// cursor.
+    fn read_term(&mut self, literal: bool) -> Result<Option<ObjectRef<'a>>, Error> {
+        let start = self.cursor.offset;
+
+        let term = match self.peek() {
+            Some(b'<') => {
+                let iriref = self.read_iriref_value()?;
+                self.iriref_term(iriref, start)?
+            }
+            Some(b'_') if self.byte(start + 1) == Some(b':') => {
+                ObjectRef::BlankNode(self.read_blank_node_label()?)
+            }
+            Some(b'[') if self.is_anon() => {
+                self.open()?;
+                self.cursor.offset += 1;
+                self.expect("]")?;
+                self.cursor.depth -= 1;
+
+                ObjectRef::BlankNode(self.generate_new_blank_node())
+            }
+            Some(b'"') | Some(b'\'') if literal => ObjectRef::Literal(self.read_rdf_literal()?),
+            Some(b'0'..=b'9') | Some(b'+') | Some(b'-') if literal => {
+                ObjectRef::Literal(self.read_numeric_literal()?)
+            }
+            Some(b'.') if literal && self.byte(start + 1).is_some_and(|b| b.is_ascii_digit()) => {
+                ObjectRef::Literal(self.read_numeric_literal()?)
+            }
+            _ => match self.read_prefixed_name()? {
+                Some(iri) => ObjectRef::Iri(iri),
+                None if literal && (self.starts_with("true") || self.starts_with("false")) => {
+                    let length = if self.starts_with("true") { 4 } else { 5 };
+                    let value = &self.source[start..start + length];
+                    self.cursor.offset += length;
+
+                    ObjectRef::Literal(LiteralRef::new(Cow::Borrowed(value), Some(xsd_ref!("boolean"))))
+                }
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(term))
+    }
+
+    /// Reads a predicate, and sets it as the current one.
+    fn read_verb(&mut self) -> Result<(), Error> {
+        self.skip_space();
+
+        let start = self.cursor.offset;
+        let predicate = self.read_predicate()?;
+
+        self.predicate = Some(predicate);
+        self.predicate_span = start..self.cursor.offset;
+
+        Ok(())
+    }
+
+    /// Reads a predicate, which is an IRI or `a`.
+    fn read_predicate(&mut self) -> Result<IriRef<'a>, Error> {
+        let start = self.cursor.offset;
+
+        match self.peek() {
+            Some(b'<') if !self.starts_with("<<") => {
+                let iriref = self.read_iriref_value()?;
+                self.iri_ref(iriref, start)
+            }
+            _ => match self.read_prefixed_name()? {
+                Some(iri) => Ok(iri),
+                None if self.peek() == Some(b'a') => {
+                    self.cursor.offset += 1;
+                    Ok(rdf_ref!("type"))
+                }
+                None => Err(self.expected("a predicate")),
+            },
+        }
+    }
+
+    /// Whether a predicate could start at the cursor.
+    fn starts_verb(&self) -> bool {
+        match self.peek() {
+            Some(b'<') => !self.starts_with("<<"),
+            Some(b':') => true,
+            Some(byte) => byte.is_ascii_alphabetic() || byte >= 0x80,
+            None => false,
+        }
+    }
+
+    /// Reads an IRIREF that's resolved against the base.
+    fn read_iriref(&mut self) -> Result<Iri, Error> {
+        let start = self.cursor.offset;
+
+        if self.peek() != Some(b'<') {
+            return Err(self.expected("an IRI"))
+        }
+
+        let iriref = self.read_iriref_value()?;
+
+        self.resolve_iriref(iriref.into_owned(), start)
+    }
+
+    /// Reads an IRIREF, unescaped but not resolved. It's borrowed from the
+    /// source unless it has escapes.
+    fn read_iriref_value(&mut self) -> Result<Cow<'a, str>, Error> {
+        let source = self.source;
+        let bytes = source.as_bytes();
+        let start = self.cursor.offset;
+        let mut escaped = false;
+        let mut i = start + 1;
+
+        let end = loop {
+            match bytes.get(i) {
+                Some(b'>') => break i,
+                Some(b'\\') => {
+                    i += self.uchar_width(i).ok_or_else(|| self.expected_at(i, "`\\u` or `\\U`"))?;
+                    escaped = true;
+                }
+                Some(&byte) if byte > b' ' && !b"<\"{}|^`".contains(&byte) => i += 1,
+                _ => return Err(self.expected_at(i, "`>`")),
+            }
+        };
+
+        self.cursor.offset = end + 1;
+
+        if !escaped {
+            return Ok(Cow::Borrowed(&source[start + 1..end]))
+        }
+
+        let mut iriref = String::with_capacity(end - start);
+        let mut i = start + 1;
+
+        while let Some(escape) = source[i..end].find('\\') {
+            iriref.push_str(&source[i..i + escape]);
+            i += escape;
+
+            let c = self.read_uchar(i)?;
+            i += self.uchar_width(i).unwrap_or(0);
+
+            // Escapes can't be used to sneak in characters that are not
+            // allowed in an IRIREF.
+            if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+                iriref.push(c);
+                return Err(Error::InvalidIri { iri: iriref, position: self.position(start) })
+            }
+
+            iriref.push(c);
+        }
+
+        iriref.push_str(&source[i..end]);
+
+        Ok(Cow::Owned(iriref))
+    }
+
+    /// Reads a prefixed name, returning `None` if there isn't one at the
+    /// cursor.
+    fn read_prefixed_name(&mut self) -> Result<Option<IriRef<'a>>, Error> {
+        let source = self.source;
+        let start = self.cursor.offset;
+        let colon = self.prefix_end(start);
+
+        if self.byte(colon) != Some(b':') {
+            return Ok(None)
+        }
+
+        let end = self.local_end(colon + 1);
+        self.cursor.offset = end;
+
+        let namespace = self.namespace(&source[start..colon + 1], start)?;
+
+        if end == colon + 1 {
+            return Ok(Some(IriRef::from(namespace)))
+        }
+
+        let local = &source[colon + 1..end];
+
+        if local.contains('\\') {
+            self.prefixed_name(&namespace, &local.replace('\\', ""), start).map(Some)
+        } else {
+            self.prefixed_name(&namespace, local, start).map(Some)
+        }
+    }
+
+    fn read_blank_node_label(&mut self) -> Result<BlankNode, Error> {
+        let start = self.cursor.offset + 2;
+
+        match self.char_at(start) {
+            Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => {}
+            _ => return Err(self.expected_at(start, "a blank node label")),
+        }
+
+        let end = self.name_end(start, is_pn_chars);
+        self.cursor.offset = end;
+
+        Ok(self.context.blank_nodes.document(&self.source[start..end]))
+    }
+
+    fn read_rdf_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
+        let start = self.cursor.offset;
+        let value = self.read_string()?;
+
+        self.check_literal_length(&value, start)?;
+
+        if self.peek() == Some(b'@') {
+            if let Some(end) = self.langtag_end(self.cursor.offset + 1) {
+                let tag = &self.source[self.cursor.offset + 1..end];
+                self.cursor.offset = end;
+
+                return self.tagged_literal(value, tag, start)
+            }
+        }
+
+        let datatype = if self.starts_with("^^") {
+            self.cursor.offset += 2;
+
+            match self.peek() {
+                Some(b'<') if !self.starts_with("<<") => {
+                    let offset = self.cursor.offset;
+                    let iriref = self.read_iriref_value()?;
+                    Some(self.iri_ref(iriref, offset)?)
+                }
+                _ => match self.read_prefixed_name()? {
+                    Some(iri) => Some(iri),
+                    None => return Err(self.expected("a datatype")),
+                },
+            }
+        } else {
+            None
+        };
+
+        self.typed_literal(value, datatype, start)
+    }
+
+    /// Reads a string, which is borrowed from the source unless it has
+    /// escapes.
+    fn read_string(&mut self) -> Result<Cow<'a, str>, Error> {
+        let source = self.source;
+        let bytes = source.as_bytes();
+        let start = self.cursor.offset;
+        let quote = bytes[start];
+        let long = bytes[start..].starts_with(&[quote; 3]);
+        let width = if long { 3 } else { 1 };
+        let mut escaped = false;
+        let mut i = start + width;
+
+        let end = loop {
+            match bytes.get(i) {
+                Some(&byte) if byte == quote && (!long || bytes[i..].starts_with(&[quote; 3])) => break i,
+                Some(b'\\') => {
+                    i += self.escape_width(i).ok_or_else(|| self.expected_at(i, "an escape sequence"))?;
+                    escaped = true;
+                }
+                Some(b'\n') | Some(b'\r') | None if !long => {
+                    return Err(self.expected_at(i, "the end of the string"))
+                }
+                Some(_) => i += 1,
+                None => return Err(self.expected_at(i, "the end of the string")),
+            }
+        };
+
+        self.cursor.offset = end + width;
+
+        if !escaped {
+            return Ok(Cow::Borrowed(&source[start + width..end]))
+        }
+
+        let mut string = String::with_capacity(end - start);
+        let mut i = start + width;
+
+        while let Some(escape) = source[i..end].find('\\') {
+            string.push_str(&source[i..i + escape]);
+            i += escape;
+
+            let c = match bytes[i + 1] {
+                b't' => '\t',
+                b'b' => '\u{08}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b'f' => '\u{0C}',
+                b'u' | b'U' => self.read_uchar(i)?,
+                other => other as char,
+            };
+
+            string.push(c);
+            i += self.escape_width(i).unwrap_or(2);
+        }
+
+        string.push_str(&source[i..end]);
+
+        Ok(Cow::Owned(string))
+    }
+
+    fn read_numeric_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
+        let bytes = self.source.as_bytes();
+        let start = self.cursor.offset;
+        let sign = match bytes[start] {
+            b'+' | b'-' => start + 1,
+            _ => start,
+        };
+
+        let integer = digits_end(bytes, sign);
+        let fraction = if bytes.get(integer) == Some(&b'.') {
+            digits_end(bytes, integer + 1)
+        } else {
+            integer
+        };
+        let has_integer = integer > sign;
+        let has_fraction = fraction > integer + 1;
+
+        let (end, datatype) = match exponent_end(bytes, fraction) {
+            Some(end) if has_integer || has_fraction => (end, xsd_ref!("double")),
+            _ if has_fraction => (fraction, xsd_ref!("decimal")),
+            _ if has_integer => (integer, xsd_ref!("integer")),
+            _ => return Err(self.expected("a number")),
+        };
+
+        self.cursor.offset = end;
+
+        Ok(LiteralRef::new(Cow::Borrowed(&self.source[start..end]), Some(datatype)))
+    }
+
+    /// Reads the `\u` or `\U` escape at `i`.
+    fn read_uchar(&self, i: usize) -> Result<char, Error> {
+        let width = self.uchar_width(i).unwrap_or(2);
+        let escape = &self.source[i..i + width];
+
+        u32::from_str_radix(&escape[2..], 16).ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| Error::InvalidEscape {
+                escape: escape.to_owned(),
+                position: self.position(i),
+            })
+    }
+
+    /// The width of the escape sequence at `i` in a string, if it's valid.
+    fn escape_width(&self, i: usize) -> Option<usize> {
+        match self.byte(i + 1)? {
+            b't' | b'b' | b'n' | b'r' | b'f' | b'"' | b'\'' | b'\\' => Some(2),
+            _ => self.uchar_width(i),
+        }
+    }
+
+    /// The width of the `\u` or `\U` escape at `i`, if it's valid.
+    fn uchar_width(&self, i: usize) -> Option<usize> {
+        let width = match self.byte(i + 1)? {
+            b'u' => 6,
+            b'U' => 10,
+            _ => return None,
+        };
+        let hex = self.source.as_bytes().get(i + 2..i + width)?;
+
+        if hex.iter().all(u8::is_ascii_hexdigit) {
+            Some(width)
+        } else {
+            None
+        }
+    }
+
+    /// The end of the prefix of a prefixed name starting at `i`, which is
+    /// `i` itself for the empty prefix.
+    fn prefix_end(&self, i: usize) -> usize {
+        match self.char_at(i) {
+            Some(c) if is_pn_chars_base(c) => self.name_end(i, is_pn_chars),
+            _ => i,
+        }
+    }
+
+    /// The end of the local part of a prefixed name starting at `i`, which
+    /// is `i` itself if it's empty.
+    fn local_end(&self, i: usize) -> usize {
+        match self.char_at(i) {
+            Some(c) if is_pn_chars_u(c) || c == ':' || c.is_ascii_digit() => {}
+            Some('%') | Some('\\') if self.escaped_width(i).is_some() => {}
+            _ => return i,
+        }
+
+        let bytes = self.source.as_bytes();
+        let mut end = i;
+        let mut i = i;
+
+        loop {
+            let width = match self.char_at(i) {
+                Some('.') => {
+                    i += 1;
+                    continue
+                }
+                Some('%') | Some('\\') => match self.escaped_width(i) {
+                    Some(width) => width,
+                    None => break,
+                },
+                Some(c) if is_pn_chars(c) || c == ':' => c.len_utf8(),
+                _ => break,
+            };
+
+            i += width;
+            end = i;
+
+            // Skip ahead over the ASCII that's always part of a name.
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+                end = i;
+            }
+        }
+
+        end
+    }
+
+    /// The width of the `%` or `\` escape at `i` in the local part of a
+    /// prefixed name, if it's valid.
+    fn escaped_width(&self, i: usize) -> Option<usize> {
+        let bytes = self.source.as_bytes();
+
+        match bytes[i] {
+            b'%' if bytes.get(i + 1..i + 3)?.iter().all(u8::is_ascii_hexdigit) => Some(3),
+            b'\\' if b"_~.-!$&'()*+,;=/?#@%".contains(bytes.get(i + 1)?) => Some(2),
+            _ => None,
+        }
+    }
+
+    /// The end of a name starting at `i` with a character that's already
+    /// been checked, whose other characters are `.` or match `is_name`, and
+    /// which can't end with a `.`.
+    fn name_end<F: Fn(char) -> bool>(&self, i: usize, is_name: F) -> usize {
+        let mut chars = self.source[i..].char_indices();
+        let mut end = i;
+
+        for (offset, c) in chars.by_ref() {
+            if c == '.' {
+                continue
+            }
+
+            if offset != 0 && !is_name(c) {
+                break
+            }
+
+            end = i + offset + c.len_utf8();
+        }
+
+        end
+    }
+
+    /// The end of the language tag starting at `i`, just after the `@`.
+    fn langtag_end(&self, i: usize) -> Option<usize> {
+        let bytes = self.source.as_bytes();
+        let letters = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_alphabetic()).count();
+        let alphanumerics = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_alphanumeric()).count();
+
+        let mut end = match letters(i) {
+            0 => return None,
+            length => i + length,
+        };
+
+        while bytes.get(end) == Some(&b'-') && alphanumerics(end + 1) > 0 {
+            end += 1 + alphanumerics(end + 1);
+        }
+
+        if bytes[end..].starts_with(b"--ltr") || bytes[end..].starts_with(b"--rtl") {
+            end += 5;
+        }
+
+        Some(end)
+    }
+
+    /// Opens the bracket at the cursor, checking it isn't nested too deeply.
+    fn open(&mut self) -> Result<(), Error> {
+        let limits = self.context.options.limits;
+
+        if self.cursor.depth >= limits.max_depth {
+            return Err(limits.depth_error(self.source, self.cursor.offset))
+        }
+
+        self.cursor.depth += 1;
+
+        Ok(())
+    }
+
+    /// Closes the structure that started at `start`, filling in the end of
+    /// its span in the spans of the triples that were emitted inside it.
+    /// Then the subject and predicate from outside it are restored.
+    fn close(&mut self, start: usize, spans: usize) {
+        self.cursor.depth -= 1;
+
+        if self.locate {
+            let end = self.cursor.offset;
+
+            for span in self.pending_spans.range_mut(spans..).flat_map(|spans| spans.iter_mut()) {
+                if span.start == start && span.end == OPEN {
+                    span.end = end;
+                }
+            }
+        }
+
+        self.pop_subject();
+        self.pop_predicate();
+    }
+
+    /// Whether the cursor is at a `[` that's only followed by whitespace and
+    /// comments before its `]`.
+    fn is_anon(&self) -> bool {
+        self.peek() == Some(b'[') && self.byte(self.space_end(self.cursor.offset + 1)) == Some(b']')
+    }
+
+    /// Whether an IRIREF starts at `i`, after any whitespace.
+    fn iriref_follows(&self, i: usize) -> bool {
+        let i = self.space_end(i);
+
+        self.byte(i) == Some(b'<') && self.byte(i + 1) != Some(b'<')
+    }
+
+    fn skip_space(&mut self) {
+        self.cursor.offset = self.space_end(self.cursor.offset);
+    }
+
+    /// The end of the whitespace and comments starting at `i`.
+    fn space_end(&self, mut i: usize) -> usize {
+        let bytes = self.source.as_bytes();
+
+        while let Some(&byte) = bytes.get(i) {
+            match byte {
+                b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+                b'#' => {
+                    i += bytes[i..].iter().position(|&b| b == b'\n' || b == b'\r')
+                        .unwrap_or(bytes.len() - i)
+                }
+                _ => break,
+            }
+        }
+
+        i
+    }
+
+    /// Skips whitespace, then `token` if it's next, returning whether it
+    /// was.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+
+        if self.starts_with(token) {
+            self.cursor.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_ignore_case(&mut self, keyword: &str) -> bool {
+        let end = self.cursor.offset + keyword.len();
+
+        match self.source.as_bytes().get(self.cursor.offset..end) {
+            Some(bytes) if bytes.eq_ignore_ascii_case(keyword.as_bytes()) => {
+                self.cursor.offset = end;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), Error> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", token)))
+        }
+    }
+
+    fn starts_with(&self, token: &str) -> bool {
+        self.source.as_bytes()[self.cursor.offset..].starts_with(token.as_bytes())
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.byte(self.cursor.offset)
+    }
+
+    fn byte(&self, i: usize) -> Option<u8> {
+        self.source.as_bytes().get(i).cloned()
+    }
+
+    fn char_at(&self, i: usize) -> Option<char> {
+        self.source.get(i..)?.chars().next()
+    }
+
+    fn expected(&self, what: &str) -> Error {
+        self.expected_at(self.cursor.offset, what)
+    }
+
+    fn expected_at(&self, offset: usize, what: &str) -> Error {
+        Error::Syntax { message: format!("expected {}", what), position: self.position(offset) }
+    }
+}
+
+/// The end of the digits starting at `i`.
+fn digits_end(bytes: &[u8], i: usize) -> usize {
+    i + bytes[i.min(bytes.len())..].iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// The end of the exponent starting at `i`, if there's one.
+fn exponent_end(bytes: &[u8], i: usize) -> Option<usize> {
+    if !matches!(bytes.get(i), Some(b'e') | Some(b'E')) {
+        return None
+    }
+
+    let sign = match bytes.get(i + 1) {
+        Some(b'+') | Some(b'-') => i + 2,
+        _ => i + 1,
+    };
+    let end = digits_end(bytes, sign);
+
+    if end > sign {
+        Some(end)
+    } else {
+        None
+    }
+}
+
+fn is_pn_chars_base(c: char) -> bool {
+    matches!(c,
+        'A'..='Z' |
+        'a'..='z' |
+        '\u{00C0}'..='\u{00D6}' |
+        '\u{00D8}'..='\u{00F6}' |
+        '\u{00F8}'..='\u{02FF}' |
+        '\u{0370}'..='\u{037D}' |
+        '\u{037F}'..='\u{1FFF}' |
+        '\u{200C}'..='\u{200D}' |
+        '\u{2070}'..='\u{218F}' |
+        '\u{2C00}'..='\u{2FEF}' |
+        '\u{3001}'..='\u{D7FF}' |
+        '\u{F900}'..='\u{FDCF}' |
+        '\u{FDF0}'..='\u{FFFD}' |
+        '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+fn is_pn_chars_u(c: char) -> bool {
+    is_pn_chars_base(c) || c == '_'
+}
+
+fn is_pn_chars(c: char) -> bool {
+    is_pn_chars_u(c) ||
+        matches!(c, '-' | '0'..='9' | '\u{00B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}')
+}
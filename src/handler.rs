@@ -0,0 +1,171 @@
+//! Receiving the parts of a document as it's parsed.
+use error::{Error, Position};
+use iri::Iri;
+use lenient::{iri_end, line_end, string_end};
+use triple::{Triple, Triples};
+use {checked, Graph};
+
+/// Receives the parts of a Turtle or TriG document as `Graph::parse_with`
+/// parses it, so a document can be streamed into a custom store or
+/// inspected without collecting its `Triples`.
+///
+/// Every method does nothing by default. The events of a statement are
+/// given once the whole statement has been parsed: first its comments and
+/// any comments before it, then its prefix or base, then its warnings and
+/// then its triples. `Triples` collects the triples.
+///
+/// ```
+/// use chelone::{Graph, Iri, Position, Triple, TurtleHandler};
+///
+/// #[derive(Default)]
+/// struct Handler {
+///     prefixs: Vec<String>,
+///     comments: Vec<(String, Position)>,
+///     triples: usize,
+/// }
+///
+/// impl TurtleHandler for Handler {
+///     fn on_prefix(&mut self, prefix: &str, _: &Iri) {
+///         self.prefixs.push(prefix.to_owned());
+///     }
+///
+///     fn on_comment(&mut self, comment: &str, position: Position) {
+///         self.comments.push((comment.to_owned(), position));
+///     }
+///
+///     fn on_triple(&mut self, _: Triple) {
+///         self.triples += 1;
+///     }
+/// }
+///
+/// let source = "@prefix ex: <http://example.org/> .\n# People\nex:a ex:knows ex:b, ex:c .";
+/// let mut handler = Handler::default();
+/// Graph::new(source).unwrap().parse_with(&mut handler).unwrap();
+///
+/// assert_eq!(handler.prefixs, ["ex"]);
+/// assert_eq!(handler.comments, [(String::from(" People"), Position::new(2, 1))]);
+/// assert_eq!(handler.triples, 2);
+/// ```
+pub trait TurtleHandler {
+    /// Called for each prefix declaration, with the prefix without its `:`
+    /// and the IRI it stands for.
+    fn on_prefix(&mut self, _prefix: &str, _iri: &Iri) {}
+
+    /// Called for each base declaration, with the IRI after it's been
+    /// resolved against the previous base.
+    fn on_base(&mut self, _iri: &Iri) {}
+
+    /// Called for each triple, in the order they're written. The triples of
+    /// every graph in a TriG document are included.
+    fn on_triple(&mut self, _triple: Triple) {}
+
+    /// Called for each comment, with the text after its `#` up to the end
+    /// of its line, and where its `#` is.
+    fn on_comment(&mut self, _comment: &str, _position: Position) {}
+
+    /// Called for each problem that's allowed by the `ParserOptions`.
+    fn on_warning(&mut self, _warning: &Error) {}
+
+    /// Called with the error that stops parsing, before it's returned from
+    /// `Graph::parse_with`.
+    fn on_error(&mut self, _error: &Error) {}
+}
+
+impl TurtleHandler for Triples {
+    fn on_triple(&mut self, triple: Triple) {
+        self.0.push(triple)
+    }
+}
+
+impl<'a> Graph<'a> {
+    /// Parses the graph, giving each part of it to `handler` as it's parsed
+    /// rather than collecting the triples. See `TurtleHandler`.
+    pub fn parse_with<H: TurtleHandler>(mut self, handler: &mut H) -> Result<(), Error> {
+        let mut comments = Comments { offset: 0, position: Position::new(1, 1) };
+        let mut prefixs = self.declared_prefixs.len();
+        let mut bases = self.declared_bases.len();
+        let mut warnings = self.context.warnings.len();
+
+        loop {
+            let more = match self.parse_next_statement() {
+                Ok(more) => more,
+                Err(error) => {
+                    handler.on_error(&error);
+                    return Err(error)
+                }
+            };
+            // Both parsers know where the statement ended once it's parsed.
+            let end = if !more {
+                self.source.len()
+            } else if self.chunk.is_some() {
+                self.offset
+            } else {
+                self.cursor.offset()
+            };
+
+            comments.report(self.source, end, handler);
+
+            for (prefix, iri) in &self.declared_prefixs[prefixs..] {
+                handler.on_prefix(prefix, iri);
+            }
+
+            for base in &self.declared_bases[bases..] {
+                handler.on_base(base);
+            }
+
+            for warning in &self.context.warnings[warnings..] {
+                handler.on_warning(warning);
+            }
+
+            prefixs = self.declared_prefixs.len();
+            bases = self.declared_bases.len();
+            warnings = self.context.warnings.len();
+
+            while let Some((triple, _)) = self.pending.pop_front() {
+                handler.on_triple(checked(triple.into_owned()));
+            }
+
+            if !more {
+                return Ok(())
+            }
+        }
+    }
+}
+
+/// How far through the source comments have been reported.
+struct Comments {
+    offset: usize,
+    position: Position,
+}
+
+impl Comments {
+    /// Reports the comments that start before `end`, skipping over IRIs and
+    /// strings so a `#` inside them isn't mistaken for a comment.
+    fn report<H: TurtleHandler>(&mut self, source: &str, end: usize, handler: &mut H) {
+        let bytes = source.as_bytes();
+        let mut i = self.offset;
+
+        while i < end {
+            match bytes[i] {
+                b'#' => {
+                    let comment_end = line_end(bytes, i);
+                    let comment = &source[i + 1..comment_end];
+
+                    self.position = self.position.after(&source[self.offset..i]);
+                    self.offset = i;
+                    handler.on_comment(comment.trim_end_matches('\r'), self.position);
+                    i = comment_end;
+                }
+                b'\\' => i += 2,
+                b'<' if bytes.get(i + 1) == Some(&b'<') => i += 2,
+                b'<' => i = iri_end(bytes, i),
+                quote @ b'"' | quote @ b'\'' => i = string_end(bytes, i, quote),
+                _ => i += 1,
+            }
+        }
+
+        let i = i.min(bytes.len());
+        self.position = self.position.after(&source[self.offset..i]);
+        self.offset = i;
+    }
+}
@@ -1,12 +1,13 @@
 //! An Internationalized Resource Identifier.
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 
 use url::Url;
 
 /// Iri with containing url, inner string does not contain wrapping `<>`, they
 /// can be added by calling `iri.to_string`.
-#[derive(Clone, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Eq, PartialOrd, Ord)]
 pub struct Iri {
     url: Url,
     had_fragment: bool,
@@ -23,15 +24,15 @@ impl Iri {
     }
 
     /// Parses `&str` into `Iri`, adding it the base `Iri` optionally provided.
-    /// Uses `Url::parse` internally.
+    /// Uses `Url::parse` internally, so a relative `raw` without a `base` is
+    /// an error.
     pub fn parse_with_base_iri(raw: &str, base: Option<&Iri>)
         -> Result<Self, url::ParseError>
     {
         let had_fragment = raw.contains("#");
         let url = Url::options()
             .base_url(base.map(|b| &**b))
-            .parse(raw)
-            .unwrap();
+            .parse(raw)?;
 
 
         Ok(Iri { url, had_fragment })
@@ -59,16 +60,46 @@ impl PartialEq for Iri {
     }
 }
 
+impl Hash for Iri {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.url.hash(state)
+    }
+}
+
 impl fmt::Debug for Iri {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.url.fmt(f)
     }
 }
 
+/// Whether `iri` starts with a scheme, which is what makes it absolute.
+pub(crate) fn has_scheme(iri: &str) -> bool {
+    let scheme = match iri.find(':') {
+        Some(colon) => &iri[..colon],
+        None => return false,
+    };
+
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+        scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+}
+
 /// A blank node generated at parse time.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct BlankNode(pub String);
 
+/// A relative IRI reference that hasn't been resolved, as it was written
+/// without the wrapping `<>`. They're only kept when parsing with
+/// `ParserOptions::keep_relative_iris` and there's no base.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RelativeIri(pub String);
+
+impl RelativeIri {
+    /// Resolves the reference against `base`.
+    pub fn resolve(&self, base: &Iri) -> Result<Iri, url::ParseError> {
+        Iri::parse_with_base_iri(&self.0, Some(base))
+    }
+}
+
 impl Deref for Iri {
     type Target = Url;
 
@@ -94,3 +125,9 @@ impl fmt::Display for BlankNode {
         write!(f,"_:{}", self.0)
     }
 }
+
+impl fmt::Display for RelativeIri {
+    fn fmt(&self, f: &mut fmt::Formatter)-> fmt::Result {
+        write!(f,"<{}>", self.0)
+    }
+}
@@ -0,0 +1,885 @@
+//! The Compaction algorithm and the IRI and value compaction it relies on.
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+use url::Url;
+
+use error::Error;
+use iri::Iri;
+
+use super::context::Context;
+use super::expand::{is_graph, is_list};
+use super::{as_array, Processor};
+
+/// An active context along with its inverse context, which picks the term
+/// to compact an IRI to.
+pub(crate) struct Active {
+    pub(crate) context: Context,
+    /// The terms for each IRI, by container and then by type or language.
+    inverse: HashMap<String, BTreeMap<String, Selection>>,
+}
+
+/// The terms for one IRI and container, by type or language.
+#[derive(Debug, Default)]
+struct Selection {
+    language: HashMap<String, String>,
+    type_: HashMap<String, String>,
+    any: HashMap<String, String>,
+}
+
+impl Active {
+    /// Runs the Inverse Context Creation algorithm.
+    pub(crate) fn new(context: Context) -> Self {
+        let mut inverse: HashMap<String, BTreeMap<String, Selection>> = HashMap::new();
+        let default_language = default_language(&context);
+        let mut terms = context.terms.iter().collect::<Vec<_>>();
+        terms.sort_by(|&(a, _), &(b, _)| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+        for (term, definition) in terms {
+            let iri = match definition.iri {
+                Some(ref iri) => iri,
+                None => continue,
+            };
+
+            let mut container = definition.container.clone();
+            container.sort();
+            let container = if container.is_empty() {
+                String::from("@none")
+            } else {
+                container.concat()
+            };
+
+            let selection = inverse.entry(iri.clone())
+                .or_default()
+                .entry(container)
+                .or_default();
+            let insert = |map: &mut HashMap<String, String>, key: &str| {
+                map.entry(key.to_owned()).or_insert_with(|| term.clone());
+            };
+
+            insert(&mut selection.any, "@none");
+
+            if definition.reverse {
+                insert(&mut selection.type_, "@reverse");
+            } else if definition.type_mapping.as_deref() == Some("@none") {
+                insert(&mut selection.language, "@any");
+                insert(&mut selection.type_, "@any");
+            } else if let Some(ref type_mapping) = definition.type_mapping {
+                insert(&mut selection.type_, type_mapping);
+            } else if definition.language.is_some() || definition.direction.is_some() {
+                let language = definition.language.clone().unwrap_or_else(|| context.language.clone());
+                let direction = definition.direction.clone().unwrap_or_else(|| context.direction.clone());
+                let key = match (language, direction) {
+                    (language, Some(direction)) => {
+                        format!("{}_{}", language.unwrap_or_default(), direction)
+                    }
+                    (Some(language), None) => language.to_lowercase(),
+                    (None, None) if definition.language.is_some() => String::from("@null"),
+                    (None, None) => String::from("@none"),
+                };
+
+                insert(&mut selection.language, &key);
+            } else {
+                insert(&mut selection.language, &default_language);
+                insert(&mut selection.language, "@none");
+                insert(&mut selection.type_, "@none");
+            }
+        }
+
+        Active { context, inverse }
+    }
+
+    /// Runs the IRI Compaction algorithm on `iri`, which is compacted
+    /// against `@vocab` if `vocab` and against the base IRI otherwise.
+    /// `value` is the value `iri` is a property of, if any.
+    pub(crate) fn compact_iri(&self, iri: &str, value: Option<&Value>, vocab: bool, reverse: bool)
+        -> String
+    {
+        if vocab && self.inverse.contains_key(iri) {
+            if let Some(term) = self.select_term(iri, value, reverse) {
+                return term
+            }
+        }
+
+        if vocab {
+            if let Some(ref vocab) = self.context.vocab {
+                if iri.len() > vocab.len() && iri.starts_with(&**vocab) {
+                    let suffix = &iri[vocab.len()..];
+
+                    if self.context.term(suffix).is_none() {
+                        return suffix.to_owned()
+                    }
+                }
+            }
+        }
+
+        let mut compact: Option<String> = None;
+
+        for (term, definition) in &self.context.terms {
+            let prefix = match definition.iri {
+                Some(ref prefix) if definition.prefix && prefix != iri && iri.starts_with(&**prefix) => {
+                    prefix
+                }
+                _ => continue,
+            };
+
+            let candidate = format!("{}:{}", term, &iri[prefix.len()..]);
+            let shorter = compact.as_ref().is_none_or(|compact| {
+                (candidate.len(), &candidate) < (compact.len(), compact)
+            });
+            let unambiguous = match self.context.term(&candidate) {
+                None => true,
+                Some(definition) => definition.iri.as_deref() == Some(iri) && value.is_none(),
+            };
+
+            if shorter && unambiguous {
+                compact = Some(candidate);
+            }
+        }
+
+        if let Some(compact) = compact {
+            return compact
+        }
+
+        if !vocab {
+            if let Some(relative) = self.context.base.as_ref().and_then(|base| relative_iri(base, iri)) {
+                return relative
+            }
+        }
+
+        iri.to_owned()
+    }
+
+    /// Picks the term for `iri` that best fits `value`, following steps
+    /// 4.1 to 4.21 of IRI Compaction and the Term Selection algorithm.
+    fn select_term(&self, iri: &str, value: Option<&Value>, reverse: bool) -> Option<String> {
+        let default_language = default_language(&self.context);
+        let object = value.and_then(Value::as_object);
+        let has = |key: &str| object.is_some_and(|object| object.contains_key(key));
+        let get = |key: &str| object.and_then(|object| object.get(key)).and_then(Value::as_str);
+        let mut containers: Vec<&str> = Vec::new();
+        let mut type_language = "@language";
+        let mut type_language_value = String::from("@null");
+
+        if has("@index") && !value.is_some_and(is_graph) {
+            containers.extend(&["@index", "@index@set"]);
+        }
+
+        if reverse {
+            type_language = "@type";
+            type_language_value = String::from("@reverse");
+            containers.push("@set");
+        } else if value.is_some_and(is_list) {
+            if !has("@index") {
+                containers.push("@list");
+            }
+
+            let list = object.and_then(|object| object["@list"].as_array()).map(|list| &**list).unwrap_or_default();
+            let mut common_type: Option<String> = None;
+            let mut common_language = if list.is_empty() { Some(default_language.clone()) } else { None };
+
+            for item in list {
+                let mut item_language = String::from("@none");
+                let mut item_type = String::from("@none");
+                let item_get = |key: &str| item.get(key).and_then(Value::as_str);
+
+                if item.get("@value").is_some() {
+                    if let Some(direction) = item_get("@direction") {
+                        item_language = format!("{}_{}", item_get("@language").unwrap_or_default(), direction);
+                    } else if let Some(language) = item_get("@language") {
+                        item_language = language.to_lowercase();
+                    } else if let Some(t) = item_get("@type") {
+                        item_type = t.to_owned();
+                    } else {
+                        item_language = String::from("@null");
+                    }
+                } else {
+                    item_type = String::from("@id");
+                }
+
+                match common_language {
+                    None => common_language = Some(item_language),
+                    Some(ref common) if *common != item_language && item.get("@value").is_some() => {
+                        common_language = Some(String::from("@none"));
+                    }
+                    _ => {}
+                }
+
+                match common_type {
+                    None => common_type = Some(item_type),
+                    Some(ref common) if *common != item_type => common_type = Some(String::from("@none")),
+                    _ => {}
+                }
+
+                if common_language.as_deref() == Some("@none") && common_type.as_deref() == Some("@none") {
+                    break
+                }
+            }
+
+            let common_language = common_language.unwrap_or_else(|| String::from("@none"));
+            let common_type = common_type.unwrap_or_else(|| String::from("@none"));
+
+            if common_type != "@none" {
+                type_language = "@type";
+                type_language_value = common_type;
+            } else {
+                type_language_value = common_language;
+            }
+        } else if value.is_some_and(is_graph) {
+            if has("@index") {
+                containers.extend(&["@graph@index", "@graph@index@set"]);
+            }
+
+            if has("@id") {
+                containers.extend(&["@graph@id", "@graph@id@set"]);
+            }
+
+            containers.extend(&["@graph", "@graph@set", "@set"]);
+
+            if !has("@index") {
+                containers.extend(&["@graph@index", "@graph@index@set"]);
+            }
+
+            if !has("@id") {
+                containers.extend(&["@graph@id", "@graph@id@set"]);
+            }
+
+            containers.extend(&["@index", "@index@set"]);
+            type_language = "@type";
+            type_language_value = String::from("@id");
+        } else {
+            if has("@value") {
+                if let (Some(direction), false) = (get("@direction"), has("@index")) {
+                    type_language_value = format!("{}_{}", get("@language").unwrap_or_default(), direction);
+                    containers.extend(&["@language", "@language@set"]);
+                } else if let (Some(language), false) = (get("@language"), has("@index")) {
+                    type_language_value = language.to_lowercase();
+                    containers.extend(&["@language", "@language@set"]);
+                } else if let Some(t) = get("@type") {
+                    type_language = "@type";
+                    type_language_value = t.to_owned();
+                }
+            } else {
+                type_language = "@type";
+                type_language_value = String::from("@id");
+                containers.extend(&["@id", "@id@set", "@type", "@set@type"]);
+            }
+
+            containers.push("@set");
+        }
+
+        containers.push("@none");
+
+        if !has("@index") {
+            containers.extend(&["@index", "@index@set"]);
+        }
+
+        if has("@value") && object.is_some_and(|object| object.len() == 1) {
+            containers.extend(&["@language", "@language@set"]);
+        }
+
+        let mut preferred = Vec::new();
+
+        if type_language_value == "@reverse" {
+            preferred.push(String::from("@reverse"));
+        }
+
+        if (type_language_value == "@id" || type_language_value == "@reverse") && has("@id") {
+            let id = get("@id").unwrap_or_default();
+            let compacted = self.compact_iri(id, None, true, false);
+            let same = self.context.term(&compacted).and_then(|term| term.iri.as_deref()) == Some(id);
+
+            if same {
+                preferred.extend(["@vocab", "@id", "@none"].iter().map(|&s| s.to_owned()));
+            } else {
+                preferred.extend(["@id", "@vocab", "@none"].iter().map(|&s| s.to_owned()));
+            }
+        } else {
+            let empty_list = object.and_then(|object| object.get("@list"))
+                .and_then(Value::as_array)
+                .is_some_and(|list| list.is_empty());
+
+            preferred.push(type_language_value);
+            preferred.push(String::from("@none"));
+
+            if empty_list {
+                type_language = "@any";
+            }
+        }
+
+        preferred.push(String::from("@any"));
+
+        let directions = preferred.iter()
+            .filter_map(|value| value.find('_').map(|index| value[index..].to_owned()))
+            .collect::<Vec<_>>();
+        preferred.extend(directions);
+
+        let selections = &self.inverse[iri];
+
+        for container in containers {
+            let selection = match selections.get(container) {
+                Some(selection) => selection,
+                None => continue,
+            };
+            let map = match type_language {
+                "@type" => &selection.type_,
+                "@any" => &selection.any,
+                _ => &selection.language,
+            };
+
+            for value in &preferred {
+                if let Some(term) = map.get(value) {
+                    return Some(term.clone())
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Runs the Value Compaction algorithm.
+    fn compact_value(&self, active_property: Option<&str>, value: &Map<String, Value>) -> Value {
+        let term = active_property.and_then(|property| self.context.term(property));
+        let language = term.and_then(|term| term.language.clone())
+            .unwrap_or_else(|| self.context.language.clone());
+        let direction = term.and_then(|term| term.direction.clone())
+            .unwrap_or_else(|| self.context.direction.clone());
+        let type_mapping = term.and_then(|term| term.type_mapping.as_deref());
+        let index_ok = !value.contains_key("@index") || term.is_some_and(|term| term.has_container("@index"));
+        let get = |key: &str| value.get(key).and_then(Value::as_str);
+
+        if value.contains_key("@id") && value.keys().all(|key| key == "@id" || key == "@index") && index_ok {
+            match type_mapping {
+                Some("@id") => return Value::from(self.compact_iri(get("@id").unwrap_or_default(), None, false, false)),
+                Some("@vocab") => return Value::from(self.compact_iri(get("@id").unwrap_or_default(), None, true, false)),
+                _ => {}
+            }
+        } else if value.contains_key("@value") {
+            let same_language = get("@language").map(str::to_lowercase) == language.as_ref().map(|l| l.to_lowercase());
+            let same_direction = get("@direction") == direction.as_deref();
+
+            if get("@type").is_some() && get("@type") == type_mapping && index_ok {
+                return value["@value"].clone()
+            } else if type_mapping == Some("@none") || (value.contains_key("@type") && get("@type") != type_mapping) {
+                // Keep the value object, compacting its type below.
+            } else if !value["@value"].is_string() {
+                if index_ok {
+                    return value["@value"].clone()
+                }
+            } else if same_language && same_direction && index_ok {
+                return value["@value"].clone()
+            }
+        }
+
+        let mut result = Map::new();
+
+        for (key, item) in value {
+            let item = match (&**key, item) {
+                ("@type", Value::String(t)) if t != "@json" => Value::from(self.compact_iri(t, None, true, false)),
+                ("@id", Value::String(id)) => Value::from(self.compact_iri(id, None, false, false)),
+                _ => item.clone(),
+            };
+
+            result.insert(self.compact_iri(key, None, true, false), item);
+        }
+
+        Value::Object(result)
+    }
+}
+
+impl<'a> Processor<'a> {
+    /// Compacts `expanded` against `context`, returning a map with the
+    /// context under `@context`.
+    pub(crate) fn compact_document(&mut self, expanded: &Value, context: &Value, base: Option<&Iri>)
+        -> Result<Value, Error>
+    {
+        let active = Context::new(base.cloned());
+        let active = Active::new(self.process_context(&active, context, base, false, true)?);
+
+        let mut result = match self.compact(&active, None, expanded)? {
+            Value::Object(result) => result,
+            Value::Array(ref items) if items.is_empty() => Map::new(),
+            compacted => {
+                let mut result = Map::new();
+                result.insert(active.compact_iri("@graph", None, true, false), as_array(compacted));
+                result
+            }
+        };
+
+        let empty = match *context {
+            Value::Null => true,
+            Value::Object(ref context) => context.is_empty(),
+            Value::Array(ref context) => context.is_empty(),
+            _ => false,
+        };
+
+        if !empty && !result.is_empty() {
+            result.insert(String::from("@context"), context.clone());
+        }
+
+        Ok(Value::Object(result))
+    }
+
+    /// Runs the Compaction algorithm on `element`.
+    pub(crate) fn compact(&mut self, active: &Active, active_property: Option<&str>, element: &Value)
+        -> Result<Value, Error>
+    {
+        match *element {
+            Value::Array(ref items) => {
+                let mut result = Vec::new();
+
+                for item in items {
+                    let compacted = self.compact(active, active_property, item)?;
+
+                    if !compacted.is_null() {
+                        result.push(compacted);
+                    }
+                }
+
+                let keep_array = result.len() != 1 ||
+                    active_property == Some("@graph") ||
+                    active_property == Some("@set") ||
+                    active.context.has_container(active_property, "@list") ||
+                    active.context.has_container(active_property, "@set");
+
+                if keep_array {
+                    Ok(Value::Array(result))
+                } else {
+                    Ok(result.pop().unwrap())
+                }
+            }
+            Value::Object(ref element) => self.compact_object(active, active_property, element),
+            ref scalar => Ok(scalar.clone()),
+        }
+    }
+
+    fn compact_object(&mut self,
+                      active: &Active,
+                      active_property: Option<&str>,
+                      element: &Map<String, Value>)
+        -> Result<Value, Error>
+    {
+        let scoped = active_property
+            .and_then(|property| active.context.term(property))
+            .and_then(|term| term.context.clone());
+        let mut changed = None;
+
+        if let Some(ref previous) = active.context.previous {
+            let node_reference = element.len() == 1 && element.contains_key("@id");
+
+            if !element.contains_key("@value") && !node_reference {
+                changed = Some(Active::new((**previous).clone()));
+            }
+        }
+
+        if let Some((context, base)) = scoped {
+            let current = &changed.as_ref().unwrap_or(active).context;
+            changed = Some(Active::new(self.process_context(current, &context, base.as_ref(), true, true)?));
+        }
+
+        let active = changed.as_ref().unwrap_or(active);
+
+        if element.contains_key("@value") || element.contains_key("@id") {
+            let compacted = active.compact_value(active_property, element);
+            let json = active_property
+                .and_then(|property| active.context.term(property))
+                .is_some_and(|term| term.type_mapping.as_deref() == Some("@json"));
+
+            if !compacted.is_object() || json {
+                return Ok(compacted)
+            }
+        }
+
+        if let Some(list) = element.get("@list") {
+            if active.context.has_container(active_property, "@list") {
+                return self.compact(active, active_property, list)
+            }
+        }
+
+        let inside_reverse = active_property == Some("@reverse");
+        let mut result = Map::new();
+        let mut type_scoped = None;
+
+        if let Some(types) = element.get("@type").and_then(Value::as_array) {
+            let mut compacted = types.iter()
+                .filter_map(Value::as_str)
+                .map(|t| active.compact_iri(t, None, true, false))
+                .collect::<Vec<_>>();
+            compacted.sort();
+
+            let mut context = active.context.clone();
+
+            for t in compacted {
+                if let Some((ref local, ref base)) = active.context.term(&t).and_then(|term| term.context.clone()) {
+                    context = self.process_context(&context, local, base.as_ref(), false, false)?;
+                }
+            }
+
+            if context != active.context {
+                type_scoped = Some(Active::new(context));
+            }
+        }
+
+        let input = active;
+        let active = type_scoped.as_ref().unwrap_or(active);
+
+        for (key, value) in element {
+            match &**key {
+                "@id" => {
+                    let id = match value.as_str() {
+                        Some(id) => Value::from(active.compact_iri(id, None, false, false)),
+                        None => value.clone(),
+                    };
+
+                    result.insert(active.compact_iri("@id", None, true, false), id);
+                    continue
+                }
+                "@type" => {
+                    let mut types = as_array(value.clone())
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|t| match t.as_str() {
+                            Some(t) => Value::from(input.compact_iri(t, None, true, false)),
+                            None => t.clone(),
+                        })
+                        .collect::<Vec<_>>();
+                    let alias = active.compact_iri("@type", None, true, false);
+                    let as_array = active.context.has_container(Some(&alias), "@set");
+                    let types = if types.len() == 1 { types.pop().unwrap() } else { Value::Array(types) };
+
+                    add_value(&mut result, &alias, types, as_array);
+                    continue
+                }
+                "@reverse" => {
+                    let mut compacted = match self.compact(active, Some("@reverse"), value)? {
+                        Value::Object(compacted) => compacted,
+                        _ => Map::new(),
+                    };
+                    let reverse_terms = compacted.keys()
+                        .filter(|property| active.context.term(property).is_some_and(|term| term.reverse))
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    for property in reverse_terms {
+                        let value = compacted.remove(&property).unwrap();
+                        let as_array = active.context.has_container(Some(&property), "@set");
+
+                        add_value(&mut result, &property, value, as_array);
+                    }
+
+                    if !compacted.is_empty() {
+                        let alias = active.compact_iri("@reverse", None, true, false);
+                        result.insert(alias, Value::Object(compacted));
+                    }
+
+                    continue
+                }
+                "@preserve" => {
+                    let compacted = self.compact(active, active_property, value)?;
+
+                    if compacted.as_array().is_none_or(|items| !items.is_empty()) {
+                        result.insert(key.clone(), compacted);
+                    }
+
+                    continue
+                }
+                "@index" if active.context.has_container(active_property, "@index") => continue,
+                "@direction" | "@index" | "@language" | "@value" => {
+                    result.insert(active.compact_iri(key, None, true, false), value.clone());
+                    continue
+                }
+                _ => {}
+            }
+
+            let items = match *value {
+                Value::Array(ref items) => &items[..],
+                ref value => ::std::slice::from_ref(value),
+            };
+
+            if items.is_empty() {
+                let property = active.compact_iri(key, Some(value), true, inside_reverse);
+                let target = nest_target(active, &mut result, &property)?;
+                add_value(target, &property, Value::Array(Vec::new()), true);
+            }
+
+            for item in items {
+                let property = active.compact_iri(key, Some(item), true, inside_reverse);
+                let term = active.context.term(&property);
+                let container = |name: &str| term.is_some_and(|term| term.has_container(name));
+                let set = container("@set") || property == "@graph" || property == "@list";
+
+                let inner = if is_list(item) {
+                    &item["@list"]
+                } else if is_graph(item) {
+                    &item["@graph"]
+                } else {
+                    item
+                };
+                let mut compacted = self.compact(active, Some(&property), inner)?;
+                let target = nest_target(active, &mut result, &property)?;
+
+                if is_list(item) {
+                    compacted = as_array(compacted);
+
+                    if container("@list") {
+                        target.insert(property, compacted);
+                    } else {
+                        let mut list = Map::new();
+                        list.insert(active.compact_iri("@list", None, true, false), compacted);
+
+                        if let Some(index) = item.get("@index") {
+                            list.insert(active.compact_iri("@index", None, true, false), index.clone());
+                        }
+
+                        add_value(target, &property, Value::Object(list), set);
+                    }
+                } else if is_graph(item) {
+                    let id = item.get("@id").and_then(Value::as_str);
+                    let index = item.get("@index").and_then(Value::as_str);
+
+                    if container("@graph") && container("@id") {
+                        let key = match id {
+                            Some(id) => active.compact_iri(id, None, false, false),
+                            None => active.compact_iri("@none", None, true, false),
+                        };
+
+                        add_to_map(target, &property, &key, compacted, set);
+                    } else if container("@graph") && container("@index") && id.is_none() {
+                        let key = match index {
+                            Some(index) => index.to_owned(),
+                            None => active.compact_iri("@none", None, true, false),
+                        };
+
+                        add_to_map(target, &property, &key, compacted, set);
+                    } else if container("@graph") && id.is_none() {
+                        if compacted.as_array().is_some_and(|items| items.len() > 1) {
+                            let mut included = Map::new();
+                            included.insert(active.compact_iri("@included", None, true, false), compacted);
+                            compacted = Value::Object(included);
+                        }
+
+                        add_value(target, &property, compacted, set);
+                    } else {
+                        let mut graph = Map::new();
+                        graph.insert(active.compact_iri("@graph", None, true, false), as_array(compacted));
+
+                        if let Some(id) = id {
+                            graph.insert(active.compact_iri("@id", None, true, false),
+                                         Value::from(active.compact_iri(id, None, false, false)));
+                        }
+
+                        if let Some(index) = index {
+                            graph.insert(active.compact_iri("@index", None, true, false), Value::from(index));
+                        }
+
+                        add_value(target, &property, Value::Object(graph), set);
+                    }
+                } else if !container("@graph") &&
+                          ["@language", "@index", "@id", "@type"].iter().any(|&name| container(name))
+                {
+                    let key;
+
+                    if container("@language") {
+                        if let Some(value) = item.get("@value") {
+                            compacted = value.clone();
+                        }
+
+                        key = item.get("@language").and_then(Value::as_str).map(str::to_owned);
+                    } else if container("@index") {
+                        match term.and_then(|term| term.index.as_deref()) {
+                            None | Some("@index") => {
+                                key = item.get("@index").and_then(Value::as_str).map(str::to_owned);
+                            }
+                            Some(index) => {
+                                let index = active.compact_iri(index, None, true, false);
+                                key = take_first(&mut compacted, &index);
+                            }
+                        }
+                    } else if container("@id") {
+                        let alias = active.compact_iri("@id", None, true, false);
+                        key = compacted.as_object_mut()
+                            .and_then(|object| object.remove(&alias))
+                            .and_then(|id| id.as_str().map(str::to_owned));
+                    } else {
+                        let alias = active.compact_iri("@type", None, true, false);
+                        key = take_first(&mut compacted, &alias);
+
+                        let id_alias = active.compact_iri("@id", None, true, false);
+                        let reference = compacted.as_object()
+                            .is_some_and(|object| object.len() == 1 && object.contains_key(&id_alias));
+
+                        if reference {
+                            let mut reference = Map::new();
+                            reference.insert(String::from("@id"), item["@id"].clone());
+                            compacted = self.compact(active, Some(&property), &Value::Object(reference))?;
+                        }
+                    }
+
+                    let key = key.unwrap_or_else(|| active.compact_iri("@none", None, true, false));
+                    add_to_map(target, &property, &key, compacted, set);
+                } else {
+                    add_value(target, &property, compacted, set);
+                }
+            }
+        }
+
+        Ok(Value::Object(result))
+    }
+}
+
+/// The map that values of `property` go in, which is the map of its
+/// `@nest` term if it has one.
+fn nest_target<'m>(active: &Active, result: &'m mut Map<String, Value>, property: &str)
+    -> Result<&'m mut Map<String, Value>, Error>
+{
+    let nest = match active.context.term(property).and_then(|term| term.nest.clone()) {
+        Some(nest) => nest,
+        None => return Ok(result),
+    };
+
+    let expanded = active.context.expand_iri(&nest, false, true, None);
+
+    if expanded.as_deref() != Some("@nest") {
+        return Err(Error::json_ld("invalid @nest value", nest))
+    }
+
+    let target = result.entry(nest).or_insert_with(|| Value::Object(Map::new()));
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    Ok(target.as_object_mut().unwrap())
+}
+
+/// Adds `value` to the map at `property` in `target` under `key`.
+fn add_to_map(target: &mut Map<String, Value>, property: &str, key: &str, value: Value, as_array: bool) {
+    let map = target.entry(property.to_owned()).or_insert_with(|| Value::Object(Map::new()));
+
+    if let Some(map) = map.as_object_mut() {
+        add_value(map, key, value, as_array);
+    }
+}
+
+/// Removes and returns the first string of `key` in `compacted`, leaving
+/// any others behind.
+fn take_first(compacted: &mut Value, key: &str) -> Option<String> {
+    let object = compacted.as_object_mut()?;
+    let mut values = match object.remove(key)? {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+
+    if values.is_empty() {
+        return None
+    }
+
+    let first = values.remove(0);
+
+    match values.len() {
+        0 => {}
+        1 => { object.insert(key.to_owned(), values.pop().unwrap()); }
+        _ => { object.insert(key.to_owned(), Value::Array(values)); }
+    }
+
+    first.as_str().map(str::to_owned)
+}
+
+/// Adds `value` to `key` in `map`, only using an array when there's more
+/// than one value or `as_array` is set.
+fn add_value(map: &mut Map<String, Value>, key: &str, value: Value, as_array: bool) {
+    if as_array && !map.contains_key(key) {
+        map.insert(key.to_owned(), Value::Array(Vec::new()));
+    }
+
+    match (value, map.get_mut(key)) {
+        (Value::Array(items), _) => {
+            if items.is_empty() && !map.contains_key(key) {
+                map.insert(key.to_owned(), Value::Array(Vec::new()));
+            }
+
+            for item in items {
+                add_value(map, key, item, as_array);
+            }
+        }
+        (value, Some(existing)) => {
+            if !existing.is_array() {
+                *existing = Value::Array(vec![existing.take()]);
+            }
+
+            existing.as_array_mut().unwrap().push(value);
+        }
+        (value, None) => {
+            map.insert(key.to_owned(), value);
+        }
+    }
+}
+
+/// The default language and direction as they appear in the inverse
+/// context.
+fn default_language(context: &Context) -> String {
+    match (&context.language, &context.direction) {
+        (language, Some(direction)) => {
+            format!("{}_{}", language.as_deref().unwrap_or_default(), direction)
+        }
+        (Some(language), None) => language.to_lowercase(),
+        (None, None) => String::from("@none"),
+    }
+}
+
+/// Makes `iri` relative to `base`, if they share a scheme and authority.
+fn relative_iri(base: &Url, iri: &str) -> Option<String> {
+    let target = Url::parse(iri).ok()?;
+
+    if target.scheme() != base.scheme() ||
+       target[..::url::Position::BeforePath] != base[..::url::Position::BeforePath] ||
+       target.cannot_be_a_base()
+    {
+        return None
+    }
+
+    let mut relative = String::new();
+
+    if target.path() != base.path() || target.query() != base.query() {
+        let mut base_directories = base.path().split('/').collect::<Vec<_>>();
+        base_directories.pop();
+
+        let mut segments = target.path().split('/').collect::<Vec<_>>();
+        let file = segments.pop().unwrap_or_default();
+        let common = base_directories.iter()
+            .zip(&segments)
+            .take_while(|&(a, b)| a == b)
+            .count();
+
+        for _ in common..base_directories.len() {
+            relative.push_str("../");
+        }
+
+        for segment in &segments[common..] {
+            relative.push_str(segment);
+            relative.push('/');
+        }
+
+        relative.push_str(file);
+
+        // A first segment with a colon would read as a scheme.
+        if relative.split('/').next().is_some_and(|segment| segment.contains(':')) ||
+           (relative.is_empty() && target.query().is_none())
+        {
+            relative.insert_str(0, "./");
+        }
+
+        if let Some(query) = target.query() {
+            relative.push('?');
+            relative.push_str(query);
+        }
+    }
+
+    if let Some(fragment) = target.fragment() {
+        relative.push('#');
+        relative.push_str(fragment);
+    }
+
+    Some(relative)
+}
@@ -0,0 +1,768 @@
+//! Context processing and IRI expansion.
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+
+use error::Error;
+use iri::Iri;
+
+use super::{has_keyword_form, is_absolute_iri, is_blank_node_id, is_keyword, resolve, Processor};
+
+/// The most remote contexts a document may load, which stops contexts that
+/// include each other from loading forever.
+const MAX_REMOTE_CONTEXTS: usize = 32;
+
+/// The keywords a context may contain besides term definitions.
+const CONTEXT_KEYWORDS: &[&str] = &[
+    "@base", "@direction", "@import", "@language", "@propagate", "@protected", "@version",
+    "@vocab",
+];
+
+/// An active context.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Context {
+    /// The base IRI, `None` if it was set to `null`.
+    pub(crate) base: Option<Iri>,
+    /// The base IRI of the document, which a `null` context reverts to.
+    pub(crate) original_base: Option<Iri>,
+    pub(crate) vocab: Option<String>,
+    pub(crate) language: Option<String>,
+    pub(crate) direction: Option<String>,
+    pub(crate) terms: BTreeMap<String, Term>,
+    /// The context to go back to when leaving a node object, if this one
+    /// came from a context with `@propagate: false`.
+    pub(crate) previous: Option<Box<Context>>,
+}
+
+/// A term definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Term {
+    /// The IRI or keyword the term maps to, `None` for terms explicitly
+    /// mapped to `null`.
+    pub(crate) iri: Option<String>,
+    pub(crate) prefix: bool,
+    pub(crate) protected: bool,
+    pub(crate) reverse: bool,
+    pub(crate) type_mapping: Option<String>,
+    /// `Some(None)` when the term's language is explicitly `null`.
+    pub(crate) language: Option<Option<String>>,
+    /// `Some(None)` when the term's direction is explicitly `null`.
+    pub(crate) direction: Option<Option<String>>,
+    pub(crate) container: Vec<String>,
+    pub(crate) index: Option<String>,
+    pub(crate) nest: Option<String>,
+    /// The term's scoped context and the base IRI to process it with.
+    pub(crate) context: Option<(Value, Option<Iri>)>,
+}
+
+impl Term {
+    pub(crate) fn has_container(&self, container: &str) -> bool {
+        self.container.iter().any(|c| c == container)
+    }
+}
+
+impl Context {
+    pub(crate) fn new(base: Option<Iri>) -> Self {
+        Context {
+            base: base.clone(),
+            original_base: base,
+            ..Context::default()
+        }
+    }
+
+    pub(crate) fn term(&self, term: &str) -> Option<&Term> {
+        self.terms.get(term)
+    }
+
+    /// Whether `container` is part of the container mapping of `term`.
+    pub(crate) fn has_container(&self, term: Option<&str>, container: &str) -> bool {
+        term.and_then(|term| self.term(term))
+            .is_some_and(|term| term.has_container(container))
+    }
+}
+
+/// The terms of a local context and whether each one has been defined yet,
+/// or is being defined.
+struct Definitions<'c> {
+    context: &'c Map<String, Value>,
+    defined: HashMap<String, bool>,
+}
+
+/// The options that apply to every term of a local context.
+#[derive(Clone, Copy)]
+struct Options<'r> {
+    base: Option<&'r Iri>,
+    remote: &'r [String],
+    protected: bool,
+    override_protected: bool,
+}
+
+impl<'a> Processor<'a> {
+    /// Processes `local` on top of `active`, returning the new active
+    /// context.
+    pub(crate) fn process_context(&mut self,
+                                  active: &Context,
+                                  local: &Value,
+                                  base: Option<&Iri>,
+                                  override_protected: bool,
+                                  propagate: bool)
+        -> Result<Context, Error>
+    {
+        self.process_context_with(active, local, base, &[], override_protected, propagate, true)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_context_with(&mut self,
+                            active: &Context,
+                            local: &Value,
+                            base: Option<&Iri>,
+                            remote: &[String],
+                            override_protected: bool,
+                            mut propagate: bool,
+                            validate_scoped: bool)
+        -> Result<Context, Error>
+    {
+        let mut result = active.clone();
+
+        if let Some(value) = local.get("@propagate") {
+            propagate = value.as_bool()
+                .ok_or_else(|| Error::json_ld("invalid @propagate value", value.to_string()))?;
+        }
+
+        if !propagate && result.previous.is_none() {
+            result.previous = Some(Box::new(active.clone()));
+        }
+
+        let locals = match *local {
+            Value::Array(ref locals) => locals.iter().collect::<Vec<_>>(),
+            ref local => vec![local],
+        };
+
+        for local in locals {
+            let context = match *local {
+                Value::Null => {
+                    if !override_protected && result.terms.values().any(|t| t.protected) {
+                        return Err(Error::json_ld("invalid context nullification", ""))
+                    }
+
+                    let previous = result;
+                    result = Context::new(active.original_base.clone());
+
+                    if !propagate {
+                        result.previous = Some(Box::new(previous));
+                    }
+
+                    continue
+                }
+                Value::String(ref iri) => {
+                    let iri = resolve(base, iri);
+
+                    if !validate_scoped && remote.contains(&iri) {
+                        continue
+                    }
+
+                    if remote.len() >= MAX_REMOTE_CONTEXTS {
+                        return Err(Error::json_ld("context overflow", iri))
+                    }
+
+                    let mut remote = remote.to_vec();
+                    remote.push(iri.clone());
+
+                    let context = self.load_context(&iri, "loading remote context failed")?;
+                    let loaded_base = Iri::parse(&iri).ok();
+
+                    result = self.process_context_with(&result,
+                                                       &context,
+                                                       loaded_base.as_ref(),
+                                                       &remote,
+                                                       false,
+                                                       true,
+                                                       validate_scoped)?;
+                    continue
+                }
+                Value::Object(ref context) => context,
+                ref local => return Err(Error::json_ld("invalid local context", local.to_string())),
+            };
+
+            let mut context = context.clone();
+
+            if let Some(version) = context.get("@version") {
+                if version.as_f64() != Some(1.1) {
+                    return Err(Error::json_ld("invalid @version value", version.to_string()))
+                }
+            }
+
+            if let Some(import) = context.get("@import").cloned() {
+                let iri = match import {
+                    Value::String(ref iri) => resolve(base, iri),
+                    import => return Err(Error::json_ld("invalid @import value", import.to_string())),
+                };
+
+                let imported = match self.load_context(&iri, "loading remote context failed")? {
+                    Value::Object(imported) => imported,
+                    imported => return Err(Error::json_ld("invalid remote context", imported.to_string())),
+                };
+
+                if imported.contains_key("@import") {
+                    return Err(Error::json_ld("invalid context entry", iri))
+                }
+
+                for (key, value) in imported {
+                    context.entry(key).or_insert(value);
+                }
+            }
+
+            if let Some(value) = context.get("@base") {
+                if remote.is_empty() {
+                    result.base = match *value {
+                        Value::Null => None,
+                        Value::String(ref iri) if is_absolute_iri(iri) => {
+                            Some(Iri::parse(iri).map_err(|_| invalid_base(value))?)
+                        }
+                        Value::String(ref iri) if result.base.is_some() => {
+                            Some(Iri::parse(&resolve(result.base.as_ref(), iri))
+                                 .map_err(|_| invalid_base(value))?)
+                        }
+                        _ => return Err(invalid_base(value)),
+                    };
+                }
+            }
+
+            if let Some(value) = context.get("@vocab") {
+                result.vocab = match *value {
+                    Value::Null => None,
+                    Value::String(ref vocab) => {
+                        let base = result.base.clone();
+                        let vocab = result.expand_iri(vocab, true, true, base.as_ref())
+                            .filter(|vocab| is_absolute_iri(vocab) || is_blank_node_id(vocab));
+                        match vocab {
+                            Some(vocab) => Some(vocab),
+                            None => return Err(Error::json_ld("invalid vocab mapping", value.to_string())),
+                        }
+                    }
+                    _ => return Err(Error::json_ld("invalid vocab mapping", value.to_string())),
+                };
+            }
+
+            if let Some(value) = context.get("@language") {
+                result.language = match *value {
+                    Value::Null => None,
+                    Value::String(ref language) => Some(language.to_lowercase()),
+                    _ => return Err(Error::json_ld("invalid default language", value.to_string())),
+                };
+            }
+
+            if let Some(value) = context.get("@direction") {
+                result.direction = match *value {
+                    Value::Null => None,
+                    Value::String(ref direction) if direction == "ltr" || direction == "rtl" => {
+                        Some(direction.clone())
+                    }
+                    _ => return Err(Error::json_ld("invalid base direction", value.to_string())),
+                };
+            }
+
+            if let Some(value) = context.get("@propagate") {
+                if !value.is_boolean() {
+                    return Err(Error::json_ld("invalid @propagate value", value.to_string()))
+                }
+            }
+
+            let protected = match context.get("@protected") {
+                Some(&Value::Bool(protected)) => protected,
+                Some(value) => return Err(Error::json_ld("invalid @protected value", value.to_string())),
+                None => false,
+            };
+
+            let mut definitions = Definitions {
+                context: &context,
+                defined: HashMap::new(),
+            };
+            let options = Options {
+                base,
+                remote,
+                protected,
+                override_protected,
+            };
+
+            for term in context.keys() {
+                if !CONTEXT_KEYWORDS.contains(&&**term) {
+                    self.create_term(&mut result, &mut definitions, term, options)?;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Loads the document at `iri` and returns its `@context`.
+    fn load_context(&mut self, iri: &str, code: &'static str) -> Result<Value, Error> {
+        if let Some(context) = self.remote_contexts.get(iri) {
+            return Ok(context.clone())
+        }
+
+        let failed = |error: Error| Error::json_ld(code, format!("{}: {}", iri, error));
+        let loader = self.loader
+            .ok_or_else(|| Error::json_ld(code, iri))?;
+        let parsed = Iri::parse(iri).map_err(|_| Error::json_ld(code, iri))?;
+        let text = loader.load(&parsed).map_err(failed)?;
+        let document = ::serde_json::from_str::<Value>(&text)
+            .map_err(|error| Error::json_ld(code, format!("{}: {}", iri, error)))?;
+
+        let context = match document {
+            Value::Object(mut document) if document.contains_key("@context") => {
+                document.remove("@context").unwrap()
+            }
+            _ => return Err(Error::json_ld("invalid remote context", iri)),
+        };
+
+        self.remote_contexts.insert(iri.to_owned(), context.clone());
+        Ok(context)
+    }
+
+    /// Runs the Create Term Definition algorithm for `term`.
+    fn create_term(&mut self,
+                   active: &mut Context,
+                   definitions: &mut Definitions,
+                   term: &str,
+                   options: Options)
+        -> Result<(), Error>
+    {
+        match definitions.defined.get(term) {
+            Some(&true) => return Ok(()),
+            Some(&false) => return Err(Error::json_ld("cyclic IRI mapping", term)),
+            None => {}
+        }
+
+        if term.is_empty() {
+            return Err(Error::json_ld("invalid term definition", term))
+        }
+
+        definitions.defined.insert(term.to_owned(), false);
+
+        let value = definitions.context[term].clone();
+
+        if term == "@type" {
+            let allowed = value.as_object().is_some_and(|value| {
+                !value.is_empty() && value.iter().all(|(key, value)| match &**key {
+                    "@container" => value == "@set",
+                    "@protected" => true,
+                    _ => false,
+                })
+            });
+
+            if !allowed {
+                return Err(Error::json_ld("keyword redefinition", term))
+            }
+        } else if is_keyword(term) {
+            return Err(Error::json_ld("keyword redefinition", term))
+        } else if has_keyword_form(term) {
+            definitions.defined.insert(term.to_owned(), true);
+            return Ok(())
+        }
+
+        let previous = active.terms.remove(term);
+
+        let (value, simple) = match value {
+            Value::Null => {
+                let mut map = Map::new();
+                map.insert(String::from("@id"), Value::Null);
+                (map, false)
+            }
+            Value::String(_) => {
+                let mut map = Map::new();
+                map.insert(String::from("@id"), value);
+                (map, true)
+            }
+            Value::Object(map) => (map, false),
+            value => return Err(Error::json_ld("invalid term definition", value.to_string())),
+        };
+
+        let protected = match value.get("@protected") {
+            Some(&Value::Bool(protected)) => protected,
+            Some(value) => return Err(Error::json_ld("invalid @protected value", value.to_string())),
+            None => options.protected,
+        };
+        let mut definition = Term { protected, ..Term::default() };
+
+        if let Some(value) = value.get("@type") {
+            let type_mapping = match *value {
+                Value::String(ref type_mapping) => {
+                    self.expand_iri_defining(active, definitions, type_mapping, false, true, options)?
+                }
+                _ => None,
+            };
+
+            match type_mapping {
+                Some(ref t) if t == "@id" || t == "@vocab" || t == "@json" || t == "@none" ||
+                               is_absolute_iri(t) => {}
+                _ => return Err(Error::json_ld("invalid type mapping", value.to_string())),
+            }
+
+            definition.type_mapping = type_mapping;
+        }
+
+        if let Some(reverse) = value.get("@reverse") {
+            if value.contains_key("@id") || value.contains_key("@nest") {
+                return Err(Error::json_ld("invalid reverse property", term))
+            }
+
+            let reverse = match *reverse {
+                Value::String(ref reverse) => reverse,
+                _ => return Err(Error::json_ld("invalid IRI mapping", reverse.to_string())),
+            };
+
+            if has_keyword_form(reverse) {
+                definitions.defined.insert(term.to_owned(), true);
+                return Ok(())
+            }
+
+            match self.expand_iri_defining(active, definitions, reverse, false, true, options)? {
+                Some(ref iri) if iri.contains(':') => definition.iri = Some(iri.clone()),
+                _ => return Err(Error::json_ld("invalid IRI mapping", reverse.clone())),
+            }
+
+            if let Some(container) = value.get("@container") {
+                match *container {
+                    Value::Null => {}
+                    Value::String(ref c) if c == "@set" || c == "@index" => {
+                        definition.container = vec![c.clone()];
+                    }
+                    _ => return Err(Error::json_ld("invalid reverse property", container.to_string())),
+                }
+            }
+
+            definition.reverse = true;
+            active.terms.insert(term.to_owned(), definition);
+            definitions.defined.insert(term.to_owned(), true);
+            return Ok(())
+        }
+
+        let colon = find_colon(term);
+
+        match value.get("@id") {
+            Some(id) if id != term => {
+                match *id {
+                    Value::Null => {}
+                    Value::String(ref id) => {
+                        if !is_keyword(id) && has_keyword_form(id) {
+                            definitions.defined.insert(term.to_owned(), true);
+                            return Ok(())
+                        }
+
+                        let iri = self.expand_iri_defining(active, definitions, id, false, true, options)?;
+
+                        match iri {
+                            Some(ref iri) if is_keyword(iri) || iri.contains(':') => {}
+                            _ => return Err(Error::json_ld("invalid IRI mapping", id.clone())),
+                        }
+
+                        let iri = iri.unwrap();
+
+                        if iri == "@context" {
+                            return Err(Error::json_ld("invalid keyword alias", term))
+                        }
+
+                        let compact_form = colon.is_some_and(|index| index < term.len() - 1);
+
+                        if compact_form || term.contains('/') {
+                            definitions.defined.insert(term.to_owned(), true);
+
+                            let expanded = self.expand_iri_defining(active, definitions, term, false, true, options)?;
+
+                            if expanded.as_ref() != Some(&iri) {
+                                return Err(Error::json_ld("invalid IRI mapping", term))
+                            }
+                        }
+
+                        if !term.contains(':') && !term.contains('/') && simple &&
+                           (iri.ends_with(|c| ":/?#[]@".contains(c)) || is_blank_node_id(&iri))
+                        {
+                            definition.prefix = true;
+                        }
+
+                        definition.iri = Some(iri);
+                    }
+                    _ => return Err(Error::json_ld("invalid IRI mapping", id.to_string())),
+                }
+            }
+            _ => {
+                definition.iri = Some(if let Some(index) = colon {
+                    let (prefix, suffix) = (&term[..index], &term[index + 1..]);
+
+                    if definitions.context.contains_key(prefix) {
+                        self.create_term(active, definitions, prefix, options)?;
+                    }
+
+                    match active.term(prefix).and_then(|p| p.iri.clone()) {
+                        Some(iri) => iri + suffix,
+                        None => term.to_owned(),
+                    }
+                } else if term.contains('/') {
+                    match self.expand_iri_defining(active, definitions, term, false, true, options)? {
+                        Some(ref iri) if is_absolute_iri(iri) => iri.clone(),
+                        _ => return Err(Error::json_ld("invalid IRI mapping", term)),
+                    }
+                } else if term == "@type" {
+                    String::from("@type")
+                } else {
+                    match active.vocab {
+                        Some(ref vocab) => format!("{}{}", vocab, term),
+                        None => return Err(Error::json_ld("invalid IRI mapping", term)),
+                    }
+                });
+            }
+        }
+
+        if let Some(container) = value.get("@container") {
+            definition.container = container_mapping(container)?;
+
+            if definition.has_container("@type") {
+                match definition.type_mapping {
+                    None => definition.type_mapping = Some(String::from("@id")),
+                    Some(ref t) if t == "@id" || t == "@vocab" => {}
+                    Some(_) => return Err(Error::json_ld("invalid type mapping", term)),
+                }
+            }
+        }
+
+        if let Some(index) = value.get("@index") {
+            let index = match *index {
+                Value::String(ref index) if definition.has_container("@index") => index,
+                _ => return Err(Error::json_ld("invalid term definition", index.to_string())),
+            };
+
+            match self.expand_iri_defining(active, definitions, index, false, true, options)? {
+                Some(ref iri) if is_absolute_iri(iri) => {}
+                _ => return Err(Error::json_ld("invalid term definition", index.clone())),
+            }
+
+            definition.index = Some(index.clone());
+        }
+
+        if let Some(context) = value.get("@context") {
+            // Check the scoped context is valid now, but only use it when the
+            // term is.
+            self.process_context_with(active,
+                                      context,
+                                      options.base,
+                                      options.remote,
+                                      true,
+                                      true,
+                                      false)
+                .map_err(|error| Error::json_ld("invalid scoped context", error.to_string()))?;
+
+            definition.context = Some((context.clone(), options.base.cloned()));
+        }
+
+        if let Some(language) = value.get("@language") {
+            if !value.contains_key("@type") {
+                definition.language = Some(match *language {
+                    Value::Null => None,
+                    Value::String(ref language) => Some(language.to_lowercase()),
+                    _ => return Err(Error::json_ld("invalid language mapping", language.to_string())),
+                });
+            }
+        }
+
+        if let Some(direction) = value.get("@direction") {
+            if !value.contains_key("@type") {
+                definition.direction = Some(match *direction {
+                    Value::Null => None,
+                    Value::String(ref d) if d == "ltr" || d == "rtl" => Some(d.clone()),
+                    _ => return Err(Error::json_ld("invalid base direction", direction.to_string())),
+                });
+            }
+        }
+
+        if let Some(nest) = value.get("@nest") {
+            match *nest {
+                Value::String(ref nest) if !is_keyword(nest) || nest == "@nest" => {
+                    definition.nest = Some(nest.clone());
+                }
+                _ => return Err(Error::json_ld("invalid @nest value", nest.to_string())),
+            }
+        }
+
+        if let Some(prefix) = value.get("@prefix") {
+            if term.contains(':') || term.contains('/') {
+                return Err(Error::json_ld("invalid term definition", term))
+            }
+
+            definition.prefix = prefix.as_bool()
+                .ok_or_else(|| Error::json_ld("invalid @prefix value", prefix.to_string()))?;
+
+            if definition.prefix && definition.iri.as_ref().is_some_and(|iri| is_keyword(iri)) {
+                return Err(Error::json_ld("invalid term definition", term))
+            }
+        }
+
+        let allowed = [
+            "@id", "@reverse", "@container", "@context", "@direction", "@index", "@language",
+            "@nest", "@prefix", "@protected", "@type",
+        ];
+
+        if let Some(key) = value.keys().find(|key| !allowed.contains(&&***key)) {
+            return Err(Error::json_ld("invalid term definition", key.clone()))
+        }
+
+        if let Some(previous) = previous {
+            if !options.override_protected && previous.protected {
+                let mut unprotected = definition.clone();
+                unprotected.protected = previous.protected;
+
+                if unprotected != previous {
+                    return Err(Error::json_ld("protected term redefinition", term))
+                }
+
+                definition = previous;
+            }
+        }
+
+        active.terms.insert(term.to_owned(), definition);
+        definitions.defined.insert(term.to_owned(), true);
+        Ok(())
+    }
+
+    /// IRI expansion while a local context is being processed, defining the
+    /// terms `value` depends on first.
+    fn expand_iri_defining(&mut self,
+                           active: &mut Context,
+                           definitions: &mut Definitions,
+                           value: &str,
+                           document_relative: bool,
+                           vocab: bool,
+                           options: Options)
+        -> Result<Option<String>, Error>
+    {
+        if !is_keyword(value) && !has_keyword_form(value) {
+            if definitions.context.contains_key(value) &&
+               definitions.defined.get(value) != Some(&true)
+            {
+                self.create_term(active, definitions, value, options)?;
+            }
+
+            if let Some(index) = find_colon(value) {
+                let prefix = &value[..index];
+
+                if definitions.context.contains_key(prefix) &&
+                   definitions.defined.get(prefix) != Some(&true)
+                {
+                    self.create_term(active, definitions, prefix, options)?;
+                }
+            }
+        }
+
+        let base = active.base.clone();
+        Ok(active.expand_iri(value, document_relative, vocab, base.as_ref()))
+    }
+}
+
+impl Context {
+    /// Runs the IRI Expansion algorithm, returning `None` if `value` expands
+    /// to `null`.
+    pub(crate) fn expand_iri(&self,
+                             value: &str,
+                             document_relative: bool,
+                             vocab: bool,
+                             base: Option<&Iri>)
+        -> Option<String>
+    {
+        if is_keyword(value) {
+            return Some(value.to_owned())
+        }
+
+        if has_keyword_form(value) {
+            return None
+        }
+
+        if let Some(term) = self.term(value) {
+            match term.iri {
+                Some(ref iri) if is_keyword(iri) => return Some(iri.clone()),
+                ref iri if vocab => return iri.clone(),
+                _ => {}
+            }
+        }
+
+        if let Some(index) = find_colon(value) {
+            let (prefix, suffix) = (&value[..index], &value[index + 1..]);
+
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_owned())
+            }
+
+            if let Some(term) = self.term(prefix) {
+                if let (Some(iri), true) = (term.iri.as_ref(), term.prefix) {
+                    return Some(format!("{}{}", iri, suffix))
+                }
+            }
+
+            if is_absolute_iri(value) {
+                return Some(value.to_owned())
+            }
+        }
+
+        if vocab {
+            if let Some(ref vocab) = self.vocab {
+                return Some(format!("{}{}", vocab, value))
+            }
+        }
+
+        if document_relative {
+            return Some(resolve(base, value))
+        }
+
+        Some(value.to_owned())
+    }
+}
+
+fn container_mapping(container: &Value) -> Result<Vec<String>, Error> {
+    let invalid = || Error::json_ld("invalid container mapping", container.to_string());
+    let values = match *container {
+        Value::String(ref value) => vec![value.clone()],
+        Value::Array(ref values) => {
+            values.iter()
+                .map(|value| value.as_str().map(str::to_owned).ok_or_else(invalid))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        _ => return Err(invalid()),
+    };
+
+    let valid = [
+        "@graph", "@id", "@index", "@language", "@list", "@set", "@type",
+    ];
+
+    if values.iter().any(|value| !valid.contains(&&**value)) {
+        return Err(invalid())
+    }
+
+    let has = |name: &str| values.iter().any(|value| value == name);
+    let others = |allowed: &[&str]| values.iter().all(|value| allowed.contains(&&**value));
+
+    let ok = match values.len() {
+        0 => true,
+        1 => true,
+        _ if has("@list") => false,
+        _ if has("@graph") => others(&["@graph", "@id", "@index", "@set"]) &&
+                              !(has("@id") && has("@index")),
+        _ if has("@set") => others(&["@set", "@index", "@graph", "@id", "@type", "@language"]) &&
+                            values.len() == 2,
+        _ => false,
+    };
+
+    if ok {
+        Ok(values)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// The index of the colon separating a compact IRI's prefix from its
+/// suffix, ignoring a colon at the start.
+pub(crate) fn find_colon(value: &str) -> Option<usize> {
+    value.char_indices().skip(1).find(|&(_, c)| c == ':').map(|(index, _)| index)
+}
+
+fn invalid_base(value: &Value) -> Error {
+    Error::json_ld("invalid base IRI", value.to_string())
+}
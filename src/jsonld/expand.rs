@@ -0,0 +1,671 @@
+//! The Expansion algorithm.
+use serde_json::{Map, Value};
+
+use error::Error;
+use iri::Iri;
+
+use super::context::Context;
+use super::{add_value, as_array, is_keyword, Processor};
+
+impl<'a> Processor<'a> {
+    /// Expands `element`, returning `Value::Null` if it expands to nothing.
+    pub(crate) fn expand(&mut self,
+                         active: &Context,
+                         active_property: Option<&str>,
+                         element: &Value,
+                         base: Option<&Iri>,
+                         from_map: bool)
+        -> Result<Value, Error>
+    {
+        let scoped = active_property
+            .and_then(|property| active.term(property))
+            .and_then(|term| term.context.clone());
+
+        match *element {
+            Value::Null => Ok(Value::Null),
+            Value::Array(ref items) => {
+                let mut result = Vec::new();
+
+                for item in items {
+                    let mut expanded = self.expand(active, active_property, item, base, from_map)?;
+
+                    if active.has_container(active_property, "@list") && expanded.is_array() {
+                        expanded = list_object(expanded);
+                    }
+
+                    match expanded {
+                        Value::Array(items) => result.extend(items),
+                        Value::Null => {}
+                        expanded => result.push(expanded),
+                    }
+                }
+
+                Ok(Value::Array(result))
+            }
+            Value::Object(ref element) => {
+                self.expand_object(active, active_property, element, base, from_map, scoped)
+            }
+            ref scalar => {
+                if active_property.is_none_or(|property| property == "@graph") {
+                    return Ok(Value::Null)
+                }
+
+                let active = match scoped {
+                    Some((ref context, ref base)) => {
+                        self.process_context(active, context, base.as_ref(), false, true)?
+                    }
+                    None => active.clone(),
+                };
+
+                Ok(expand_value(&active, active_property, scalar))
+            }
+        }
+    }
+
+    fn expand_object(&mut self,
+                     active: &Context,
+                     active_property: Option<&str>,
+                     element: &Map<String, Value>,
+                     base: Option<&Iri>,
+                     from_map: bool,
+                     scoped: Option<(Value, Option<Iri>)>)
+        -> Result<Value, Error>
+    {
+        let mut active = active.clone();
+
+        if let Some(previous) = active.previous.clone() {
+            let keeps_context = element.keys().any(|key| {
+                active.expand_iri(key, false, true, None).as_deref() == Some("@value")
+            }) || (element.len() == 1 && element.keys().all(|key| {
+                active.expand_iri(key, false, true, None).as_deref() == Some("@id")
+            }));
+
+            if !from_map && !keeps_context {
+                active = *previous;
+            }
+        }
+
+        if let Some((context, scoped_base)) = scoped {
+            active = self.process_context(&active, &context, scoped_base.as_ref(), true, true)?;
+        }
+
+        if let Some(context) = element.get("@context") {
+            active = self.process_context(&active, context, base, false, true)?;
+        }
+
+        let type_scoped = active.clone();
+        let mut input_type = None;
+
+        for key in element.keys() {
+            if active.expand_iri(key, false, true, None).as_deref() != Some("@type") {
+                continue
+            }
+
+            let mut types = match element[key] {
+                Value::Array(ref types) => types.iter().filter_map(Value::as_str).collect(),
+                Value::String(ref t) => vec![&**t],
+                _ => Vec::new(),
+            };
+            types.sort();
+
+            for t in &types {
+                if let Some((ref context, ref base)) = type_scoped.term(t).and_then(|t| t.context.clone()) {
+                    active = self.process_context(&active, context, base.as_ref(), false, false)?;
+                }
+            }
+
+            input_type = types.last()
+                .and_then(|t| active.expand_iri(t, false, true, None));
+        }
+
+        let mut result = Map::new();
+        self.expand_entries(&active,
+                            &type_scoped,
+                            active_property,
+                            element,
+                            base,
+                            input_type.as_deref(),
+                            &mut result)?;
+
+        if let Some(value) = result.get("@value").cloned() {
+            let allowed = ["@direction", "@index", "@language", "@type", "@value"];
+
+            if result.keys().any(|key| !allowed.contains(&&**key)) ||
+               (result.contains_key("@type") &&
+                (result.contains_key("@language") || result.contains_key("@direction")))
+            {
+                return Err(Error::json_ld("invalid value object", Value::Object(result).to_string()))
+            }
+
+            // Frames use value objects as patterns, which may hold wildcards.
+            if self.frame_expansion || result.get("@type").is_some_and(|t| t == "@json") {
+                return Ok(Value::Object(result))
+            }
+
+            if value.is_null() {
+                return Ok(Value::Null)
+            }
+
+            if !value.is_string() && result.contains_key("@language") {
+                return Err(Error::json_ld("invalid language-tagged value", value.to_string()))
+            }
+
+            if let Some(t) = result.get("@type") {
+                if !t.as_str().is_some_and(|t| t.contains(':') && !t.starts_with("_:")) {
+                    return Err(Error::json_ld("invalid typed value", t.to_string()))
+                }
+            }
+        } else if result.get("@type").is_some_and(|t| !t.is_array()) {
+            let types = as_array(result.remove("@type").unwrap());
+            result.insert(String::from("@type"), types);
+        } else if result.contains_key("@set") || result.contains_key("@list") {
+            if result.len() > 2 || (result.len() == 2 && !result.contains_key("@index")) {
+                return Err(Error::json_ld("invalid set or list object", Value::Object(result).to_string()))
+            }
+
+            if let Some(set) = result.remove("@set") {
+                return Ok(set)
+            }
+        }
+
+        if result.len() == 1 && result.contains_key("@language") {
+            return Ok(Value::Null)
+        }
+
+        // Frames keep their top-level objects, which may be empty or have
+        // nothing but an `@id`.
+        if active_property.is_none_or(|property| property == "@graph") &&
+           !self.frame_expansion &&
+           (result.is_empty() ||
+            result.contains_key("@value") ||
+            result.contains_key("@list") ||
+            (result.len() == 1 && result.contains_key("@id")))
+        {
+            return Ok(Value::Null)
+        }
+
+        Ok(Value::Object(result))
+    }
+
+    /// Expands each entry of `element` into `result`, then the entries of
+    /// any `@nest`ed objects.
+    #[allow(clippy::too_many_arguments)]
+    fn expand_entries(&mut self,
+                      active: &Context,
+                      type_scoped: &Context,
+                      active_property: Option<&str>,
+                      element: &Map<String, Value>,
+                      base: Option<&Iri>,
+                      input_type: Option<&str>,
+                      result: &mut Map<String, Value>)
+        -> Result<(), Error>
+    {
+        let mut nests = Vec::new();
+        let document_base = active.base.clone();
+        let document_base = document_base.as_ref();
+
+        for (key, value) in element {
+            if key == "@context" {
+                continue
+            }
+
+            let property = match active.expand_iri(key, false, true, None) {
+                Some(ref property) if property.contains(':') || is_keyword(property) => {
+                    property.clone()
+                }
+                _ => continue,
+            };
+
+            if is_keyword(&property) {
+                if active_property == Some("@reverse") {
+                    return Err(Error::json_ld("invalid reverse property map", key.clone()))
+                }
+
+                if result.contains_key(&property) && property != "@included" && property != "@type" {
+                    return Err(Error::json_ld("colliding keywords", property))
+                }
+
+                let expanded = match &*property {
+                    "@id" | "@type" | "@value" | "@language" if self.frame_expansion && is_frame_wildcard(value) => {
+                        Value::Array(value.as_object().map(|_| Value::Object(Map::new())).into_iter().collect())
+                    }
+                    "@id" if self.frame_expansion && value.is_array() => {
+                        let ids = value.as_array().unwrap().iter().map(|id| match id.as_str() {
+                            Some(id) => Ok(active.expand_iri(id, true, false, document_base).map_or(Value::Null, Value::from)),
+                            None => Err(Error::json_ld("invalid @id value", value.to_string())),
+                        });
+
+                        Value::Array(ids.collect::<Result<_, _>>()?)
+                    }
+                    "@default" | "@embed" | "@explicit" | "@omitDefault" | "@requireAll"
+                        if self.frame_expansion => value.clone(),
+                    "@id" => match *value {
+                        Value::String(ref id) => {
+                            match active.expand_iri(id, true, false, document_base) {
+                                Some(id) => Value::String(id),
+                                None => Value::Null,
+                            }
+                        }
+                        _ => return Err(Error::json_ld("invalid @id value", value.to_string())),
+                    },
+                    "@type" => {
+                        let types = match *value {
+                            Value::String(_) => vec![value],
+                            Value::Array(ref types) if types.iter().all(Value::is_string) => {
+                                types.iter().collect()
+                            }
+                            _ => return Err(Error::json_ld("invalid type value", value.to_string())),
+                        };
+
+                        let mut expanded = types.into_iter()
+                            .filter_map(|t| type_scoped.expand_iri(t.as_str().unwrap(), true, true, document_base))
+                            .map(Value::String)
+                            .collect::<Vec<_>>();
+
+                        if let Some(existing) = result.remove("@type") {
+                            let mut existing = match as_array(existing) {
+                                Value::Array(existing) => existing,
+                                _ => unreachable!(),
+                            };
+                            existing.append(&mut expanded);
+                            expanded = existing;
+                        }
+
+                        if value.is_string() && expanded.len() == 1 {
+                            expanded.pop().unwrap()
+                        } else {
+                            Value::Array(expanded)
+                        }
+                    }
+                    "@graph" => as_array(self.expand(active, Some("@graph"), value, base, false)?),
+                    "@included" => {
+                        let included = as_array(self.expand(active, None, value, base, false)?);
+
+                        let invalid = included.as_array().unwrap().iter().any(|item| {
+                            item.as_object().is_none_or(|item| {
+                                item.contains_key("@value") || item.contains_key("@list") ||
+                                    item.contains_key("@set")
+                            })
+                        });
+
+                        if invalid {
+                            return Err(Error::json_ld("invalid @included value", value.to_string()))
+                        }
+
+                        match result.remove("@included") {
+                            Some(Value::Array(mut existing)) => {
+                                existing.extend(included.as_array().unwrap().iter().cloned());
+                                Value::Array(existing)
+                            }
+                            _ => included,
+                        }
+                    }
+                    "@value" => {
+                        if input_type == Some("@json") {
+                            value.clone()
+                        } else if value.is_array() || value.is_object() {
+                            return Err(Error::json_ld("invalid value object value", value.to_string()))
+                        } else if value.is_null() {
+                            result.insert(property, Value::Null);
+                            continue
+                        } else {
+                            value.clone()
+                        }
+                    }
+                    "@language" => match *value {
+                        Value::String(ref language) => Value::String(language.to_lowercase()),
+                        _ => return Err(Error::json_ld("invalid language-tagged string", value.to_string())),
+                    },
+                    "@direction" => match *value {
+                        Value::String(ref d) if d == "ltr" || d == "rtl" => value.clone(),
+                        _ => return Err(Error::json_ld("invalid base direction", value.to_string())),
+                    },
+                    "@index" => match *value {
+                        Value::String(_) => value.clone(),
+                        _ => return Err(Error::json_ld("invalid @index value", value.to_string())),
+                    },
+                    "@list" => {
+                        if active_property.is_none_or(|property| property == "@graph") {
+                            continue
+                        }
+
+                        let items = match *value {
+                            Value::Array(ref items) => &items[..],
+                            ref value => ::std::slice::from_ref(value),
+                        };
+                        let mut list = Vec::new();
+
+                        // Arrays inside a list are lists themselves.
+                        for item in items {
+                            match self.expand(active, active_property, item, base, false)? {
+                                Value::Array(expanded) if item.is_array() => {
+                                    list.push(list_object(Value::Array(expanded)))
+                                }
+                                Value::Array(expanded) => list.extend(expanded),
+                                Value::Null => {}
+                                expanded => list.push(expanded),
+                            }
+                        }
+
+                        Value::Array(list)
+                    }
+                    "@set" => self.expand(active, active_property, value, base, false)?,
+                    "@reverse" => {
+                        if !value.is_object() {
+                            return Err(Error::json_ld("invalid @reverse value", value.to_string()))
+                        }
+
+                        let expanded = match self.expand(active, Some("@reverse"), value, base, false)? {
+                            Value::Object(expanded) => expanded,
+                            _ => Map::new(),
+                        };
+
+                        for (property, items) in expanded {
+                            if property == "@reverse" {
+                                for (property, items) in items.as_object().cloned().unwrap_or_default() {
+                                    add_value(result, &property, items);
+                                }
+
+                                continue
+                            }
+
+                            let mut reverse = match result.remove("@reverse") {
+                                Some(Value::Object(reverse)) => reverse,
+                                _ => Map::new(),
+                            };
+
+                            for item in items.as_array().cloned().unwrap_or_default() {
+                                if is_value_or_list(&item) {
+                                    return Err(Error::json_ld("invalid reverse property value", item.to_string()))
+                                }
+
+                                add_value(&mut reverse, &property, item);
+                            }
+
+                            result.insert(String::from("@reverse"), Value::Object(reverse));
+                        }
+
+                        continue
+                    }
+                    "@nest" => {
+                        nests.push(key);
+                        continue
+                    }
+                    _ => continue,
+                };
+
+                if !expanded.is_null() || property == "@value" {
+                    result.insert(property, expanded);
+                }
+
+                continue
+            }
+
+            let term = active.term(key);
+            let container = |name: &str| term.is_some_and(|term| term.has_container(name));
+
+            let mut expanded = if term.and_then(|t| t.type_mapping.as_ref()).is_some_and(|t| t == "@json") {
+                let mut json = Map::new();
+                json.insert(String::from("@value"), value.clone());
+                json.insert(String::from("@type"), Value::from("@json"));
+                Value::Object(json)
+            } else if container("@language") && value.is_object() {
+                let direction = match term.and_then(|t| t.direction.clone()) {
+                    Some(direction) => direction,
+                    None => active.direction.clone(),
+                };
+
+                let mut expanded = Vec::new();
+
+                for (language, items) in value.as_object().unwrap() {
+                    let none = active.expand_iri(language, false, true, None)
+                        .is_some_and(|language| language == "@none");
+
+                    for item in as_array(items.clone()).as_array().unwrap() {
+                        let item = match *item {
+                            Value::Null => continue,
+                            Value::String(_) => item.clone(),
+                            _ => return Err(Error::json_ld("invalid language map value", item.to_string())),
+                        };
+
+                        let mut object = Map::new();
+                        object.insert(String::from("@value"), item);
+
+                        if !none {
+                            object.insert(String::from("@language"), Value::from(language.to_lowercase()));
+                        }
+
+                        if let Some(ref direction) = direction {
+                            object.insert(String::from("@direction"), Value::from(direction.clone()));
+                        }
+
+                        expanded.push(Value::Object(object));
+                    }
+                }
+
+                Value::Array(expanded)
+            } else if (container("@index") || container("@type") || container("@id")) && value.is_object() {
+                self.expand_map(active, key, value.as_object().unwrap(), base)?
+            } else {
+                self.expand(active, Some(key), value, base, false)?
+            };
+
+            if expanded.is_null() {
+                continue
+            }
+
+            if container("@list") && !is_list(&expanded) {
+                expanded = list_object(as_array(expanded));
+            }
+
+            if container("@graph") && !container("@id") && !container("@index") {
+                expanded = Value::Array(as_array(expanded).as_array().unwrap().iter().map(|item| {
+                    let mut graph = Map::new();
+                    graph.insert(String::from("@graph"), as_array(item.clone()));
+                    Value::Object(graph)
+                }).collect());
+            }
+
+            if term.is_some_and(|term| term.reverse) {
+                let mut reverse = match result.remove("@reverse") {
+                    Some(Value::Object(reverse)) => reverse,
+                    _ => Map::new(),
+                };
+
+                for item in as_array(expanded).as_array().unwrap() {
+                    if is_value_or_list(item) {
+                        return Err(Error::json_ld("invalid reverse property value", item.to_string()))
+                    }
+
+                    add_value(&mut reverse, &property, item.clone());
+                }
+
+                result.insert(String::from("@reverse"), Value::Object(reverse));
+            } else {
+                add_value(result, &property, expanded);
+            }
+        }
+
+        for key in nests {
+            for nested in as_array(element[key].clone()).as_array().unwrap() {
+                let nested = match *nested {
+                    Value::Object(ref nested) => nested,
+                    _ => return Err(Error::json_ld("invalid @nest value", nested.to_string())),
+                };
+
+                let has_value = nested.keys().any(|key| {
+                    active.expand_iri(key, false, true, None).is_some_and(|key| key == "@value")
+                });
+
+                if has_value {
+                    return Err(Error::json_ld("invalid @nest value", key.clone()))
+                }
+
+                self.expand_entries(active, type_scoped, active_property, nested, base, input_type, result)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expands the value of a term with an `@index`, `@type` or `@id`
+    /// container.
+    fn expand_map(&mut self,
+                  active: &Context,
+                  key: &str,
+                  value: &Map<String, Value>,
+                  base: Option<&Iri>)
+        -> Result<Value, Error>
+    {
+        let term = active.term(key).cloned().unwrap_or_default();
+        let index_key = term.index.clone().unwrap_or_else(|| String::from("@index"));
+        let mut expanded = Vec::new();
+
+        for (index, items) in value {
+            let previous = active.previous.as_ref().map_or(active, |previous| &**previous);
+            let scoped = previous.term(index).and_then(|t| t.context.clone());
+
+            let map_context = match scoped {
+                Some((ref context, ref base)) if term.has_container("@type") => {
+                    self.process_context(previous, context, base.as_ref(), false, true)?
+                }
+                _ => active.clone(),
+            };
+
+            let expanded_index = active.expand_iri(index, false, true, None);
+            let none = expanded_index.as_ref().is_some_and(|index| index == "@none");
+            let items = self.expand(&map_context, Some(key), &as_array(items.clone()), base, true)?;
+
+            for mut item in as_array(items).as_array().cloned().unwrap_or_default() {
+                if term.has_container("@graph") && !is_graph(&item) {
+                    let mut graph = Map::new();
+                    graph.insert(String::from("@graph"), as_array(item));
+                    item = Value::Object(graph);
+                }
+
+                let object = match item.as_object_mut() {
+                    Some(object) => object,
+                    None => continue,
+                };
+
+                if term.has_container("@index") && index_key != "@index" && !none {
+                    let reexpanded = expand_value(active, Some(&index_key), &Value::from(index.clone()));
+                    let property = active.expand_iri(&index_key, false, true, None)
+                        .unwrap_or_else(|| index_key.clone());
+
+                    let mut values = vec![reexpanded];
+                    if let Some(existing) = object.remove(&property) {
+                        values.extend(as_array(existing).as_array().cloned().unwrap_or_default());
+                    }
+
+                    object.insert(property, Value::Array(values));
+
+                    if object.contains_key("@value") && object.len() > 1 {
+                        return Err(Error::json_ld("invalid value object", Value::Object(object.clone()).to_string()))
+                    }
+                } else if term.has_container("@index") && !object.contains_key("@index") && !none {
+                    object.insert(String::from("@index"), Value::from(index.clone()));
+                } else if term.has_container("@id") && !object.contains_key("@id") && !none {
+                    let base = active.base.clone();
+                    let id = active.expand_iri(index, true, false, base.as_ref());
+                    object.insert(String::from("@id"), id.map_or(Value::Null, Value::from));
+                } else if term.has_container("@type") && !none {
+                    let mut types = vec![Value::from(expanded_index.clone().unwrap_or_default())];
+
+                    if let Some(existing) = object.remove("@type") {
+                        types.extend(as_array(existing).as_array().cloned().unwrap_or_default());
+                    }
+
+                    object.insert(String::from("@type"), Value::Array(types));
+                }
+
+                expanded.push(item);
+            }
+        }
+
+        Ok(Value::Array(expanded))
+    }
+}
+
+/// Runs the Value Expansion algorithm on a scalar.
+pub(crate) fn expand_value(active: &Context, active_property: Option<&str>, value: &Value) -> Value {
+    let term = active_property.and_then(|property| active.term(property));
+    let type_mapping = term.and_then(|term| term.type_mapping.as_ref());
+    let base = active.base.clone();
+
+    if let (Some(t), Some(id)) = (type_mapping, value.as_str()) {
+        let iri = match &**t {
+            "@id" => Some(active.expand_iri(id, true, false, base.as_ref())),
+            "@vocab" => Some(active.expand_iri(id, true, true, base.as_ref())),
+            _ => None,
+        };
+
+        if let Some(iri) = iri {
+            let mut object = Map::new();
+            object.insert(String::from("@id"), iri.map_or(Value::Null, Value::from));
+            return Value::Object(object)
+        }
+    }
+
+    let mut result = Map::new();
+    result.insert(String::from("@value"), value.clone());
+
+    match type_mapping {
+        Some(t) if t != "@id" && t != "@vocab" && t != "@none" => {
+            result.insert(String::from("@type"), Value::from(t.clone()));
+        }
+        _ if value.is_string() => {
+            let language = match term.and_then(|term| term.language.clone()) {
+                Some(language) => language,
+                None => active.language.clone(),
+            };
+            let direction = match term.and_then(|term| term.direction.clone()) {
+                Some(direction) => direction,
+                None => active.direction.clone(),
+            };
+
+            if let Some(language) = language {
+                result.insert(String::from("@language"), Value::from(language));
+            }
+
+            if let Some(direction) = direction {
+                result.insert(String::from("@direction"), Value::from(direction));
+            }
+        }
+        _ => {}
+    }
+
+    Value::Object(result)
+}
+
+fn list_object(items: Value) -> Value {
+    let mut list = Map::new();
+    list.insert(String::from("@list"), items);
+    Value::Object(list)
+}
+
+pub(crate) fn is_list(value: &Value) -> bool {
+    value.as_object().is_some_and(|value| value.contains_key("@list"))
+}
+
+pub(crate) fn is_graph(value: &Value) -> bool {
+    value.as_object().is_some_and(|value| {
+        value.contains_key("@graph") &&
+            value.keys().all(|key| key == "@graph" || key == "@id" || key == "@index" ||
+                                   key == "@context")
+    })
+}
+
+/// Whether `value` is `{}` or `[]`, which match any value or no value in a
+/// frame.
+fn is_frame_wildcard(value: &Value) -> bool {
+    match *value {
+        Value::Object(ref object) => object.is_empty(),
+        Value::Array(ref items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_value_or_list(value: &Value) -> bool {
+    value.as_object().is_some_and(|value| value.contains_key("@value") || value.contains_key("@list"))
+}
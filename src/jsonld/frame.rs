@@ -0,0 +1,485 @@
+//! The Framing algorithm.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde_json::{Map, Value};
+
+use error::Error;
+use iri::Iri;
+
+use super::expand::is_list;
+use super::{as_array, is_blank_node_id, is_keyword, Processor};
+
+/// The nodes being framed by `@id`.
+type Nodes = BTreeMap<String, Map<String, Value>>;
+
+/// When a node that's the value of a property is embedded in its parent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Embed {
+    Always,
+    Once,
+    Never,
+}
+
+/// The flags of a frame, which nested frames inherit.
+#[derive(Clone, Copy, Debug)]
+struct Flags {
+    embed: Embed,
+    explicit: bool,
+    omit_default: bool,
+    require_all: bool,
+}
+
+impl Flags {
+    /// The flags of `frame`, falling back to the ones of its parent.
+    fn update(self, frame: &Map<String, Value>) -> Result<Self, Error> {
+        let flag = |key: &'static str, default: bool| match frame.get(key) {
+            None => Ok(default),
+            Some(&Value::Bool(value)) => Ok(value),
+            Some(value) => Err(Error::json_ld("invalid frame", format!("{}: {}", key, value))),
+        };
+
+        let embed = match frame.get("@embed") {
+            None => self.embed,
+            Some(&Value::Bool(true)) => Embed::Once,
+            Some(&Value::Bool(false)) => Embed::Never,
+            Some(value) => match value.as_str() {
+                Some("@always") => Embed::Always,
+                Some("@once") => Embed::Once,
+                Some("@never") => Embed::Never,
+                _ => return Err(Error::json_ld("invalid @embed value", value.to_string())),
+            },
+        };
+
+        Ok(Flags {
+            embed,
+            explicit: flag("@explicit", self.explicit)?,
+            omit_default: flag("@omitDefault", self.omit_default)?,
+            require_all: flag("@requireAll", self.require_all)?,
+        })
+    }
+}
+
+struct State<'n> {
+    nodes: &'n Nodes,
+    /// The nodes embedded under the current top-level node.
+    embedded: HashSet<String>,
+    /// The nodes being embedded, which can't be embedded again inside
+    /// themselves.
+    stack: Vec<String>,
+}
+
+impl<'a> Processor<'a> {
+    /// Frames `expanded`, a flattened document such as the output of
+    /// `from_rdf`, with `frame`, which is expanded with `context` in place
+    /// of its own. The result is expanded, with default values wrapped in
+    /// `@preserve`.
+    pub(crate) fn frame(&mut self, expanded: &Value, frame: &Value, context: &Value, base: Option<&Iri>)
+        -> Result<Value, Error>
+    {
+        let mut frame = frame.clone();
+
+        if let Some(frame) = frame.as_object_mut() {
+            frame.insert(String::from("@context"), context.clone());
+        }
+
+        self.frame_expansion = true;
+        let expanded_frame = self.expand_document(frame.clone(), base);
+        self.frame_expansion = false;
+
+        let frame = match expanded_frame?.as_array().unwrap()[..] {
+            [] => Map::new(),
+            [Value::Object(ref frame)] => frame.clone(),
+            _ => return Err(Error::json_ld("invalid frame", frame.to_string())),
+        };
+
+        let nodes = expanded.as_array()
+            .map(|nodes| &**nodes)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node| {
+                let node = node.as_object()?;
+                Some((node.get("@id")?.as_str()?.to_owned(), node.clone()))
+            })
+            .collect::<Nodes>();
+
+        let mut state = State {
+            nodes: &nodes,
+            embedded: HashSet::new(),
+            stack: Vec::new(),
+        };
+        let flags = Flags {
+            embed: Embed::Once,
+            explicit: false,
+            omit_default: false,
+            require_all: false,
+        };
+        let ids = nodes.keys().cloned().collect::<Vec<_>>();
+        let mut results = Value::Array(state.frame_nodes(&ids, &frame, flags, true)?);
+
+        let mut counts = HashMap::new();
+        count_blank_nodes(&results, &mut counts);
+        prune_blank_nodes(&mut results, &counts);
+
+        Ok(results)
+    }
+}
+
+impl<'n> State<'n> {
+    /// Frames each of the nodes in `ids` that match `frame`.
+    fn frame_nodes(&mut self, ids: &[String], frame: &Map<String, Value>, flags: Flags, top: bool)
+        -> Result<Vec<Value>, Error>
+    {
+        let flags = flags.update(frame)?;
+        let mut outputs = Vec::new();
+
+        for id in ids {
+            if !self.matches(id, frame, flags.require_all) {
+                continue
+            }
+
+            if top {
+                self.embedded.clear();
+            }
+
+            let mut output = Map::new();
+            output.insert(String::from("@id"), Value::from(id.clone()));
+
+            let node = match self.nodes.get(id) {
+                Some(node) => node,
+                None => {
+                    outputs.push(Value::Object(output));
+                    continue
+                }
+            };
+
+            let reference = flags.embed == Embed::Never ||
+                self.stack.contains(id) ||
+                (flags.embed == Embed::Once && self.embedded.contains(id));
+
+            if reference {
+                outputs.push(Value::Object(output));
+                continue
+            }
+
+            self.embedded.insert(id.clone());
+            self.stack.push(id.clone());
+
+            for (property, values) in node {
+                if property == "@id" {
+                    continue
+                }
+
+                if is_keyword(property) {
+                    output.insert(property.clone(), values.clone());
+                    continue
+                }
+
+                if flags.explicit && !frame.contains_key(property) {
+                    continue
+                }
+
+                let subframe = first_frame(frame.get(property));
+
+                for value in values.as_array().map(|values| &**values).unwrap_or_default() {
+                    if is_list(value) {
+                        let list_frame = first_frame(subframe.get("@list"));
+                        let list_frame = if subframe.contains_key("@list") { &list_frame } else { &subframe };
+                        let mut list = Vec::new();
+
+                        for item in value["@list"].as_array().map(|items| &**items).unwrap_or_default() {
+                            match reference_id(item) {
+                                Some(id) => list.extend(self.frame_nodes(&[id], list_frame, flags, false)?),
+                                None => list.push(item.clone()),
+                            }
+                        }
+
+                        let mut object = Map::new();
+                        object.insert(String::from("@list"), Value::Array(list));
+                        add_output(&mut output, property, Value::Object(object));
+                    } else if let Some(id) = reference_id(value) {
+                        for framed in self.frame_nodes(&[id], &subframe, flags, false)? {
+                            add_output(&mut output, property, framed);
+                        }
+                    } else if value_matches(&subframe, value) {
+                        add_output(&mut output, property, value.clone());
+                    }
+                }
+            }
+
+            for (property, subframe) in frame {
+                if is_keyword(property) || output.contains_key(property) {
+                    continue
+                }
+
+                let subframe = first_frame(Some(subframe));
+                let omit_default = match subframe.get("@omitDefault") {
+                    Some(&Value::Bool(omit_default)) => omit_default,
+                    _ => flags.omit_default,
+                };
+
+                if !omit_default {
+                    let default = subframe.get("@default").cloned().unwrap_or_else(|| Value::from("@null"));
+                    let mut preserve = Map::new();
+                    preserve.insert(String::from("@preserve"), default);
+                    output.insert(property.clone(), Value::Array(vec![Value::Object(preserve)]));
+                }
+            }
+
+            if let Some(reverse_frame) = frame.get("@reverse").and_then(Value::as_object) {
+                let mut reverse = Map::new();
+
+                for (property, subframe) in reverse_frame {
+                    let subframe = first_frame(Some(subframe));
+                    let subjects = self.nodes.iter()
+                        .filter(|&(_, node)| {
+                            node.get(property)
+                                .and_then(Value::as_array)
+                                .is_some_and(|values| values.iter().any(|value| reference_id(value).as_ref() == Some(id)))
+                        })
+                        .map(|(subject, _)| subject.clone())
+                        .collect::<Vec<_>>();
+
+                    let framed = self.frame_nodes(&subjects, &subframe, flags, false)?;
+
+                    if !framed.is_empty() {
+                        reverse.insert(property.clone(), Value::Array(framed));
+                    }
+                }
+
+                if !reverse.is_empty() {
+                    output.insert(String::from("@reverse"), Value::Object(reverse));
+                }
+            }
+
+            self.stack.pop();
+            outputs.push(Value::Object(output));
+        }
+
+        Ok(outputs)
+    }
+
+    /// Whether the node `id` matches `frame`.
+    fn matches(&self, id: &str, frame: &Map<String, Value>, require_all: bool) -> bool {
+        let empty = Map::new();
+        let node = self.nodes.get(id).unwrap_or(&empty);
+        let mut wildcard = true;
+        let mut matches_some = false;
+
+        for (key, pattern) in frame {
+            let patterns = as_array(pattern.clone());
+            let patterns = patterns.as_array().unwrap();
+
+            match &**key {
+                "@id" => {
+                    wildcard = false;
+
+                    if patterns.is_empty() || is_wildcard(&patterns[0]) ||
+                       patterns.iter().any(|pattern| pattern == id)
+                    {
+                        matches_some = true;
+                    } else {
+                        return false
+                    }
+                }
+                "@type" => {
+                    wildcard = false;
+                    let types = node.get("@type").and_then(Value::as_array).map(|t| &**t).unwrap_or_default();
+
+                    let matched = match patterns.first() {
+                        None => types.is_empty(),
+                        Some(pattern) if is_wildcard(pattern) => !types.is_empty(),
+                        Some(pattern) if pattern.get("@default").is_some() => true,
+                        Some(_) => types.iter().any(|t| patterns.contains(t)),
+                    };
+
+                    if matched {
+                        matches_some = true;
+                    } else {
+                        return false
+                    }
+                }
+                key if is_keyword(key) => {}
+                key => {
+                    wildcard = false;
+                    let values = node.get(key).and_then(Value::as_array).map(|v| &**v).unwrap_or_default();
+
+                    let subframe = match patterns.first() {
+                        // An empty array matches nodes without the property.
+                        None if values.is_empty() => {
+                            matches_some = true;
+                            continue
+                        }
+                        None => return false,
+                        Some(subframe) => subframe.as_object().cloned().unwrap_or_default(),
+                    };
+
+                    if values.is_empty() {
+                        if subframe.contains_key("@default") {
+                            matches_some = true;
+                        } else if require_all {
+                            return false
+                        }
+
+                        continue
+                    }
+
+                    let matched = if is_wildcard(&Value::Object(subframe.clone())) {
+                        true
+                    } else if ["@value", "@type", "@language"].iter().any(|&key| subframe.contains_key(key)) {
+                        values.iter().any(|value| value_matches(&subframe, value))
+                    } else if subframe.contains_key("@list") {
+                        values.iter().any(is_list)
+                    } else {
+                        values.iter()
+                            .filter_map(reference_id)
+                            .any(|id| self.matches(&id, &subframe, require_all))
+                    };
+
+                    if matched {
+                        matches_some = true;
+                    } else if require_all {
+                        return false
+                    }
+                }
+            }
+        }
+
+        wildcard || matches_some
+    }
+}
+
+/// The first frame in `frames`, or an empty one.
+fn first_frame(frames: Option<&Value>) -> Map<String, Value> {
+    match frames {
+        Some(Value::Array(frames)) => frames.first().and_then(Value::as_object).cloned(),
+        Some(Value::Object(frame)) => Some(frame.clone()),
+        _ => None,
+    }.unwrap_or_default()
+}
+
+/// Whether `frame` matches anything, having nothing but flags.
+fn is_wildcard(frame: &Value) -> bool {
+    frame.as_object().is_some_and(|frame| {
+        frame.keys().all(|key| {
+            ["@default", "@embed", "@explicit", "@omitDefault", "@requireAll"].contains(&&**key)
+        })
+    })
+}
+
+/// The `@id` of `value` if it's a node object.
+fn reference_id(value: &Value) -> Option<String> {
+    let object = value.as_object()?;
+
+    if object.contains_key("@value") {
+        return None
+    }
+
+    object.get("@id").and_then(Value::as_str).map(str::to_owned)
+}
+
+/// Runs the Value Pattern Matching algorithm.
+fn value_matches(pattern: &Map<String, Value>, value: &Value) -> bool {
+    let patterns = |key: &str| match pattern.get(key) {
+        Some(Value::Array(values)) => values.clone(),
+        Some(value) => vec![value.clone()],
+        None => Vec::new(),
+    };
+    let (values, types, languages) = (patterns("@value"), patterns("@type"), patterns("@language"));
+
+    if values.is_empty() && types.is_empty() && languages.is_empty() {
+        return true
+    }
+
+    let matches = |patterns: &[Value], key: &str| {
+        let value = value.get(key);
+
+        match value {
+            None => patterns.is_empty(),
+            Some(value) => patterns.contains(value) || patterns.first().is_some_and(is_wildcard),
+        }
+    };
+
+    matches(&values, "@value") && matches(&types, "@type") && matches(&languages, "@language")
+}
+
+fn add_output(output: &mut Map<String, Value>, property: &str, value: Value) {
+    output.entry(property.to_owned())
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .unwrap()
+        .push(value);
+}
+
+/// Counts how many times each blank node is used as an `@id`.
+fn count_blank_nodes(value: &Value, counts: &mut HashMap<String, usize>) {
+    match *value {
+        Value::Array(ref items) => {
+            for item in items {
+                count_blank_nodes(item, counts);
+            }
+        }
+        Value::Object(ref object) => {
+            for (key, item) in object {
+                match (&**key, item.as_str()) {
+                    ("@id", Some(id)) if is_blank_node_id(id) => *counts.entry(id.to_owned()).or_default() += 1,
+                    _ => count_blank_nodes(item, counts),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Removes the `@id` of blank nodes that are only used once.
+fn prune_blank_nodes(value: &mut Value, counts: &HashMap<String, usize>) {
+    match *value {
+        Value::Array(ref mut items) => {
+            for item in items {
+                prune_blank_nodes(item, counts);
+            }
+        }
+        Value::Object(ref mut object) => {
+            let unused = object.get("@id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| counts.get(id) == Some(&1));
+
+            if unused && !object.contains_key("@value") {
+                object.remove("@id");
+            }
+
+            for (_, item) in object.iter_mut() {
+                prune_blank_nodes(item, counts);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces each `@preserve` object that framing left behind with the value
+/// inside it, turning `@null` into `null`.
+pub(crate) fn remove_preserve(value: &mut Value) {
+    match *value {
+        Value::Array(ref mut items) => {
+            for item in items.iter_mut() {
+                remove_preserve(item);
+            }
+        }
+        Value::Object(ref mut object) => {
+            if let Some(mut preserved) = object.remove("@preserve") {
+                remove_preserve(&mut preserved);
+
+                *value = match preserved {
+                    Value::String(ref s) if s == "@null" => Value::Null,
+                    Value::Array(ref items) if items.len() == 1 && items[0] == "@null" => Value::Null,
+                    preserved => preserved,
+                };
+                return
+            }
+
+            for (_, item) in object.iter_mut() {
+                remove_preserve(item);
+            }
+        }
+        _ => {}
+    }
+}
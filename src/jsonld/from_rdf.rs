@@ -0,0 +1,247 @@
+//! The Serialize RDF as JSON-LD algorithm.
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{self, Map, Number, Value};
+
+use error::Error;
+use literal::{is_valid_xsd_integer, Literal};
+use object::Object;
+use subject::Subject;
+use triple::{Triple, Triples};
+
+use super::is_blank_node_id;
+
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_LIST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#List";
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+/// A triple whose object is a blank node or `rdf:nil`.
+struct Usage {
+    subject: String,
+    property: String,
+}
+
+/// Turns `triples` into an expanded document, with one node object per
+/// subject ordered by `@id`. Well-formed collections become `@list`s.
+pub(crate) fn from_rdf(triples: &Triples, native_types: bool) -> Result<Value, Error> {
+    let mut nodes: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+    let mut usages: HashMap<String, Vec<Usage>> = HashMap::new();
+
+    for triple in triples.iter() {
+        let subject = subject_id(&triple.subject)?;
+        let property = triple.predicate.as_str();
+
+        node(&mut nodes, &subject);
+
+        let object = match triple.object {
+            Object::Iri(ref iri) => Some(iri.as_str().to_owned()),
+            Object::BlankNode(ref node) => Some(format!("_:{}", node.0)),
+            Object::RelativeIri(ref relative) => Some(relative.0.clone()),
+            Object::Literal(_) => None,
+            Object::Triple(ref quoted) => return Err(quoted_triple(quoted)),
+        };
+
+        if let Some(ref object) = object {
+            node(&mut nodes, object);
+
+            if property == RDF_TYPE {
+                add_unique(node(&mut nodes, &subject), "@type", Value::from(object.clone()));
+                continue
+            }
+        }
+
+        let value = match triple.object {
+            Object::Literal(ref literal) => literal_to_value(literal, native_types)?,
+            _ => reference(object.clone().unwrap()),
+        };
+
+        add_unique(node(&mut nodes, &subject), property, value);
+
+        if let Some(object) = object.filter(|object| is_blank_node_id(object) || object == RDF_NIL) {
+            usages.entry(object).or_default().push(Usage {
+                subject,
+                property: property.to_owned(),
+            });
+        }
+    }
+
+    // The items of each list by the `@id` of its first node, which is
+    // `rdf:nil` for the empty list.
+    let mut lists = HashMap::new();
+    let mut list_nodes = Vec::new();
+    lists.insert(String::from(RDF_NIL), Vec::new());
+
+    for usage in usages.get(RDF_NIL).map(|usages| &**usages).unwrap_or_default() {
+        let mut subject = &usage.subject;
+        let mut property = &usage.property;
+        let mut list = Vec::new();
+
+        while property == RDF_REST && is_list_node(&nodes, &usages, subject) {
+            list.push(nodes[subject][RDF_FIRST][0].clone());
+            list_nodes.push(subject.clone());
+
+            let node_usage = &usages[subject][0];
+
+            if node_usage.property != RDF_REST || !is_list_node(&nodes, &usages, &node_usage.subject) {
+                list.reverse();
+                lists.insert(subject.clone(), list);
+                break
+            }
+
+            subject = &node_usage.subject;
+            property = &node_usage.property;
+        }
+    }
+
+    for id in list_nodes {
+        nodes.remove(&id);
+    }
+
+    let result = nodes.into_iter()
+        .filter(|(_, node)| node.len() > 1)
+        .map(|(_, mut node)| {
+            for (key, values) in node.iter_mut() {
+                if key != "@id" && key != "@type" {
+                    for value in values.as_array_mut().unwrap() {
+                        replace_lists(value, &lists);
+                    }
+                }
+            }
+
+            Value::Object(node)
+        })
+        .collect();
+
+    Ok(Value::Array(result))
+}
+
+/// Replaces `value` with a list object if it refers to the first node of
+/// a list.
+fn replace_lists(value: &mut Value, lists: &HashMap<String, Vec<Value>>) {
+    let items = value.get("@id")
+        .and_then(Value::as_str)
+        .and_then(|id| lists.get(id));
+
+    if let Some(items) = items {
+        let mut items = items.clone();
+
+        for item in &mut items {
+            replace_lists(item, lists);
+        }
+
+        let mut object = Map::new();
+        object.insert(String::from("@list"), Value::Array(items));
+        *value = Value::Object(object);
+    }
+}
+
+/// Whether the blank node `id` is only used as the `rdf:rest` of one other
+/// node and has nothing but an `rdf:first` and `rdf:rest`.
+fn is_list_node(nodes: &BTreeMap<String, Map<String, Value>>,
+                usages: &HashMap<String, Vec<Usage>>,
+                id: &str)
+    -> bool
+{
+    let node = match nodes.get(id) {
+        Some(node) if is_blank_node_id(id) => node,
+        _ => return false,
+    };
+    let single = |key: &str| node.get(key)
+        .and_then(Value::as_array)
+        .is_some_and(|values| values.len() == 1);
+    let list_type = node.get("@type").is_none_or(|types| *types == Value::from(vec![RDF_LIST]));
+
+    usages.get(id).is_some_and(|usages| usages.len() == 1) &&
+        single(RDF_FIRST) &&
+        single(RDF_REST) &&
+        list_type &&
+        node.keys().all(|key| ["@id", "@type", RDF_FIRST, RDF_REST].contains(&&**key))
+}
+
+/// Runs the RDF to Object Conversion algorithm on a literal.
+fn literal_to_value(literal: &Literal, native_types: bool) -> Result<Value, Error> {
+    let mut result = Map::new();
+    let datatype = literal.iri.as_str();
+    let mut value = Value::from(literal.value.clone());
+    let mut type_ = None;
+
+    if datatype == RDF_JSON {
+        value = serde_json::from_str(&literal.value)
+            .map_err(|_| Error::json_ld("invalid JSON literal", literal.value.clone()))?;
+        type_ = Some(String::from("@json"));
+    } else if native_types && datatype == XSD_BOOLEAN && ["true", "false"].contains(&&*literal.value) {
+        value = Value::from(literal.value == "true");
+    } else if native_types && datatype == XSD_INTEGER && is_valid_xsd_integer(&literal.value) {
+        match literal.value.parse::<i64>() {
+            Ok(number) => value = Value::from(number),
+            Err(_) => type_ = Some(datatype.to_owned()),
+        }
+    } else if native_types && datatype == XSD_DOUBLE {
+        match literal.value.parse::<f64>().ok().and_then(Number::from_f64) {
+            Some(number) => value = Value::Number(number),
+            None => type_ = Some(datatype.to_owned()),
+        }
+    } else if let Some(ref language) = literal.language_tag {
+        result.insert(String::from("@language"), Value::from(language.clone()));
+
+        if let Some(direction) = literal.direction {
+            result.insert(String::from("@direction"), Value::from(direction.as_str()));
+        }
+    } else if datatype != XSD_STRING {
+        type_ = Some(datatype.to_owned());
+    }
+
+    result.insert(String::from("@value"), value);
+
+    if let Some(type_) = type_ {
+        result.insert(String::from("@type"), Value::from(type_));
+    }
+
+    Ok(Value::Object(result))
+}
+
+fn subject_id(subject: &Subject) -> Result<String, Error> {
+    match *subject {
+        Subject::Iri(ref iri) => Ok(iri.as_str().to_owned()),
+        Subject::BlankNode(ref node) => Ok(format!("_:{}", node.0)),
+        Subject::RelativeIri(ref relative) => Ok(relative.0.clone()),
+        Subject::Triple(ref quoted) => Err(quoted_triple(quoted)),
+    }
+}
+
+/// JSON-LD 1.1 has no way to write quoted triples.
+fn quoted_triple(triple: &Triple) -> Error {
+    Error::json_ld("invalid embedded node", Subject::from(triple.clone()).to_string())
+}
+
+fn reference(id: String) -> Value {
+    let mut reference = Map::new();
+    reference.insert(String::from("@id"), Value::from(id));
+    Value::Object(reference)
+}
+
+fn node<'m>(nodes: &'m mut BTreeMap<String, Map<String, Value>>, id: &str)
+    -> &'m mut Map<String, Value>
+{
+    nodes.entry(id.to_owned()).or_insert_with(|| {
+        let mut node = Map::new();
+        node.insert(String::from("@id"), Value::from(id.to_owned()));
+        node
+    })
+}
+
+fn add_unique(node: &mut Map<String, Value>, key: &str, value: Value) {
+    let values = node.entry(key.to_owned()).or_insert_with(|| Value::Array(Vec::new()));
+    let values = values.as_array_mut().unwrap();
+
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
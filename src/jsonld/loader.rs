@@ -0,0 +1,91 @@
+//! Fetching the remote documents that contexts refer to.
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use error::{Error, Position};
+use iri::Iri;
+
+/// Loads the JSON documents that `@context` and `@import` refer to.
+///
+/// Any `Fn(&Iri) -> Result<String, Error>` is a loader, as are maps from
+/// IRIs to documents.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use chelone::{Iri, JsonLdReader};
+///
+/// let context = Iri::parse("http://example.org/context.jsonld").unwrap();
+/// let mut contexts = HashMap::new();
+/// contexts.insert(context, String::from(r#"{ "@context": { "@vocab": "http://schema.org/" } }"#));
+///
+/// let input = r#"{ "@context": "http://example.org/context.jsonld", "name": "Chelone" }"#;
+/// let mut reader = JsonLdReader::new(input.as_bytes());
+/// reader.set_loader(contexts);
+///
+/// assert_eq!(reader.parse().unwrap().len(), 1);
+/// ```
+pub trait DocumentLoader {
+    /// Returns the text of the document at `iri`.
+    fn load(&self, iri: &Iri) -> Result<String, Error>;
+}
+
+impl<F> DocumentLoader for F
+    where F: Fn(&Iri) -> Result<String, Error>
+{
+    fn load(&self, iri: &Iri) -> Result<String, Error> {
+        self(iri)
+    }
+}
+
+impl DocumentLoader for HashMap<Iri, String> {
+    fn load(&self, iri: &Iri) -> Result<String, Error> {
+        self.get(iri).cloned().ok_or_else(|| not_found(iri))
+    }
+}
+
+impl DocumentLoader for BTreeMap<Iri, String> {
+    fn load(&self, iri: &Iri) -> Result<String, Error> {
+        self.get(iri).cloned().ok_or_else(|| not_found(iri))
+    }
+}
+
+/// Loads documents from a local directory, reading the file at the path of
+/// each IRI relative to a base IRI. With a base of `http://example.org/`
+/// and a directory of `contexts`, `http://example.org/a/b.jsonld` is read
+/// from `contexts/a/b.jsonld`. IRIs whose paths could lead outside of the
+/// directory, such as through `..` or an absolute path, aren't loaded.
+#[derive(Clone, Debug)]
+pub struct DirectoryLoader {
+    base: Iri,
+    directory: PathBuf,
+}
+
+impl DirectoryLoader {
+    /// Creates a loader for the IRIs starting with `base`.
+    pub fn new<P: Into<PathBuf>>(base: Iri, directory: P) -> Self {
+        DirectoryLoader {
+            base,
+            directory: directory.into(),
+        }
+    }
+}
+
+impl DocumentLoader for DirectoryLoader {
+    fn load(&self, iri: &Iri) -> Result<String, Error> {
+        // Only plain segments are followed, as a root, prefix or `..` would
+        // lead outside of the directory.
+        let path = iri.as_str()
+            .strip_prefix(self.base.as_str())
+            .map(Path::new)
+            .filter(|path| path.components().all(|component| matches!(component, Component::Normal(_))))
+            .ok_or_else(|| not_found(iri))?;
+
+        fs::read_to_string(self.directory.join(path))
+            .map_err(|error| Error::from_io(&error, Position::new(1, 1)))
+    }
+}
+
+fn not_found(iri: &Iri) -> Error {
+    Error::json_ld("loading document failed", iri.as_str())
+}
@@ -0,0 +1,467 @@
+//! Reading and writing JSON-LD.
+//!
+//! Documents are expanded and turned into RDF following the JSON-LD 1.1
+//! Processing Algorithms. Contexts that are referenced by IRI are fetched
+//! through a `DocumentLoader`, so they can come from a local directory or an
+//! in-memory map rather than the network. Writing goes the other way, with
+//! compaction against a context and optional framing.
+mod compact;
+mod context;
+mod expand;
+mod frame;
+mod from_rdf;
+mod loader;
+mod to_rdf;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, Read};
+
+use serde_json::{self, Map, Value};
+use url::Url;
+
+use error::{Error, Position};
+use iri::Iri;
+use labeller::{BlankNodeLabeller, BlankNodes};
+use quad::{Dataset, Quad};
+use triple::Triples;
+
+use self::context::Context;
+
+pub use self::loader::{DirectoryLoader, DocumentLoader};
+
+const KEYWORDS: &[&str] = &[
+    "@base", "@container", "@context", "@direction", "@graph", "@id", "@import", "@included",
+    "@index", "@json", "@language", "@list", "@nest", "@none", "@prefix", "@propagate",
+    "@protected", "@reverse", "@set", "@type", "@value", "@version", "@vocab",
+    // Framing keywords.
+    "@default", "@embed", "@explicit", "@omitDefault", "@requireAll",
+];
+
+/// JSON-LD parser reading from any `Read`. The whole document is read and
+/// expanded before the first quad is returned, and its blank nodes are
+/// relabelled as with `NTriplesReader`.
+///
+/// ```
+/// use chelone::JsonLdReader;
+///
+/// let input = r#"{
+///     "@context": { "@vocab": "http://schema.org/" },
+///     "@id": "http://example.org/book",
+///     "@type": "Book",
+///     "name": "Chelone",
+///     "author": { "name": "Aaron" }
+/// }"#;
+/// let triples = JsonLdReader::new(input.as_bytes()).parse().unwrap();
+///
+/// assert_eq!(triples.len(), 4);
+/// ```
+pub struct JsonLdReader<R: Read> {
+    reader: Option<R>,
+    base: Option<Iri>,
+    loader: Option<Box<dyn DocumentLoader>>,
+    blank_nodes: BlankNodes,
+    pending: VecDeque<Quad>,
+    finished: bool,
+}
+
+impl<R: Read> JsonLdReader<R> {
+    /// Creates a new `JsonLdReader` that parses JSON-LD read from `reader`.
+    pub fn new(reader: R) -> Self {
+        JsonLdReader {
+            reader: Some(reader),
+            base: None,
+            loader: None,
+            blank_nodes: BlankNodes::default(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.base = Some(iri)
+    }
+
+    /// Sets the loader used to fetch remote contexts. Without one, any
+    /// `@context` or `@import` that refers to an IRI is an error.
+    pub fn set_loader<L: DocumentLoader + 'static>(&mut self, loader: L) {
+        self.loader = Some(Box::new(loader))
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Parse the whole input into a set of Triples, merging every named
+    /// graph into the default graph.
+    pub fn parse(self) -> Result<Triples, Error> {
+        self.map(|quad| quad.map(Quad::into_triple)).collect()
+    }
+
+    /// Parse the whole input into a `Dataset`, keeping named graphs apart.
+    pub fn parse_dataset(self) -> Result<Dataset, Error> {
+        self.collect()
+    }
+
+    /// Runs the expansion algorithm on the input, returning the expanded
+    /// document as an array.
+    pub fn expand(mut self) -> Result<Value, Error> {
+        let document = self.read()?;
+        let mut processor = Processor::new(self.loader.as_deref());
+
+        processor.expand_document(document, self.base.as_ref())
+    }
+
+    fn read(&mut self) -> Result<Value, Error> {
+        let reader = match self.reader.take() {
+            Some(reader) => reader,
+            None => return Ok(Value::Array(Vec::new())),
+        };
+
+        serde_json::from_reader(reader).map_err(from_json)
+    }
+
+    fn parse_document(&mut self) -> Result<(), Error> {
+        let document = self.read()?;
+        let mut processor = Processor::new(self.loader.as_deref());
+        let expanded = processor.expand_document(document, self.base.as_ref())?;
+        let blank_nodes = &mut self.blank_nodes;
+        let quads = to_rdf::to_rdf(&expanded)?.into_iter()
+            .map(|quad| quad.map_blank_nodes(&mut |node| blank_nodes.document(&node.0)));
+
+        self.pending.extend(quads);
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for JsonLdReader<R> {
+    type Item = Result<Quad, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(quad) = self.pending.pop_front() {
+            return Some(Ok(quad))
+        }
+
+        if self.finished {
+            return None
+        }
+
+        self.finished = true;
+
+        match self.parse_document() {
+            Ok(()) => self.pending.pop_front().map(Ok),
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+impl<R: Read> fmt::Debug for JsonLdReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JsonLdReader")
+            .field("base", &self.base)
+            .field("blank_nodes", &self.blank_nodes)
+            .field("pending", &self.pending)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+/// JSON-LD serializer for `Triples`. The triples are compacted against the
+/// declared prefixes and context, which makes the output read like plain
+/// JSON, and then framed if there's a frame. Collections become JSON
+/// arrays. `to_value` gives the document as a `serde_json::Value`, and
+/// `to_json_string` writes it out.
+///
+/// ```
+/// #[macro_use] extern crate serde_json;
+/// extern crate chelone;
+///
+/// use chelone::{Error, Graph, JsonLdWriter, Triples};
+///
+/// # fn main() {
+/// let graph = Graph::new(r#"
+///     @prefix : <http://schema.org/> .
+///     @prefix ex: <http://example.org/> .
+///     ex:book a :Book ; :name "Chelone" ; :author [ :name "Aaron" ] .
+/// "#).unwrap();
+/// let mut parsed = graph.triples();
+/// let triples = parsed.by_ref().collect::<Result<Triples, Error>>().unwrap();
+///
+/// let json = JsonLdWriter::new(&triples)
+///     .prefixs(parsed.prefixs().clone())
+///     .frame(json!({ "@type": "Book" }))
+///     .to_json_string()
+///     .unwrap();
+///
+/// assert_eq!(json, r#"{
+///   "@context": {
+///     "@vocab": "http://schema.org/",
+///     "ex": "http://example.org/"
+///   },
+///   "@id": "ex:book",
+///   "@type": "Book",
+///   "author": {
+///     "name": "Aaron"
+///   },
+///   "name": "Chelone"
+/// }"#);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct JsonLdWriter<'a> {
+    triples: &'a Triples,
+    prefixs: BTreeMap<String, Iri>,
+    context: Option<Value>,
+    frame: Option<Value>,
+    native_types: bool,
+    loader: Option<&'a dyn DocumentLoader>,
+}
+
+impl<'a> JsonLdWriter<'a> {
+    /// Creates a writer for `triples` with no prefixes or context.
+    pub fn new(triples: &'a Triples) -> Self {
+        JsonLdWriter {
+            triples,
+            prefixs: BTreeMap::new(),
+            context: None,
+            frame: None,
+            native_types: false,
+            loader: None,
+        }
+    }
+
+    /// Declares `prefix` as short for `iri`. The empty prefix becomes the
+    /// `@vocab`, so the IRIs starting with it are written as bare terms.
+    pub fn prefix(mut self, prefix: &str, iri: Iri) -> Self {
+        self.prefixs.insert(prefix.to_owned(), iri);
+        self
+    }
+
+    /// Declares each of `prefixs`, such as the ones returned by
+    /// `Graph::prefixs`.
+    pub fn prefixs<I>(mut self, prefixs: I) -> Self
+        where I: IntoIterator<Item = (String, Iri)>
+    {
+        self.prefixs.extend(prefixs);
+        self
+    }
+
+    /// Compacts the output against `context`, which takes precedence over
+    /// the prefixes.
+    pub fn context(mut self, context: Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Frames the output with `frame`, embedding the nodes that match it in
+    /// one JSON object each. The frame's own `@context` is used on top of
+    /// the prefixes and context.
+    pub fn frame(mut self, frame: Value) -> Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    /// Writes `xsd:integer`, `xsd:double` and `xsd:boolean` literals as JSON
+    /// numbers and booleans.
+    pub fn native_types(mut self, native_types: bool) -> Self {
+        self.native_types = native_types;
+        self
+    }
+
+    /// Sets the loader used to fetch remote contexts.
+    pub fn loader(mut self, loader: &'a dyn DocumentLoader) -> Self {
+        self.loader = Some(loader);
+        self
+    }
+
+    /// Runs the serialization, returning the JSON document.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        let mut processor = Processor::new(self.loader);
+        let mut expanded = from_rdf::from_rdf(self.triples, self.native_types)?;
+        let context = self.local_context();
+
+        if let Some(ref frame) = self.frame {
+            expanded = processor.frame(&expanded, frame, &context, None)?;
+        }
+
+        let mut compacted = processor.compact_document(&expanded, &context, None)?;
+        frame::remove_preserve(&mut compacted);
+
+        Ok(compacted)
+    }
+
+    /// Runs the serialization, returning the JSON document pretty printed.
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(&self.to_value()?).map_err(from_json)
+    }
+
+    /// The context to compact with, merging the prefixes, the context and
+    /// the frame's context into one map where possible.
+    fn local_context(&self) -> Value {
+        let mut prefixs = Map::new();
+
+        for (prefix, iri) in &self.prefixs {
+            if prefix.is_empty() {
+                prefixs.insert(String::from("@vocab"), Value::from(iri.as_str()));
+            } else if !prefix.starts_with('@') && !prefix.contains(':') {
+                prefixs.insert(prefix.clone(), Value::from(iri.as_str()));
+            }
+        }
+
+        let frame_context = self.frame.as_ref().and_then(|frame| frame.get("@context"));
+        let mut parts = Vec::new();
+
+        if !prefixs.is_empty() {
+            parts.push(Value::Object(prefixs));
+        }
+
+        for context in self.context.iter().chain(frame_context) {
+            match *context {
+                Value::Array(ref contexts) => parts.extend(contexts.iter().cloned()),
+                Value::Null => {}
+                ref context => parts.push(context.clone()),
+            }
+        }
+
+        if parts.iter().all(Value::is_object) {
+            let mut merged = Map::new();
+
+            for part in parts {
+                merged.extend(part.as_object().unwrap().clone());
+            }
+
+            Value::Object(merged)
+        } else if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            Value::Array(parts)
+        }
+    }
+}
+
+impl<'a> fmt::Debug for JsonLdWriter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JsonLdWriter")
+            .field("triples", &self.triples)
+            .field("prefixs", &self.prefixs)
+            .field("context", &self.context)
+            .field("frame", &self.frame)
+            .field("native_types", &self.native_types)
+            .finish()
+    }
+}
+
+/// Runs the JSON-LD algorithms, keeping the remote contexts that have
+/// already been loaded.
+pub(crate) struct Processor<'a> {
+    loader: Option<&'a dyn DocumentLoader>,
+    remote_contexts: HashMap<String, Value>,
+    /// Whether a frame is being expanded, which keeps the framing keywords
+    /// and allows wildcards in `@id` and `@type`.
+    frame_expansion: bool,
+}
+
+impl<'a> Processor<'a> {
+    pub(crate) fn new(loader: Option<&'a dyn DocumentLoader>) -> Self {
+        Processor {
+            loader,
+            remote_contexts: HashMap::new(),
+            frame_expansion: false,
+        }
+    }
+
+    /// Expands `document`, always returning an array.
+    pub(crate) fn expand_document(&mut self, document: Value, base: Option<&Iri>)
+        -> Result<Value, Error>
+    {
+        let context = Context::new(base.cloned());
+        let expanded = self.expand(&context, None, &document, base, false)?;
+
+        Ok(match expanded {
+            Value::Object(mut map) if map.len() == 1 && map.contains_key("@graph") => {
+                as_array(map.remove("@graph").unwrap())
+            }
+            Value::Null => Value::Array(Vec::new()),
+            expanded => as_array(expanded),
+        })
+    }
+}
+
+/// Wraps `value` in an array unless it already is one.
+pub(crate) fn as_array(value: Value) -> Value {
+    match value {
+        Value::Array(_) => value,
+        value => Value::Array(vec![value]),
+    }
+}
+
+/// Appends `value`, or each of its items if it is an array, to the array at
+/// `key` in `map`.
+pub(crate) fn add_value(map: &mut serde_json::Map<String, Value>, key: &str, value: Value) {
+    let entry = map.entry(key.to_owned()).or_insert_with(|| Value::Array(Vec::new()));
+
+    if !entry.is_array() {
+        *entry = Value::Array(vec![entry.take()]);
+    }
+
+    let array = entry.as_array_mut().unwrap();
+
+    match value {
+        Value::Array(items) => array.extend(items),
+        value => array.push(value),
+    }
+}
+
+pub(crate) fn is_keyword(value: &str) -> bool {
+    KEYWORDS.contains(&value)
+}
+
+/// Whether `value` looks like a keyword, `@` followed by letters, which
+/// JSON-LD reserves for future keywords.
+pub(crate) fn has_keyword_form(value: &str) -> bool {
+    value.len() > 1 &&
+        value.starts_with('@') &&
+        value[1..].bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+pub(crate) fn is_blank_node_id(value: &str) -> bool {
+    value.starts_with("_:")
+}
+
+/// Whether `value` starts with an IRI scheme.
+pub(crate) fn is_absolute_iri(value: &str) -> bool {
+    match value.find(':') {
+        Some(index) => {
+            let scheme = &value[..index];
+
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+                scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        }
+        None => false,
+    }
+}
+
+/// Resolves `value` against `base`, leaving it as is when there is no base.
+pub(crate) fn resolve(base: Option<&Iri>, value: &str) -> String {
+    match base.map(|base| Url::join(base, value)) {
+        Some(Ok(url)) => url.into_string(),
+        _ => value.to_owned(),
+    }
+}
+
+fn from_json(error: serde_json::Error) -> Error {
+    let position = Position::new(error.line(), error.column());
+
+    if error.is_io() {
+        let error = io::Error::from(error);
+        Error::from_io(&error, position)
+    } else {
+        // Drop the position from the message as it's already in the error.
+        let message = error.to_string();
+        let message = message.split(" at line ").next().unwrap_or_default().to_owned();
+
+        Error::Syntax { message, position }
+    }
+}
@@ -0,0 +1,462 @@
+//! Node map generation and the Deserialize JSON-LD to RDF algorithm.
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+
+use error::Error;
+use iri::{BlankNode, Iri};
+use literal::{Direction, Literal};
+use object::Object;
+use quad::{GraphName, Quad};
+use subject::Subject;
+
+use super::{add_value, as_array, is_blank_node_id};
+
+/// The nodes of each graph by `@id`, with `@default` for the default graph.
+pub(crate) type NodeMap = BTreeMap<String, BTreeMap<String, Map<String, Value>>>;
+
+/// Relabels blank nodes as `_:b0`, `_:b1`, ...
+#[derive(Debug, Default)]
+pub(crate) struct BlankNodes {
+    labels: HashMap<String, String>,
+    counter: usize,
+}
+
+impl BlankNodes {
+    /// The new label for `id`, or a fresh one if `id` is `None`.
+    pub(crate) fn label(&mut self, id: Option<&str>) -> String {
+        if let Some(label) = id.and_then(|id| self.labels.get(id)) {
+            return label.clone()
+        }
+
+        let label = format!("_:b{}", self.counter);
+        self.counter += 1;
+
+        if let Some(id) = id {
+            self.labels.insert(id.to_owned(), label.clone());
+        }
+
+        label
+    }
+}
+
+/// Who a node being added to the node map is the value of.
+#[derive(Clone, Copy)]
+enum Parent<'a> {
+    None,
+    /// The node is the value of `property` on the node with this `@id`.
+    Node(&'a str, &'a str),
+    /// The node has a reverse `property` whose value is this node reference.
+    Reverse(&'a Value, &'a str),
+}
+
+/// Turns an expanded document into quads.
+pub(crate) fn to_rdf(expanded: &Value) -> Result<Vec<Quad>, Error> {
+    let mut node_map = NodeMap::new();
+    let mut blank_nodes = BlankNodes::default();
+
+    node_map.insert(String::from("@default"), BTreeMap::new());
+    generate_node_map(expanded, &mut node_map, "@default", Parent::None, None, &mut blank_nodes)?;
+
+    let mut quads = Vec::new();
+
+    for (name, graph) in &node_map {
+        let graph_name = match &**name {
+            "@default" => None,
+            name => match to_graph_name(name) {
+                Some(name) => Some(name),
+                None => continue,
+            },
+        };
+
+        for (id, node) in graph {
+            let subject = match to_subject(id) {
+                Some(subject) => subject,
+                None => continue,
+            };
+
+            for (property, values) in node {
+                let values = values.as_array().map(|values| &**values).unwrap_or_default();
+
+                if property == "@type" {
+                    for t in values.iter().filter_map(Value::as_str) {
+                        if let Some(object) = to_subject(t) {
+                            quads.push(Quad::new(subject.clone(),
+                                                 rdf!("type"),
+                                                 object.as_object(),
+                                                 graph_name.clone()));
+                        }
+                    }
+
+                    continue
+                }
+
+                if property.starts_with('@') || is_blank_node_id(property) {
+                    continue
+                }
+
+                let predicate = match Iri::parse(property) {
+                    Ok(predicate) => predicate,
+                    Err(_) => continue,
+                };
+
+                for item in values {
+                    let mut list_triples = Vec::new();
+                    let object = object_to_rdf(item, &mut list_triples, &mut blank_nodes);
+
+                    if let Some(object) = object {
+                        quads.push(Quad::new(subject.clone(), predicate.clone(), object, graph_name.clone()));
+                    }
+
+                    quads.extend(list_triples.into_iter().map(|(subject, predicate, object)| {
+                        Quad::new(subject, predicate, object, graph_name.clone())
+                    }));
+                }
+            }
+        }
+    }
+
+    Ok(quads)
+}
+
+/// Runs the Node Map Generation algorithm.
+fn generate_node_map(element: &Value,
+                     node_map: &mut NodeMap,
+                     active_graph: &str,
+                     parent: Parent,
+                     list: Option<&mut Vec<Value>>,
+                     blank_nodes: &mut BlankNodes)
+    -> Result<(), Error>
+{
+    if let Value::Array(ref items) = *element {
+        let mut list = list;
+
+        for item in items {
+            generate_node_map(item, node_map, active_graph, parent, list.as_deref_mut(), blank_nodes)?;
+        }
+
+        return Ok(())
+    }
+
+    let mut element = match element.as_object() {
+        Some(element) => element.clone(),
+        None => return Ok(()),
+    };
+
+    if let Some(types) = element.get_mut("@type") {
+        for t in types.as_array_mut().into_iter().flatten() {
+            if let Some(label) = t.as_str().filter(|t| is_blank_node_id(t)).map(|t| blank_nodes.label(Some(t))) {
+                *t = Value::String(label);
+            }
+        }
+    }
+
+    if element.contains_key("@value") {
+        let element = Value::Object(element);
+
+        match list {
+            Some(list) => list.push(element),
+            None => if let Parent::Node(id, property) = parent {
+                let node = node(node_map, active_graph, id);
+                let values = node.entry(property.to_owned()).or_insert_with(|| Value::Array(Vec::new()));
+
+                if !values.as_array().unwrap().contains(&element) {
+                    add_value(node, property, element);
+                }
+            },
+        }
+
+        return Ok(())
+    }
+
+    if let Some(items) = element.get("@list") {
+        let mut result = Vec::new();
+        generate_node_map(items, node_map, active_graph, parent, Some(&mut result), blank_nodes)?;
+
+        let mut object = Map::new();
+        object.insert(String::from("@list"), Value::Array(result));
+        let object = Value::Object(object);
+
+        match list {
+            Some(list) => list.push(object),
+            None => if let Parent::Node(id, property) = parent {
+                add_value(node(node_map, active_graph, id), property, object);
+            },
+        }
+
+        return Ok(())
+    }
+
+    let id = match element.remove("@id") {
+        Some(Value::String(ref id)) if !is_blank_node_id(id) => id.clone(),
+        Some(Value::String(ref id)) => blank_nodes.label(Some(id)),
+        _ => blank_nodes.label(None),
+    };
+
+    node(node_map, active_graph, &id);
+
+    let mut reference = Map::new();
+    reference.insert(String::from("@id"), Value::String(id.clone()));
+    let reference = Value::Object(reference);
+
+    match parent {
+        Parent::Reverse(subject, property) => {
+            add_unique(node(node_map, active_graph, &id), property, subject.clone());
+        }
+        Parent::Node(subject, property) => match list {
+            Some(list) => list.push(reference.clone()),
+            None => add_unique(node(node_map, active_graph, subject), property, reference.clone()),
+        },
+        Parent::None => {}
+    }
+
+    if let Some(types) = element.remove("@type") {
+        for t in as_array(types).as_array().unwrap() {
+            add_unique(node(node_map, active_graph, &id), "@type", t.clone());
+        }
+    }
+
+    if let Some(index) = element.remove("@index") {
+        let node = node(node_map, active_graph, &id);
+
+        match node.get("@index") {
+            Some(existing) if *existing != index => {
+                return Err(Error::json_ld("conflicting indexes", id))
+            }
+            _ => {
+                node.insert(String::from("@index"), index);
+            }
+        }
+    }
+
+    if let Some(Value::Object(reverse)) = element.remove("@reverse") {
+        for (property, values) in reverse {
+            for value in as_array(values).as_array().unwrap() {
+                generate_node_map(value,
+                                  node_map,
+                                  active_graph,
+                                  Parent::Reverse(&reference, &property),
+                                  None,
+                                  blank_nodes)?;
+            }
+        }
+    }
+
+    if let Some(graph) = element.remove("@graph") {
+        node_map.entry(id.clone()).or_default();
+        generate_node_map(&graph, node_map, &id, Parent::None, None, blank_nodes)?;
+    }
+
+    if let Some(included) = element.remove("@included") {
+        generate_node_map(&included, node_map, active_graph, Parent::None, None, blank_nodes)?;
+    }
+
+    for (property, value) in element {
+        let property = if is_blank_node_id(&property) {
+            blank_nodes.label(Some(&property))
+        } else {
+            property
+        };
+
+        node(node_map, active_graph, &id)
+            .entry(property.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+
+        generate_node_map(&value, node_map, active_graph, Parent::Node(&id, &property), None, blank_nodes)?;
+    }
+
+    Ok(())
+}
+
+/// The node with `id` in `graph`, adding it if it's new.
+fn node<'m>(node_map: &'m mut NodeMap, graph: &str, id: &str) -> &'m mut Map<String, Value> {
+    node_map.entry(graph.to_owned())
+        .or_default()
+        .entry(id.to_owned())
+        .or_insert_with(|| {
+            let mut node = Map::new();
+            node.insert(String::from("@id"), Value::String(id.to_owned()));
+            node
+        })
+}
+
+/// Appends `value` to the array at `key` unless it's already there.
+fn add_unique(map: &mut Map<String, Value>, key: &str, value: Value) {
+    let exists = map.get(key)
+        .and_then(Value::as_array)
+        .is_some_and(|values| values.contains(&value));
+
+    if !exists {
+        add_value(map, key, value);
+    }
+}
+
+fn object_to_rdf(item: &Value,
+                 list_triples: &mut Vec<(Subject, Iri, Object)>,
+                 blank_nodes: &mut BlankNodes)
+    -> Option<Object>
+{
+    let item = item.as_object()?;
+
+    if let Some(id) = item.get("@id") {
+        return to_subject(id.as_str()?).map(|subject| subject.as_object())
+    }
+
+    if let Some(list) = item.get("@list") {
+        return Some(list_to_rdf(list.as_array()?, list_triples, blank_nodes))
+    }
+
+    let value = item.get("@value")?;
+    let datatype = item.get("@type").and_then(Value::as_str);
+
+    if let Some(datatype) = datatype {
+        if datatype != "@json" && Iri::parse(datatype).is_err() {
+            return None
+        }
+    }
+
+    let language = item.get("@language").and_then(Value::as_str);
+
+    if language.is_some_and(|language| !is_language_tag(language)) {
+        return None
+    }
+
+    let (value, default) = if datatype == Some("@json") {
+        (canonical_json(value), Some(rdf!("JSON")))
+    } else {
+        match *value {
+            Value::Bool(value) => (value.to_string(), Some(xsd!("boolean"))),
+            Value::Number(ref number) => {
+                let is_double = datatype == Some(xsd!("double").as_str()) ||
+                    number.is_f64() && number.as_f64().is_some_and(|n| n.fract() != 0.0 || n.abs() >= 1e21);
+
+                if is_double {
+                    (canonical_double(number.as_f64().unwrap()), Some(xsd!("double")))
+                } else {
+                    let integer = number.as_i64().map(|n| n.to_string())
+                        .or_else(|| number.as_u64().map(|n| n.to_string()))
+                        .unwrap_or_else(|| format!("{}", number.as_f64().unwrap()));
+
+                    (integer, Some(xsd!("integer")))
+                }
+            }
+            Value::String(ref value) => (value.clone(), None),
+            _ => return None,
+        }
+    };
+
+    let datatype = match datatype {
+        Some("@json") => default,
+        Some(datatype) => Iri::parse(datatype).ok(),
+        None => default,
+    };
+
+    let direction = item.get("@direction").and_then(Value::as_str).and_then(Direction::parse);
+
+    Some(Object::Literal(match (language, direction) {
+        (Some(language), Some(direction)) => Literal::new_directional(value, language.to_owned(), direction),
+        _ => Literal::new(value, language.map(str::to_owned), datatype),
+    }))
+}
+
+fn list_to_rdf(list: &[Value],
+               list_triples: &mut Vec<(Subject, Iri, Object)>,
+               blank_nodes: &mut BlankNodes)
+    -> Object
+{
+    if list.is_empty() {
+        return Object::Iri(rdf!("nil"))
+    }
+
+    let nodes = list.iter()
+        .map(|_| blank_node(&blank_nodes.label(None)))
+        .collect::<Vec<_>>();
+
+    for (index, item) in list.iter().enumerate() {
+        let subject = Subject::BlankNode(nodes[index].clone());
+        let mut embedded = Vec::new();
+
+        if let Some(object) = object_to_rdf(item, &mut embedded, blank_nodes) {
+            list_triples.push((subject.clone(), rdf!("first"), object));
+        }
+
+        let rest = match nodes.get(index + 1) {
+            Some(next) => Object::BlankNode(next.clone()),
+            None => Object::Iri(rdf!("nil")),
+        };
+
+        list_triples.push((subject, rdf!("rest"), rest));
+        list_triples.append(&mut embedded);
+    }
+
+    Object::BlankNode(nodes[0].clone())
+}
+
+fn to_subject(id: &str) -> Option<Subject> {
+    if is_blank_node_id(id) {
+        Some(Subject::BlankNode(blank_node(id)))
+    } else {
+        Iri::parse(id).ok().map(Subject::Iri)
+    }
+}
+
+fn to_graph_name(id: &str) -> Option<GraphName> {
+    to_subject(id).and_then(GraphName::from_subject)
+}
+
+fn blank_node(id: &str) -> BlankNode {
+    BlankNode(id[2..].to_owned())
+}
+
+/// Whether `tag` is shaped like a BCP 47 language tag.
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+
+    (1..=8).contains(&primary.len()) &&
+        primary.bytes().all(|b| b.is_ascii_alphabetic()) &&
+        subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+        })
+}
+
+/// Formats `value` in the canonical form of `xsd:double`, such as `1.5E1`.
+fn canonical_double(value: f64) -> String {
+    let formatted = format!("{:E}", value);
+    let (mantissa, exponent) = formatted.split_once('E').unwrap();
+
+    if mantissa.contains('.') {
+        formatted
+    } else {
+        format!("{}.0E{}", mantissa, exponent)
+    }
+}
+
+/// Serializes JSON with its object keys sorted and no whitespace.
+fn canonical_json(value: &Value) -> String {
+    match *value {
+        Value::Array(ref items) => {
+            let items = items.iter().map(canonical_json).collect::<Vec<_>>();
+            format!("[{}]", items.join(","))
+        }
+        Value::Object(ref map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|&(key, _)| key.encode_utf16().collect::<Vec<_>>());
+
+            let entries = entries.into_iter()
+                .map(|(key, value)| format!("{}:{}", Value::from(key.clone()), canonical_json(value)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Number(ref number) if number.is_f64() => {
+            let n = number.as_f64().unwrap();
+
+            if n.fract() == 0.0 && n.abs() < 1e21 {
+                format!("{}", n as i64)
+            } else {
+                number.to_string()
+            }
+        }
+        ref value => value.to_string(),
+    }
+}
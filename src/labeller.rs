@@ -0,0 +1,213 @@
+//! Choosing the labels of parsed blank nodes.
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use iri::BlankNode;
+
+/// Chooses the labels of the blank nodes in a parsed document.
+///
+/// Every blank node is relabelled, including those written `_:label` in
+/// the document, so the labels in one parse never depend on another. Each
+/// label in a document is only passed to the labeller once per parse, and a
+/// label the labeller has already returned is never reused, so blank nodes
+/// that are distinct in the document always stay distinct. Any
+/// `FnMut(Option<&str>) -> String` is a labeller.
+///
+/// ```
+/// use chelone::Graph;
+/// use chelone::labeller::CounterLabeller;
+///
+/// let mut graph = Graph::new("_:b1 <http://example.org/p> [] .").unwrap();
+/// graph.set_labeller(CounterLabeller::new("first"));
+/// let triples = graph.parse().unwrap();
+///
+/// assert_eq!(triples[0].to_string(), "_:first1 <http://example.org/p> _:first2 .");
+/// ```
+pub trait BlankNodeLabeller {
+    /// Returns the label for a new blank node. `label` is its label in the
+    /// document, or `None` if it doesn't have one, as with `[]`.
+    fn label(&mut self, label: Option<&str>) -> String;
+}
+
+impl<F> BlankNodeLabeller for F
+    where F: FnMut(Option<&str>) -> String
+{
+    fn label(&mut self, label: Option<&str>) -> String {
+        self(label)
+    }
+}
+
+/// Labels blank nodes with a prefix followed by a counter, such as `b1`,
+/// `b2`, and so on. Labellers given the same prefix give out the same
+/// labels, so an explicit prefix should only be shared by documents whose
+/// triples are kept apart.
+///
+/// The default labeller, which every parser starts with, has a prefix of
+/// its own such as `b3_`, so the blank nodes of separate parses are never
+/// merged.
+#[derive(Clone, Debug)]
+pub struct CounterLabeller {
+    prefix: String,
+    count: usize,
+}
+
+impl CounterLabeller {
+    /// Creates a new `CounterLabeller` whose labels start with `prefix`,
+    /// which has to be a valid blank node label.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        CounterLabeller { prefix: prefix.into(), count: 0 }
+    }
+}
+
+impl Default for CounterLabeller {
+    fn default() -> Self {
+        static LABELLERS: AtomicUsize = AtomicUsize::new(0);
+
+        Self::new(format!("b{}_", LABELLERS.fetch_add(1, Ordering::Relaxed)))
+    }
+}
+
+impl BlankNodeLabeller for CounterLabeller {
+    fn label(&mut self, _: Option<&str>) -> String {
+        self.count += 1;
+        format!("{}{}", self.prefix, self.count)
+    }
+}
+
+/// Labels blank nodes with random labels, so the blank nodes of separate
+/// parses are kept apart without choosing prefixes.
+#[derive(Clone, Debug, Default)]
+pub struct RandomLabeller {
+    state: RandomState,
+    count: usize,
+}
+
+impl RandomLabeller {
+    /// Creates a new `RandomLabeller`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlankNodeLabeller for RandomLabeller {
+    fn label(&mut self, _: Option<&str>) -> String {
+        self.count += 1;
+        format!("r{:016x}", self.state.hash_one(self.count))
+    }
+}
+
+/// Labels blank nodes with a hash of the document's content and the blank
+/// node's label in the document, so parsing the same document again gives
+/// the same labels while different documents get different ones.
+#[derive(Clone, Debug)]
+pub struct HashLabeller {
+    seed: u64,
+    count: usize,
+}
+
+impl HashLabeller {
+    /// Creates a new `HashLabeller` for the document `content`.
+    pub fn new(content: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+
+        HashLabeller { seed: hasher.finish(), count: 0 }
+    }
+}
+
+impl BlankNodeLabeller for HashLabeller {
+    fn label(&mut self, label: Option<&str>) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.seed.hash(&mut hasher);
+
+        match label {
+            Some(label) => label.hash(&mut hasher),
+            None => {
+                self.count += 1;
+                self.count.hash(&mut hasher);
+            }
+        }
+
+        format!("h{:016x}", hasher.finish())
+    }
+}
+
+/// The blank nodes of a single parse.
+pub(crate) struct BlankNodes {
+    labeller: Box<dyn BlankNodeLabeller>,
+    /// The blank node each label in the document stands for.
+    document: HashMap<String, BlankNode>,
+    /// Every label given out so far.
+    used: HashSet<String>,
+}
+
+impl BlankNodes {
+    pub(crate) fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.labeller = Box::new(labeller);
+    }
+
+    /// The blank node written `_:label` in the document.
+    pub(crate) fn document(&mut self, label: &str) -> BlankNode {
+        if let Some(node) = self.document.get(label) {
+            return node.clone()
+        }
+
+        let node = self.new_node(Some(label));
+        self.document.insert(label.to_owned(), node.clone());
+
+        node
+    }
+
+    /// A blank node that doesn't have a label in the document.
+    pub(crate) fn generate(&mut self) -> BlankNode {
+        self.new_node(None)
+    }
+
+    /// The label in the document of each blank node that had one, or `None`
+    /// if there weren't any blank nodes.
+    pub(crate) fn into_labels(self) -> Option<HashMap<BlankNode, String>> {
+        if self.used.is_empty() {
+            return None
+        }
+
+        Some(self.document.into_iter().map(|(label, node)| (node, label)).collect())
+    }
+
+    /// Asks the labeller for labels until it gives one that hasn't been
+    /// used. Retries are asked for as unlabelled nodes, so labellers that
+    /// derive labels from the document's label can give a different one.
+    fn new_node(&mut self, mut label: Option<&str>) -> BlankNode {
+        loop {
+            let new = self.labeller.label(label);
+
+            if self.used.insert(new.clone()) {
+                return BlankNode(new)
+            }
+
+            label = None;
+        }
+    }
+}
+
+impl Default for BlankNodes {
+    fn default() -> Self {
+        BlankNodes {
+            labeller: Box::new(CounterLabeller::default()),
+            document: HashMap::new(),
+            used: HashSet::new(),
+        }
+    }
+}
+
+impl fmt::Debug for BlankNodes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlankNodes")
+            .field("labeller", &"#hidden#")
+            .field("document", &self.document)
+            .field("used", &self.used.len())
+            .finish()
+    }
+}
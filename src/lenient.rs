@@ -0,0 +1,230 @@
+//! Turtle parsing that recovers from errors.
+use std::mem;
+
+use pest::Parser;
+
+use error::{Diagnostic, Error, Position};
+use iri::Iri;
+use labeller::BlankNodeLabeller;
+use limits::Limits;
+use parser::{Rule, TurtleParser};
+use triple::Triples;
+use {checked, Context, Graph, BYTE_ORDER_MARK};
+
+/// Turtle parser that keeps going after an error. Each statement is parsed
+/// on its own, and when one fails it's skipped up to the next `.` that can
+/// end a statement, so every problem in a document can be found in one pass.
+///
+/// ```
+/// use chelone::LenientReader;
+///
+/// let (triples, diagnostics) = LenientReader::new(r#"
+///     @prefix : <http://example.org/> .
+///     :a :b :c .
+///     :d :e :f :g .
+///     :h :i ex:j .
+///     :k :l :m .
+/// "#).parse();
+///
+/// assert_eq!(triples.len(), 2);
+/// assert_eq!(diagnostics.len(), 2);
+/// assert_eq!(diagnostics[0].error.position().unwrap().line, 4);
+/// assert_eq!(diagnostics[1].error.position().unwrap().line, 5);
+/// ```
+#[derive(Debug)]
+pub struct LenientReader<'a> {
+    source: &'a str,
+    context: Context,
+}
+
+impl<'a> LenientReader<'a> {
+    /// Creates a new `LenientReader` from the turtle source.
+    pub fn new(source: &'a str) -> Self {
+        LenientReader {
+            source: source.trim_start_matches(BYTE_ORDER_MARK),
+            context: Context::default(),
+        }
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.context.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Sets the limits the document has to be within. See `Limits`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.context.options.limits = limits
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.context.base = Some(iri)
+    }
+
+    /// Parses the whole source, returning the triples of every statement
+    /// that could be parsed and a diagnostic for each part that was skipped.
+    pub fn parse(mut self) -> (Triples, Vec<Diagnostic>) {
+        let mut triples = Triples::default();
+        let mut diagnostics = Vec::new();
+        let mut offset = 0;
+        let mut position = Position::new(1, 1);
+
+        while offset < self.source.len() {
+            let rest = &self.source[offset..];
+            let limits = self.context.options.limits;
+
+            let (error, start, end) = if let Some(deep) = limits.too_deep(rest, true) {
+                // Too deeply nested to be given to pest.
+                (Some(limits.depth_error(rest, deep)), skip_trivia(rest.as_bytes()), resync(rest, deep))
+            } else {
+                match TurtleParser::parse(Rule::statementChunk, rest) {
+                    Ok(parsed) => {
+                        let mut input = parsed.flatten().peekable();
+                        let (start, end) = match input.peek() {
+                            Some(pair) if pair.as_rule() == Rule::statement => {
+                                (pair.as_span().start(), pair.as_span().end())
+                            }
+                            // Only whitespace and comments are left.
+                            _ => break,
+                        };
+
+                        let context = mem::take(&mut self.context);
+                        let mut graph = Graph::from_input(Some(input), rest, context);
+                        let result = graph.parse_statement();
+
+                        self.context = graph.context;
+
+                        match result {
+                            Ok(()) => {
+                                triples.extend(graph.pending.into_iter().map(|(triple, _)| checked(triple.into_owned())));
+                                (None, start, end)
+                            }
+                            Err(error) => (Some(error), start, end),
+                        }
+                    }
+
+                    Err(error) => {
+                        let failed = match error.location {
+                            pest::error::InputLocation::Pos(offset) |
+                            pest::error::InputLocation::Span((_, offset)) => offset,
+                        };
+
+                        (Some(Error::from_pest(error)), skip_trivia(rest.as_bytes()), resync(rest, failed))
+                    }
+                }
+            };
+
+            if let Some(error) = error {
+                diagnostics.push(Diagnostic {
+                    error: error.relative_to(position),
+                    start: position.after(&rest[..start]),
+                    end: position.after(&rest[..end]),
+                });
+            }
+
+            position = position.after(&rest[..end]);
+            offset += end;
+        }
+
+        (triples, diagnostics)
+    }
+}
+
+/// The offset of the first byte in `bytes` that isn't whitespace or part of
+/// a comment.
+pub(crate) fn skip_trivia(bytes: &[u8]) -> usize {
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+            b'#' => i = line_end(bytes, i),
+            _ => break,
+        }
+    }
+
+    i
+}
+
+/// The offset just after the first `.` at or after `from` that can end a
+/// statement, or the end of `text` if there isn't one.
+fn resync(text: &str, from: usize) -> usize {
+    statement_end(text.as_bytes(), from).unwrap_or(text.len())
+}
+
+/// The offset just after the first `.` at or after `from` that can end a
+/// statement. IRIs, strings and comments are skipped over so the dots in
+/// them aren't mistaken for the end of a statement, as are dots that
+/// continue a name or number.
+pub(crate) fn statement_end(bytes: &[u8], from: usize) -> Option<usize> {
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => i = line_end(bytes, i),
+            b'\\' => i += 2,
+            b'<' if bytes.get(i + 1) == Some(&b'<') => i += 2,
+            b'<' => i = iri_end(bytes, i),
+            quote @ b'"' | quote @ b'\'' => i = string_end(bytes, i, quote),
+            // A run of dots can be inside a name.
+            b'.' if i >= from && !dots_end(bytes, i).is_some_and(|end| continues_name(bytes[end])) => {
+                return Some(i + 1)
+            }
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// The offset of the first byte after the run of dots starting at `i`, if
+/// there's one.
+pub(crate) fn dots_end(bytes: &[u8], i: usize) -> Option<usize> {
+    bytes[i..].iter().position(|&b| b != b'.').map(|length| i + length)
+}
+
+/// The offset of the `\n` ending the line that `i` is on.
+pub(crate) fn line_end(bytes: &[u8], i: usize) -> usize {
+    bytes[i..].iter().position(|&b| b == b'\n').map_or(bytes.len(), |end| i + end)
+}
+
+/// The offset just after the IRI starting at `i`. An IRI that's missing its
+/// closing `>` ends at the first whitespace.
+pub(crate) fn iri_end(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+
+    while i < bytes.len() && !b"> \t\r\n".contains(&bytes[i]) {
+        i += 1;
+    }
+
+    if bytes.get(i) == Some(&b'>') {
+        i += 1;
+    }
+
+    i
+}
+
+/// The offset just after the string starting at `i`. A short string that's
+/// missing its closing quote ends at the end of its line.
+pub(crate) fn string_end(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    let long = bytes[i..].starts_with(&[quote; 3]);
+    i += if long { 3 } else { 1 };
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' if !long => return i,
+            b if b == quote && (!long || bytes[i..].starts_with(&[quote; 3])) => {
+                return i + if long { 3 } else { 1 }
+            }
+            _ => i += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+/// Whether `byte` can follow a `.` inside a prefixed name or a number.
+pub(crate) fn continues_name(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte >= 0x80 || b"_-:%\\".contains(&byte)
+}
@@ -21,7 +21,7 @@
 //!
 //! fn main() {
 //!     let graph = Graph::new(TURTLE).unwrap_or_else(|e| panic!("{}", e));
-//!     let triples = graph.parse();
+//!     let triples = graph.parse().unwrap_or_else(|e| panic!("{}", e));
 //!
 //!     println!("{}", triples);
 //! }
@@ -35,76 +35,308 @@ extern crate pest;
 extern crate url;
 extern crate itertools;
 extern crate petgraph;
+extern crate serde_json;
+extern crate xml;
 
 #[macro_use] mod macros;
+mod fast;
 mod parser;
+pub mod borrowed;
+pub mod cst;
+pub mod document;
+pub mod error;
+pub mod handler;
 pub mod iri;
+pub mod jsonld;
+pub mod labeller;
+pub mod lenient;
+pub mod limits;
 pub mod literal;
+pub mod located;
+pub mod n3;
+pub mod nquads;
+pub mod ntriples;
 pub mod object;
+pub mod options;
+pub mod parallel;
+pub mod quad;
+pub mod rdfxml;
+pub mod reader;
 pub mod subject;
+pub mod trig;
 pub mod triple;
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::iter::Peekable;
 use std::fmt;
+use std::mem;
+use std::ops::Range;
 
 use pest::Parser;
-use pest::iterators::FlatPairs;
-
-use literal::Literal;
-use object::Object;
+use pest::iterators::{FlatPairs, Pair};
+
+use borrowed::{LiteralRef, ObjectRef, SubjectRef};
+use fast::Cursor;
+use iri::has_scheme;
+use labeller::BlankNodes;
+use limits::Limit;
+use literal::{is_well_formed_language_tag, is_well_typed};
+use located::LineIndex;
 use parser::{Rule, TurtleParser};
-use subject::Subject;
 
-pub use iri::{BlankNode, Iri};
+pub use borrowed::{IriRef, TripleRef};
+pub use cst::SyntaxTree;
+pub use document::Document;
+pub use error::{Diagnostic, Error, Position};
+pub use handler::TurtleHandler;
+pub use iri::{BlankNode, Iri, RelativeIri};
+pub use jsonld::{DirectoryLoader, DocumentLoader, JsonLdReader, JsonLdWriter};
+pub use labeller::BlankNodeLabeller;
+pub use lenient::LenientReader;
+pub use limits::Limits;
+pub use located::{Located, Span};
+pub use n3::{N3Document, N3Reader};
+pub use nquads::NQuadsReader;
+pub use ntriples::NTriplesReader;
+pub use options::{ParserOptions, Policy};
+pub use parallel::{ParallelReader, TripleSink};
+pub use quad::{Dataset, GraphName, Quad};
+pub use rdfxml::{RdfXmlReader, RdfXmlWriter};
+pub use reader::TurtleReader;
+pub use trig::TriGWriter;
 pub use triple::{Triple, Triples, TripleSearcher};
 
 #[cfg(debug_assertions)]
-const _GRAMMAR: &'static str = include_str!("grammar.pest");
-const TYPE_PREDICATE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
-
-macro_rules! get {
-    ($this:ident: $rule:expr) => {{
-        use Rule::*;
-        let next = $this.input.next()?;
-        // println!("{:?}", next.as_rule());
-        assert_eq!(next.as_rule(), $rule);
-        next
-    }}
+const _GRAMMAR: &str = include_str!("grammar.pest");
+#[cfg(debug_assertions)]
+const _N3_GRAMMAR: &str = include_str!("n3.pest");
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+/// What relative IRIs are resolved against when there's no base and they're
+/// allowed anyway.
+const RELATIVE_BASE: &str = "file:///";
+
+/// Converts a term that was parsed without borrowing, whose IRIs were all
+/// checked as they were parsed.
+fn checked<T>(owned: Result<T, url::ParseError>) -> T {
+    owned.expect("IRIs are checked as they're parsed unless borrowing")
 }
 
-/// Graph parser.
-pub struct Graph<'a> {
-    input: Peekable<FlatPairs<'a, Rule>>,
+/// State that carries over from one statement to the next.
+#[derive(Debug, Default)]
+struct Context {
     base: Option<Iri>,
-    blank_node_counter: usize,
+    blank_nodes: BlankNodes,
     prefixs: HashMap<String, Iri>,
-    subject: Option<Subject>,
-    predicate: Option<Iri>,
-    subject_stack: Vec<Subject>,
-    predicate_stack: Vec<Iri>,
-    triples: Triples,
-    _source: &'a str
+    /// The prefixes declared in the document, to find duplicates.
+    declared: HashSet<String>,
+    options: ParserOptions,
+    /// The number of triples emitted so far, to check against the limits.
+    triples: usize,
+    /// The errors that were accepted because of the options.
+    warnings: Vec<Error>,
+}
+
+impl Context {
+    fn new(options: ParserOptions) -> Self {
+        Context { base: options.base.clone(), options, ..Context::default() }
+    }
+}
+
+/// A nested structure that's partly parsed. These are kept on a stack
+/// rather than parsed recursively, so how deeply structures can be nested
+/// is bounded by `Limits::max_depth` rather than the size of the stack.
+#[derive(Debug)]
+enum Frame {
+    /// The rest of a predicate object list ending at `end`.
+    Predicates { end: usize },
+    /// The rest of an object list ending at `end`.
+    Objects { end: usize },
+    /// An object at `span` that's emitted once its value has been parsed.
+    Object { span: Range<usize> },
+    /// A collection at `span`, whose last node so far is `node`.
+    Collection { head: BlankNode, node: Option<BlankNode>, span: Range<usize> },
+    /// The predicates of the `[ ... ]` for `node`.
+    PropertyList { node: BlankNode },
+    /// The predicates of an annotation block.
+    Annotation,
+}
+
+/// Graph parser.
+pub struct Graph<'a> {
+    /// The pairs of the statement pest has parsed, or `None` when the source
+    /// is read by the hand-written parser in `fast` instead.
+    input: Option<Peekable<FlatPairs<'a, Rule>>>,
+    /// The rule each statement is parsed with when pest parses the source
+    /// one statement at a time, see `parse_chunk`.
+    chunk: Option<Rule>,
+    /// How far the source has been parsed one statement at a time, and
+    /// where that is.
+    offset: usize,
+    start: Position,
+    /// Where the hand-written parser is in the source.
+    cursor: Cursor,
+    context: Context,
+    graph_name: Option<GraphName>,
+    subject: Option<SubjectRef<'a>>,
+    predicate: Option<IriRef<'a>>,
+    /// Where the current subject and predicate are in the source.
+    subject_span: Range<usize>,
+    predicate_span: Range<usize>,
+    subject_stack: Vec<(Option<SubjectRef<'a>>, Range<usize>)>,
+    predicate_stack: Vec<(Option<IriRef<'a>>, Range<usize>)>,
+    pending: VecDeque<(TripleRef<'a>, Option<GraphName>)>,
+    /// The spans of the subject, predicate and object of each pending quad,
+    /// only kept when `locate` is set.
+    pending_spans: VecDeque<[Range<usize>; 3]>,
+    locate: bool,
+    /// Whether terms borrow from the source without their IRIs being
+    /// parsed, for `triple_refs`.
+    borrow: bool,
+    /// The prefix and base declarations in the order they were parsed.
+    declared_prefixs: Vec<(String, Iri)>,
+    declared_bases: Vec<Iri>,
+    source: &'a str
 }
 
 impl<'a> Graph<'a> {
-    /// Creates a new `Graph` from the turtle source.
-    pub fn new(_source: &'a str) -> Result<Self, pest::error::Error<Rule>> {
-        let parsed = TurtleParser::parse(Rule::turtleDoc, _source)?;
-        let input = parsed.flatten().peekable();
+    /// Creates a new `Graph` from the turtle source. The source is parsed
+    /// one statement at a time as the graph is read, so syntax errors are
+    /// returned from `parse` and the iterators rather than from here.
+    ///
+    /// Turtle-star's quoted triples can be used as subjects and objects, and
+    /// an annotation block after an object adds triples about the triple
+    /// that was just asserted.
+    ///
+    /// ```
+    /// use chelone::Graph;
+    /// use chelone::subject::Subject;
+    ///
+    /// let triples = Graph::new(r#"
+    ///     @prefix : <http://example.org/> .
+    ///     :s :p :o {| :source :x |} .
+    /// "#).unwrap().parse().unwrap();
+    ///
+    /// assert_eq!(triples.len(), 2);
+    /// assert_eq!(triples[1].subject, Subject::from(triples[0].clone()));
+    /// ```
+    pub fn new(source: &'a str) -> Result<Self, Error> {
+        Self::with_options(source, ParserOptions::default())
+    }
+
+    /// Creates a new `Graph` from the turtle source, which has to be within
+    /// `limits`. See `Limits`.
+    pub fn with_limits(source: &'a str, limits: Limits) -> Result<Self, Error> {
+        Self::with_options(source, ParserOptions::new().limits(limits))
+    }
+
+    /// Creates a new `Graph` from the turtle source, parsed with `options`.
+    /// See `ParserOptions`.
+    pub fn with_options(source: &'a str, options: ParserOptions) -> Result<Self, Error> {
+        let source = source.trim_start_matches(BYTE_ORDER_MARK);
+        options.limits.check_depth(source)?;
+
+        Ok(Self::from_chunks(Rule::statementChunk, source, Context::new(options)))
+    }
+
+    /// Creates a new `Graph` from the turtle source, which is read by a
+    /// hand-written parser rather than pest. It produces the same triples as
+    /// `Graph::new` in a single pass over the source, so it's faster on
+    /// large documents, though its syntax errors are worded differently. As
+    /// it stops at the first error in the source, it can report an error
+    /// before the syntax error that `Graph::new` would report.
+    ///
+    /// ```
+    /// use chelone::Graph;
+    ///
+    /// let source = "@prefix : <http://example.org/> .\n:a :b ( 1 [ :c :d ] ) .";
+    /// let mut triples = Graph::fast(source).unwrap().parse().unwrap();
+    ///
+    /// assert!(triples.is_isomorphic(&mut Graph::new(source).unwrap().parse().unwrap()));
+    /// ```
+    pub fn fast(source: &'a str) -> Result<Self, Error> {
+        Self::fast_with_options(source, ParserOptions::default())
+    }
 
-        Ok(Graph {
+    /// Creates a new `Graph` from the turtle source, which is read by the
+    /// hand-written parser with `options`. See `Graph::fast` and
+    /// `ParserOptions`.
+    pub fn fast_with_options(source: &'a str, options: ParserOptions) -> Result<Self, Error> {
+        let source = source.trim_start_matches(BYTE_ORDER_MARK);
+
+        Ok(Self::from_input(None, source, Context::new(options)))
+    }
+
+    /// Creates a new `Graph` from TriG source, which is Turtle where triples
+    /// can be grouped into named graphs with `GRAPH <g> { ... }` blocks.
+    ///
+    /// ```
+    /// use chelone::Graph;
+    ///
+    /// let graph = Graph::trig(r#"
+    ///     @prefix : <http://example.org/> .
+    ///     :a :b :c .
+    ///     GRAPH :g { :d :e :f, :g }
+    /// "#).unwrap();
+    /// let dataset = graph.parse_dataset().unwrap();
+    ///
+    /// assert_eq!(dataset.default_graph.len(), 1);
+    /// assert_eq!(dataset.named_graphs.len(), 1);
+    /// ```
+    pub fn trig(source: &'a str) -> Result<Self, Error> {
+        Self::trig_with_options(source, ParserOptions::default())
+    }
+
+    /// Creates a new `Graph` from TriG source, which has to be within
+    /// `limits`. See `Limits`.
+    pub fn trig_with_limits(source: &'a str, limits: Limits) -> Result<Self, Error> {
+        Self::trig_with_options(source, ParserOptions::new().limits(limits))
+    }
+
+    /// Creates a new `Graph` from TriG source, parsed with `options`. See
+    /// `ParserOptions`.
+    pub fn trig_with_options(source: &'a str, options: ParserOptions) -> Result<Self, Error> {
+        let source = source.trim_start_matches(BYTE_ORDER_MARK);
+        options.limits.check_depth(source)?;
+
+        Ok(Self::from_chunks(Rule::trigChunk, source, Context::new(options)))
+    }
+
+    /// A `Graph` that has pest parse each statement with `rule` when it's
+    /// reached, rather than the whole source up front.
+    fn from_chunks(rule: Rule, source: &'a str, context: Context) -> Self {
+        let mut graph = Self::from_input(None, source, context);
+        graph.chunk = Some(rule);
+        graph
+    }
+
+    fn from_input(input: Option<Peekable<FlatPairs<'a, Rule>>>,
+                  source: &'a str,
+                  context: Context)
+        -> Self
+    {
+        Graph {
             input,
-            base: Option::default(),
-            blank_node_counter: usize::default(),
-            prefixs: HashMap::default(),
+            chunk: None,
+            offset: 0,
+            start: Position::new(1, 1),
+            cursor: Cursor::default(),
+            context,
+            graph_name: Option::default(),
             subject: Option::default(),
             predicate: Option::default(),
+            subject_span: Range::default(),
+            predicate_span: Range::default(),
             subject_stack: Vec::default(),
             predicate_stack: Vec::default(),
-            triples: Triples::default(),
-            _source
-        })
+            pending: VecDeque::default(),
+            pending_spans: VecDeque::default(),
+            locate: false,
+            borrow: false,
+            declared_prefixs: Vec::default(),
+            declared_bases: Vec::default(),
+            source
+        }
     }
 
     fn _debug_input(input: Peekable<FlatPairs<'a, Rule>>) {
@@ -115,309 +347,843 @@ impl<'a> Graph<'a> {
 
     /// Sets the initial base url to resolve relative urls against.
     pub fn set_base(&mut self, iri: Iri) {
-        self.base = Some(iri)
+        self.context.base = Some(iri)
     }
 
-    /// Parse graph into a set of Triples.
-    pub fn parse(mut self) -> Triples {
-        self.take();
+    /// Sets how blank nodes are labelled, which is a `CounterLabeller` with a
+    /// prefix of its own by default. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.context.blank_nodes.set_labeller(labeller)
+    }
 
-        while let Some(_) = self.input.peek() {
-            if self.input.peek().map(|x| x.as_rule() == Rule::EOI).unwrap() ||
-               self.parse_statement().is_none()
-            {
-                break
+    /// Adds prefixes, without their trailing `:`, that can be used without
+    /// being declared, so fragments of a larger document can be parsed.
+    /// Declarations in the document take precedence.
+    ///
+    /// ```
+    /// use chelone::{Graph, Iri};
+    ///
+    /// let mut graph = Graph::new("ex:a ex:b ex:c .").unwrap();
+    /// graph.set_prefixs(vec![(String::from("ex"), Iri::parse("http://example.org/").unwrap())]);
+    ///
+    /// assert_eq!(graph.parse().unwrap().len(), 1);
+    /// ```
+    pub fn set_prefixs<I>(&mut self, prefixs: I)
+        where I: IntoIterator<Item = (String, Iri)>
+    {
+        self.context.prefixs.extend(prefixs)
+    }
+
+    /// The prefixes declared so far, without their trailing `:`.
+    pub fn prefixs(&self) -> &HashMap<String, Iri> {
+        &self.context.prefixs
+    }
+
+    /// Parse graph into a set of Triples. The triples of every graph in a
+    /// TriG document are included.
+    pub fn parse(self) -> Result<Triples, Error> {
+        self.triples().collect()
+    }
+
+    /// Parse graph into a `Document`, keeping the prefix and base
+    /// declarations along with the triples.
+    pub fn parse_document(self) -> Result<Document, Error> {
+        let mut quads = self.quads();
+        let triples = quads.by_ref()
+            .map(|quad| quad.map(Quad::into_triple))
+            .collect::<Result<Triples, Error>>()?;
+        let graph = quads.graph;
+
+        Ok(Document {
+            prefixs: graph.declared_prefixs,
+            bases: graph.declared_bases,
+            triples,
+            warnings: graph.context.warnings,
+        })
+    }
+
+    /// Parse graph into a dataset, keeping the graph each triple is in.
+    pub fn parse_dataset(self) -> Result<Dataset, Error> {
+        self.quads().collect()
+    }
+
+    /// Returns an iterator that parses one statement at a time and yields
+    /// each triple as soon as it's produced, so the triples never have to
+    /// be held in memory all at once. The iterator stops after the first
+    /// error, and no triples are yielded from the statement that failed.
+    ///
+    /// ```
+    /// use chelone::Graph;
+    ///
+    /// let graph = Graph::new(r#"
+    ///     @prefix : <http://example.org/> .
+    ///     :a :b :c .
+    ///     :d :e :f, ex:g .
+    /// "#).unwrap();
+    /// let mut triples = graph.triples();
+    ///
+    /// assert!(triples.next().unwrap().is_ok());
+    /// assert!(triples.next().unwrap().is_err());
+    /// assert!(triples.next().is_none());
+    /// ```
+    pub fn triples(self) -> TripleIter<'a> {
+        TripleIter { quads: self.quads() }
+    }
+
+    /// Like `triples`, but each triple comes with where its subject,
+    /// predicate and object are in the source.
+    ///
+    /// ```
+    /// use chelone::{Graph, Position};
+    ///
+    /// let graph = Graph::new("@prefix : <http://example.org/> .\n:a :b :c, :d .").unwrap();
+    /// let triples = graph.located_triples().collect::<Result<Vec<_>, _>>().unwrap();
+    ///
+    /// assert_eq!(triples[1].object.bytes, 44..46);
+    /// assert_eq!(triples[1].object.start, Position::new(2, 11));
+    /// assert_eq!(triples[1].subject.start, Position::new(2, 1));
+    /// ```
+    pub fn located_triples(mut self) -> LocatedTriples<'a> {
+        self.locate = true;
+
+        LocatedTriples {
+            lines: LineIndex::new(self.source),
+            quads: self.quads(),
+        }
+    }
+
+    /// Like `triples`, but yields `TripleRef`s whose terms borrow from the
+    /// source instead of being copied, for loading large documents. IRIs
+    /// that are written in full aren't parsed until they're converted with
+    /// `TripleRef::to_owned`, which fails if one turns out to be malformed.
+    pub fn triple_refs(mut self) -> TripleRefIter<'a> {
+        self.borrow = true;
+
+        TripleRefIter { quads: self.quads() }
+    }
+
+    /// Like `triples`, but yields quads so the graph each triple is in is
+    /// kept.
+    pub fn quads(self) -> QuadIter<'a> {
+        QuadIter { graph: self, finished: false }
+    }
+
+    /// Parses the next statement, returning `false` once the end of the
+    /// document has been reached.
+    fn parse_next_statement(&mut self) -> Result<bool, Error> {
+        let result = if let Some(rule) = self.chunk {
+            match self.parse_chunk(rule) {
+                Ok(false) => return Ok(false),
+                result => result.map(|_| ()),
+            }
+        } else if self.input.is_none() {
+            if self.at_end() {
+                return Ok(false)
+            }
+
+            self.read_statement()
+        } else {
+            match self.peek_rule() {
+                None | Some(Rule::EOI) => return Ok(false),
+                Some(Rule::statement) => self.parse_statement(),
+                Some(Rule::directive) => self.parse_directive(),
+                Some(Rule::block) => self.parse_block(),
+                _ => Err(self.unexpected_peek()),
             }
+        };
+
+        if let Err(error) = result {
+            self.pending.clear();
+            self.pending_spans.clear();
+            return Err(error)
         }
 
-        self.triples
+        Ok(true)
     }
 
-    fn parse_statement(&mut self) -> Option<()> {
+    /// Has pest parse the statement, or TriG directive or block, at the
+    /// start of the rest of the source with `rule`, and then parses it with
+    /// a `Graph` of its own. Returns `false` once only whitespace and
+    /// comments are left.
+    fn parse_chunk(&mut self, rule: Rule) -> Result<bool, Error> {
+        let source = self.source;
+        let rest = &source[self.offset..];
+        let start = self.start;
+
+        let parsed = TurtleParser::parse(rule, rest)
+            .map_err(|error| Error::from_pest(error).relative_to(start))?;
+        let input = parsed.flatten().peekable();
+        let end = match input.clone().next() {
+            Some(ref pair) if pair.as_rule() != Rule::EOI => pair.as_span().end(),
+            _ => return Ok(false),
+        };
+
+        let context = mem::take(&mut self.context);
+        let warnings = context.warnings.len();
+        let mut chunk = Graph::from_input(Some(input), rest, context);
+        chunk.locate = self.locate;
+        chunk.borrow = self.borrow;
+        let result = chunk.parse_next_statement();
+
+        self.context = chunk.context;
+        let moved = self.context.warnings.split_off(warnings);
+        self.context.warnings.extend(moved.into_iter().map(|warning| warning.relative_to(start)));
+        result.map_err(|error| error.relative_to(start))?;
+
+        let offset = self.offset;
+        self.pending.extend(chunk.pending);
+        self.pending_spans.extend(chunk.pending_spans.into_iter().map(|spans| {
+            spans.map(|span| span.start + offset..span.end + offset)
+        }));
+        self.declared_prefixs.extend(chunk.declared_prefixs);
+        self.declared_bases.extend(chunk.declared_bases);
+        self.start = start.after(&rest[..end]);
+        self.offset += end;
+
+        Ok(true)
+    }
+
+    fn parse_statement(&mut self) -> Result<(), Error> {
         get!(self: statement);
-        let rule = self.input.peek()?.as_rule();
-        let text = self.input.peek()?.as_str();
 
-        match rule {
-            Rule::directive => self.parse_directive(),
-            Rule::triples => self.parse_triples(),
-            _ => self.unreachable(rule, text),
+        match self.peek_rule() {
+            Some(Rule::directive) => self.parse_directive(),
+            Some(Rule::triples) => self.parse_triples(),
+            _ => Err(self.unexpected_peek()),
         }
     }
 
-    fn parse_directive(&mut self) -> Option<()> {
+    fn parse_directive(&mut self) -> Result<(), Error> {
         get!(self: directive);
 
-        let pair = self.input.next()?;
-        let rule = pair.as_rule();
+        let pair = self.next()?;
 
-        match rule {
+        match pair.as_rule() {
             Rule::prefixID | Rule::sparqlPrefix => {
-                let key = self.input.next()?.as_str().replace(':', "");
+                let pair = self.next()?;
+                let key = pair.as_str().replace(':', "");
                 let value = self.parse_iriref()?;
-                self.prefixs.insert(key, value);
+
+                self.declare_prefix(key, value, pair.as_span().start())?;
             }
 
             Rule::base | Rule::sparqlBase => {
-                self.base = Some(self.parse_iriref()?);
+                let base = self.parse_iriref()?;
+                self.declare_base(base);
             },
 
-            _ => unreachable!(),
+            _ => return Err(self.unexpected(&pair)),
         }
 
-        Some(())
+        Ok(())
     }
 
-    fn parse_triples(&mut self) -> Option<()> {
+    /// Declares `key` as a prefix for `value`, where `offset` is the start
+    /// of the prefix in the source.
+    fn declare_prefix(&mut self, key: String, value: Iri, offset: usize) -> Result<(), Error> {
+        let limit = self.context.options.limits.max_prefixes;
+
+        if !self.context.prefixs.contains_key(&key) && self.context.prefixs.len() >= limit {
+            return Err(Error::LimitExceeded {
+                limit: Limit::Prefixes,
+                max: limit,
+                position: self.position(offset),
+            })
+        }
+
+        if !self.context.declared.insert(key.clone()) {
+            let error = Error::DuplicatePrefix { prefix: key.clone(), position: self.position(offset) };
+            self.tolerate(self.context.options.duplicate_prefixes, error)?;
+        }
+
+        self.declared_prefixs.push((key.clone(), value.clone()));
+        self.context.prefixs.insert(key, value);
+
+        Ok(())
+    }
+
+    fn declare_base(&mut self, base: Iri) {
+        self.declared_bases.push(base.clone());
+        self.context.base = Some(base);
+    }
+
+    fn parse_block(&mut self) -> Result<(), Error> {
+        get!(self: block);
+
+        let pair = self.next()?;
+
+        match pair.as_rule() {
+            Rule::namedGraph => {
+                get!(self: GRAPH);
+                let name = self.parse_label_or_subject(true)?;
+                self.parse_wrapped_graph(Some(name))?;
+            }
+
+            Rule::triplesOrGraph => {
+                let span = self.peek_span();
+                let graph = pair.into_inner().nth(1)
+                    .is_some_and(|next| next.as_rule() == Rule::wrappedGraph);
+                let label = self.parse_label_or_subject(graph)?;
+
+                match self.peek_rule() {
+                    Some(Rule::wrappedGraph) => self.parse_wrapped_graph(Some(label))?,
+                    Some(Rule::predicateObjectList) => {
+                        self.subject = Some(label);
+                        self.subject_span = span;
+                        self.parse_predicate_object_list()?;
+                    }
+                    _ => return Err(self.unexpected_peek()),
+                }
+            }
+
+            Rule::wrappedGraph => self.parse_graph_contents(pair.as_span().end())?,
+
+            Rule::triples2 => {
+                self.subject_span = self.peek_span();
+
+                match self.peek_rule() {
+                    Some(Rule::blankNodePropertyList) | Some(Rule::collection) => {
+                        self.subject = Some(self.parse_node()?);
+                    }
+                    Some(Rule::quotedTriple) => {
+                        self.subject = Some(SubjectRef::from(self.parse_quoted_triple()?));
+                    }
+                    _ => return Err(self.unexpected_peek()),
+                }
+
+                if self.peek_rule() == Some(Rule::predicateObjectList) {
+                    self.parse_predicate_object_list()?;
+                }
+            }
+
+            _ => return Err(self.unexpected(&pair)),
+        }
+
+        Ok(())
+    }
+
+    /// Parses a graph's name, or a subject if `graph` isn't set. Graph names
+    /// are never kept as relative IRIs.
+    fn parse_label_or_subject(&mut self, graph: bool) -> Result<SubjectRef<'a>, Error> {
+        get!(self: labelOrSubject);
+
+        match self.peek_rule() {
+            Some(Rule::iri) if graph => Ok(SubjectRef::Iri(IriRef::from(self.parse_owned_iri()?))),
+            Some(Rule::iri) => Ok(self.parse_iri_term()?.into_subject()),
+            Some(Rule::BlankNode) => Ok(SubjectRef::BlankNode(self.parse_blank_node()?)),
+            _ => Err(self.unexpected_peek()),
+        }
+    }
+
+    fn parse_wrapped_graph(&mut self, name: Option<SubjectRef<'a>>) -> Result<(), Error> {
+        let end = get!(self: wrappedGraph).as_span().end();
+
+        self.graph_name = name.and_then(|name| GraphName::from_subject(checked(name.into_owned())));
+
+        let result = self.parse_graph_contents(end);
+        self.graph_name = None;
+
+        result
+    }
+
+    fn parse_graph_contents(&mut self, end: usize) -> Result<(), Error> {
+        while self.belongs_to_list(Rule::triples, end) {
+            self.parse_triples()?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_triples(&mut self) -> Result<(), Error> {
         get!(self: triples);
 
-        match self.input.peek()?.as_rule() {
-            Rule::subject => {
+        match self.peek_rule() {
+            Some(Rule::subject) => {
                 self.parse_subject()?;
                 self.parse_predicate_object_list()?;
             },
 
-            Rule::blankNodePropertyList => {
-                let node = self.parse_blank_node_property_list()?;
+            Some(Rule::blankNodePropertyList) => {
+                let span = self.peek_span();
 
-                self.subject = Some(Subject::BlankNode(node));
-                if self.input.peek()?.as_rule() == Rule::predicateObjectList {
+                self.subject = Some(self.parse_node()?);
+                self.subject_span = span;
+                if self.peek_rule() == Some(Rule::predicateObjectList) {
                     self.parse_predicate_object_list()?;
                 }
             }
 
-            _ => unreachable!(),
+            _ => return Err(self.unexpected_peek()),
         }
-        Some(())
+
+        Ok(())
     }
 
-    fn parse_predicate_object_list(&mut self) -> Option<()> {
-        let next = get!(self: predicateObjectList);
-        let end = next.into_span().end();
+    fn parse_predicate_object_list(&mut self) -> Result<(), Error> {
+        let frame = self.predicates()?;
+        self.parse_frames(vec![frame], None)?;
 
-        while self.belongs_to_list(Rule::verb, end) {
-            self.predicate = Some(self.parse_verb()?);
-            self.parse_object_list()?;
-        }
+        Ok(())
+    }
+
+    /// Parses a collection or `[ ... ]` that's used as a subject.
+    fn parse_node(&mut self) -> Result<SubjectRef<'a>, Error> {
+        let mut frames = Vec::new();
+        let value = self.start_node(&mut frames)?;
 
-        Some(())
+        match self.parse_frames(frames, value)? {
+            Some(node) => Ok(node.into_subject()),
+            None => Err(self.unexpected_peek()),
+        }
     }
 
-    fn parse_verb(&mut self) -> Option<Iri> {
+    fn parse_verb(&mut self) -> Result<IriRef<'a>, Error> {
         let next = get!(self: verb);
         if next.as_str() == "a" {
-            Iri::parse(TYPE_PREDICATE).ok()
+            Ok(rdf_ref!("type"))
         } else {
             self.parse_iri()
         }
     }
 
-    fn parse_subject(&mut self) -> Option<()> {
-        get!(self: subject);
+    fn parse_subject(&mut self) -> Result<(), Error> {
+        let span = get!(self: subject).as_span();
 
-        let subject = match self.input.peek()?.as_rule() {
-            Rule::iri => Subject::Iri(self.parse_iri()?),
-            Rule::BlankNode => Subject::BlankNode(self.parse_blank_node()?),
-            Rule::collection => self.parse_collection()?.to_subject(),
-            _ => unreachable!(),
+        let subject = match self.peek_rule() {
+            Some(Rule::iri) => self.parse_iri_term()?.into_subject(),
+            Some(Rule::BlankNode) => SubjectRef::BlankNode(self.parse_blank_node()?),
+            Some(Rule::collection) => self.parse_node()?,
+            Some(Rule::quotedTriple) => SubjectRef::from(self.parse_quoted_triple()?),
+            _ => return Err(self.unexpected_peek()),
         };
 
         self.subject = Some(subject);
+        self.subject_span = span.start()..span.end();
 
-        Some(())
+        Ok(())
     }
 
-    fn parse_blank_node(&mut self) -> Option<BlankNode> {
+    fn parse_blank_node(&mut self) -> Result<BlankNode, Error> {
         get!(self: BlankNode);
 
-        let node = match self.input.peek()?.as_rule() {
-            Rule::BLANK_NODE_LABEL => {
+        let node = match self.peek_rule() {
+            Some(Rule::BLANK_NODE_LABEL) => {
                 get!(self: BLANK_NODE_LABEL);
-                BlankNode(String::from(self.input.next()?.as_str()))
+                let label = self.next()?;
+                self.context.blank_nodes.document(label.as_str())
             },
-            Rule::ANON => {
+            Some(Rule::ANON) => {
                 get!(self: ANON);
                 self.generate_new_blank_node()
             },
-            r => unreachable!("unexpected: {:?}", r),
+            _ => return Err(self.unexpected_peek()),
         };
 
-        Some(node)
+        Ok(node)
     }
 
-    fn parse_object_list(&mut self) -> Option<()> {
-        let end = get!(self: objectList).into_span().end();
+    /// The frame for the predicate object list that's next.
+    fn predicates(&mut self) -> Result<Frame, Error> {
+        let end = get!(self: predicateObjectList).as_span().end();
+
+        Ok(Frame::Predicates { end })
+    }
+
+    /// Parses until every frame in `frames` is finished, returning the value
+    /// of the outermost one. `value` is the value of the structure that was
+    /// parsed last, which is given to the frame on top of the stack.
+    fn parse_frames(&mut self, mut frames: Vec<Frame>, mut value: Option<ObjectRef<'a>>)
+        -> Result<Option<ObjectRef<'a>>, Error>
+    {
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Predicates { end } => {
+                    if self.belongs_to_list(Rule::verb, end) {
+                        let span = self.peek_span();
+                        self.predicate = Some(self.parse_verb()?);
+                        self.predicate_span = span;
+
+                        let objects = get!(self: objectList).as_span().end();
+                        frames.push(Frame::Predicates { end });
+                        frames.push(Frame::Objects { end: objects });
+                    }
+                }
+
+                Frame::Objects { end } => match value.take() {
+                    Some(object) if self.belongs_to_list(Rule::annotation, end) => {
+                        frames.push(Frame::Objects { end });
+                        self.start_annotation(object)?;
+                        frames.push(Frame::Annotation);
+                        frames.push(self.predicates()?);
+                    }
+
+                    _ if self.belongs_to_list(Rule::object, end) => {
+                        frames.push(Frame::Objects { end });
+                        value = self.start_object(&mut frames)?;
+                    }
+
+                    _ => {}
+                },
+
+                Frame::Object { span } => {
+                    let object = match value.take() {
+                        Some(object) => object,
+                        None => return Err(self.unexpected_peek()),
+                    };
+
+                    self.emit_triple(object.clone(), span)?;
+                    value = Some(object);
+                }
+
+                // The item that was just parsed has already been emitted, so
+                // its value is replaced.
+                Frame::Collection { head, node, span } => {
+                    match node {
+                        None => {
+                            self.subject = Some(SubjectRef::BlankNode(head.clone()));
+                            self.predicate = Some(rdf_ref!("first"));
+                            frames.push(Frame::Collection { node: Some(head.clone()), head, span });
+                            value = self.start_object(&mut frames)?;
+                        }
+
+                        Some(node) if self.belongs_to_list(Rule::object, span.end) => {
+                            let new_node = self.generate_new_blank_node();
+                            self.subject = Some(SubjectRef::BlankNode(node));
+                            self.predicate = Some(rdf_ref!("rest"));
+                            self.emit_triple(ObjectRef::BlankNode(new_node.clone()), span.clone())?;
+
+                            self.subject = Some(SubjectRef::BlankNode(new_node.clone()));
+                            self.predicate = Some(rdf_ref!("first"));
+                            frames.push(Frame::Collection { head, node: Some(new_node), span });
+                            value = self.start_object(&mut frames)?;
+                        }
+
+                        Some(node) => {
+                            self.subject = Some(SubjectRef::BlankNode(node));
+                            self.predicate = Some(rdf_ref!("rest"));
+                            self.emit_triple(ObjectRef::Iri(rdf_ref!("nil")), span)?;
+
+                            self.pop_subject();
+                            self.pop_predicate();
+                            value = Some(ObjectRef::BlankNode(head));
+                        }
+                    }
+                }
+
+                Frame::PropertyList { node } => {
+                    self.pop_subject();
+                    self.pop_predicate();
+                    value = Some(ObjectRef::BlankNode(node));
+                }
 
-        while self.belongs_to_list(Rule::object, end) {
-            self.parse_object()?;
+                Frame::Annotation => {
+                    self.pop_subject();
+                    self.pop_predicate();
+                }
+            }
         }
 
-        Some(())
+        Ok(value)
     }
 
-    fn parse_object(&mut self) -> Option<()> {
-        get!(self: object);
-
-        let object = match self.input.peek()?.as_rule() {
-            Rule::iri => Object::Iri(self.parse_iri()?),
-            Rule::literal => Object::Literal(self.parse_literal()?),
-            Rule::BlankNode => Object::BlankNode(self.parse_blank_node()?),
-            Rule::collection => self.parse_collection()?,
-            Rule::blankNodePropertyList => {
-                Object::BlankNode(self.parse_blank_node_property_list()?)
+    /// Starts parsing the next object. An object that's a single term is
+    /// emitted and returned straight away, otherwise the frames for it are
+    /// pushed and it's emitted once they're finished.
+    fn start_object(&mut self, frames: &mut Vec<Frame>) -> Result<Option<ObjectRef<'a>>, Error> {
+        let span = get!(self: object).as_span();
+        let span = span.start()..span.end();
+
+        let object = match self.peek_rule() {
+            Some(Rule::iri) => self.parse_iri_term()?,
+            Some(Rule::literal) => ObjectRef::Literal(self.parse_literal()?),
+            Some(Rule::BlankNode) => ObjectRef::BlankNode(self.parse_blank_node()?),
+            Some(Rule::quotedTriple) => ObjectRef::from(self.parse_quoted_triple()?),
+            Some(Rule::collection) | Some(Rule::blankNodePropertyList) => {
+                frames.push(Frame::Object { span });
+                return self.start_node(frames)
             }
-            _ => unreachable!(),
+            _ => return Err(self.unexpected_peek()),
         };
 
-        self.emit_triple(object);
-        Some(())
-    }
+        self.emit_triple(object.clone(), span)?;
 
-    fn parse_collection(&mut self) -> Option<Object> {
-        self.save_subject();
-        self.save_predicate();
+        Ok(Some(object))
+    }
 
-        let end = get!(self: collection).into_span().end();
-        let mut node = self.generate_new_blank_node();
+    /// Starts parsing a collection or `[ ... ]`. The triples linking a
+    /// collection together are located at the whole collection, except for
+    /// the objects of `rdf:first`. An empty collection is `rdf:nil`, which
+    /// is returned straight away.
+    fn start_node(&mut self, frames: &mut Vec<Frame>) -> Result<Option<ObjectRef<'a>>, Error> {
+        match self.peek_rule() {
+            Some(Rule::collection) => {
+                let span = get!(self: collection).as_span();
+                let span = span.start()..span.end();
+
+                if !self.belongs_to_list(Rule::object, span.end) {
+                    return Ok(Some(ObjectRef::Iri(rdf_ref!("nil"))))
+                }
 
-        if !self.belongs_to_list(Rule::object, end) {
-            self.pop_subject();
-            self.pop_predicate();
+                self.save_subject();
+                self.save_predicate();
+                self.subject_span = span.clone();
+                self.predicate_span = span.clone();
 
-            Some(Object::Iri(rdf!("nil")))
-        } else {
-            self.subject = Some(Subject::BlankNode(node.clone()));
-            self.predicate = Some(rdf!("rest"));
-            self.emit_triple(Object::Iri(rdf!("nil")));
-            self.predicate = Some(rdf!("first"));
-            self.parse_object().unwrap();
-
-            while self.belongs_to_list(Rule::object, end) {
-                let new_node = self.generate_new_blank_node();
-                self.subject = Some(Subject::BlankNode(new_node.clone()));
-                self.predicate = Some(rdf!("first"));
-                self.parse_object().unwrap();
-
-                self.predicate = Some(rdf!("rest"));
-                self.emit_triple(Object::BlankNode(node.clone())).unwrap();
-                node = new_node;
+                let head = self.generate_new_blank_node();
+                frames.push(Frame::Collection { head, node: None, span });
             }
 
-            self.pop_subject();
-            self.pop_predicate();
+            Some(Rule::blankNodePropertyList) => {
+                let span = get!(self: blankNodePropertyList).as_span();
+                let node = self.generate_new_blank_node();
+
+                self.save_subject();
+                self.subject = Some(SubjectRef::BlankNode(node.clone()));
+                self.subject_span = span.start()..span.end();
+                self.save_predicate();
 
-            Some(Object::BlankNode(node))
+                frames.push(Frame::PropertyList { node });
+                frames.push(self.predicates()?);
+            }
+
+            _ => return Err(self.unexpected_peek()),
         }
+
+        Ok(None)
     }
 
-    fn parse_blank_node_property_list(&mut self) -> Option<BlankNode> {
-        get!(self: blankNodePropertyList);
+    /// Starts parsing an annotation block, whose triples have the triple
+    /// that was just emitted with `object` as their subject.
+    fn start_annotation(&mut self, object: ObjectRef<'a>) -> Result<(), Error> {
+        let span = get!(self: annotation).as_span();
 
-        let new_node = self.generate_new_blank_node();
+        let triple = match (&self.subject, &self.predicate) {
+            (Some(subject), Some(predicate)) => TripleRef::new(subject.clone(), predicate.clone(), object),
+            _ => return Err(self.unexpected_peek()),
+        };
 
         self.save_subject();
-        self.subject = Some(Subject::BlankNode(new_node.clone()));
         self.save_predicate();
+        self.subject = Some(SubjectRef::from(triple));
+        self.subject_span = span.start()..span.end();
+
+        Ok(())
+    }
 
-        self.parse_predicate_object_list()?;
-        self.pop_subject();
-        self.pop_predicate();
+    /// Parses a quoted triple. Unlike the other terms, it doesn't assert
+    /// any triples.
+    fn parse_quoted_triple(&mut self) -> Result<TripleRef<'a>, Error> {
+        // The quoted triples that contain the one being parsed, kept on a
+        // stack like `Frame`s. Each one is waiting for either its subject,
+        // or its object after its subject and predicate.
+        let mut outer: Vec<Option<(SubjectRef<'a>, IriRef<'a>)>> = Vec::new();
+
+        'triple: loop {
+            get!(self: quotedTriple);
+            get!(self: qtSubject);
+
+            let mut subject = match self.peek_rule() {
+                Some(Rule::iri) => self.parse_iri_term()?.into_subject(),
+                Some(Rule::BlankNode) => SubjectRef::BlankNode(self.parse_blank_node()?),
+                Some(Rule::quotedTriple) => {
+                    outer.push(None);
+                    continue 'triple
+                }
+                _ => return Err(self.unexpected_peek()),
+            };
 
-        Some(new_node)
+            loop {
+                let predicate = self.parse_verb()?;
+
+                get!(self: qtObject);
+
+                let object = match self.peek_rule() {
+                    Some(Rule::iri) => self.parse_iri_term()?,
+                    Some(Rule::BlankNode) => ObjectRef::BlankNode(self.parse_blank_node()?),
+                    Some(Rule::literal) => ObjectRef::Literal(self.parse_literal()?),
+                    Some(Rule::quotedTriple) => {
+                        outer.push(Some((subject, predicate)));
+                        continue 'triple
+                    }
+                    _ => return Err(self.unexpected_peek()),
+                };
+
+                let mut triple = TripleRef::new(subject, predicate, object);
+
+                // Finish the quoted triples that this one is the object of,
+                // until one is found that it's the subject of.
+                subject = loop {
+                    match outer.pop() {
+                        None => return Ok(triple),
+                        Some(None) => break SubjectRef::from(triple),
+                        Some(Some((outer_subject, outer_predicate))) => {
+                            triple = TripleRef::new(outer_subject, outer_predicate, ObjectRef::from(triple));
+                        }
+                    }
+                };
+            }
+        }
     }
 
-    fn parse_literal(&mut self) -> Option<Literal> {
+    fn parse_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
         get!(self: literal);
 
-        match self.input.peek()?.as_rule() {
-            Rule::RDFLiteral => self.parse_rdf_literal(),
-            Rule::NumericLiteral => self.parse_numeric_literal(),
-            Rule::BooleanLiteral => self.parse_bool_literal(),
-            _ => unreachable!(),
+        match self.peek_rule() {
+            Some(Rule::RDFLiteral) => self.parse_rdf_literal(),
+            Some(Rule::NumericLiteral) => self.parse_numeric_literal(),
+            Some(Rule::BooleanLiteral) => self.parse_bool_literal(),
+            _ => Err(self.unexpected_peek()),
         }
     }
 
-    fn parse_rdf_literal(&mut self) -> Option<Literal> {
-        get!(self: RDFLiteral);
-
+    fn parse_rdf_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
+        let offset = get!(self: RDFLiteral).as_span().start();
         let value = self.parse_string()?;
-        Some(Literal::new(value, self.parse_langtag(), self.parse_datatype()))
+
+        if let Some(tag) = self.parse_langtag() {
+            return self.tagged_literal(value, tag, offset)
+        }
+
+        let datatype = self.parse_datatype()?;
+
+        self.typed_literal(value, datatype, offset)
     }
 
-    fn parse_numeric_literal(&mut self) -> Option<Literal> {
-        get!(self: NumericLiteral);
+    /// A literal with a language tag, where `offset` is the start of the
+    /// literal in the source.
+    fn tagged_literal(&mut self, value: Cow<'a, str>, tag: &'a str, offset: usize)
+        -> Result<LiteralRef<'a>, Error>
+    {
+        let literal = LiteralRef::new_tagged(value, tag);
 
-        let pair = self.input.next()?;
-        let mut value = String::from(pair.as_str());
-
-        Some(match pair.as_rule() {
-            Rule::INTEGER => Literal::new_integer(value),
-            Rule::DECIMAL => Literal::new_decimal(value),
-            Rule::DOUBLE => {
-                if self.input.peek().map(|p| p.as_rule()) == Some(Rule::EXPONENT)
-                {
-                    value.push_str(pair.as_str());
-                    self.take();
-                }
+        if literal.language_tag.as_ref().is_some_and(|language| !is_well_formed_language_tag(language)) {
+            let error = Error::InvalidLanguageTag { tag: tag.to_owned(), position: self.position(offset) };
+            self.tolerate(self.context.options.invalid_language_tags, error)?;
+        }
 
-                Literal::new_double(value)
-            }
-            _ => unreachable!(),
-        })
+        Ok(literal)
     }
 
-    fn parse_bool_literal(&mut self) -> Option<Literal> {
-        Some(Literal::new_bool(String::from(self.input.next()?.as_str())))
+    /// A literal with a datatype, or `xsd:string` without one, where
+    /// `offset` is the start of the literal in the source.
+    fn typed_literal(&mut self, value: Cow<'a, str>, datatype: Option<IriRef<'a>>, offset: usize)
+        -> Result<LiteralRef<'a>, Error>
+    {
+        let literal = LiteralRef::new(value, datatype);
+
+        if !is_well_typed(&literal.value, literal.iri.as_str()) {
+            let error = Error::IllTypedLiteral {
+                value: literal.value.clone().into_owned(),
+                datatype: literal.iri.as_str().to_owned(),
+                position: self.position(offset),
+            };
+            self.tolerate(self.context.options.ill_typed_literals, error)?;
+        }
+
+        Ok(literal)
     }
 
-    fn parse_string(&mut self) -> Option<String> {
-        get!(self: STRING);
-        // because we don't care about which quote syntax was used.
-        self.take();
+    fn parse_numeric_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
+        get!(self: NumericLiteral);
 
-        let mut string = String::new();
+        let pair = self.next()?;
+        let value = Cow::Borrowed(pair.as_str());
+
+        let datatype = match pair.as_rule() {
+            Rule::INTEGER => xsd_ref!("integer"),
+            Rule::DECIMAL => xsd_ref!("decimal"),
+            Rule::DOUBLE => xsd_ref!("double"),
+            _ => return Err(self.unexpected(&pair)),
+        };
+
+        Ok(LiteralRef::new(value, Some(datatype)))
+    }
 
-        while self.input.peek()?.as_rule().is_string_value() {
-            let value = self.input.next()?;
-            let (peek_rule, peek_str) = {
-                let peek = self.input.peek()?;
+    fn parse_bool_literal(&mut self) -> Result<LiteralRef<'a>, Error> {
+        let value = Cow::Borrowed(self.next()?.as_str());
+
+        Ok(LiteralRef::new(value, Some(xsd_ref!("boolean"))))
+    }
 
-                (peek.as_rule(), peek.as_str())
+    /// Parses a string, which is borrowed from the source unless it has
+    /// escapes.
+    fn parse_string(&mut self) -> Result<Cow<'a, str>, Error> {
+        let pair = get!(self: STRING);
+        let quoted = self.next()?;
+        let text = quoted.as_str();
+
+        if !text.contains('\\') {
+            let quote = match quoted.as_rule() {
+                Rule::STRING_LITERAL_LONG_QUOTE | Rule::STRING_LITERAL_LONG_SINGLE_QUOTE => 3,
+                _ => 1,
             };
+            let string = &text[quote..text.len() - quote];
 
-            if value.as_str() == peek_str {
-                match peek_rule {
-                    Rule::ECHAR => string.push(self.parse_echar()?),
-                    Rule::UCHAR => string.push(self.parse_uchar()?),
-                    _ => string.push_str(peek_str),
+            self.skip_until(quoted.as_span().end());
+            self.check_literal_length(string, pair.as_span().start())?;
+
+            return Ok(Cow::Borrowed(string))
+        }
+
+        let mut string = String::new();
+
+        while self.peek_rule().is_some_and(Rule::is_string_value) {
+            let value = self.next()?;
+            let start = value.as_span().start();
+            // Escapes are nested inside the string value that contains them.
+            let escape = match self.peek_pair() {
+                Some(peek) if peek.as_span().start() == start => {
+                    Some(peek.as_rule())
                 }
-            } else {
-                string.push_str(value.as_str());
+                _ => None,
+            };
+
+            match escape {
+                Some(Rule::ECHAR) => string.push(self.parse_echar()?),
+                Some(Rule::UCHAR) => string.push(self.parse_uchar()?),
+                _ => string.push_str(value.as_str()),
             }
 
+            self.check_literal_length(&string, pair.as_span().start())?;
         }
 
-        Some(string)
+        Ok(Cow::Owned(string))
     }
 
-    fn parse_langtag(&mut self) -> Option<String> {
-        if self.input.peek()?.as_rule() == Rule::LANGTAG {
-            Some(self.input.next()?.as_str().replace("@", ""))
+    fn check_literal_length(&self, string: &str, offset: usize) -> Result<(), Error> {
+        let limit = self.context.options.limits.max_literal_length;
+
+        if string.len() > limit {
+            return Err(Error::LimitExceeded {
+                limit: Limit::LiteralLength,
+                max: limit,
+                position: self.position(offset),
+            })
+        }
+
+        Ok(())
+    }
+
+    fn parse_langtag(&mut self) -> Option<&'a str> {
+        if self.peek_rule() == Some(Rule::LANGTAG) {
+            Some(&self.next().ok()?.as_str()[1..])
         } else {
             None
         }
     }
 
-    fn parse_datatype(&mut self) -> Option<Iri> {
-        if self.input.peek()?.as_rule() == Rule::iri {
-            self.parse_iri()
+    fn parse_datatype(&mut self) -> Result<Option<IriRef<'a>>, Error> {
+        if self.peek_rule() == Some(Rule::iri) {
+            self.parse_iri().map(Some)
         } else {
-            None
+            Ok(None)
         }
     }
 
-    fn parse_echar(&mut self) -> Option<char> {
-        Some(match self.input.next()?.as_str() {
+    fn parse_echar(&mut self) -> Result<char, Error> {
+        let pair = get!(self: ECHAR);
+
+        Ok(match pair.as_str() {
             "\\t" => '\t',
             "\\b" => '\u{08}',
             "\\n" => '\n',
@@ -426,156 +1192,404 @@ impl<'a> Graph<'a> {
             "\\'" => '\'',
             "\\\"" => '\"',
             "\\\\" => '\\',
-            c => unreachable!("Unexpected {:?}", c),
+            escape => return Err(Error::InvalidEscape {
+                escape: escape.to_owned(),
+                position: Position::from_pest(&pair.as_span().start_pos()),
+            }),
         })
     }
 
-    fn parse_uchar(&mut self) -> Option<char> {
-        get!(self: UCHAR);
+    fn parse_uchar(&mut self) -> Result<char, Error> {
+        let pair = get!(self: UCHAR);
         let mut hex = 0;
 
-        while self.input.peek()?.as_rule() == Rule::HEX {
-            let value = self.input.next()?;
+        while self.peek_rule() == Some(Rule::HEX) {
+            let value = self.next()?;
+            let digit = value.as_str().chars().next().and_then(|c| c.to_digit(16));
 
             hex <<= 4;
-            hex |= value.as_str().chars().next()?.to_digit(16)?;
+            hex |= match digit {
+                Some(digit) => digit,
+                None => return Err(self.unexpected(&value)),
+            };
         }
 
-        std::char::from_u32(hex)
+        std::char::from_u32(hex).ok_or_else(|| Error::InvalidEscape {
+            escape: pair.as_str().to_owned(),
+            position: Position::from_pest(&pair.as_span().start_pos()),
+        })
     }
 
-    fn parse_iri(&mut self) -> Option<Iri> {
+    fn parse_iri(&mut self) -> Result<IriRef<'a>, Error> {
         get!(self: iri);
-        let iri = match self.input.peek()?.as_rule() {
-            Rule::PrefixedName => self.parse_prefixed_name(),
-            Rule::IRIREF => self.parse_iriref(),
-            _ => unreachable!(),
-        };
 
-        iri
+        match self.peek_rule() {
+            Some(Rule::PrefixedName) => self.parse_prefixed_name(),
+            Some(Rule::IRIREF) => {
+                let (iriref, offset) = self.parse_iriref_value()?;
+                self.iri_ref(iriref, offset)
+            }
+            _ => Err(self.unexpected_peek()),
+        }
     }
 
-    fn parse_iriref(&mut self) -> Option<Iri> {
+    /// Parses an IRI that's needed as an `Iri`, which is checked even when
+    /// borrowing.
+    fn parse_owned_iri(&mut self) -> Result<Iri, Error> {
+        let borrow = mem::replace(&mut self.borrow, false);
+        let iri = self.parse_iri();
+        self.borrow = borrow;
 
-        let end = self.input.next()?.into_span().end();
-        let mut next_start = self.input.peek()?.clone().into_span().start();
-        let mut iriref = String::new();
+        Ok(checked(iri?.into_owned()))
+    }
+
+    /// Parses an IRI that's a subject or object, which is kept as a
+    /// `RelativeIri` if it's relative, there's no base, and the options say
+    /// to keep them.
+    fn parse_iri_term(&mut self) -> Result<ObjectRef<'a>, Error> {
+        if !self.context.options.keep_relative_iris || self.context.base.is_some() {
+            return self.parse_iri().map(ObjectRef::Iri)
+        }
 
-        if next_start > end {
-            return self.base.clone()
+        get!(self: iri);
+
+        if self.peek_rule() != Some(Rule::IRIREF) {
+            return self.parse_prefixed_name().map(ObjectRef::Iri)
         }
 
-        while next_start < end {
-            match self.input.peek()?.as_rule() {
-                Rule::IRI_VALUE => iriref.push_str(self.input.next()?.as_str()),
-                Rule::UCHAR => iriref.push(self.parse_uchar()?),
-                _ => unreachable!(),
+        let (iriref, offset) = self.parse_iriref_value()?;
+
+        self.iriref_term(iriref, offset)
+    }
+
+    /// The term for an IRIREF that's a subject or object, see
+    /// `parse_iri_term`.
+    fn iriref_term(&mut self, iriref: Cow<'a, str>, offset: usize) -> Result<ObjectRef<'a>, Error> {
+        if !self.context.options.keep_relative_iris || self.context.base.is_some() ||
+            has_scheme(&iriref)
+        {
+            return self.iri_ref(iriref, offset).map(ObjectRef::Iri)
+        }
+
+        self.check_iri_length(iriref.len(), offset)?;
+
+        Ok(ObjectRef::RelativeIri(RelativeIri(iriref.into_owned())))
+    }
+
+    /// The `IriRef` for an IRIREF. When borrowing, an absolute IRI is kept as
+    /// it's written, otherwise it's parsed so it's checked and resolved.
+    fn iri_ref(&mut self, iriref: Cow<'a, str>, offset: usize) -> Result<IriRef<'a>, Error> {
+        if self.borrow && has_scheme(&iriref) {
+            self.check_iri_length(iriref.len(), offset)?;
+            return Ok(IriRef::new(iriref))
+        }
+
+        self.resolve_iriref(iriref.into_owned(), offset).map(IriRef::from)
+    }
+
+    fn parse_iriref(&mut self) -> Result<Iri, Error> {
+        let (iriref, offset) = self.parse_iriref_value()?;
+
+        self.resolve_iriref(iriref.into_owned(), offset)
+    }
+
+    /// Resolves an IRIREF against the base, where `offset` is the start of
+    /// the IRIREF in the source.
+    fn resolve_iriref(&mut self, iriref: String, offset: usize) -> Result<Iri, Error> {
+        let iri = match Iri::parse_with_base_iri(&iriref, self.context.base.as_ref()) {
+            Ok(iri) => iri,
+            Err(url::ParseError::RelativeUrlWithoutBase)
+                if self.context.options.relative_iris != Policy::Reject =>
+            {
+                let base = Iri::parse(RELATIVE_BASE).expect("the relative base is an IRI");
+                let position = self.position(offset);
+                let iri = Iri::parse_with_base_iri(&iriref, Some(&base))
+                    .map_err(|_| Error::InvalidIri { iri: iriref.clone(), position })?;
+                let error = Error::InvalidIri { iri: iriref, position };
+
+                self.tolerate(self.context.options.relative_iris, error)?;
+                iri
             }
+            Err(_) => return Err(Error::InvalidIri { iri: iriref, position: self.position(offset) }),
+        };
+
+        self.check_iri_length(iri.as_str().len(), offset)?;
+
+        Ok(iri)
+    }
+
+    /// Parses an IRIREF, unescaped but not resolved. It's borrowed from the
+    /// source unless it has escapes.
+    fn parse_iriref_value(&mut self) -> Result<(Cow<'a, str>, usize), Error> {
+        let pair = get!(self: IRIREF);
+        let text = pair.as_str();
+        let end = pair.as_span().end();
+        let offset = pair.as_span().start();
+
+        if !text.contains('\\') {
+            self.skip_until(end);
+            return Ok((Cow::Borrowed(&text[1..text.len() - 1]), offset))
+        }
 
-            next_start = if let Some(peek) = self.input.peek(){
-                peek.clone().into_span().start()
-            } else {
+        let mut iriref = String::new();
+
+        while let Some(start) = self.peek_pair().map(|p| p.as_span().start()) {
+            if start >= end {
                 break
-            };
-        };
+            }
 
-        Iri::parse_with_base_iri(&iriref, self.base.as_ref()).ok()
+            match self.peek_rule() {
+                Some(Rule::IRI_VALUE) => iriref.push_str(self.next()?.as_str()),
+                Some(Rule::UCHAR) => {
+                    let c = self.parse_uchar()?;
+
+                    // Escapes can't be used to sneak in characters that are
+                    // not allowed in an IRIREF.
+                    if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+                        iriref.push(c);
+                        return Err(Error::InvalidIri { iri: iriref, position: self.position(offset) })
+                    }
+
+                    iriref.push(c);
+                }
+                _ => return Err(self.unexpected_peek()),
+            }
+        }
+
+        Ok((Cow::Owned(iriref), offset))
     }
 
-    fn parse_prefixed_name(&mut self) -> Option<Iri> {
+    fn parse_prefixed_name(&mut self) -> Result<IriRef<'a>, Error> {
         get!(self: PrefixedName);
 
-        let rule = self.input.peek()?.as_rule();
+        match self.peek_rule() {
+            Some(Rule::PNAME_LN) => self.parse_pname_ln(),
+            Some(Rule::PNAME_NS) => self.parse_pname_ns().map(IriRef::from),
+            _ => Err(self.unexpected_peek()),
+        }
+    }
+
+    fn parse_pname_ln(&mut self) -> Result<IriRef<'a>, Error> {
+        let offset = get!(self: PNAME_LN).as_span().start();
+        let namespace = self.parse_pname_ns()?;
+        // Replace should work here as the escapes are validated in pest.
+        let local = get!(self: PN_LOCAL).as_str().replace('\\', "");
+
+        self.prefixed_name(&namespace, &local, offset)
+    }
 
-        match rule {
-            Rule::PNAME_LN => self.parse_pname_ln(),
-            Rule::PNAME_NS => self.parse_pname_ns(),
-            _ => unreachable!(),
+    /// The IRI of a prefixed name whose prefix stands for `namespace`, where
+    /// `offset` is the start of the prefixed name in the source.
+    fn prefixed_name(&mut self, namespace: &Iri, local: &str, offset: usize)
+        -> Result<IriRef<'a>, Error>
+    {
+        let mut iri = namespace.as_str().to_owned();
+        iri.push_str(local);
+
+        if self.borrow {
+            self.check_iri_length(iri.len(), offset)?;
+            return Ok(IriRef::new(iri))
         }
+
+        let iri = Iri::parse(&iri)
+            .map_err(|_| Error::InvalidIri { iri, position: self.position(offset) })?;
+        self.check_iri_length(iri.as_str().len(), offset)?;
+
+        Ok(IriRef::from(iri))
     }
 
-    fn parse_pname_ln(&mut self) -> Option<Iri> {
-        get!(self: PNAME_LN);
+    fn parse_pname_ns(&mut self) -> Result<Iri, Error> {
+        let pair = get!(self: PNAME_NS);
 
-        let base = self.parse_pname_ns()?;
-        let mut base = base.as_str().to_owned();
-        // Replace should work here as the escapes are validated in pest.
-        let pn_local = get!(self: PN_LOCAL).as_str().replace('\\', "");
+        self.namespace(pair.as_str(), pair.as_span().start())
+    }
 
-        base.push_str(&pn_local);
+    /// The IRI that a prefix, written with its trailing `:`, stands for,
+    /// where `offset` is the start of the prefix in the source.
+    fn namespace(&mut self, name: &str, offset: usize) -> Result<Iri, Error> {
+        let prefix = &name[..name.len() - 1];
 
-        Iri::parse(&base).ok()
+        if let Some(iri) = self.context.prefixs.get(prefix) {
+            return Ok(iri.clone())
+        }
+
+        let position = self.position(offset);
+        let policy = self.context.options.unknown_prefixes;
+        let prefix = prefix.to_owned();
+
+        // The prefix is read as the scheme of an IRI, which the empty prefix
+        // can't be.
+        match Iri::parse(name) {
+            Ok(iri) if policy != Policy::Reject => {
+                self.tolerate(policy, Error::UndefinedPrefix { prefix, position })?;
+                Ok(iri)
+            }
+            _ => Err(Error::UndefinedPrefix { prefix, position }),
+        }
     }
 
-    fn parse_pname_ns(&mut self) -> Option<Iri> {
-        let (_next_rule, next) = {
-            let next = self.input.next()?;
+    /// Accepts `error` if `policy` allows it, keeping it as a warning if
+    /// the policy says to.
+    fn tolerate(&mut self, policy: Policy, error: Error) -> Result<(), Error> {
+        match policy {
+            Policy::Reject => Err(error),
+            Policy::Warn => {
+                self.context.warnings.push(error);
+                Ok(())
+            }
+            Policy::Allow => Ok(()),
+        }
+    }
 
-            (next.as_rule(), next.as_str())
-        };
+    fn check_iri_length(&self, length: usize, offset: usize) -> Result<(), Error> {
+        let limit = self.context.options.limits.max_iri_length;
+
+        if length > limit {
+            return Err(Error::LimitExceeded {
+                limit: Limit::IriLength,
+                max: limit,
+                position: self.position(offset),
+            })
+        }
+
+        Ok(())
+    }
+
+    /// Counts a triple that's about to be emitted, where `offset` is the
+    /// start of its object.
+    fn count_triple(&mut self, offset: usize) -> Result<(), Error> {
+        let limit = self.context.options.limits.max_triples;
+
+        if self.context.triples >= limit {
+            return Err(Error::LimitExceeded {
+                limit: Limit::Triples,
+                max: limit,
+                position: self.position(offset),
+            })
+        }
 
-        let prefix = next.replace(":", "");
-        Some(self.prefixs[&prefix].clone())
+        self.context.triples += 1;
+
+        Ok(())
     }
 
-    fn emit_triple(&mut self, object: Object) -> Option<()> {
-        let subject = self.subject.clone().expect("No Subject found");
-        let predicate = self.predicate.clone().expect("No Predicate found");
+    fn emit_triple(&mut self, object: ObjectRef<'a>, object_span: Range<usize>) -> Result<(), Error> {
+        let (subject, predicate) = match (&self.subject, &self.predicate) {
+            (Some(subject), Some(predicate)) => (subject.clone(), predicate.clone()),
+            _ => return Err(self.unexpected_peek()),
+        };
+
+        self.count_triple(object_span.start)?;
 
-        self.triples.push(Triple::new(subject, predicate, object));
+        let graph_name = self.graph_name.clone();
+        self.pending.push_back((TripleRef::new(subject, predicate, object), graph_name));
 
-        Some(())
+        if self.locate {
+            let spans = [self.subject_span.clone(), self.predicate_span.clone(), object_span];
+            self.pending_spans.push_back(spans);
+        }
+
+        Ok(())
     }
 
     fn belongs_to_list(&mut self, rule: Rule, end: usize) -> bool {
-        if let Some(peek) = self.input.peek() {
-            peek.as_rule() == rule && end > peek.clone().into_span().start()
+        if let Some(peek) = self.peek_pair() {
+            peek.as_rule() == rule && end > peek.as_span().start()
         } else {
             false
         }
     }
 
     fn save_subject(&mut self) {
-        if let Some(iri) = self.subject.take() {
-            self.subject_stack.push(iri);
-        }
+        let span = self.subject_span.clone();
+        self.subject_stack.push((self.subject.take(), span));
     }
 
     fn save_predicate(&mut self) {
-        if let Some(iri) = self.predicate.take() {
-            self.predicate_stack.push(iri);
-        }
+        let span = self.predicate_span.clone();
+        self.predicate_stack.push((self.predicate.take(), span));
     }
 
     fn pop_subject(&mut self) {
-        self.subject = self.subject_stack.pop();
+        let (subject, span) = self.subject_stack.pop().unwrap_or_default();
+        self.subject = subject;
+        self.subject_span = span;
     }
 
     fn pop_predicate(&mut self) {
-        self.predicate = self.predicate_stack.pop();
+        let (predicate, span) = self.predicate_stack.pop().unwrap_or_default();
+        self.predicate = predicate;
+        self.predicate_span = span;
+    }
+
+    /// The span of the next pair, or an empty span at the end of the
+    /// source if there isn't one.
+    fn peek_span(&mut self) -> Range<usize> {
+        match self.peek_pair() {
+            Some(pair) => pair.as_span().start()..pair.as_span().end(),
+            None => self.source.len()..self.source.len(),
+        }
     }
 
     fn generate_new_blank_node(&mut self) -> BlankNode {
-        self.blank_node_counter += 1;
-        let label = format!("b{}", self.blank_node_counter);
+        self.context.blank_nodes.generate()
+    }
 
+    fn next(&mut self) -> Result<Pair<'a, Rule>, Error> {
+        match self.input.as_mut().and_then(Iterator::next) {
+            Some(pair) => Ok(pair),
+            None => Err(self.unexpected_end()),
+        }
+    }
 
-        if self.prefixs.get(&label).is_none() {
-            BlankNode(label)
-        } else {
-            self.generate_new_blank_node()
+    fn peek_pair(&mut self) -> Option<&Pair<'a, Rule>> {
+        self.input.as_mut()?.peek()
+    }
+
+    fn peek_rule(&mut self) -> Option<Rule> {
+        self.peek_pair().map(|p| p.as_rule())
+    }
+
+    /// Skips the pairs that start before `end`, such as the characters of
+    /// a term that's read straight from the source.
+    fn skip_until(&mut self, end: usize) {
+        while self.peek_pair().is_some_and(|pair| pair.as_span().start() < end) {
+            self.take();
         }
     }
 
     fn take(&mut self) {
-        let _x = self.input.next();
+        let _x = self.input.as_mut().and_then(Iterator::next);
         //println!("{:?}", _x.map(|x| x.as_rule()));
     }
 
-    fn unreachable(&mut self, rule: Rule, text: &str) -> ! {
-        println!("Parser state:\n{:#?}", self);
+    /// The position of `offset` in the source.
+    fn position(&self, offset: usize) -> Position {
+        let position = pest::Position::new(self.source, offset)
+            .expect("offset is in the source");
+
+        Position::from_pest(&position)
+    }
+
+    fn unexpected(&self, pair: &Pair<'a, Rule>) -> Error {
+        Error::Unexpected {
+            found: format!("{:?} {:?}", pair.as_rule(), pair.as_str()),
+            position: Position::from_pest(&pair.as_span().start_pos()),
+        }
+    }
+
+    fn unexpected_peek(&mut self) -> Error {
+        match self.peek_pair().cloned() {
+            Some(pair) => self.unexpected(&pair),
+            None => self.unexpected_end(),
+        }
+    }
 
-        unreachable!("Unexpected {:?}: {:?}", rule, text)
+    fn unexpected_end(&self) -> Error {
+        Error::Unexpected {
+            found: String::from("end of input"),
+            position: self.position(self.source.len()),
+        }
     }
 }
 
@@ -583,13 +1597,164 @@ impl<'a> fmt::Debug for Graph<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Graph")
             .field("input", &"#hidden#")
-            .field("base", &self.base)
-            .field("prefixs", &self.prefixs)
+            .field("base", &self.context.base)
+            .field("prefixs", &self.context.prefixs)
+            .field("graph_name", &self.graph_name)
             .field("subject", &self.subject)
             .field("predicate", &self.predicate)
             .field("subject_stack", &self.subject_stack)
             .field("predicate_stack", &self.predicate_stack)
-            .field("triples", &self.triples)
+            .field("pending", &self.pending)
             .finish()
     }
 }
+
+/// An iterator over the triples of a `Graph`, created by `Graph::triples`.
+#[derive(Debug)]
+pub struct TripleIter<'a> {
+    quads: QuadIter<'a>,
+}
+
+impl<'a> TripleIter<'a> {
+    /// The prefixes declared in the statements parsed so far.
+    pub fn prefixs(&self) -> &HashMap<String, Iri> {
+        self.quads.prefixs()
+    }
+
+    /// The warnings from the statements parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        self.quads.warnings()
+    }
+}
+
+impl<'a> Iterator for TripleIter<'a> {
+    type Item = Result<Triple, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.quads.next().map(|quad| quad.map(Quad::into_triple))
+    }
+}
+
+/// An iterator over the triples of a `Graph` and where they are in the
+/// source, created by `Graph::located_triples`.
+#[derive(Debug)]
+pub struct LocatedTriples<'a> {
+    quads: QuadIter<'a>,
+    lines: LineIndex,
+}
+
+impl<'a> LocatedTriples<'a> {
+    /// The prefixes declared in the statements parsed so far.
+    pub fn prefixs(&self) -> &HashMap<String, Iri> {
+        self.quads.prefixs()
+    }
+
+    /// The warnings from the statements parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        self.quads.warnings()
+    }
+}
+
+impl<'a> Iterator for LocatedTriples<'a> {
+    type Item = Result<Located<Triple>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let quad = match self.quads.next()? {
+            Ok(quad) => quad,
+            Err(error) => return Some(Err(error)),
+        };
+        let graph = &mut self.quads.graph;
+        let [subject, predicate, object] = graph.pending_spans.pop_front().unwrap_or_default();
+
+        Some(Ok(Located {
+            value: quad.into_triple(),
+            subject: self.lines.span(graph.source, subject),
+            predicate: self.lines.span(graph.source, predicate),
+            object: self.lines.span(graph.source, object),
+        }))
+    }
+}
+
+/// An iterator over the quads of a `Graph`, created by `Graph::quads`.
+#[derive(Debug)]
+pub struct QuadIter<'a> {
+    graph: Graph<'a>,
+    finished: bool,
+}
+
+impl<'a> QuadIter<'a> {
+    /// The prefixes declared in the statements parsed so far.
+    pub fn prefixs(&self) -> &HashMap<String, Iri> {
+        self.graph.prefixs()
+    }
+
+    /// The warnings from the statements parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.graph.context.warnings
+    }
+}
+
+impl<'a> QuadIter<'a> {
+    /// The next triple as the walker made it, along with its graph.
+    fn next_ref(&mut self) -> Option<Result<(TripleRef<'a>, Option<GraphName>), Error>> {
+        loop {
+            if let Some(quad) = self.graph.pending.pop_front() {
+                return Some(Ok(quad))
+            }
+
+            if self.finished {
+                return None
+            }
+
+            match self.graph.parse_next_statement() {
+                Ok(true) => {}
+                Ok(false) => self.finished = true,
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error))
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for QuadIter<'a> {
+    type Item = Result<Quad, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let quad = self.next_ref()?.map(|(triple, graph_name)| {
+            let Triple { subject, predicate, object } = checked(triple.into_owned());
+
+            Quad::new(subject, predicate, object, graph_name)
+        });
+
+        Some(quad)
+    }
+}
+
+/// An iterator over the triples of a `Graph` that borrow from its source,
+/// created by `Graph::triple_refs`.
+#[derive(Debug)]
+pub struct TripleRefIter<'a> {
+    quads: QuadIter<'a>,
+}
+
+impl<'a> TripleRefIter<'a> {
+    /// The prefixes declared in the statements parsed so far.
+    pub fn prefixs(&self) -> &HashMap<String, Iri> {
+        self.quads.prefixs()
+    }
+
+    /// The warnings from the statements parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        self.quads.warnings()
+    }
+}
+
+impl<'a> Iterator for TripleRefIter<'a> {
+    type Item = Result<TripleRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.quads.next_ref().map(|quad| quad.map(|(triple, _)| triple))
+    }
+}
@@ -0,0 +1,149 @@
+//! Limits on the documents a parser will accept.
+use std::fmt;
+
+use error::{Error, Position};
+use lenient::{continues_name, iri_end, line_end, string_end};
+
+/// Limits on the shape and size of a document, so untrusted input can't use
+/// up the stack or an unbounded amount of memory. Going over a limit is an
+/// `Error::LimitExceeded`.
+///
+/// By default nesting is limited to 128 levels and everything else is
+/// unlimited.
+///
+/// ```
+/// use chelone::{Error, Graph, Limits};
+/// use chelone::limits::Limit;
+///
+/// let limits = Limits { max_depth: 2, ..Limits::default() };
+/// assert!(Graph::with_limits("<http://a/s> <http://a/p> ((1)) .", limits).is_ok());
+///
+/// match Graph::with_limits("<http://a/s> <http://a/p> (((1))) .", limits) {
+///     Err(Error::LimitExceeded { limit, .. }) => assert_eq!(limit, Limit::Depth),
+///     result => panic!("Expected a limit error, got: {:?}", result),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// How deeply collections, `[ ... ]`, quoted triples, annotations,
+    /// graphs and formulas can be nested inside one another.
+    pub max_depth: usize,
+    /// The most bytes a literal's value can have, after unescaping.
+    pub max_literal_length: usize,
+    /// The most bytes an IRI can have, after resolving it.
+    pub max_iri_length: usize,
+    /// The most triples a document can have.
+    pub max_triples: usize,
+    /// The most distinct prefixes a document can declare.
+    pub max_prefixes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_depth: 128,
+            max_literal_length: usize::MAX,
+            max_iri_length: usize::MAX,
+            max_triples: usize::MAX,
+            max_prefixes: usize::MAX,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the nesting of the whole of `text`, which has to happen before
+    /// it's given to pest as pest's recursion is only bounded by the stack.
+    pub(crate) fn check_depth(&self, text: &str) -> Result<(), Error> {
+        match self.too_deep(text, false) {
+            Some(offset) => Err(self.depth_error(text, offset)),
+            None => Ok(()),
+        }
+    }
+
+    /// The offset of the first bracket in `text` that's nested deeper than
+    /// `max_depth`. With `statement` set, only the first statement of
+    /// `text` is checked.
+    pub(crate) fn too_deep(&self, text: &str, statement: bool) -> Option<usize> {
+        let bytes = text.as_bytes();
+        let mut depth = 0usize;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let (step, width) = match bytes[i] {
+                b'#' => {
+                    i = line_end(bytes, i);
+                    continue
+                }
+                b'<' if bytes.get(i + 1) == Some(&b'<') => (1, 2),
+                b'>' if bytes.get(i + 1) == Some(&b'>') => (-1, 2),
+                // N3's `<=`, which isn't the start of an IRI.
+                b'<' if bytes.get(i + 1) == Some(&b'=') => (0, 2),
+                b'<' => {
+                    i = iri_end(bytes, i);
+                    continue
+                }
+                quote @ b'"' | quote @ b'\'' => {
+                    i = string_end(bytes, i, quote);
+                    continue
+                }
+                b'\\' => (0, 2),
+                b'(' | b'[' | b'{' => (1, 1),
+                b')' | b']' | b'}' => (-1, 1),
+                b'.' if statement && depth == 0 &&
+                    !bytes.get(i + 1).is_some_and(|&b| continues_name(b)) => return None,
+                _ => (0, 1),
+            };
+
+            if step > 0 {
+                depth += 1;
+
+                if depth > self.max_depth {
+                    return Some(i)
+                }
+            } else if step < 0 {
+                depth = depth.saturating_sub(1);
+            }
+
+            i += width;
+        }
+
+        None
+    }
+
+    /// The error for a bracket at `offset` in `text` that's nested too
+    /// deeply.
+    pub(crate) fn depth_error(&self, text: &str, offset: usize) -> Error {
+        Error::LimitExceeded {
+            limit: Limit::Depth,
+            max: self.max_depth,
+            position: Position::new(1, 1).after(&text[..offset]),
+        }
+    }
+}
+
+/// Which of the `Limits` was exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// `Limits::max_depth`.
+    Depth,
+    /// `Limits::max_literal_length`.
+    LiteralLength,
+    /// `Limits::max_iri_length`.
+    IriLength,
+    /// `Limits::max_triples`.
+    Triples,
+    /// `Limits::max_prefixes`.
+    Prefixes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Limit::Depth => "nesting depth",
+            Limit::LiteralLength => "literal length",
+            Limit::IriLength => "IRI length",
+            Limit::Triples => "number of triples",
+            Limit::Prefixes => "number of prefixes",
+        })
+    }
+}
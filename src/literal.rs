@@ -1,8 +1,41 @@
 //! A literal (String, Integer, Decimal, Double, Bool)
-use std::fmt;
+use std::fmt::{self, Write};
 
 use iri::Iri;
 
+/// The base direction of a language-tagged string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    /// Left to right, written `--ltr`.
+    Ltr,
+    /// Right to left, written `--rtl`.
+    Rtl,
+}
+
+impl Direction {
+    /// Parses `ltr` or `rtl`.
+    pub fn parse(direction: &str) -> Option<Self> {
+        match direction {
+            "ltr" => Some(Direction::Ltr),
+            "rtl" => Some(Direction::Rtl),
+            _ => None,
+        }
+    }
+
+    /// `ltr` or `rtl`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// A Literal
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -11,6 +44,9 @@ pub struct Literal {
     pub value: String,
     /// The language tag of the string.
     pub language_tag: Option<String>,
+    /// The base direction of the string, which is only set along with a
+    /// language tag.
+    pub direction: Option<Direction>,
     /// The iri type.
     pub iri: Iri,
 }
@@ -26,7 +62,7 @@ impl Literal {
     {
         let iri = if let Some(iri) = iri {
             iri
-        } else if let Some(_) = language_tag.as_ref() {
+        } else if language_tag.is_some() {
             rdf!("langString")
         } else {
             xsd!("string")
@@ -35,35 +71,250 @@ impl Literal {
         Literal {
             value,
             language_tag,
+            direction: None,
             iri,
         }
     }
 
-    pub(crate) fn new_bool(value: String) -> Self {
-        Self::new(value, None, Some(xsd!("boolean")))
+    /// Creates a language-tagged string with a base direction. Its IRI is
+    /// `http://www.w3.org/1999/02/22-rdf-syntax-ns#dirLangString`.
+    pub fn new_directional(value: String, language_tag: String, direction: Direction) -> Self {
+        Literal {
+            value,
+            language_tag: Some(language_tag),
+            direction: Some(direction),
+            iri: rdf!("dirLangString"),
+        }
     }
 
-    pub(crate) fn new_double(value: String) -> Self {
-        Self::new(value, None, Some(xsd!("double")))
+    /// Creates a literal from a tag as written after `@`, such as `en` or
+    /// `ar--rtl`.
+    pub(crate) fn new_tagged(value: String, tag: &str) -> Self {
+        match split_tag(tag) {
+            (language, Some(direction)) => Self::new_directional(value, language.to_owned(), direction),
+            (language, None) => Self::new(value, Some(language.to_owned()), None),
+        }
     }
 
-    pub(crate) fn new_decimal(value: String) -> Self {
-        Self::new(value, None, Some(xsd!("decimal")))
+    /// The language tag followed by `--` and the direction, if there is one.
+    pub(crate) fn tag(&self) -> Option<String> {
+        let tag = self.language_tag.as_ref()?;
+
+        Some(match self.direction {
+            Some(direction) => format!("{}--{}", tag, direction),
+            None => tag.clone(),
+        })
     }
 
-    pub(crate) fn new_integer(value: String) -> Self {
-        Self::new(value, None, Some(xsd!("integer")))
+    /// Writes the value as a quoted string, escaping the characters that
+    /// can't appear in one.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_string(f, &self.value)
     }
 }
 
+/// Formats the literal as it would be written in N-Triples.
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut value = format!("\"{}\"", self.value);
+        self.fmt_value(f)?;
+
+        if let Some(tag) = self.tag() {
+            write!(f, "@{}", tag)
+        } else {
+            write!(f, "^^{}", self.iri)
+        }
+    }
+}
+
+/// Splits a tag as written after `@`, such as `ar--rtl`, into its language
+/// tag and direction.
+pub(crate) fn split_tag(tag: &str) -> (&str, Option<Direction>) {
+    let direction = tag.rfind("--").and_then(|index| {
+        Direction::parse(&tag[index + 2..]).map(|direction| (index, direction))
+    });
+
+    match direction {
+        Some((index, direction)) => (&tag[..index], Some(direction)),
+        None => (tag, None),
+    }
+}
+
+/// Whether `value` is valid for the datatype `datatype`. Only the numeric
+/// and boolean XSD datatypes are checked, every other datatype is assumed to
+/// be valid.
+pub(crate) fn is_well_typed(value: &str, datatype: &str) -> bool {
+    const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+    let datatype = match datatype.strip_prefix(XSD) {
+        Some(datatype) => datatype,
+        None => return true,
+    };
+
+    let range = match datatype {
+        "boolean" => return ["true", "false", "1", "0"].contains(&value),
+        "decimal" => return is_valid_xsd_decimal(value),
+        "double" | "float" => {
+            return ["INF", "+INF", "-INF", "NaN"].contains(&value) || is_valid_xsd_double(value)
+        }
+        "integer" => (i128::MIN, i128::MAX),
+        "nonNegativeInteger" => (0, i128::MAX),
+        "positiveInteger" => (1, i128::MAX),
+        "nonPositiveInteger" => (i128::MIN, 0),
+        "negativeInteger" => (i128::MIN, -1),
+        "long" => (i64::MIN.into(), i64::MAX.into()),
+        "int" => (i32::MIN.into(), i32::MAX.into()),
+        "short" => (i16::MIN.into(), i16::MAX.into()),
+        "byte" => (i8::MIN.into(), i8::MAX.into()),
+        "unsignedLong" => (0, u64::MAX.into()),
+        "unsignedInt" => (0, u32::MAX.into()),
+        "unsignedShort" => (0, u16::MAX.into()),
+        "unsignedByte" => (0, u8::MAX.into()),
+        _ => return true,
+    };
+
+    if !is_valid_xsd_integer(value) {
+        return false
+    }
+
+    // Integers too big for an `i128` are only valid for the unbounded
+    // types, which are the ones with a bound of an `i128`'s limits.
+    match value.parse::<i128>() {
+        Ok(number) => range.0 <= number && number <= range.1,
+        Err(_) if value.starts_with('-') => range.0 == i128::MIN,
+        Err(_) => range.1 == i128::MAX,
+    }
+}
+
+/// Writes `value` as a quoted string, escaping the characters that can't
+/// appear in one.
+pub(crate) fn fmt_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+/// Whether `value` matches `[+-]?[0-9]+`, which is both the lexical space
+/// of `xsd:integer` and Turtle's `INTEGER`.
+pub(crate) fn is_valid_xsd_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(&['+', '-'][..]).unwrap_or(value);
+
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Whether `value` matches `[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)`.
+pub(crate) fn is_valid_xsd_decimal(value: &str) -> bool {
+    let unsigned = value.strip_prefix(&['+', '-'][..]).unwrap_or(value);
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(dot) => (&unsigned[..dot], &unsigned[dot + 1..]),
+        None => (unsigned, ""),
+    };
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+    !(whole.is_empty() && fraction.is_empty()) && digits(whole) && digits(fraction)
+}
+
+/// Whether `value` is a decimal, optionally followed by an exponent.
+pub(crate) fn is_valid_xsd_double(value: &str) -> bool {
+    match value.find(&['e', 'E'][..]) {
+        Some(e) => is_valid_xsd_decimal(&value[..e]) && is_valid_xsd_integer(&value[e + 1..]),
+        None => is_valid_xsd_decimal(value),
+    }
+}
+
+/// Whether `value` can be written as Turtle's `DECIMAL`, which needs digits
+/// after its `.`.
+pub(crate) fn is_turtle_decimal(value: &str) -> bool {
+    value.contains('.') && !value.ends_with('.') && is_valid_xsd_decimal(value)
+}
+
+/// Whether `value` can be written as Turtle's `DOUBLE`, which needs an
+/// exponent.
+pub(crate) fn is_turtle_double(value: &str) -> bool {
+    value.contains(&['e', 'E'][..]) && is_valid_xsd_double(value)
+}
+
+/// Whether `tag` is a well-formed BCP 47 language tag, without checking
+/// that its subtags are registered.
+pub(crate) fn is_well_formed_language_tag(tag: &str) -> bool {
+    let subtags = tag.split('-').collect::<Vec<_>>();
+    let alpha = |subtag: &str, min, max| {
+        (min..=max).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+    };
+    let alphanumeric = |subtag: &str, min, max| {
+        (min..=max).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+    };
+    let digits = |subtag: &str, length| {
+        subtag.len() == length && subtag.bytes().all(|b| b.is_ascii_digit())
+    };
+    // Private use subtags, and the irregular grandfathered tags such as
+    // `i-klingon`, are anything after `x` or `i`.
+    let private = |rest: &[&str]| {
+        !rest.is_empty() && rest.iter().all(|subtag| alphanumeric(subtag, 1, 8))
+    };
+
+    let language = subtags[0];
+
+    if language.eq_ignore_ascii_case("x") || language.eq_ignore_ascii_case("i") {
+        return private(&subtags[1..])
+    }
+
+    if !alpha(language, 2, 8) {
+        return false
+    }
 
-        if let Some(tag) = &self.language_tag {
-            value.push_str(&format!("@{}", tag));
+    let mut i = 1;
+    let next = |i: usize| subtags.get(i).cloned().unwrap_or("");
+
+    if language.len() <= 3 {
+        // Up to three extended language subtags.
+        while i < 4 && alpha(next(i), 3, 3) {
+            i += 1;
         }
+    }
 
-        write!(f, "{}^^{}", value, self.iri)
+    if alpha(next(i), 4, 4) {
+        // Script.
+        i += 1;
+    }
+
+    if alpha(next(i), 2, 2) || digits(next(i), 3) {
+        // Region.
+        i += 1;
+    }
+
+    while alphanumeric(next(i), 5, 8) ||
+        (next(i).len() == 4 && next(i).as_bytes()[0].is_ascii_digit() && alphanumeric(next(i), 4, 4))
+    {
+        // Variants.
+        i += 1;
     }
+
+    while alphanumeric(next(i), 1, 1) && !next(i).eq_ignore_ascii_case("x") {
+        // Extensions, each of which needs at least one subtag.
+        i += 1;
+
+        if !alphanumeric(next(i), 2, 8) {
+            return false
+        }
+
+        while alphanumeric(next(i), 2, 8) {
+            i += 1;
+        }
+    }
+
+    if next(i).eq_ignore_ascii_case("x") {
+        return private(&subtags[i + 1..])
+    }
+
+    i == subtags.len()
 }
@@ -0,0 +1,65 @@
+//! Where parsed triples came from in the source.
+use std::ops::Range;
+
+use error::Position;
+
+/// A range of the source.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The byte offsets of the range.
+    pub bytes: Range<usize>,
+    /// The position of the first character.
+    pub start: Position,
+    /// The position just after the last character.
+    pub end: Position,
+}
+
+/// A value along with where its subject, predicate and object were written.
+///
+/// Terms that aren't written out get the span of the syntax that implies
+/// them: `a` for `rdf:type`, `[ ... ]` for its blank node, the whole
+/// `( ... )` for the nodes and links of a collection, and the `{| ... |}`
+/// block for the triple an annotation is about.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Located<T> {
+    /// The located value.
+    pub value: T,
+    /// Where the subject is.
+    pub subject: Span,
+    /// Where the predicate is.
+    pub predicate: Span,
+    /// Where the object is.
+    pub object: Span,
+}
+
+/// The byte offsets of the start of every line, so positions can be found
+/// without rescanning the source.
+#[derive(Debug)]
+pub(crate) struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(source: &str) -> Self {
+        let starts = Some(0).into_iter()
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        LineIndex { starts }
+    }
+
+    pub(crate) fn span(&self, source: &str, bytes: Range<usize>) -> Span {
+        Span {
+            start: self.position(source, bytes.start),
+            end: self.position(source, bytes.end),
+            bytes,
+        }
+    }
+
+    fn position(&self, source: &str, offset: usize) -> Position {
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let column = source[self.starts[line - 1]..offset].chars().count() + 1;
+
+        Position::new(line, column)
+    }
+}
@@ -16,3 +16,48 @@ macro_rules! xsd {
     }};
 }
 
+macro_rules! rdf_ref {
+    ($typ:expr) => {{
+        use borrowed::IriRef;
+
+        IriRef::new(concat!("http://www.w3.org/1999/02/22-rdf-syntax-ns#", $typ))
+    }};
+}
+
+macro_rules! xsd_ref {
+    ($typ:expr) => {{
+        use borrowed::IriRef;
+
+        IriRef::new(concat!("http://www.w3.org/2001/XMLSchema#", $typ))
+    }};
+}
+
+macro_rules! log {
+    ($typ:expr) => {{
+        use iri::Iri;
+
+        let raw = concat!("http://www.w3.org/2000/10/swap/log#", $typ).into();
+        Iri::parse(raw).unwrap()
+    }};
+}
+
+macro_rules! owl {
+    ($typ:expr) => {{
+        use iri::Iri;
+
+        let raw = concat!("http://www.w3.org/2002/07/owl#", $typ).into();
+        Iri::parse(raw).unwrap()
+    }};
+}
+
+macro_rules! get {
+    ($this:ident: $rule:expr) => {{
+        use Rule::*;
+        let next = $this.next()?;
+        // println!("{:?}", next.as_rule());
+        if next.as_rule() != $rule {
+            return Err($this.unexpected(&next))
+        }
+        next
+    }}
+}
@@ -0,0 +1,492 @@
+//! Notation3 (N3) parsing.
+//!
+//! N3 extends Turtle with quoted formulas (`{ ... }`), quick variables
+//! (`?x`), implications (`=>` and `<=`), `=` for `owl:sameAs`,
+//! `@forAll`/`@forSome` declarations, and `!`/`^` paths. Statements that
+//! only use RDF terms are kept as `Triple`s, everything else is an
+//! `N3Triple` of `Term`s.
+use std::collections::HashMap;
+use std::fmt;
+
+use pest::Parser;
+
+use error::Error;
+use iri::{BlankNode, Iri};
+use labeller::BlankNodeLabeller;
+use limits::Limits;
+use options::ParserOptions;
+use literal::Literal;
+use object::Object;
+use parser::{Rule, TurtleParser};
+use subject::Subject;
+use triple::{Triple, Triples};
+use {checked, Context, Graph, BYTE_ORDER_MARK};
+
+/// N3 parser. Like `Graph`, it parses a string that's already in memory,
+/// but it produces an `N3Document` rather than triples.
+///
+/// ```
+/// use chelone::N3Reader;
+/// use chelone::n3::{Statement, Term};
+///
+/// let document = N3Reader::new(r#"
+///     @prefix : <http://example.org/> .
+///     :alice :knows :bob .
+///     { ?x :knows ?y } => { ?y :knows ?x } .
+/// "#).unwrap().parse().unwrap();
+///
+/// assert_eq!(document.formula.triples().len(), 1);
+///
+/// match document.formula.statements[1] {
+///     Statement::N3(ref rule) => match rule.subject {
+///         Term::Formula(ref formula) => assert_eq!(formula.statements.len(), 1),
+///         ref subject => panic!("Expected a formula, got: {}", subject),
+///     },
+///     ref statement => panic!("Expected a rule, got: {}", statement),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct N3Reader<'a> {
+    graph: Graph<'a>,
+}
+
+impl<'a> N3Reader<'a> {
+    /// Creates a new `N3Reader` from N3 source.
+    pub fn new(source: &'a str) -> Result<Self, Error> {
+        Self::with_limits(source, Limits::default())
+    }
+
+    /// Creates a new `N3Reader` from N3 source, which has to be within
+    /// `limits`. See `Limits`.
+    pub fn with_limits(source: &'a str, limits: Limits) -> Result<Self, Error> {
+        let source = source.trim_start_matches(BYTE_ORDER_MARK);
+        limits.check_depth(source)?;
+
+        let parsed = TurtleParser::parse(Rule::n3Doc, source)
+            .map_err(Error::from_pest)?;
+        let mut input = parsed.flatten().peekable();
+        // Skip `n3Doc`, directives and statements are read from its children.
+        input.next();
+        let context = Context::new(ParserOptions::new().limits(limits));
+
+        Ok(N3Reader { graph: Graph::from_input(Some(input), source, context) })
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.graph.context.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.graph.set_base(iri)
+    }
+
+    /// Parse the source into an `N3Document`.
+    pub fn parse(mut self) -> Result<N3Document, Error> {
+        let mut formula = Formula::default();
+
+        loop {
+            match self.graph.peek_rule() {
+                None | Some(Rule::EOI) => break,
+                Some(Rule::directive) => self.graph.parse_directive()?,
+                Some(Rule::n3Statement) => self.graph.parse_n3_statement(&mut formula)?,
+                _ => return Err(self.graph.unexpected_peek()),
+            }
+        }
+
+        Ok(N3Document {
+            prefixs: self.graph.context.prefixs,
+            formula,
+        })
+    }
+}
+
+/// A parsed N3 document.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct N3Document {
+    /// The prefixes declared in the document, without their trailing `:`.
+    pub prefixs: HashMap<String, Iri>,
+    /// The statements of the document.
+    pub formula: Formula,
+}
+
+impl fmt::Display for N3Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.formula.fmt_contents(f, "\n")?;
+
+        if !self.formula.is_empty() {
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of statements, either a whole document or a `{ ... }` formula.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Formula {
+    /// The IRIs declared with `@forAll`.
+    pub universals: Vec<Iri>,
+    /// The IRIs declared with `@forSome`.
+    pub existentials: Vec<Iri>,
+    /// The statements in the order they were parsed.
+    pub statements: Vec<Statement>,
+}
+
+impl Formula {
+    /// The statements that are plain RDF triples.
+    pub fn triples(&self) -> Triples {
+        self.statements.iter().filter_map(|statement| match *statement {
+            Statement::Triple(ref triple) => Some(triple.clone()),
+            Statement::N3(_) => None,
+        }).collect()
+    }
+
+    /// Whether the formula has no declarations or statements.
+    pub fn is_empty(&self) -> bool {
+        self.universals.is_empty() &&
+        self.existentials.is_empty() &&
+        self.statements.is_empty()
+    }
+
+    fn push(&mut self, subject: Term, predicate: Term, object: Term) {
+        let triple = N3Triple { subject, predicate, object };
+
+        let statement = match triple.as_triple() {
+            Some(triple) => Statement::Triple(triple),
+            None => Statement::N3(triple),
+        };
+
+        self.statements.push(statement);
+    }
+
+    fn fmt_contents(&self, f: &mut fmt::Formatter, separator: &str) -> fmt::Result {
+        let mut first = true;
+        let mut write_separator = |f: &mut fmt::Formatter| {
+            if !first {
+                f.write_str(separator)?;
+            }
+            first = false;
+            Ok(())
+        };
+
+        for &(keyword, iris) in &[("@forAll", &self.universals), ("@forSome", &self.existentials)] {
+            if iris.is_empty() {
+                continue
+            }
+
+            write_separator(f)?;
+            write!(f, "{} ", keyword)?;
+
+            for (i, iri) in iris.iter().enumerate() {
+                if i != 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", iri)?;
+            }
+
+            f.write_str(" .")?;
+        }
+
+        for statement in &self.statements {
+            write_separator(f)?;
+            write!(f, "{}", statement)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("{}")
+        }
+
+        f.write_str("{ ")?;
+        self.fmt_contents(f, " ")?;
+        f.write_str(" }")
+    }
+}
+
+/// A statement in a formula.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Statement {
+    /// A plain RDF triple.
+    Triple(Triple),
+    /// A triple that uses variables, formulas, or terms in places RDF
+    /// doesn't allow them, such as a literal subject.
+    N3(N3Triple),
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Statement::Triple(ref triple) => triple.fmt(f),
+            Statement::N3(ref triple) => triple.fmt(f),
+        }
+    }
+}
+
+/// A triple where any position can hold any N3 term.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct N3Triple {
+    /// The subject of the triple.
+    pub subject: Term,
+    /// The predicate of the triple.
+    pub predicate: Term,
+    /// The object of the triple.
+    pub object: Term,
+}
+
+impl N3Triple {
+    /// The triple as an RDF triple, if all of its terms are allowed where
+    /// they are in RDF.
+    pub fn as_triple(&self) -> Option<Triple> {
+        let subject = match self.subject {
+            Term::Iri(ref iri) => Subject::Iri(iri.clone()),
+            Term::BlankNode(ref node) => Subject::BlankNode(node.clone()),
+            _ => return None,
+        };
+        let predicate = match self.predicate {
+            Term::Iri(ref iri) => iri.clone(),
+            _ => return None,
+        };
+        let object = match self.object {
+            Term::Iri(ref iri) => Object::Iri(iri.clone()),
+            Term::BlankNode(ref node) => Object::BlankNode(node.clone()),
+            Term::Literal(ref literal) => Object::Literal(literal.clone()),
+            _ => return None,
+        };
+
+        Some(Triple::new(subject, predicate, object))
+    }
+}
+
+impl fmt::Display for N3Triple {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} .", self.subject, self.predicate, self.object)
+    }
+}
+
+/// A term in an N3 statement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Term {
+    /// An IRI
+    Iri(Iri),
+    /// A blank node
+    BlankNode(BlankNode),
+    /// A literal
+    Literal(Literal),
+    /// A quick variable, written `?name`.
+    Variable(Variable),
+    /// A quoted formula, written `{ ... }`.
+    Formula(Box<Formula>),
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Term::Iri(ref iri) => iri.fmt(f),
+            Term::BlankNode(ref node) => node.fmt(f),
+            Term::Literal(ref literal) => literal.fmt(f),
+            Term::Variable(ref variable) => variable.fmt(f),
+            Term::Formula(ref formula) => formula.fmt(f),
+        }
+    }
+}
+
+/// A quick variable. The name doesn't include the leading `?`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Variable(pub String);
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "?{}", self.0)
+    }
+}
+
+impl<'a> Graph<'a> {
+    fn parse_n3_statement(&mut self, formula: &mut Formula) -> Result<(), Error> {
+        get!(self: n3Statement);
+
+        let pair = self.next()?;
+        let end = pair.as_span().end();
+
+        match pair.as_rule() {
+            Rule::n3Universal => {
+                while self.belongs_to_list(Rule::iri, end) {
+                    formula.universals.push(self.parse_owned_iri()?);
+                }
+            }
+
+            Rule::n3Existential => {
+                while self.belongs_to_list(Rule::iri, end) {
+                    formula.existentials.push(self.parse_owned_iri()?);
+                }
+            }
+
+            Rule::n3Triples => {
+                let subject = self.parse_n3_path(formula)?;
+
+                if self.belongs_to_list(Rule::n3PredicateObjectList, end) {
+                    self.parse_n3_predicate_object_list(&subject, formula)?;
+                }
+            }
+
+            _ => return Err(self.unexpected(&pair)),
+        }
+
+        Ok(())
+    }
+
+    fn parse_n3_predicate_object_list(&mut self, subject: &Term, formula: &mut Formula)
+        -> Result<(), Error>
+    {
+        let end = get!(self: n3PredicateObjectList).as_span().end();
+
+        while self.belongs_to_list(Rule::n3Verb, end) {
+            let (predicate, inverse) = self.parse_n3_verb(formula)?;
+            let list_end = get!(self: n3ObjectList).as_span().end();
+
+            while self.belongs_to_list(Rule::n3Object, list_end) {
+                let start = get!(self: n3Object).as_span().start();
+                let object = self.parse_n3_path(formula)?;
+                self.count_triple(start)?;
+
+                if inverse {
+                    formula.push(object, predicate.clone(), subject.clone());
+                } else {
+                    formula.push(subject.clone(), predicate.clone(), object);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a verb, returning the predicate and whether the subject and
+    /// object are swapped, as they are for `<=`.
+    fn parse_n3_verb(&mut self, formula: &mut Formula) -> Result<(Term, bool), Error> {
+        let verb = get!(self: n3Verb);
+        let end = verb.as_span().end();
+
+        let rule = match self.peek_pair() {
+            Some(peek) if peek.as_span().start() < end => peek.as_rule(),
+            _ => return Ok((Term::Iri(rdf!("type")), false)),
+        };
+
+        Ok(match rule {
+            Rule::n3Path => (self.parse_n3_path(formula)?, false),
+            Rule::n3Implies => {
+                self.take();
+                (Term::Iri(log!("implies")), false)
+            }
+            Rule::n3ImpliedBy => {
+                self.take();
+                (Term::Iri(log!("implies")), true)
+            }
+            Rule::n3SameAs => {
+                self.take();
+                (Term::Iri(owl!("sameAs")), false)
+            }
+            _ => return Err(self.unexpected_peek()),
+        })
+    }
+
+    /// Parses a path. `x!p` is a new blank node `b` with `x p b`, and
+    /// `x^p` is one with `b p x`.
+    fn parse_n3_path(&mut self, formula: &mut Formula) -> Result<Term, Error> {
+        let span = get!(self: n3Path).as_span();
+        let end = span.end();
+        let mut term = self.parse_n3_path_item(formula)?;
+
+        loop {
+            let forward = if self.belongs_to_list(Rule::n3PathForward, end) {
+                true
+            } else if self.belongs_to_list(Rule::n3PathBackward, end) {
+                false
+            } else {
+                break
+            };
+
+            self.take();
+            let predicate = self.parse_n3_path_item(formula)?;
+            let node = Term::BlankNode(self.generate_new_blank_node());
+            self.count_triple(span.start())?;
+
+            if forward {
+                formula.push(term, predicate, node.clone());
+            } else {
+                formula.push(node.clone(), predicate, term);
+            }
+
+            term = node;
+        }
+
+        Ok(term)
+    }
+
+    fn parse_n3_path_item(&mut self, formula: &mut Formula) -> Result<Term, Error> {
+        get!(self: n3PathItem);
+
+        Ok(match self.peek_rule() {
+            Some(Rule::iri) => Term::Iri(self.parse_owned_iri()?),
+            Some(Rule::BlankNode) => Term::BlankNode(self.parse_blank_node()?),
+            Some(Rule::literal) => Term::Literal(checked(self.parse_literal()?.into_owned())),
+            Some(Rule::quickVar) => {
+                let name = self.next()?.as_str()[1..].to_owned();
+                Term::Variable(Variable(name))
+            }
+            Some(Rule::n3Collection) => self.parse_n3_collection(formula)?,
+            Some(Rule::n3BlankNodePropertyList) => {
+                get!(self: n3BlankNodePropertyList);
+                let node = Term::BlankNode(self.generate_new_blank_node());
+                self.parse_n3_predicate_object_list(&node, formula)?;
+                node
+            }
+            Some(Rule::formula) => Term::Formula(Box::new(self.parse_formula()?)),
+            _ => return Err(self.unexpected_peek()),
+        })
+    }
+
+    /// Parses a collection into `rdf:first`/`rdf:rest` statements, as
+    /// Turtle does.
+    fn parse_n3_collection(&mut self, formula: &mut Formula) -> Result<Term, Error> {
+        let span = get!(self: n3Collection).as_span();
+        let end = span.end();
+        let mut items = Vec::new();
+
+        while self.belongs_to_list(Rule::n3Object, end) {
+            get!(self: n3Object);
+            items.push(self.parse_n3_path(formula)?);
+        }
+
+        let nodes = items.iter()
+            .map(|_| Term::BlankNode(self.generate_new_blank_node()))
+            .collect::<Vec<_>>();
+
+        for (i, item) in items.into_iter().enumerate() {
+            let rest = nodes.get(i + 1).cloned().unwrap_or_else(|| Term::Iri(rdf!("nil")));
+
+            self.count_triple(span.start())?;
+            formula.push(nodes[i].clone(), Term::Iri(rdf!("first")), item);
+            self.count_triple(span.start())?;
+            formula.push(nodes[i].clone(), Term::Iri(rdf!("rest")), rest);
+        }
+
+        Ok(nodes.into_iter().next().unwrap_or_else(|| Term::Iri(rdf!("nil"))))
+    }
+
+    fn parse_formula(&mut self) -> Result<Formula, Error> {
+        let end = get!(self: formula).as_span().end();
+        let mut formula = Formula::default();
+
+        while self.belongs_to_list(Rule::n3Statement, end) {
+            self.parse_n3_statement(&mut formula)?;
+        }
+
+        Ok(formula)
+    }
+}
@@ -0,0 +1,89 @@
+//! A strict N-Quads parser.
+use std::io::BufRead;
+
+use pest::Parser;
+
+use error::Error;
+use labeller::{BlankNodeLabeller, BlankNodes};
+use ntriples::grammar::{NTriplesParser, Rule};
+use ntriples::{first_inner, parse_blank_node, parse_iriref, parse_terms, unexpected_pair, Lines};
+use quad::{Dataset, GraphName, Quad};
+
+/// N-Quads parser reading one line at a time from a `BufRead`. Iterating over
+/// an `NQuadsReader` yields its quads, and the dataset they make up can be
+/// written back out as N-Quads with `Dataset`'s `Display` implementation.
+///
+/// ```
+/// use chelone::NQuadsReader;
+///
+/// let input = "<http://a.example/s> <http://a.example/p> \"o\" <http://a.example/g> .\n\
+///              <http://a.example/s> <http://a.example/p> \"o\" .\n";
+/// let dataset = NQuadsReader::new(input.as_bytes()).parse().unwrap();
+///
+/// assert_eq!(dataset.default_graph.len(), 1);
+/// assert_eq!(dataset.named_graphs.len(), 1);
+/// assert_eq!(dataset.to_string().lines().count(), 2);
+/// ```
+///
+/// Blank nodes, including graph names, are relabelled as they are read as
+/// with `NTriplesReader`.
+#[derive(Debug)]
+pub struct NQuadsReader<R> {
+    lines: Lines<R>,
+    blank_nodes: BlankNodes,
+}
+
+impl<R: BufRead> NQuadsReader<R> {
+    /// Creates a new `NQuadsReader` that parses N-Quads read from `reader`.
+    pub fn new(reader: R) -> Self {
+        NQuadsReader { lines: Lines::new(reader), blank_nodes: BlankNodes::default() }
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Parse the whole input into a dataset.
+    pub fn parse(self) -> Result<Dataset, Error> {
+        self.collect()
+    }
+}
+
+impl<R: BufRead> Iterator for NQuadsReader<R> {
+    type Item = Result<Quad, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let blank_nodes = &mut self.blank_nodes;
+
+        self.lines.parse_next(parse_line)
+            .map(|result| result.map(|quad| quad.map_blank_nodes(&mut |node| blank_nodes.document(&node.0))))
+    }
+}
+
+/// Parses a single line, which may be empty or only contain a comment.
+fn parse_line(line: &str) -> Result<Option<Quad>, Error> {
+    let mut parsed = NTriplesParser::parse(Rule::quadLine, line)
+        .map_err(Error::from_pest)?;
+    let quad = match parsed.next().and_then(|l| l.into_inner().next()) {
+        Some(pair) if pair.as_rule() == Rule::quad => pair,
+        _ => return Ok(None),
+    };
+
+    let mut terms = quad.into_inner();
+    let (subject, predicate, object) = parse_terms(&mut terms)?;
+    let graph_name = match terms.next() {
+        Some(label) => {
+            let term = first_inner(label)?;
+
+            Some(match term.as_rule() {
+                Rule::IRIREF => GraphName::Iri(parse_iriref(term)?),
+                Rule::BLANK_NODE_LABEL => GraphName::BlankNode(parse_blank_node(term)?),
+                _ => return Err(unexpected_pair(&term)),
+            })
+        }
+        None => None,
+    };
+
+    Ok(Some(Quad::new(subject, predicate, object, graph_name)))
+}
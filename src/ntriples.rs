@@ -0,0 +1,318 @@
+//! A strict N-Triples parser.
+use std::io::BufRead;
+use std::str;
+
+use pest::Parser;
+use pest::iterators::{Pair, Pairs};
+
+use error::{Error, Position};
+use iri::{BlankNode, Iri};
+use labeller::{BlankNodeLabeller, BlankNodes};
+use literal::Literal;
+use object::Object;
+use subject::Subject;
+use triple::{Triple, Triples};
+use BYTE_ORDER_MARK;
+
+pub(crate) mod grammar {
+    #![allow(bad_style)]
+
+    #[derive(Parser)]
+    #[grammar = "ntriples.pest"]
+    pub struct NTriplesParser;
+}
+
+use self::grammar::{NTriplesParser, Rule};
+
+#[cfg(debug_assertions)]
+const _GRAMMAR: &str = include_str!("ntriples.pest");
+
+/// N-Triples parser reading one line at a time from a `BufRead`. Unlike
+/// `Graph` it only accepts N-Triples, so prefixes, relative IRIs and any of
+/// Turtle's abbreviations are errors. Quoted triples, written
+/// `<< s p o >>` as in N-Triples-star, are accepted as subjects and
+/// objects. Iterating over an `NTriplesReader` yields its triples.
+///
+/// Blank nodes are relabelled as they are read, so the triples of two
+/// documents never share a blank node unless the same labeller is used.
+///
+/// ```
+/// use chelone::NTriplesReader;
+///
+/// let input = "<http://a.example/s> <http://a.example/p> \"o\" .\n";
+/// let triples = NTriplesReader::new(input.as_bytes()).parse().unwrap();
+///
+/// assert_eq!(triples.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct NTriplesReader<R> {
+    lines: Lines<R>,
+    blank_nodes: BlankNodes,
+}
+
+impl<R: BufRead> NTriplesReader<R> {
+    /// Creates a new `NTriplesReader` that parses N-Triples read from
+    /// `reader`.
+    pub fn new(reader: R) -> Self {
+        NTriplesReader { lines: Lines::new(reader), blank_nodes: BlankNodes::default() }
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Parse the whole input into a set of Triples.
+    pub fn parse(self) -> Result<Triples, Error> {
+        self.collect()
+    }
+}
+
+impl<R: BufRead> Iterator for NTriplesReader<R> {
+    type Item = Result<Triple, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let blank_nodes = &mut self.blank_nodes;
+
+        self.lines.parse_next(parse_line).map(|result| result.map(|mut triple| {
+            triple.map_blank_nodes(&mut |node| blank_nodes.document(&node.0));
+            triple
+        }))
+    }
+}
+
+/// Reads the input of the line based formats one line at a time.
+#[derive(Debug)]
+pub(crate) struct Lines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    line: usize,
+    finished: bool,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Lines {
+            reader,
+            buffer: Vec::new(),
+            line: 0,
+            finished: false,
+        }
+    }
+
+    /// Calls `parse` on each line until it returns an item, returning `None`
+    /// at the end of the input and after the first error.
+    pub(crate) fn parse_next<T, F>(&mut self, parse: F) -> Option<Result<T, Error>>
+        where F: Fn(&str) -> Result<Option<T>, Error>
+    {
+        if self.finished {
+            return None
+        }
+
+        let result = self.parse_until_item(parse);
+
+        match result {
+            Ok(None) | Err(_) => self.finished = true,
+            Ok(Some(_)) => {}
+        }
+
+        result.transpose()
+    }
+
+    fn parse_until_item<T, F>(&mut self, parse: F) -> Result<Option<T>, Error>
+        where F: Fn(&str) -> Result<Option<T>, Error>
+    {
+        loop {
+            self.buffer.clear();
+            self.line += 1;
+
+            let position = Position::new(self.line, 1);
+            let bytes = self.reader.read_until(b'\n', &mut self.buffer)
+                .map_err(|e| Error::from_io(&e, position))?;
+
+            if bytes == 0 {
+                return Ok(None)
+            }
+
+            let mut line = str::from_utf8(&self.buffer).map_err(|error| {
+                let valid = str::from_utf8(&self.buffer[..error.valid_up_to()])
+                    .unwrap_or_default();
+
+                Error::InvalidUtf8 {
+                    position: Position::new(self.line, valid.chars().count() + 1),
+                }
+            })?;
+
+            if self.line == 1 {
+                line = line.trim_start_matches(BYTE_ORDER_MARK);
+            }
+
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+            let item = parse(line).map_err(|e| e.relative_to(position))?;
+
+            if item.is_some() {
+                return Ok(item)
+            }
+        }
+    }
+}
+
+/// Parses a single line, which may be empty or only contain a comment.
+pub(crate) fn parse_line(line: &str) -> Result<Option<Triple>, Error> {
+    let mut parsed = NTriplesParser::parse(Rule::line, line)
+        .map_err(Error::from_pest)?;
+    let triple = match parsed.next().and_then(|l| l.into_inner().next()) {
+        Some(pair) if pair.as_rule() == Rule::triple => pair,
+        _ => return Ok(None),
+    };
+
+    let mut terms = triple.into_inner();
+    let (subject, predicate, object) = parse_terms(&mut terms)?;
+
+    Ok(Some(Triple::new(subject, predicate, object)))
+}
+
+/// Parses the subject, predicate and object at the start of a triple or quad.
+pub(crate) fn parse_terms(terms: &mut Pairs<Rule>)
+    -> Result<(Subject, Iri, Object), Error>
+{
+    let (subject, predicate, object) = match (terms.next(), terms.next(), terms.next()) {
+        (Some(s), Some(p), Some(o)) => (s, p, o),
+        _ => return Err(unexpected(Position::new(1, 1), "end of statement")),
+    };
+
+    Ok((parse_subject(subject)?,
+        parse_iriref(first_inner(predicate)?)?,
+        parse_object(object)?))
+}
+
+fn parse_subject(pair: Pair<Rule>) -> Result<Subject, Error> {
+    let term = first_inner(pair)?;
+
+    match term.as_rule() {
+        Rule::IRIREF => Ok(Subject::Iri(parse_iriref(term)?)),
+        Rule::BLANK_NODE_LABEL => Ok(Subject::BlankNode(parse_blank_node(term)?)),
+        Rule::quotedTriple => Ok(Subject::from(parse_quoted_triple(term)?)),
+        _ => Err(unexpected_pair(&term)),
+    }
+}
+
+fn parse_object(pair: Pair<Rule>) -> Result<Object, Error> {
+    let term = first_inner(pair)?;
+
+    match term.as_rule() {
+        Rule::IRIREF => Ok(Object::Iri(parse_iriref(term)?)),
+        Rule::BLANK_NODE_LABEL => Ok(Object::BlankNode(parse_blank_node(term)?)),
+        Rule::literal => Ok(Object::Literal(parse_literal(term)?)),
+        Rule::quotedTriple => Ok(Object::from(parse_quoted_triple(term)?)),
+        _ => Err(unexpected_pair(&term)),
+    }
+}
+
+fn parse_quoted_triple(pair: Pair<Rule>) -> Result<Triple, Error> {
+    let (subject, predicate, object) = parse_terms(&mut pair.into_inner())?;
+
+    Ok(Triple::new(subject, predicate, object))
+}
+
+pub(crate) fn parse_blank_node(pair: Pair<Rule>) -> Result<BlankNode, Error> {
+    Ok(BlankNode(first_inner(pair)?.as_str().to_owned()))
+}
+
+fn parse_literal(pair: Pair<Rule>) -> Result<Literal, Error> {
+    let mut inner = pair.into_inner();
+    let string = match inner.next() {
+        Some(string) => first_inner(string)?,
+        None => return Err(unexpected(Position::new(1, 1), "end of literal")),
+    };
+    let value = unescape(string.as_str(), position(&string))?;
+
+    match inner.next() {
+        Some(ref tag) if tag.as_rule() == Rule::LANGTAG => Ok(Literal::new_tagged(value, &tag.as_str()[1..])),
+        Some(iri) => Ok(Literal::new(value, None, Some(parse_iriref(iri)?))),
+        None => Ok(Literal::new(value, None, None)),
+    }
+}
+
+pub(crate) fn parse_iriref(pair: Pair<Rule>) -> Result<Iri, Error> {
+    let position = position(&pair);
+    let raw = first_inner(pair)?;
+    let iri = unescape(raw.as_str(), position)?;
+
+    // Escapes can't be used to sneak in characters that are not allowed in
+    // an IRIREF.
+    if iri.chars().any(|c| c <= ' ' || "<>\"{}|^`\\".contains(c)) {
+        return Err(Error::InvalidIri { iri, position })
+    }
+
+    Iri::parse(&iri).map_err(|_| Error::InvalidIri { iri, position })
+}
+
+/// Replaces `ECHAR` and `UCHAR` escapes with the characters they represent.
+fn unescape(raw: &str, position: Position) -> Result<String, Error> {
+    let mut string = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue
+        }
+
+        let invalid = |end: usize| Error::InvalidEscape {
+            escape: raw[start..end].to_owned(),
+            position,
+        };
+
+        let escape = match chars.next() {
+            Some((_, escape)) => escape,
+            None => return Err(invalid(raw.len())),
+        };
+
+        let digits = match escape {
+            't' => { string.push('\t'); continue }
+            'b' => { string.push('\u{08}'); continue }
+            'n' => { string.push('\n'); continue }
+            'r' => { string.push('\r'); continue }
+            'f' => { string.push('\u{0C}'); continue }
+            '"' | '\'' | '\\' => { string.push(escape); continue }
+            'u' => 4,
+            'U' => 8,
+            _ => return Err(invalid(start + 2)),
+        };
+
+        let end = (start + 2 + digits).min(raw.len());
+        let decoded = raw.get(start + 2..end)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(std::char::from_u32);
+
+        match decoded {
+            Some(c) => string.push(c),
+            None => return Err(invalid(end)),
+        }
+
+        for _ in 0..digits {
+            chars.next();
+        }
+    }
+
+    Ok(string)
+}
+
+pub(crate) fn first_inner(pair: Pair<Rule>) -> Result<Pair<Rule>, Error> {
+    let position = position(&pair);
+
+    pair.into_inner().next().ok_or_else(|| unexpected(position, "end of term"))
+}
+
+fn position(pair: &Pair<Rule>) -> Position {
+    Position::from_pest(&pair.as_span().start_pos())
+}
+
+pub(crate) fn unexpected_pair(pair: &Pair<Rule>) -> Error {
+    unexpected(position(pair), &format!("{:?} {:?}", pair.as_rule(), pair.as_str()))
+}
+
+fn unexpected(position: Position, found: &str) -> Error {
+    Error::Unexpected { found: found.to_owned(), position }
+}
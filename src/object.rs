@@ -1,9 +1,10 @@
 //! An object from RDF graph.
 use std::fmt;
 
-use iri::{BlankNode, Iri};
+use iri::{BlankNode, Iri, RelativeIri};
 use literal::Literal;
 use subject::Subject;
+use triple::Triple;
 
 /// The object at end of a Triple.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -14,15 +15,16 @@ pub enum Object {
     BlankNode(BlankNode),
     /// A literal
     Literal(Literal),
+    /// A quoted triple, written `<< s p o >>`.
+    Triple(Box<Triple>),
+    /// A relative IRI that hasn't been resolved.
+    RelativeIri(RelativeIri),
 }
 
 impl Object {
     /// Checks ig the object is a blank node.
     pub fn is_blank_node(&self) -> bool {
-        match *self {
-            Object::BlankNode(_) => true,
-            _ => false,
-        }
+        matches!(*self, Object::BlankNode(_))
     }
 
     pub(crate) fn into_blank_node(self) -> Option<BlankNode> {
@@ -39,11 +41,28 @@ impl Object {
         match self {
             Object::Iri(iri) => Subject::Iri(iri),
             Object::BlankNode(node) => Subject::BlankNode(node),
+            Object::Triple(triple) => Subject::Triple(triple),
+            Object::RelativeIri(relative) => Subject::RelativeIri(relative),
             _ => panic!("Tried to convert literal to subject"),
         }
     }
+
+    /// Resolves the object against `base` if it's a relative IRI, including
+    /// those in quoted triples.
+    pub fn resolve(self, base: &Iri) -> Result<Self, url::ParseError> {
+        Ok(match self {
+            Object::RelativeIri(relative) => Object::Iri(relative.resolve(base)?),
+            Object::Triple(triple) => Object::from(triple.resolve(base)?),
+            object => object,
+        })
+    }
 }
 
+impl From<Triple> for Object {
+    fn from(triple: Triple) -> Self {
+        Object::Triple(Box::new(triple))
+    }
+}
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -51,6 +70,8 @@ impl fmt::Display for Object {
             Object::Iri(ref iri) => iri.fmt(f),
             Object::BlankNode(ref node) => node.fmt(f),
             Object::Literal(ref literal) => literal.fmt(f),
+            Object::Triple(ref triple) => triple.fmt_quoted(f),
+            Object::RelativeIri(ref relative) => relative.fmt(f),
         }
     }
 }
@@ -0,0 +1,151 @@
+//! How strictly documents are parsed.
+use iri::Iri;
+use limits::Limits;
+
+/// What the parser does when it finds something questionable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// It's an error.
+    Reject,
+    /// It's accepted, and the error it would have been is kept as a warning.
+    Warn,
+    /// It's accepted silently.
+    Allow,
+}
+
+/// Options for parsing Turtle and TriG, given to `Graph::with_options` or
+/// `TurtleReader::set_options`.
+///
+/// Each questionable case has its own `Policy`. By default only relative
+/// IRIs without a base and unknown prefixes are rejected, and everything
+/// else is allowed. `strict` rejects every case, for authoring, and
+/// `lenient` warns about every case, for ingesting data from elsewhere.
+///
+/// ```
+/// use chelone::{Error, Graph, ParserOptions};
+///
+/// let source = r#"<a> <b> "1.5"^^<http://www.w3.org/2001/XMLSchema#integer> ."#;
+///
+/// let document = Graph::with_options(source, ParserOptions::lenient())
+///     .unwrap()
+///     .parse_document()
+///     .unwrap();
+///
+/// assert_eq!(document.triples.len(), 1);
+/// assert_eq!(document.warnings.len(), 3);
+///
+/// match Graph::with_options(source, ParserOptions::strict()).unwrap().parse() {
+///     Err(Error::InvalidIri { iri, .. }) => assert_eq!(iri, "a"),
+///     result => panic!("Expected a relative IRI error, got: {:?}", result),
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub(crate) base: Option<Iri>,
+    pub(crate) limits: Limits,
+    pub(crate) relative_iris: Policy,
+    pub(crate) keep_relative_iris: bool,
+    pub(crate) unknown_prefixes: Policy,
+    pub(crate) invalid_language_tags: Policy,
+    pub(crate) ill_typed_literals: Policy,
+    pub(crate) duplicate_prefixes: Policy,
+}
+
+impl ParserOptions {
+    /// Creates the default `ParserOptions`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates `ParserOptions` that reject every questionable case.
+    pub fn strict() -> Self {
+        Self::with_policy(Policy::Reject)
+    }
+
+    /// Creates `ParserOptions` that accept every questionable case with a
+    /// warning.
+    pub fn lenient() -> Self {
+        Self::with_policy(Policy::Warn)
+    }
+
+    fn with_policy(policy: Policy) -> Self {
+        ParserOptions {
+            base: None,
+            limits: Limits::default(),
+            relative_iris: policy,
+            keep_relative_iris: false,
+            unknown_prefixes: policy,
+            invalid_language_tags: policy,
+            ill_typed_literals: policy,
+            duplicate_prefixes: policy,
+        }
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn base(mut self, iri: Iri) -> Self {
+        self.base = Some(iri);
+        self
+    }
+
+    /// Sets the limits the document has to be within.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// What to do with a relative IRI when there's no base to resolve it
+    /// against. When it's accepted, it's resolved against `file:///`.
+    pub fn relative_iris(mut self, policy: Policy) -> Self {
+        self.relative_iris = policy;
+        self
+    }
+
+    /// Whether to keep relative IRIs that are subjects or objects as
+    /// `RelativeIri`s when there's no base, for templates and fragments of
+    /// documents. They can be resolved later with `Triple::resolve` or as
+    /// they're written with `TriGWriter::base`. Relative IRIs anywhere else,
+    /// such as predicates and datatypes, still follow `relative_iris`.
+    pub fn keep_relative_iris(mut self, keep: bool) -> Self {
+        self.keep_relative_iris = keep;
+        self
+    }
+
+    /// What to do with a prefixed name whose prefix wasn't declared. When
+    /// it's accepted, the prefixed name is read as an IRI, so `ex:a` is
+    /// `<ex:a>`.
+    pub fn unknown_prefixes(mut self, policy: Policy) -> Self {
+        self.unknown_prefixes = policy;
+        self
+    }
+
+    /// What to do with a language tag that isn't well-formed according to
+    /// BCP 47.
+    pub fn invalid_language_tags(mut self, policy: Policy) -> Self {
+        self.invalid_language_tags = policy;
+        self
+    }
+
+    /// What to do with a literal whose value isn't valid for its XSD
+    /// datatype, such as `"a"^^xsd:integer`. Only the numeric and boolean
+    /// datatypes are checked.
+    pub fn ill_typed_literals(mut self, policy: Policy) -> Self {
+        self.ill_typed_literals = policy;
+        self
+    }
+
+    /// What to do when a prefix is declared more than once.
+    pub fn duplicate_prefixes(mut self, policy: Policy) -> Self {
+        self.duplicate_prefixes = policy;
+        self
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            relative_iris: Policy::Reject,
+            unknown_prefixes: Policy::Reject,
+            ..Self::with_policy(Policy::Allow)
+        }
+    }
+}
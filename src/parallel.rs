@@ -0,0 +1,581 @@
+//! Parsing large N-Triples and Turtle documents on several threads.
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::mem;
+use std::str;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use error::{Error, Position};
+use iri::{BlankNode, Iri};
+use labeller::BlankNodeLabeller;
+use lenient::{dots_end, skip_trivia, statement_end};
+use limits::{Limit, Limits};
+use ntriples::parse_line;
+use options::ParserOptions;
+use triple::{Triple, Triples};
+use {checked, Context, Graph};
+
+/// The number of bytes a chunk has before it's given to a thread, by
+/// default.
+const CHUNK_SIZE: usize = 1024 * 1024;
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// Receives the triples of a document in the order they were written, such
+/// as from a `ParallelReader`. `Triples` collects them, and any
+/// `FnMut(Triple)` is a sink.
+pub trait TripleSink {
+    /// Receives the next triple of the document.
+    fn triple(&mut self, triple: Triple);
+}
+
+impl<F> TripleSink for F
+    where F: FnMut(Triple)
+{
+    fn triple(&mut self, triple: Triple) {
+        self(triple)
+    }
+}
+
+impl TripleSink for Triples {
+    fn triple(&mut self, triple: Triple) {
+        self.0.push(triple)
+    }
+}
+
+/// Parser that splits a large N-Triples or Turtle document into chunks
+/// and parses them on a pool of threads, while the input is still being
+/// read from a `BufRead`.
+///
+/// N-Triples is split between lines, and its blank nodes are relabelled
+/// as with `NTriplesReader`. Turtle is split after the `.` that ends a
+/// statement, and its chunks are parsed by the same hand-written parser as
+/// `Graph::fast`. Directives are parsed as they're read, so chunks are
+/// parsed with the prefixes and base in effect where they start, and the
+/// blank nodes from every chunk are labelled as though the document had
+/// been parsed in one go, so `_:a` is the same blank node throughout.
+///
+/// The triples are given to a `TripleSink` in the order they were written,
+/// and parsing stops at the first error in the document.
+///
+/// ```
+/// use chelone::ParallelReader;
+///
+/// let input = "@prefix : <http://example.org/> .\n:a :b _:c .\n:d :e _:c .";
+/// let mut reader = ParallelReader::turtle(input.as_bytes());
+/// reader.set_chunk_size(16);
+/// let triples = reader.parse().unwrap();
+///
+/// assert_eq!(triples.len(), 2);
+/// assert_eq!(triples[0].object, triples[1].object);
+/// ```
+#[derive(Debug)]
+pub struct ParallelReader<R> {
+    reader: R,
+    format: Format,
+    /// The input that's been read but not given to a thread.
+    buffer: Vec<u8>,
+    at_start: bool,
+    eof: bool,
+    threads: usize,
+    chunk_size: usize,
+    /// The blank nodes, prefixes and base of the whole document.
+    context: Context,
+    /// The `Limits::max_triples` of the options, which is checked as the
+    /// chunks are put back together rather than by each chunk.
+    max_triples: usize,
+    warnings: Vec<Error>,
+}
+
+/// The formats that `ParallelReader` can split.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    NTriples,
+    Turtle,
+}
+
+/// The state a chunk of Turtle is parsed with.
+#[derive(Debug)]
+struct Header {
+    base: Option<Iri>,
+    prefixs: HashMap<String, Iri>,
+    options: ParserOptions,
+}
+
+impl Header {
+    fn context(&self) -> Context {
+        Context {
+            base: self.base.clone(),
+            prefixs: self.prefixs.clone(),
+            ..Context::new(self.options.clone())
+        }
+    }
+}
+
+/// A chunk that's waiting for a thread to parse it.
+#[derive(Debug)]
+struct Job {
+    format: Format,
+    source: Vec<u8>,
+    header: Arc<Header>,
+}
+
+/// A chunk that has been parsed.
+#[derive(Debug)]
+struct Parsed {
+    triples: Vec<Triple>,
+    /// The label in the document of each of the chunk's blank nodes that
+    /// had one, or `None` if they're still labelled as in the document.
+    labels: Option<HashMap<BlankNode, String>>,
+    /// The warnings, which are relative to the start of the chunk.
+    warnings: Vec<Error>,
+    /// The position just after the chunk, relative to its start.
+    extent: Position,
+    source: String,
+    header: Arc<Header>,
+}
+
+/// Either a chunk for a thread to parse, or a chunk that has already been
+/// parsed, or failed to be read.
+enum Item {
+    Job(Job),
+    Parsed(Result<Parsed, Error>),
+}
+
+impl<R: BufRead> ParallelReader<R> {
+    /// Creates a new `ParallelReader` that parses N-Triples read from
+    /// `reader`.
+    pub fn ntriples(reader: R) -> Self {
+        Self::new(reader, Format::NTriples)
+    }
+
+    /// Creates a new `ParallelReader` that parses turtle read from `reader`.
+    pub fn turtle(reader: R) -> Self {
+        Self::new(reader, Format::Turtle)
+    }
+
+    fn new(reader: R, format: Format) -> Self {
+        ParallelReader {
+            reader,
+            format,
+            buffer: Vec::new(),
+            at_start: true,
+            eof: false,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            chunk_size: CHUNK_SIZE,
+            context: Context::default(),
+            max_triples: usize::MAX,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Sets how many threads parse chunks, which is the number of CPUs
+    /// available by default.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1)
+    }
+
+    /// Sets roughly how many bytes each chunk has, which is 1 MiB by
+    /// default. Chunks only end between statements, so they can be
+    /// larger.
+    pub fn set_chunk_size(&mut self, bytes: usize) {
+        self.chunk_size = bytes.max(1)
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.context.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Sets the limits turtle has to be within. See `Limits`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.context.options.limits = limits
+    }
+
+    /// Sets the options to parse turtle with, including their base if they
+    /// have one. See `ParserOptions`.
+    pub fn set_options(&mut self, options: ParserOptions) {
+        if let Some(ref base) = options.base {
+            self.context.base = Some(base.clone());
+        }
+
+        self.context.options = options;
+    }
+
+    /// Sets the initial base url to resolve relative urls in turtle
+    /// against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.context.base = Some(iri)
+    }
+
+    /// The warnings from the chunks parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Parse the whole input into a set of Triples.
+    pub fn parse(mut self) -> Result<Triples, Error> {
+        let mut triples = Triples::default();
+        self.parse_into(&mut triples)?;
+
+        Ok(triples)
+    }
+
+    /// Parses the whole input, giving each triple to `sink` in the order
+    /// they were written. The triples before the first error are given to
+    /// `sink` before the error is returned.
+    pub fn parse_into<S: TripleSink>(&mut self, sink: &mut S) -> Result<(), Error> {
+        self.max_triples = self.context.options.limits.max_triples;
+        self.context.options.limits.max_triples = usize::MAX;
+
+        let result = self.parse_chunks(sink);
+        self.context.options.limits.max_triples = self.max_triples;
+
+        result
+    }
+
+    /// Reads the input in chunks, parsing them on `threads` threads and
+    /// putting them back together in order.
+    fn parse_chunks<S: TripleSink>(&mut self, sink: &mut S) -> Result<(), Error> {
+        let threads = self.threads;
+        let (job_sender, jobs) = mpsc::channel::<(usize, Job)>();
+        let jobs = Mutex::new(jobs);
+        let (result_sender, results) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                let jobs = &jobs;
+                let results = result_sender.clone();
+
+                scope.spawn(move || loop {
+                    let (index, job) = match jobs.lock().map(|jobs| jobs.recv()) {
+                        Ok(Ok(job)) => job,
+                        _ => break,
+                    };
+
+                    if results.send((index, job.run())).is_err() {
+                        break
+                    }
+                });
+            }
+
+            // The chunks that are finished but waiting for earlier ones,
+            // and where the next chunk to be put back starts.
+            let mut finished = BTreeMap::new();
+            let mut next = 0;
+            let mut start = Position::new(1, 1);
+            let mut in_flight = 0;
+            let mut index = 0;
+
+            while let Some(item) = self.next_item() {
+                let failed = match item {
+                    Item::Job(job) => {
+                        job_sender.send((index, job)).expect("threads only stop once jobs are sent");
+                        in_flight += 1;
+                        false
+                    }
+                    Item::Parsed(result) => {
+                        let failed = result.is_err();
+                        finished.insert(index, result);
+                        failed
+                    }
+                };
+
+                index += 1;
+
+                // Keep enough chunks queued to keep every thread busy, but
+                // no more, so the input isn't read faster than it's parsed.
+                while in_flight >= threads * 2 || (failed && in_flight > 0) {
+                    let (index, result) = results.recv().expect("threads only stop once jobs are sent");
+                    finished.insert(index, result);
+                    in_flight -= 1;
+                }
+
+                self.merge(&mut finished, &mut next, &mut start, sink)?;
+
+                if failed {
+                    break
+                }
+            }
+
+            drop(job_sender);
+
+            while in_flight > 0 {
+                let (index, result) = results.recv().expect("threads only stop once jobs are sent");
+                finished.insert(index, result);
+                in_flight -= 1;
+            }
+
+            self.merge(&mut finished, &mut next, &mut start, sink)
+        })
+    }
+
+    /// Gives the triples of the chunks that are finished to `sink`, in
+    /// order, until one that's still being parsed. `start` is where the
+    /// chunk at `next` starts in the document.
+    fn merge<S: TripleSink>(&mut self,
+                            finished: &mut BTreeMap<usize, Result<Parsed, Error>>,
+                            next: &mut usize,
+                            start: &mut Position,
+                            sink: &mut S)
+        -> Result<(), Error>
+    {
+        while let Some(result) = finished.remove(next) {
+            let parsed = result.map_err(|error| error.relative_to(*start))?;
+            let count = self.context.triples + parsed.triples.len();
+
+            if count > self.max_triples {
+                return Err(self.triple_limit_error(&parsed).relative_to(*start))
+            }
+
+            self.context.triples = count;
+            self.warnings.extend(parsed.warnings.into_iter().map(|warning| warning.relative_to(*start)));
+
+            let blank_nodes = &mut self.context.blank_nodes;
+
+            match parsed.labels {
+                Some(labels) => {
+                    let mut generated = HashMap::new();
+                    let mut relabel = |node: &BlankNode| match labels.get(node) {
+                        Some(label) => blank_nodes.document(label),
+                        None => generated.entry(node.clone()).or_insert_with(|| blank_nodes.generate()).clone(),
+                    };
+
+                    for mut triple in parsed.triples {
+                        triple.map_blank_nodes(&mut relabel);
+                        sink.triple(triple);
+                    }
+                }
+                None => for mut triple in parsed.triples {
+                    triple.map_blank_nodes(&mut |node| blank_nodes.document(&node.0));
+                    sink.triple(triple);
+                }
+            }
+
+            *start = parsed.extent.relative_to(*start);
+            *next += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Parses `parsed` again counting from the triples before it, to find
+    /// where it went over `Limits::max_triples`.
+    fn triple_limit_error(&self, parsed: &Parsed) -> Error {
+        let mut context = parsed.header.context();
+        context.options.limits.max_triples = self.max_triples;
+        context.triples = self.context.triples;
+
+        let mut graph = Graph::from_input(None, &parsed.source, context);
+
+        loop {
+            match graph.parse_next_statement() {
+                Ok(true) => graph.pending.clear(),
+                Ok(false) => break,
+                Err(error) => return error,
+            }
+        }
+
+        Error::LimitExceeded { limit: Limit::Triples, max: self.max_triples, position: Position::new(1, 1) }
+    }
+
+    /// Reads the next chunk, which ends between statements once it's at
+    /// least the chunk size. A statement that could be a directive is
+    /// parsed straight away instead, so the chunks after it can be given
+    /// the prefixes and base it declares.
+    fn next_item(&mut self) -> Option<Item> {
+        let mut end = 0;
+
+        while end < self.chunk_size {
+            match self.statement_end(end) {
+                Some((length, true)) if end == 0 => return Some(Item::Parsed(self.parse_directives(length))),
+                Some((_, true)) => break,
+                Some((length, false)) => end += length,
+                None if self.eof => break,
+                None => {
+                    if let Err(error) = self.fill() {
+                        let error = Error::from_io(&error, Position::new(1, 1).after(&String::from_utf8_lossy(&self.buffer)));
+                        self.buffer.clear();
+                        self.eof = true;
+
+                        return Some(Item::Parsed(Err(error)))
+                    }
+                }
+            }
+        }
+
+        if end == 0 {
+            return None
+        }
+
+        let source = self.buffer.drain(..end).collect();
+        let header = Arc::new(Header {
+            base: self.context.base.clone(),
+            prefixs: self.context.prefixs.clone(),
+            options: self.context.options.clone(),
+        });
+
+        Some(Item::Job(Job { format: self.format, source, header }))
+    }
+
+    /// The length of the statement starting at `offset` in the buffer, and
+    /// whether it could be a directive, or `None` if more has to be read to
+    /// find its end. Whitespace and comments at the end of the input are a
+    /// statement of their own.
+    fn statement_end(&self, offset: usize) -> Option<(usize, bool)> {
+        let bytes = &self.buffer[offset..];
+
+        if bytes.is_empty() {
+            return None
+        }
+
+        if self.format == Format::NTriples {
+            return match bytes.iter().position(|&b| b == b'\n') {
+                Some(newline) => Some((newline + 1, false)),
+                None if self.eof => Some((bytes.len(), false)),
+                None => None,
+            }
+        }
+
+        let start = skip_trivia(bytes);
+
+        if start == bytes.len() {
+            return if self.eof { Some((start, false)) } else { None }
+        }
+
+        let end = match statement_end(bytes, start) {
+            // Dots at the end of the buffer could still be part of a name.
+            Some(end) if dots_end(bytes, end - 1).is_some() || self.eof => end,
+            None if self.eof => bytes.len(),
+            _ => return None,
+        };
+
+        Some((end, could_be_directive(&bytes[start..end])))
+    }
+
+    /// Parses the statements at the start of the buffer that could be
+    /// directives, with the document's prefixes and base, which they then
+    /// update.
+    fn parse_directives(&mut self, length: usize) -> Result<Parsed, Error> {
+        let source = self.buffer.drain(..length).collect::<Vec<_>>();
+        let source = String::from_utf8(source).map_err(invalid_utf8)?;
+        let header = Arc::new(Header {
+            base: self.context.base.clone(),
+            prefixs: self.context.prefixs.clone(),
+            options: self.context.options.clone(),
+        });
+
+        // Blank nodes are labelled when they're put back in order, like
+        // those of every other chunk.
+        let blank_nodes = mem::take(&mut self.context.blank_nodes);
+        let context = mem::take(&mut self.context);
+        let mut graph = Graph::from_input(None, &source, context);
+        let result = read_triples(&mut graph);
+
+        self.context = mem::take(&mut graph.context);
+        let labels = mem::replace(&mut self.context.blank_nodes, blank_nodes).into_labels();
+        let warnings = mem::take(&mut self.context.warnings);
+        drop(graph);
+
+        Ok(Parsed {
+            triples: result?,
+            labels,
+            warnings,
+            extent: Position::new(1, 1).after(&source),
+            source,
+            header,
+        })
+    }
+
+    /// Reads more input onto the end of the buffer.
+    fn fill(&mut self) -> Result<(), ::std::io::Error> {
+        let read = {
+            let bytes = self.reader.fill_buf()?;
+            self.buffer.extend_from_slice(bytes);
+            bytes.len()
+        };
+
+        self.reader.consume(read);
+        self.eof = read == 0;
+
+        if self.at_start && (self.buffer.len() >= BYTE_ORDER_MARK.len() || self.eof) {
+            if self.buffer.starts_with(BYTE_ORDER_MARK) {
+                self.buffer.drain(..BYTE_ORDER_MARK.len());
+            }
+
+            self.at_start = false;
+        }
+
+        Ok(())
+    }
+}
+
+impl Job {
+    fn run(self) -> Result<Parsed, Error> {
+        let source = String::from_utf8(self.source).map_err(invalid_utf8)?;
+
+        let (triples, labels, warnings) = match self.format {
+            Format::NTriples => {
+                let mut triples = Vec::new();
+
+                for (line, text) in source.split('\n').enumerate() {
+                    let triple = parse_line(text.trim_end_matches('\r'))
+                        .map_err(|error| error.relative_to(Position::new(line + 1, 1)))?;
+
+                    triples.extend(triple);
+                }
+
+                (triples, None, Vec::new())
+            }
+
+            Format::Turtle => {
+                let mut graph = Graph::from_input(None, &source, self.header.context());
+                let triples = read_triples(&mut graph)?;
+                let context = mem::take(&mut graph.context);
+
+                (triples, context.blank_nodes.into_labels(), context.warnings)
+            }
+        };
+
+        Ok(Parsed {
+            triples,
+            labels,
+            warnings,
+            extent: Position::new(1, 1).after(&source),
+            source,
+            header: self.header,
+        })
+    }
+}
+
+/// Whether `statement` could be or contain a directive. A SPARQL style
+/// directive can follow a `.` without any space, in which case the `.` is
+/// taken to be part of a name when splitting.
+fn could_be_directive(statement: &[u8]) -> bool {
+    let keyword = |i: usize, keyword: &[u8]| {
+        statement[i..].get(..keyword.len()).is_some_and(|word| word.eq_ignore_ascii_case(keyword))
+    };
+
+    statement[0] == b'@' || keyword(0, b"PREFIX") || keyword(0, b"BASE") ||
+        statement.iter().enumerate()
+            .any(|(i, &b)| b == b'.' && (keyword(i + 1, b"PREFIX") || keyword(i + 1, b"BASE")))
+}
+
+/// Reads every triple from `graph`.
+fn read_triples(graph: &mut Graph) -> Result<Vec<Triple>, Error> {
+    let mut triples = Vec::new();
+
+    while graph.parse_next_statement()? {
+        triples.extend(graph.pending.drain(..).map(|(triple, _)| checked(triple.into_owned())));
+    }
+
+    Ok(triples)
+}
+
+fn invalid_utf8(error: ::std::string::FromUtf8Error) -> Error {
+    let valid = &error.as_bytes()[..error.utf8_error().valid_up_to()];
+    let valid = str::from_utf8(valid).unwrap_or_default();
+
+    Error::InvalidUtf8 { position: Position::new(1, 1).after(valid) }
+}
@@ -2,16 +2,16 @@
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
+#[grammar = "n3.pest"]
 pub struct TurtleParser;
 
 impl Rule {
     pub fn is_string_value(self) -> bool {
-        match self {
+        matches!(self,
             Rule::STRING_VALUE |
             Rule::SINGLE_STRING_VALUE |
             Rule::SINGLE_LONG_STRING_VALUE |
-            Rule::LONG_STRING_VALUE => true,
-            _ => false,
-        }
+            Rule::LONG_STRING_VALUE
+        )
     }
 }
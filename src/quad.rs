@@ -0,0 +1,207 @@
+//! Quads and datasets of named graphs.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+
+use iri::{BlankNode, Iri};
+use object::Object;
+use subject::Subject;
+use triple::{Triple, Triples};
+
+/// The name of a graph in a `Dataset`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GraphName {
+    /// An IRI
+    Iri(Iri),
+    /// A blank node
+    BlankNode(BlankNode),
+}
+
+impl GraphName {
+    /// The graph name for `subject`, which can't be a quoted triple or a
+    /// relative IRI.
+    pub(crate) fn from_subject(subject: Subject) -> Option<Self> {
+        match subject {
+            Subject::Iri(iri) => Some(GraphName::Iri(iri)),
+            Subject::BlankNode(node) => Some(GraphName::BlankNode(node)),
+            Subject::Triple(_) | Subject::RelativeIri(_) => None,
+        }
+    }
+}
+
+impl From<Iri> for GraphName {
+    fn from(iri: Iri) -> Self {
+        GraphName::Iri(iri)
+    }
+}
+
+impl From<BlankNode> for GraphName {
+    fn from(node: BlankNode) -> Self {
+        GraphName::BlankNode(node)
+    }
+}
+
+impl fmt::Display for GraphName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphName::Iri(ref iri) => iri.fmt(f),
+            GraphName::BlankNode(ref node) => node.fmt(f),
+        }
+    }
+}
+
+/// A triple along with the graph it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Quad {
+    /// The subject of the quad.
+    pub subject: Subject,
+    /// The predicate of the quad.
+    pub predicate: Iri,
+    /// The object of the quad.
+    pub object: Object,
+    /// The graph the quad belongs to, `None` for the default graph.
+    pub graph_name: Option<GraphName>,
+}
+
+impl Quad {
+    /// Instantiates a new Quad.
+    pub fn new(subject: Subject,
+               predicate: Iri,
+               object: Object,
+               graph_name: Option<GraphName>)
+        -> Self
+    {
+        Quad {
+            subject,
+            predicate,
+            object,
+            graph_name,
+        }
+    }
+
+    /// Drops the graph name, leaving the quad's triple.
+    pub fn into_triple(self) -> Triple {
+        Triple::new(self.subject, self.predicate, self.object)
+    }
+
+    /// Replaces each blank node in the quad, including its graph name and
+    /// those in quoted triples, with the one `map` gives for it.
+    pub(crate) fn map_blank_nodes<F: FnMut(&BlankNode) -> BlankNode>(self, map: &mut F) -> Self {
+        let graph_name = match self.graph_name {
+            Some(GraphName::BlankNode(ref node)) => Some(GraphName::BlankNode(map(node))),
+            ref graph_name => graph_name.clone(),
+        };
+        let mut triple = Triple::new(self.subject, self.predicate, self.object);
+
+        triple.map_blank_nodes(map);
+        Quad::new(triple.subject, triple.predicate, triple.object, graph_name)
+    }
+}
+
+/// A triple in the default graph.
+impl From<Triple> for Quad {
+    fn from(triple: Triple) -> Self {
+        Quad::new(triple.subject, triple.predicate, triple.object, None)
+    }
+}
+
+/// Formats the quad as an N-Quads statement.
+impl fmt::Display for Quad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.subject, self.predicate, self.object)?;
+
+        if let Some(ref graph_name) = self.graph_name {
+            write!(f, " {}", graph_name)?;
+        }
+
+        write!(f, " .")
+    }
+}
+
+/// A default graph and any number of named graphs.
+#[derive(Clone, Debug, Default)]
+pub struct Dataset {
+    /// The triples that are not in a named graph.
+    pub default_graph: Triples,
+    /// The triples of each named graph.
+    pub named_graphs: BTreeMap<GraphName, Triples>,
+}
+
+impl Dataset {
+    /// Creates an empty dataset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a quad to the graph it names, creating the graph if needed.
+    pub fn insert(&mut self, quad: Quad) {
+        let graph = match quad.graph_name.clone() {
+            Some(name) => self.named_graphs.entry(name).or_default(),
+            None => &mut self.default_graph,
+        };
+
+        graph.push(quad.into_triple());
+    }
+
+    /// The triples of the graph named `name`, or of the default graph if
+    /// `name` is `None`.
+    pub fn graph(&self, name: Option<&GraphName>) -> Option<&Triples> {
+        match name {
+            Some(name) => self.named_graphs.get(name),
+            None => Some(&self.default_graph),
+        }
+    }
+
+    /// Every triple in the dataset along with the graph it's in, starting
+    /// with the default graph.
+    pub fn quads<'a>(&'a self) -> impl Iterator<Item = Quad> + 'a {
+        let default = self.default_graph.iter().map(|t| Quad::from(t.clone()));
+        let named = self.named_graphs.iter().flat_map(|(name, triples)| {
+            triples.iter().map(move |t| {
+                let mut quad = Quad::from(t.clone());
+                quad.graph_name = Some(name.clone());
+                quad
+            })
+        });
+
+        default.chain(named)
+    }
+
+    /// The number of quads in the dataset.
+    pub fn len(&self) -> usize {
+        self.default_graph.len() +
+            self.named_graphs.values().map(|t| t.len()).sum::<usize>()
+    }
+
+    /// Whether the dataset has no quads.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FromIterator<Quad> for Dataset {
+    fn from_iter<I: IntoIterator<Item = Quad>>(iter: I) -> Self {
+        let mut dataset = Dataset::new();
+        dataset.extend(iter);
+        dataset
+    }
+}
+
+impl Extend<Quad> for Dataset {
+    fn extend<I: IntoIterator<Item = Quad>>(&mut self, iter: I) {
+        for quad in iter {
+            self.insert(quad);
+        }
+    }
+}
+
+/// Formats the dataset as an N-Quads document.
+impl fmt::Display for Dataset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for quad in self.quads() {
+            writeln!(f, "{}", quad)?;
+        }
+
+        Ok(())
+    }
+}
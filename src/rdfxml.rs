@@ -0,0 +1,1352 @@
+//! Reading and writing RDF/XML.
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::io::Read;
+
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position as XmlPosition, TextPosition};
+use xml::name::OwnedName;
+use xml::reader::{self, EventReader, ParserConfig, XmlEvent};
+
+use error::{Error, Position};
+use iri::{BlankNode, Iri};
+use labeller::{BlankNodeLabeller, BlankNodes};
+use literal::{Direction, Literal};
+use object::Object;
+use subject::Subject;
+use triple::{Triple, Triples};
+
+pub(crate) const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub(crate) const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+/// The namespace of `its:dir`, which RDF 1.2 uses for base directions.
+const ITS_NAMESPACE: &str = "http://www.w3.org/2005/11/its";
+
+/// Names in the RDF namespace that are part of the syntax rather than
+/// properties or classes.
+const CORE_SYNTAX_TERMS: &[&str] = &[
+    "RDF", "ID", "about", "parseType", "resource", "nodeID", "datatype",
+];
+/// Names that have been removed from the language.
+const OLD_TERMS: &[&str] = &["aboutEach", "aboutEachPrefix", "bagID"];
+/// Attributes that may be written without a namespace for compatibility with
+/// older documents.
+const UNQUALIFIED_ATTRIBUTES: &[&str] = &["ID", "about", "resource", "parseType", "type"];
+
+/// RDF/XML parser reading from any `Read`. The document is read one XML event
+/// at a time, and iterating over an `RdfXmlReader` yields triples as soon as
+/// they are known.
+///
+/// ```
+/// use chelone::RdfXmlReader;
+///
+/// let input = r#"
+///     <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+///              xmlns:dc="http://purl.org/dc/elements/1.1/">
+///       <rdf:Description rdf:about="http://example.org/book">
+///         <dc:title xml:lang="en">Chelone</dc:title>
+///         <dc:creator rdf:parseType="Resource">
+///           <dc:title>Aaron</dc:title>
+///         </dc:creator>
+///       </rdf:Description>
+///     </rdf:RDF>
+/// "#;
+/// let triples = RdfXmlReader::new(input.as_bytes()).parse().unwrap();
+///
+/// assert_eq!(triples.len(), 3);
+/// ```
+pub struct RdfXmlReader<R: Read> {
+    reader: EventReader<R>,
+    base: Option<Iri>,
+    stack: Vec<Element>,
+    ids: HashSet<Iri>,
+    blank_nodes: BlankNodes,
+    pending: VecDeque<Triple>,
+    finished: bool,
+}
+
+/// The `xml:base`, `xml:lang` and `its:dir` in effect for an element.
+#[derive(Clone, Debug, Default)]
+struct Scope {
+    base: Option<Iri>,
+    language: Option<String>,
+    direction: Option<Direction>,
+}
+
+#[derive(Debug)]
+struct Element {
+    scope: Scope,
+    kind: Kind,
+}
+
+/// The subject and predicate of a property element, along with the IRI of
+/// the statement if it has an `rdf:ID` and should be reified.
+#[derive(Debug)]
+struct Statement {
+    subject: Subject,
+    predicate: Iri,
+    id: Option<Iri>,
+}
+
+#[derive(Debug)]
+enum Kind {
+    /// `rdf:RDF`, which contains node elements.
+    Rdf,
+    /// A node element, which contains property elements.
+    Node {
+        subject: Subject,
+        /// The number of the next `rdf:li`.
+        li: usize,
+    },
+    /// A property element whose object is a literal, a node element, or the
+    /// resource named by its attributes if it turns out to be empty.
+    Property {
+        statement: Statement,
+        datatype: Option<Iri>,
+        resource: Option<Subject>,
+        properties: Vec<(Iri, Object)>,
+        text: String,
+        has_node: bool,
+    },
+    /// `rdf:parseType="Collection"`, whose node elements are the items of a
+    /// list.
+    Collection {
+        statement: Statement,
+        items: Vec<Subject>,
+    },
+    /// `rdf:parseType="Literal"`, whose content is kept as XML.
+    XmlLiteral {
+        statement: Statement,
+        xml: String,
+        /// The namespaces declared by each open element of the content.
+        namespaces: Vec<Vec<(String, String)>>,
+    },
+}
+
+impl<R: Read> RdfXmlReader<R> {
+    /// Creates a new `RdfXmlReader` that parses RDF/XML read from `reader`.
+    pub fn new(reader: R) -> Self {
+        let config = ParserConfig::new()
+            .whitespace_to_characters(true)
+            .cdata_to_characters(true)
+            .ignore_comments(true);
+
+        RdfXmlReader {
+            reader: EventReader::new_with_config(reader, config),
+            base: None,
+            stack: Vec::new(),
+            ids: HashSet::new(),
+            blank_nodes: BlankNodes::default(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.base = Some(iri)
+    }
+
+    /// Parse the whole input into a set of Triples.
+    pub fn parse(self) -> Result<Triples, Error> {
+        self.collect()
+    }
+
+    /// Handles the next XML event, returning `false` at the end of the
+    /// document.
+    fn parse_next_event(&mut self) -> Result<bool, Error> {
+        let event = self.reader.next().map_err(from_xml)?;
+
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                self.start_element(name, attributes)?
+            }
+            XmlEvent::EndElement { name } => self.end_element(&name)?,
+            XmlEvent::Characters(text) => self.characters(&text)?,
+            XmlEvent::EndDocument => return Ok(false),
+            _ => {}
+        }
+
+        Ok(true)
+    }
+
+    fn start_element(&mut self, name: OwnedName, attributes: Vec<OwnedAttribute>)
+        -> Result<(), Error>
+    {
+        let mut parent = self.stack.pop();
+
+        if let Some(Element { kind: Kind::XmlLiteral { ref mut xml, ref mut namespaces, .. }, .. }) = parent {
+            write_start_tag(xml, namespaces, &name, &attributes);
+            self.stack.extend(parent);
+            return Ok(())
+        }
+
+        let scope = {
+            let inherited = match parent {
+                Some(ref parent) => parent.scope.clone(),
+                None => Scope { base: self.base.clone(), ..Scope::default() },
+            };
+
+            self.scope(inherited, &attributes)?
+        };
+        let attributes = self.rdf_attributes(attributes)?;
+        let iri = self.name_iri(&name)?;
+
+        let kind = match parent.as_mut().map(|p| &mut p.kind) {
+            None if iri == rdf!("RDF") => Kind::Rdf,
+            None | Some(Kind::Rdf) => self.node_element(&iri, attributes, &scope)?,
+
+            Some(Kind::Node { subject, li }) => {
+                let subject = subject.clone();
+                self.property_element(subject, li, &iri, attributes, &scope)?
+            }
+
+            Some(Kind::Property { statement, datatype, resource, properties, text, has_node }) => {
+                let allowed = !*has_node && datatype.is_none() && resource.is_none() &&
+                    properties.is_empty() && text.trim().is_empty();
+
+                if !allowed {
+                    return Err(self.syntax("a property element can only contain one node element"))
+                }
+
+                let kind = self.node_element(&iri, attributes, &scope)?;
+                let object = node_subject(&kind).as_object();
+
+                *has_node = true;
+                self.emit_statement(statement, object);
+                kind
+            }
+
+            Some(Kind::Collection { items, .. }) => {
+                let kind = self.node_element(&iri, attributes, &scope)?;
+                items.push(node_subject(&kind).clone());
+                kind
+            }
+
+            Some(Kind::XmlLiteral { .. }) => unreachable!(),
+        };
+
+        self.stack.extend(parent);
+        self.stack.push(Element { scope, kind });
+
+        Ok(())
+    }
+
+    fn node_element(&mut self, iri: &Iri, attributes: Vec<(Iri, String)>, scope: &Scope)
+        -> Result<Kind, Error>
+    {
+        if let Some(name) = rdf_name(iri) {
+            if CORE_SYNTAX_TERMS.contains(&name) || OLD_TERMS.contains(&name) || name == "li" {
+                return Err(self.syntax(&format!("rdf:{} is not allowed as a node element", name)))
+            }
+        }
+
+        let mut subject = None;
+        let mut properties = Vec::new();
+
+        for (attribute, value) in attributes {
+            let node = match rdf_name(&attribute) {
+                Some("about") => Subject::Iri(self.resolve(&value, scope)?),
+                Some("ID") => Subject::Iri(self.id(&value, scope)?),
+                Some("nodeID") => Subject::BlankNode(self.node_id(&value)?),
+                Some("type") => {
+                    properties.push((attribute, Object::Iri(self.resolve(&value, scope)?)));
+                    continue
+                }
+                Some(name) if is_reserved(name) || name == "Description" => {
+                    return Err(self.syntax(&format!("rdf:{} is not allowed on a node element", name)))
+                }
+                _ => {
+                    properties.push((attribute, Object::Literal(scope.literal(value))));
+                    continue
+                }
+            };
+
+            if subject.is_some() {
+                return Err(self.syntax("only one of rdf:about, rdf:ID and rdf:nodeID is allowed"))
+            }
+
+            subject = Some(node);
+        }
+
+        let subject = match subject {
+            Some(subject) => subject,
+            None => Subject::BlankNode(self.generate_new_blank_node()),
+        };
+
+        if *iri != rdf!("Description") {
+            self.emit(subject.clone(), rdf!("type"), Object::Iri(iri.clone()));
+        }
+
+        for (predicate, object) in properties {
+            self.emit(subject.clone(), predicate, object);
+        }
+
+        Ok(Kind::Node { subject, li: 1 })
+    }
+
+    fn property_element(&mut self,
+                        subject: Subject,
+                        li: &mut usize,
+                        iri: &Iri,
+                        attributes: Vec<(Iri, String)>,
+                        scope: &Scope)
+        -> Result<Kind, Error>
+    {
+        let predicate = match rdf_name(iri) {
+            Some("li") => {
+                let predicate = self.rdf_iri(&format!("_{}", li))?;
+                *li += 1;
+                predicate
+            }
+            Some(name) if is_reserved(name) || name == "Description" => {
+                return Err(self.syntax(&format!("rdf:{} is not allowed as a property element", name)))
+            }
+            _ => iri.clone(),
+        };
+
+        let mut id = None;
+        let mut parse_type = None;
+        let mut resource = None;
+        let mut datatype = None;
+        let mut properties = Vec::new();
+
+        for (attribute, value) in attributes {
+            match rdf_name(&attribute) {
+                Some("ID") => id = Some(self.id(&value, scope)?),
+                Some("parseType") => parse_type = Some(value),
+                Some("datatype") => datatype = Some(self.resolve(&value, scope)?),
+                Some(name @ "resource") | Some(name @ "nodeID") => {
+                    if resource.is_some() {
+                        return Err(self.syntax("only one of rdf:resource and rdf:nodeID is allowed"))
+                    }
+
+                    resource = Some(if name == "resource" {
+                        Subject::Iri(self.resolve(&value, scope)?)
+                    } else {
+                        Subject::BlankNode(self.node_id(&value)?)
+                    });
+                }
+                Some("type") => {
+                    properties.push((attribute, Object::Iri(self.resolve(&value, scope)?)))
+                }
+                Some(name) if is_reserved(name) || name == "Description" => {
+                    return Err(self.syntax(&format!("rdf:{} is not allowed on a property element", name)))
+                }
+                _ => properties.push((attribute, Object::Literal(scope.literal(value)))),
+            }
+        }
+
+        let statement = Statement { subject, predicate, id };
+
+        let parse_type = match parse_type {
+            Some(parse_type) => parse_type,
+            None => {
+                if datatype.is_some() && (resource.is_some() || !properties.is_empty()) {
+                    return Err(self.syntax("rdf:datatype is only allowed on literal property elements"))
+                }
+
+                return Ok(Kind::Property {
+                    statement,
+                    datatype,
+                    resource,
+                    properties,
+                    text: String::new(),
+                    has_node: false,
+                })
+            }
+        };
+
+        if datatype.is_some() || resource.is_some() || !properties.is_empty() {
+            return Err(self.syntax("rdf:parseType only allows rdf:ID as well"))
+        }
+
+        Ok(match &*parse_type {
+            "Resource" => {
+                let node = self.generate_new_blank_node();
+                self.emit_statement(&statement, Object::BlankNode(node.clone()));
+
+                Kind::Node { subject: Subject::BlankNode(node), li: 1 }
+            }
+            "Collection" => Kind::Collection { statement, items: Vec::new() },
+            _ => Kind::XmlLiteral { statement, xml: String::new(), namespaces: Vec::new() },
+        })
+    }
+
+    fn end_element(&mut self, name: &OwnedName) -> Result<(), Error> {
+        let mut element = match self.stack.pop() {
+            Some(element) => element,
+            None => return Err(self.syntax("unexpected end of element")),
+        };
+
+        if let Kind::XmlLiteral { ref mut xml, ref mut namespaces, .. } = element.kind {
+            if namespaces.pop().is_some() {
+                // An element of the content ended, not the property element.
+                write_end_tag(xml, name);
+                self.stack.push(element);
+                return Ok(())
+            }
+        }
+
+        let Element { scope, kind } = element;
+
+        match kind {
+            Kind::Rdf | Kind::Node { .. } => {}
+
+            Kind::Property { statement, datatype, resource, properties, text, has_node } => {
+                if has_node {
+                    return Ok(())
+                }
+
+                if !text.is_empty() || datatype.is_some() ||
+                    (resource.is_none() && properties.is_empty())
+                {
+                    if resource.is_some() || !properties.is_empty() {
+                        return Err(self.syntax("a property element with rdf:resource, rdf:nodeID or property attributes must be empty"))
+                    }
+
+                    let literal = match datatype {
+                        Some(datatype) => Literal::new(text, None, Some(datatype)),
+                        None => scope.literal(text),
+                    };
+
+                    self.emit_statement(&statement, Object::Literal(literal));
+                    return Ok(())
+                }
+
+                let object = match resource {
+                    Some(resource) => resource,
+                    None => Subject::BlankNode(self.generate_new_blank_node()),
+                };
+
+                self.emit_statement(&statement, object.as_object());
+
+                for (predicate, value) in properties {
+                    self.emit(object.clone(), predicate, value);
+                }
+            }
+
+            Kind::Collection { statement, items } => {
+                let nodes = items.iter()
+                    .map(|_| self.generate_new_blank_node())
+                    .collect::<Vec<_>>();
+
+                let head = match nodes.first() {
+                    Some(node) => Object::BlankNode(node.clone()),
+                    None => Object::Iri(rdf!("nil")),
+                };
+
+                self.emit_statement(&statement, head);
+
+                for (index, (node, item)) in nodes.iter().zip(items).enumerate() {
+                    let rest = match nodes.get(index + 1) {
+                        Some(next) => Object::BlankNode(next.clone()),
+                        None => Object::Iri(rdf!("nil")),
+                    };
+
+                    self.emit(Subject::BlankNode(node.clone()), rdf!("first"), item.as_object());
+                    self.emit(Subject::BlankNode(node.clone()), rdf!("rest"), rest);
+                }
+            }
+
+            Kind::XmlLiteral { statement, xml, .. } => {
+                let literal = Literal::new(xml, None, Some(rdf!("XMLLiteral")));
+                self.emit_statement(&statement, Object::Literal(literal));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn characters(&mut self, characters: &str) -> Result<(), Error> {
+        match self.stack.last_mut().map(|e| &mut e.kind) {
+            Some(Kind::XmlLiteral { xml, .. }) => escape_text(xml, characters),
+            Some(Kind::Property { text, has_node: false, .. }) => text.push_str(characters),
+            _ if characters.trim().is_empty() => {}
+            _ => return Err(self.syntax("text is only allowed in property elements")),
+        }
+
+        Ok(())
+    }
+
+    /// Applies an element's `xml:base`, `xml:lang` and `its:dir` to the
+    /// scope it inherited from its parent.
+    fn scope(&self, mut scope: Scope, attributes: &[OwnedAttribute]) -> Result<Scope, Error> {
+        for attribute in attributes {
+            let namespace = attribute.name.namespace_ref();
+
+            match (namespace, &*attribute.name.local_name) {
+                (Some(XML_NAMESPACE), "base") => {
+                    scope.base = Some(self.resolve(&attribute.value, &scope)?)
+                }
+                (Some(XML_NAMESPACE), "lang") if attribute.value.is_empty() => scope.language = None,
+                (Some(XML_NAMESPACE), "lang") => scope.language = Some(attribute.value.clone()),
+                (Some(ITS_NAMESPACE), "dir") => match Direction::parse(&attribute.value) {
+                    Some(direction) => scope.direction = Some(direction),
+                    None => return Err(self.syntax("its:dir must be ltr or rtl")),
+                },
+                _ => {}
+            }
+        }
+
+        Ok(scope)
+    }
+
+    /// The attributes that RDF/XML gives a meaning to, as IRIs. Attributes in
+    /// the XML namespace, or reserved for XML, are left out.
+    fn rdf_attributes(&self, attributes: Vec<OwnedAttribute>)
+        -> Result<Vec<(Iri, String)>, Error>
+    {
+        let mut rdf_attributes = Vec::with_capacity(attributes.len());
+
+        for attribute in attributes {
+            let name = attribute.name;
+
+            let iri = match name.namespace {
+                Some(ref namespace) if namespace == XML_NAMESPACE || namespace == ITS_NAMESPACE => continue,
+                Some(_) => self.name_iri(&name)?,
+                None if name.local_name.to_lowercase().starts_with("xml") => continue,
+                None if UNQUALIFIED_ATTRIBUTES.contains(&&*name.local_name) => {
+                    self.rdf_iri(&name.local_name)?
+                }
+                None => {
+                    return Err(self.syntax(&format!("attribute `{}` has no namespace", name.local_name)))
+                }
+            };
+
+            rdf_attributes.push((iri, attribute.value));
+        }
+
+        Ok(rdf_attributes)
+    }
+
+    fn name_iri(&self, name: &OwnedName) -> Result<Iri, Error> {
+        let namespace = match name.namespace {
+            Some(ref namespace) => namespace,
+            None => {
+                return Err(self.syntax(&format!("element `{}` has no namespace", name.local_name)))
+            }
+        };
+        let iri = format!("{}{}", namespace, name.local_name);
+
+        Iri::parse(&iri).map_err(|_| Error::InvalidIri { iri, position: self.position() })
+    }
+
+    fn rdf_iri(&self, name: &str) -> Result<Iri, Error> {
+        let iri = format!("{}{}", RDF_NAMESPACE, name);
+
+        Iri::parse(&iri).map_err(|_| Error::InvalidIri { iri, position: self.position() })
+    }
+
+    fn resolve(&self, iri: &str, scope: &Scope) -> Result<Iri, Error> {
+        Iri::parse_with_base_iri(iri, scope.base.as_ref()).map_err(|_| Error::InvalidIri {
+            iri: iri.to_owned(),
+            position: self.position(),
+        })
+    }
+
+    /// Resolves an `rdf:ID`, which may only be used once for each base.
+    fn id(&mut self, id: &str, scope: &Scope) -> Result<Iri, Error> {
+        if !is_nc_name(id) {
+            return Err(self.syntax(&format!("rdf:ID `{}` is not an XML name", id)))
+        }
+
+        let iri = self.resolve(&format!("#{}", id), scope)?;
+
+        if !self.ids.insert(iri.clone()) {
+            return Err(self.syntax(&format!("rdf:ID `{}` is used more than once", id)))
+        }
+
+        Ok(iri)
+    }
+
+    fn node_id(&mut self, id: &str) -> Result<BlankNode, Error> {
+        if !is_nc_name(id) {
+            return Err(self.syntax(&format!("rdf:nodeID `{}` is not an XML name", id)))
+        }
+
+        Ok(self.blank_nodes.document(id))
+    }
+
+    /// Emits a property element's triple, and its reification if it has an
+    /// `rdf:ID`.
+    fn emit_statement(&mut self, statement: &Statement, object: Object) {
+        self.emit(statement.subject.clone(), statement.predicate.clone(), object.clone());
+
+        if let Some(ref id) = statement.id {
+            let reified = Subject::Iri(id.clone());
+            let predicate = Object::Iri(statement.predicate.clone());
+
+            self.emit(reified.clone(), rdf!("type"), Object::Iri(rdf!("Statement")));
+            self.emit(reified.clone(), rdf!("subject"), statement.subject.as_object());
+            self.emit(reified.clone(), rdf!("predicate"), predicate);
+            self.emit(reified, rdf!("object"), object);
+        }
+    }
+
+    fn emit(&mut self, subject: Subject, predicate: Iri, object: Object) {
+        self.pending.push_back(Triple::new(subject, predicate, object));
+    }
+
+    fn generate_new_blank_node(&mut self) -> BlankNode {
+        self.blank_nodes.generate()
+    }
+
+    fn position(&self) -> Position {
+        from_text_position(self.reader.position())
+    }
+
+    fn syntax(&self, message: &str) -> Error {
+        Error::Syntax { message: message.to_owned(), position: self.position() }
+    }
+}
+
+impl<R: Read> Iterator for RdfXmlReader<R> {
+    type Item = Result<Triple, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple))
+            }
+
+            if self.finished {
+                return None
+            }
+
+            match self.parse_next_event() {
+                Ok(true) => {}
+                Ok(false) => self.finished = true,
+                Err(error) => {
+                    self.finished = true;
+                    self.pending.clear();
+                    return Some(Err(error))
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> fmt::Debug for RdfXmlReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RdfXmlReader")
+            .field("reader", &"#hidden#")
+            .field("base", &self.base)
+            .field("stack", &self.stack)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl Scope {
+    fn literal(&self, value: String) -> Literal {
+        match (&self.language, self.direction) {
+            (Some(language), Some(direction)) => Literal::new_directional(value, language.clone(), direction),
+            _ => Literal::new(value, self.language.clone(), None),
+        }
+    }
+}
+
+/// Formats `Triples` as RDF/XML. Subjects with an `rdf:type` are written as
+/// typed node elements, and blank nodes that are the object of only one
+/// triple are nested inside it. Namespaces come from the given prefixes,
+/// with `ns0`, `ns1`, ... made up for any other predicates and types.
+///
+/// RDF/XML can only write predicates that end in an XML name, so writing
+/// fails if a predicate such as `<http://example.org/1>` doesn't. It has no
+/// syntax for quoted triples either.
+///
+/// ```
+/// use chelone::{Graph, Iri, RdfXmlWriter};
+///
+/// let triples = Graph::new(r#"
+///     @prefix : <http://example.org/> .
+///     :book a :Book ; :author [ :name "Aaron"@en ] .
+/// "#).unwrap().parse().unwrap();
+/// let prefix = Iri::parse("http://example.org/").unwrap();
+/// let xml = RdfXmlWriter::new(&triples).prefix("ex", prefix).to_xml().unwrap();
+///
+/// assert_eq!(xml, r#"<?xml version="1.0" encoding="utf-8"?>
+/// <rdf:RDF xmlns:ex="http://example.org/" xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+///   <ex:Book rdf:about="http://example.org/book">
+///     <ex:author>
+///       <rdf:Description>
+///         <ex:name xml:lang="en">Aaron</ex:name>
+///       </rdf:Description>
+///     </ex:author>
+///   </ex:Book>
+/// </rdf:RDF>
+/// "#);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RdfXmlWriter<'a> {
+    triples: &'a Triples,
+    prefixs: BTreeMap<String, Iri>,
+}
+
+impl<'a> RdfXmlWriter<'a> {
+    /// Creates a writer for `triples` with no prefixes.
+    pub fn new(triples: &'a Triples) -> Self {
+        RdfXmlWriter {
+            triples,
+            prefixs: BTreeMap::new(),
+        }
+    }
+
+    /// Declares `prefix` as the XML namespace prefix for `iri`.
+    pub fn prefix(mut self, prefix: &str, iri: Iri) -> Self {
+        self.prefixs.insert(prefix.to_owned(), iri);
+        self
+    }
+
+    /// Declares each of `prefixs`, such as the ones returned by
+    /// `Graph::prefixs`.
+    pub fn prefixs<I>(mut self, prefixs: I) -> Self
+        where I: IntoIterator<Item = (String, Iri)>
+    {
+        self.prefixs.extend(prefixs);
+        self
+    }
+
+    /// Writes the triples as an RDF/XML document, or returns an
+    /// `Error::Unwritable` for the first triple that RDF/XML can't express.
+    pub fn to_xml(&self) -> Result<String, Error> {
+        let mut document = Document::new(self)?;
+        document.write();
+
+        Ok(document.xml)
+    }
+}
+
+/// The state of writing one RDF/XML document.
+struct Document<'a> {
+    /// Subjects and their triples in the order they first appear.
+    subjects: Vec<(&'a Subject, Vec<&'a Triple>)>,
+    /// The index of each subject in `subjects`.
+    indices: HashMap<&'a Subject, usize>,
+    /// Prefixes by namespace.
+    namespaces: BTreeMap<String, String>,
+    /// The qualified names of predicates and types.
+    names: HashMap<&'a Iri, String>,
+    /// Blank nodes that are the object of exactly one triple.
+    nestable: HashSet<&'a BlankNode>,
+    written: HashSet<&'a Subject>,
+    node_ids: HashMap<&'a BlankNode, String>,
+    xml: String,
+}
+
+impl<'a> Document<'a> {
+    /// Groups the triples by subject and names every predicate, failing if
+    /// one can't be written as an XML name or a triple has a quoted triple
+    /// in it.
+    fn new(writer: &RdfXmlWriter<'a>) -> Result<Self, Error> {
+        let mut document = Document {
+            subjects: Vec::new(),
+            indices: HashMap::new(),
+            namespaces: BTreeMap::new(),
+            names: HashMap::new(),
+            nestable: HashSet::new(),
+            written: HashSet::new(),
+            node_ids: HashMap::new(),
+            xml: String::new(),
+        };
+
+        // Prefixes that aren't XML names, or that XML reserves, are left
+        // out, so their namespaces are given a fresh prefix instead.
+        for (prefix, iri) in &writer.prefixs {
+            let usable = prefix.is_empty() ||
+                (is_nc_name(prefix) && !prefix.to_ascii_lowercase().starts_with("xml"));
+
+            if usable && prefix != "rdf" && iri.as_str() != RDF_NAMESPACE {
+                document.namespaces.insert(iri.as_str().to_owned(), prefix.clone());
+            }
+        }
+
+        document.namespaces.insert(RDF_NAMESPACE.to_owned(), String::from("rdf"));
+
+        let directional = writer.triples.iter().any(|triple| {
+            matches!(triple.object, Object::Literal(Literal { direction: Some(_), .. }))
+        });
+
+        // `its:dir` is an attribute, so its namespace needs a prefix.
+        if directional && document.its_prefix().is_none() {
+            let prefix = if document.namespaces.values().any(|prefix| prefix == "its") {
+                document.fresh_prefix()
+            } else {
+                String::from("its")
+            };
+
+            document.namespaces.insert(ITS_NAMESPACE.to_owned(), prefix);
+        }
+
+        let mut references = HashMap::new();
+
+        for (index, triple) in writer.triples.iter().enumerate() {
+            if let (Subject::Triple(_), _) | (_, Object::Triple(_)) = (&triple.subject, &triple.object) {
+                return Err(unwritable(index, triple, "RDF/XML has no syntax for quoted triples"))
+            }
+
+            match document.indices.get(&triple.subject) {
+                Some(&index) => document.subjects[index].1.push(triple),
+                None => {
+                    document.indices.insert(&triple.subject, document.subjects.len());
+                    document.subjects.push((&triple.subject, vec![triple]));
+                }
+            }
+
+            if !document.name(&triple.predicate) {
+                return Err(unwritable(index, triple, "the predicate doesn't end in an XML name"))
+            }
+
+            if let Some(c) = forbidden_char(triple) {
+                let message = format!("U+{:04X} isn't allowed in XML 1.0", c as u32);
+                return Err(unwritable(index, triple, &message))
+            }
+
+            match triple.object {
+                Object::BlankNode(ref node) => *references.entry(node).or_insert(0) += 1,
+                Object::Iri(ref iri) if triple.predicate == rdf!("type") => {
+                    document.name(iri);
+                }
+                _ => {}
+            }
+        }
+
+        document.nestable = references.into_iter()
+            .filter(|&(_, count)| count == 1)
+            .map(|(node, _)| node)
+            .collect();
+
+        Ok(document)
+    }
+
+    /// Finds or makes up a qualified name for `iri`, returning whether it
+    /// has one.
+    fn name(&mut self, iri: &'a Iri) -> bool {
+        if self.names.contains_key(iri) {
+            return true
+        }
+
+        let iri_str = iri.as_str();
+
+        if let Some(name) = rdf_name(iri) {
+            let allowed = is_nc_name(name) && !is_reserved(name) && name != "Description";
+
+            if allowed {
+                self.names.insert(iri, format!("rdf:{}", name));
+            }
+
+            return allowed
+        }
+
+        let declared = self.namespaces.iter()
+            .filter_map(|(namespace, prefix)| {
+                let local = iri_str.strip_prefix(namespace.as_str())?;
+
+                if is_nc_name(local) {
+                    Some((prefix, local))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|&(_, local)| local.len());
+
+        if let Some((prefix, local)) = declared {
+            let name = if prefix.is_empty() {
+                local.to_owned()
+            } else {
+                format!("{}:{}", prefix, local)
+            };
+
+            self.names.insert(iri, name);
+            return true
+        }
+
+        // The longest XML name that the IRI ends with.
+        let start = iri_str.char_indices()
+            .map(|(index, _)| index)
+            .find(|&index| is_nc_name(&iri_str[index..]));
+
+        let (namespace, local) = match start {
+            Some(index) => iri_str.split_at(index),
+            None => return false,
+        };
+
+        let prefix = self.fresh_prefix();
+
+        self.names.insert(iri, format!("{}:{}", prefix, local));
+        self.namespaces.insert(namespace.to_owned(), prefix);
+        true
+    }
+
+    /// The first `nsN` prefix that isn't declared yet.
+    fn fresh_prefix(&self) -> String {
+        (0..)
+            .map(|n| format!("ns{}", n))
+            .find(|prefix| !self.namespaces.values().any(|p| p == prefix))
+            .unwrap()
+    }
+
+    /// The prefix `its:dir` is written with, if the namespace has one.
+    fn its_prefix(&self) -> Option<&str> {
+        self.namespaces.get(ITS_NAMESPACE)
+            .map(String::as_str)
+            .filter(|prefix| !prefix.is_empty())
+    }
+
+    fn write(&mut self) {
+        self.xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rdf:RDF");
+
+        let mut namespaces = self.namespaces.iter().collect::<Vec<_>>();
+        namespaces.sort_by(|a, b| a.1.cmp(b.1));
+
+        for (namespace, prefix) in namespaces {
+            if prefix.is_empty() {
+                self.xml.push_str(" xmlns=\"");
+            } else {
+                self.xml.push_str(" xmlns:");
+                self.xml.push_str(prefix);
+                self.xml.push_str("=\"");
+            }
+
+            escape_attribute(&mut self.xml, namespace);
+            self.xml.push('"');
+        }
+
+        if let Some(prefix) = self.its_prefix() {
+            self.xml.push_str(&format!(" {}:version=\"2.0\"", prefix));
+        }
+
+        self.xml.push_str(">\n");
+
+        let subjects = self.subjects.clone();
+
+        for &(subject, ref triples) in &subjects {
+            if !self.is_nestable(subject) {
+                self.write_node(subject, triples, 1, false);
+            }
+        }
+
+        // Blank nodes that only reference each other in a cycle.
+        for &(subject, ref triples) in &subjects {
+            if !self.written.contains(subject) {
+                self.write_node(subject, triples, 1, false);
+            }
+        }
+
+        self.xml.push_str("</rdf:RDF>\n");
+    }
+
+    fn is_nestable(&self, subject: &Subject) -> bool {
+        match *subject {
+            Subject::BlankNode(ref node) => self.nestable.contains(node),
+            Subject::Iri(_) | Subject::Triple(_) | Subject::RelativeIri(_) => false,
+        }
+    }
+
+    fn write_node(&mut self, subject: &'a Subject, triples: &[&'a Triple], depth: usize, nested: bool) {
+        self.written.insert(subject);
+
+        let typed = triples.iter().position(|triple| {
+            triple.predicate == rdf!("type") && match triple.object {
+                Object::Iri(ref iri) => self.names.contains_key(iri),
+                _ => false,
+            }
+        });
+        let name = match typed.map(|index| &triples[index].object) {
+            Some(Object::Iri(iri)) => self.names[iri].clone(),
+            _ => String::from("rdf:Description"),
+        };
+
+        self.indent(depth);
+        self.xml.push('<');
+        self.xml.push_str(&name);
+
+        match *subject {
+            Subject::Iri(ref iri) => {
+                self.xml.push_str(" rdf:about=\"");
+                escape_attribute(&mut self.xml, iri.as_str());
+                self.xml.push('"');
+            }
+            Subject::RelativeIri(ref relative) => {
+                self.xml.push_str(" rdf:about=\"");
+                escape_attribute(&mut self.xml, &relative.0);
+                self.xml.push('"');
+            }
+            Subject::BlankNode(ref node) if !nested => {
+                self.xml.push_str(" rdf:nodeID=\"");
+                let id = self.node_id(node);
+                self.xml.push_str(&id);
+                self.xml.push('"');
+            }
+            Subject::BlankNode(_) => {}
+            Subject::Triple(_) => unreachable!("quoted triples are rejected up front"),
+        }
+
+        if typed.is_some() && triples.len() == 1 {
+            self.xml.push_str("/>\n");
+            return
+        }
+
+        self.xml.push_str(">\n");
+
+        for (index, triple) in triples.iter().enumerate() {
+            if Some(index) != typed {
+                self.write_property(triple, depth + 1);
+            }
+        }
+
+        self.indent(depth);
+        self.xml.push_str("</");
+        self.xml.push_str(&name);
+        self.xml.push_str(">\n");
+    }
+
+    fn write_property(&mut self, triple: &'a Triple, depth: usize) {
+        let name = self.names[&triple.predicate].clone();
+
+        self.indent(depth);
+        self.xml.push('<');
+        self.xml.push_str(&name);
+
+        match triple.object {
+            Object::Iri(ref iri) => {
+                self.xml.push_str(" rdf:resource=\"");
+                escape_attribute(&mut self.xml, iri.as_str());
+                self.xml.push_str("\"/>\n");
+                return
+            }
+            Object::RelativeIri(ref relative) => {
+                self.xml.push_str(" rdf:resource=\"");
+                escape_attribute(&mut self.xml, &relative.0);
+                self.xml.push_str("\"/>\n");
+                return
+            }
+            Object::BlankNode(ref node) => {
+                let subject = self.indices.get(&Subject::BlankNode(node.clone()))
+                    .map(|&index| &self.subjects[index])
+                    .filter(|&&(subject, _)| !self.written.contains(subject))
+                    .cloned();
+
+                match subject {
+                    Some((subject, ref triples)) if self.nestable.contains(node) => {
+                        self.xml.push_str(">\n");
+                        self.write_node(subject, triples, depth + 1, true);
+                        self.indent(depth);
+                    }
+                    _ => {
+                        self.xml.push_str(" rdf:nodeID=\"");
+                        let id = self.node_id(node);
+                        self.xml.push_str(&id);
+                        self.xml.push_str("\"/>\n");
+                        return
+                    }
+                }
+            }
+            Object::Literal(ref literal) => self.write_literal(literal),
+            Object::Triple(_) => unreachable!("quoted triples are rejected up front"),
+        }
+
+        self.xml.push_str("</");
+        self.xml.push_str(&name);
+        self.xml.push_str(">\n");
+    }
+
+    fn write_literal(&mut self, literal: &Literal) {
+        if let Some(ref tag) = literal.language_tag {
+            self.xml.push_str(" xml:lang=\"");
+            escape_attribute(&mut self.xml, tag);
+
+            if let Some(direction) = literal.direction {
+                let attribute = format!("\" {}:dir=\"", self.its_prefix().unwrap_or("its"));
+                self.xml.push_str(&attribute);
+                self.xml.push_str(direction.as_str());
+            }
+
+            self.xml.push_str("\">");
+        } else if literal.iri == rdf!("XMLLiteral") && self.is_canonical_xml(&literal.value) {
+            self.xml.push_str(" rdf:parseType=\"Literal\">");
+            self.xml.push_str(&literal.value);
+            return
+        } else if literal.iri != xsd!("string") {
+            self.xml.push_str(" rdf:datatype=\"");
+            escape_attribute(&mut self.xml, literal.iri.as_str());
+            self.xml.push_str("\">");
+        } else {
+            self.xml.push('>');
+        }
+
+        escape_text(&mut self.xml, &literal.value);
+    }
+
+    /// Whether `value` is XML content that `RdfXmlReader` would read back
+    /// as the same literal, in the default namespace of the document.
+    fn is_canonical_xml(&self, value: &str) -> bool {
+        let default = self.namespaces.iter()
+            .find(|&(_, prefix)| prefix.is_empty())
+            .map(|(namespace, _)| namespace.as_str());
+
+        canonical_xml(value, default).is_some_and(|canonical| canonical == value)
+    }
+
+    /// The `rdf:nodeID` of `node`. Blank node labels aren't always XML
+    /// names, so every node is given a new one.
+    fn node_id(&mut self, node: &'a BlankNode) -> String {
+        let next = self.node_ids.len();
+
+        self.node_ids.entry(node)
+            .or_insert_with(|| format!("b{}", next))
+            .clone()
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.xml.push_str("  ");
+        }
+    }
+}
+
+/// The subject of a node element that has just been started.
+fn node_subject(kind: &Kind) -> &Subject {
+    match *kind {
+        Kind::Node { ref subject, .. } => subject,
+        _ => unreachable!("node elements are always `Kind::Node`"),
+    }
+}
+
+/// The first character of `triple` that XML 1.0 doesn't allow. Blank node
+/// labels are replaced by `rdf:nodeID`s, so they aren't checked.
+fn forbidden_char(triple: &Triple) -> Option<char> {
+    let subject = match triple.subject {
+        Subject::Iri(ref iri) => iri.as_str(),
+        Subject::RelativeIri(ref relative) => &relative.0,
+        Subject::BlankNode(_) | Subject::Triple(_) => "",
+    };
+    let object = match triple.object {
+        Object::Iri(ref iri) => vec![iri.as_str()],
+        Object::RelativeIri(ref relative) => vec![&relative.0[..]],
+        Object::Literal(ref literal) => vec![
+            &literal.value[..],
+            literal.iri.as_str(),
+            literal.language_tag.as_ref().map_or("", |tag| &tag[..]),
+        ],
+        Object::BlankNode(_) | Object::Triple(_) => Vec::new(),
+    };
+
+    let is_xml_char = |c: char| matches!(c,
+        '\u{9}' | '\u{A}' | '\u{D}' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..='\u{10FFFF}'
+    );
+
+    Some(subject).into_iter()
+        .chain(Some(triple.predicate.as_str()))
+        .chain(object)
+        .flat_map(str::chars)
+        .find(|&c| !is_xml_char(c))
+}
+
+/// The content `value` as `RdfXmlReader` reads it from an
+/// `rdf:parseType="Literal"` property element, with `default` as the
+/// default namespace, or `None` if it isn't well-formed XML content.
+fn canonical_xml(value: &str, default: Option<&str>) -> Option<String> {
+    let mut document = String::from("<literal");
+
+    if let Some(namespace) = default {
+        document.push_str(" xmlns=\"");
+        escape_attribute(&mut document, namespace);
+        document.push('"');
+    }
+
+    document.push('>');
+    document.push_str(value);
+    document.push_str("</literal>");
+
+    let config = ParserConfig::new()
+        .whitespace_to_characters(true)
+        .cdata_to_characters(true)
+        .ignore_comments(true);
+    let mut xml = String::new();
+    let mut namespaces = Vec::new();
+    let mut depth = 0;
+
+    for event in EventReader::new_with_config(document.as_bytes(), config) {
+        match event.ok()? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                if depth > 0 {
+                    write_start_tag(&mut xml, &mut namespaces, &name, &attributes);
+                }
+
+                depth += 1;
+            }
+            XmlEvent::EndElement { name } => {
+                depth -= 1;
+
+                if depth > 0 {
+                    namespaces.pop();
+                    write_end_tag(&mut xml, &name);
+                }
+            }
+            XmlEvent::Characters(text) => escape_text(&mut xml, &text),
+            _ => {}
+        }
+    }
+
+    Some(xml)
+}
+
+/// The error for the triple at `index` that can't be written.
+fn unwritable(index: usize, triple: &Triple, message: &str) -> Error {
+    Error::Unwritable {
+        triple: triple.to_string(),
+        message: message.to_owned(),
+        index,
+    }
+}
+
+/// The local name of an IRI in the RDF namespace.
+fn rdf_name(iri: &Iri) -> Option<&str> {
+    iri.as_str().strip_prefix(RDF_NAMESPACE)
+}
+
+fn is_reserved(name: &str) -> bool {
+    CORE_SYNTAX_TERMS.contains(&name) || OLD_TERMS.contains(&name) || name == "li"
+}
+
+/// Whether `name` is an XML name without a colon, as `rdf:ID` and
+/// `rdf:nodeID` have to be.
+fn is_nc_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_alphanumeric() || "._-\u{B7}".contains(c))
+}
+
+fn from_text_position(position: TextPosition) -> Position {
+    Position::new(position.row as usize + 1, position.column as usize + 1)
+}
+
+fn from_xml(error: reader::Error) -> Error {
+    let position = from_text_position(error.position());
+
+    match *error.kind() {
+        reader::ErrorKind::Io(ref error) => Error::from_io(error, position),
+        reader::ErrorKind::Utf8(_) => Error::InvalidUtf8 { position },
+        _ => Error::Syntax { message: error.msg().to_owned(), position },
+    }
+}
+
+/// Writes the start tag of an element inside an XML literal in exclusive
+/// canonical form, declaring only the namespaces it uses that aren't already
+/// declared by an enclosing element of the literal.
+fn write_start_tag(xml: &mut String,
+                   namespaces: &mut Vec<Vec<(String, String)>>,
+                   name: &OwnedName,
+                   attributes: &[OwnedAttribute])
+{
+    let mut used = vec![(
+        name.prefix.clone().unwrap_or_default(),
+        name.namespace.clone().unwrap_or_default(),
+    )];
+
+    for attribute in attributes {
+        if let (Some(prefix), Some(namespace)) = (&attribute.name.prefix, &attribute.name.namespace) {
+            if namespace != XML_NAMESPACE {
+                used.push((prefix.clone(), namespace.clone()));
+            }
+        }
+    }
+
+    used.sort();
+    used.dedup();
+
+    let declared = used.into_iter().filter(|(prefix, namespace)| {
+        let enclosing = namespaces.iter().rev()
+            .flat_map(|declared| declared.iter())
+            .find(|(declared, _)| declared == prefix);
+
+        match enclosing {
+            Some((_, enclosing)) => enclosing != namespace,
+            None => !namespace.is_empty(),
+        }
+    }).collect::<Vec<_>>();
+
+    xml.push('<');
+    push_name(xml, name);
+
+    for (prefix, namespace) in &declared {
+        if prefix.is_empty() {
+            xml.push_str(" xmlns=\"");
+        } else {
+            xml.push_str(" xmlns:");
+            xml.push_str(prefix);
+            xml.push_str("=\"");
+        }
+
+        escape_attribute(xml, namespace);
+        xml.push('"');
+    }
+
+    let mut attributes = attributes.iter().collect::<Vec<_>>();
+    attributes.sort_by(|a, b| {
+        (&a.name.namespace, &a.name.local_name).cmp(&(&b.name.namespace, &b.name.local_name))
+    });
+
+    for attribute in attributes {
+        xml.push(' ');
+        push_name(xml, &attribute.name);
+        xml.push_str("=\"");
+        escape_attribute(xml, &attribute.value);
+        xml.push('"');
+    }
+
+    xml.push('>');
+    namespaces.push(declared);
+}
+
+fn write_end_tag(xml: &mut String, name: &OwnedName) {
+    xml.push_str("</");
+    push_name(xml, name);
+    xml.push('>');
+}
+
+fn push_name(xml: &mut String, name: &OwnedName) {
+    if let Some(ref prefix) = name.prefix {
+        xml.push_str(prefix);
+        xml.push(':');
+    }
+
+    xml.push_str(&name.local_name);
+}
+
+fn escape_text(xml: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '\r' => xml.push_str("&#xD;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+fn escape_attribute(xml: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '"' => xml.push_str("&quot;"),
+            '\t' => xml.push_str("&#x9;"),
+            '\n' => xml.push_str("&#xA;"),
+            '\r' => xml.push_str("&#xD;"),
+            c => xml.push(c),
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Incremental parsing of Turtle from any `BufRead`.
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::mem;
+use std::str;
+
+use pest::Parser;
+
+use error::{Error, Position};
+use iri::Iri;
+use labeller::BlankNodeLabeller;
+use limits::Limits;
+use options::ParserOptions;
+use parser::{Rule, TurtleParser};
+use triple::{Triple, Triples};
+use {checked, Context, Graph, BYTE_ORDER_MARK};
+
+/// The minimum number of bytes read from the underlying reader at a time.
+const MIN_READ: usize = 8 * 1024;
+
+/// Turtle parser reading from a `BufRead`. Only the statement currently being
+/// parsed is held in memory, each statement is parsed as soon as it has been
+/// read, and its triples are yielded by iterating over the `TurtleReader`.
+///
+/// ```
+/// use chelone::TurtleReader;
+///
+/// let input = "<http://a.example/s> <http://a.example/p> 1, 2 .".as_bytes();
+/// let triples = TurtleReader::new(input).parse().unwrap();
+///
+/// assert_eq!(triples.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct TurtleReader<R> {
+    reader: R,
+    buffer: String,
+    /// Where `buffer` starts in the document.
+    start: Position,
+    context: Context,
+    pending: VecDeque<Triple>,
+    warnings: Vec<Error>,
+    at_start: bool,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> TurtleReader<R> {
+    /// Creates a new `TurtleReader` that parses turtle read from `reader`.
+    pub fn new(reader: R) -> Self {
+        TurtleReader {
+            reader,
+            buffer: String::new(),
+            start: Position::new(1, 1),
+            context: Context::default(),
+            pending: VecDeque::default(),
+            warnings: Vec::new(),
+            at_start: true,
+            eof: false,
+            finished: false,
+        }
+    }
+
+    /// Sets how blank nodes are labelled. See `BlankNodeLabeller`.
+    pub fn set_labeller<L: BlankNodeLabeller + 'static>(&mut self, labeller: L) {
+        self.context.blank_nodes.set_labeller(labeller)
+    }
+
+    /// Sets the limits the document has to be within. See `Limits`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.context.options.limits = limits
+    }
+
+    /// Sets the options to parse with, including their base if they have
+    /// one. See `ParserOptions`.
+    pub fn set_options(&mut self, options: ParserOptions) {
+        if let Some(ref base) = options.base {
+            self.context.base = Some(base.clone());
+        }
+
+        self.context.options = options;
+    }
+
+    /// The warnings from the statements parsed so far. See `ParserOptions`.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Sets the initial base url to resolve relative urls against.
+    pub fn set_base(&mut self, iri: Iri) {
+        self.context.base = Some(iri)
+    }
+
+    /// Parse the whole input into a set of Triples.
+    pub fn parse(self) -> Result<Triples, Error> {
+        self.collect()
+    }
+
+    /// Parses the next statement, returning `false` once the end of the
+    /// input has been reached.
+    fn parse_next_statement(&mut self) -> Result<bool, Error> {
+        loop {
+            let limits = self.context.options.limits;
+
+            if let Some(offset) = limits.too_deep(&self.buffer, true) {
+                return Err(limits.depth_error(&self.buffer, offset).relative_to(self.start))
+            }
+
+            let end = match TurtleParser::parse(Rule::statementChunk, &self.buffer) {
+                Ok(parsed) => {
+                    let mut input = parsed.flatten().peekable();
+                    let (rule, end) = match input.peek() {
+                        Some(pair) => (pair.as_rule(), pair.as_span().end()),
+                        None => (Rule::EOI, self.buffer.len()),
+                    };
+
+                    if rule == Rule::statement {
+                        let context = mem::take(&mut self.context);
+                        let mut graph = Graph::from_input(Some(input), &self.buffer, context);
+                        let result = graph.parse_statement();
+
+                        self.context = graph.context;
+
+                        let start = self.start;
+                        let warnings = self.context.warnings.drain(..);
+                        self.warnings.extend(warnings.map(|warning| warning.relative_to(start)));
+
+                        result.map_err(|e| e.relative_to(self.start))?;
+                        self.pending.extend(graph.pending.into_iter().map(|(triple, _)| checked(triple.into_owned())));
+                        self.consume(end);
+
+                        return Ok(true)
+                    }
+
+                    // Only whitespace and comments are left.
+                    end
+                }
+
+                Err(error) => {
+                    let offset = match error.location {
+                        pest::error::InputLocation::Pos(offset) |
+                        pest::error::InputLocation::Span((_, offset)) => offset,
+                    };
+
+                    // The parser only gave up because it ran out of input, so
+                    // the statement may continue past the end of the buffer.
+                    if offset < self.buffer.len() || self.eof {
+                        return Err(Error::from_pest(error).relative_to(self.start))
+                    }
+
+                    0
+                }
+            };
+
+            if self.eof {
+                return Ok(false)
+            }
+
+            self.consume(end);
+            self.fill_buffer()?;
+        }
+    }
+
+    /// Reads at least `MIN_READ` bytes, or as many bytes as are already
+    /// buffered, so long statements don't get parsed over and over again.
+    fn fill_buffer(&mut self) -> Result<(), Error> {
+        let wanted = self.buffer.len().max(MIN_READ);
+        let mut read = 0;
+        let mut line = Vec::new();
+
+        while read < wanted {
+            line.clear();
+
+            let bytes = self.reader.read_until(b'\n', &mut line)
+                .map_err(|e| Error::from_io(&e, self.end_position()))?;
+
+            if bytes == 0 {
+                self.eof = true;
+                break
+            }
+
+            // Lines are split on `\n` which can't be part of a multi-byte
+            // sequence, so each line can be checked on its own.
+            match str::from_utf8(&line) {
+                Ok(mut line) => {
+                    if self.at_start {
+                        line = line.trim_start_matches(BYTE_ORDER_MARK);
+                    }
+
+                    self.buffer.push_str(line);
+                }
+
+                Err(error) => {
+                    let valid = str::from_utf8(&line[..error.valid_up_to()])
+                        .unwrap_or_default();
+                    let mut position = self.end_position();
+
+                    position.column += valid.chars().count();
+
+                    return Err(Error::InvalidUtf8 { position })
+                }
+            }
+
+            self.at_start = false;
+            read += bytes;
+        }
+
+        Ok(())
+    }
+
+    /// Drops the first `end` bytes of the buffer.
+    fn consume(&mut self, end: usize) {
+        self.start = self.position_at(end);
+        self.buffer.drain(..end);
+    }
+
+    /// The position just after the end of the buffer.
+    fn end_position(&self) -> Position {
+        self.position_at(self.buffer.len())
+    }
+
+    /// The position of the byte `offset` in the buffer.
+    fn position_at(&self, offset: usize) -> Position {
+        self.start.after(&self.buffer[..offset])
+    }
+}
+
+impl<R: BufRead> Iterator for TurtleReader<R> {
+    type Item = Result<Triple, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.pending.pop_front() {
+                return Some(Ok(triple))
+            }
+
+            if self.finished {
+                return None
+            }
+
+            match self.parse_next_statement() {
+                Ok(true) => {}
+                Ok(false) => self.finished = true,
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error))
+                }
+            }
+        }
+    }
+}
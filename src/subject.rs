@@ -2,8 +2,9 @@
 
 use std::fmt;
 
-use iri::{BlankNode, Iri};
+use iri::{BlankNode, Iri, RelativeIri};
 use object::Object;
+use triple::Triple;
 
 /// The subject of a Triple.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -12,24 +13,37 @@ pub enum Subject {
     Iri(Iri),
     /// A blank node
     BlankNode(BlankNode),
+    /// A quoted triple, written `<< s p o >>`.
+    Triple(Box<Triple>),
+    /// A relative IRI that hasn't been resolved.
+    RelativeIri(RelativeIri),
 }
 
 impl Subject {
     /// Is the subject a blank node
     pub fn is_blank_node(&self) -> bool {
-        match *self {
-            Subject::BlankNode(_) => true,
-            _ => false,
-        }
+        matches!(*self, Subject::BlankNode(_))
     }
 
     pub(crate) fn as_object(&self) -> Object {
         match *self {
             Subject::BlankNode(ref b) => Object::BlankNode(b.clone()),
             Subject::Iri(ref i) => Object::Iri(i.clone()),
+            Subject::Triple(ref t) => Object::Triple(t.clone()),
+            Subject::RelativeIri(ref r) => Object::RelativeIri(r.clone()),
         }
     }
 
+    /// Resolves the subject against `base` if it's a relative IRI, including
+    /// those in quoted triples.
+    pub fn resolve(self, base: &Iri) -> Result<Self, url::ParseError> {
+        Ok(match self {
+            Subject::RelativeIri(relative) => Subject::Iri(relative.resolve(base)?),
+            Subject::Triple(triple) => Subject::from(triple.resolve(base)?),
+            subject => subject,
+        })
+    }
+
     pub(crate) fn into_blank_node(self) -> Option<BlankNode> {
         match self {
             Subject::BlankNode(b) => Some(b),
@@ -38,6 +52,12 @@ impl Subject {
     }
 }
 
+impl From<Triple> for Subject {
+    fn from(triple: Triple) -> Self {
+        Subject::Triple(Box::new(triple))
+    }
+}
+
 impl From<Iri> for Subject {
     fn from(iri: Iri) -> Self {
         Subject::Iri(iri)
@@ -49,6 +69,8 @@ impl fmt::Display for Subject {
         match *self {
             Subject::Iri(ref iri) => iri.fmt(f),
             Subject::BlankNode(ref node) => node.fmt(f),
+            Subject::Triple(ref triple) => triple.fmt_quoted(f),
+            Subject::RelativeIri(ref relative) => relative.fmt(f),
         }
     }
 }
@@ -0,0 +1,264 @@
+//! Writing datasets as TriG.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use iri::{Iri, RelativeIri};
+use literal::{is_turtle_decimal, is_turtle_double, is_valid_xsd_integer, Literal};
+use object::Object;
+use quad::{Dataset, GraphName};
+use subject::Subject;
+use triple::{Triple, Triples};
+
+/// The objects of each of a subject's predicates.
+type Predicates<'a> = Vec<(&'a Iri, Vec<&'a Object>)>;
+
+/// Formats a `Dataset` as pretty printed TriG. Triples are grouped by subject
+/// and predicate, IRIs are shortened with the given prefixes, and literals
+/// are written in their short form where Turtle has one.
+///
+/// ```
+/// use chelone::{Graph, Iri, TriGWriter};
+///
+/// let graph = Graph::trig(r#"
+///     @prefix : <http://example.org/> .
+///     :g { :a :b :c, :d ; :e 1 . }
+/// "#).unwrap();
+/// let dataset = graph.parse_dataset().unwrap();
+/// let prefix = Iri::parse("http://example.org/").unwrap();
+/// let trig = TriGWriter::new(&dataset).prefix("", prefix).to_string();
+///
+/// assert_eq!(trig, "@prefix : <http://example.org/> .\n\
+///                   \n\
+///                   :g {\n    \
+///                       :a :b :c, :d ;\n        \
+///                           :e 1 .\n\
+///                   }\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TriGWriter<'a> {
+    dataset: &'a Dataset,
+    prefixs: BTreeMap<String, Iri>,
+    base: Option<Iri>,
+}
+
+impl<'a> TriGWriter<'a> {
+    /// Creates a writer for `dataset` with no prefixes.
+    pub fn new(dataset: &'a Dataset) -> Self {
+        TriGWriter {
+            dataset,
+            prefixs: BTreeMap::new(),
+            base: None,
+        }
+    }
+
+    /// Declares `prefix`, without the trailing `:`, as short for `iri`.
+    pub fn prefix(mut self, prefix: &str, iri: Iri) -> Self {
+        self.prefixs.insert(prefix.to_owned(), iri);
+        self
+    }
+
+    /// Declares each of `prefixs`, such as the ones returned by
+    /// `Graph::prefixs`.
+    pub fn prefixs<I>(mut self, prefixs: I) -> Self
+        where I: IntoIterator<Item = (String, Iri)>
+    {
+        self.prefixs.extend(prefixs);
+        self
+    }
+
+    /// Resolves relative IRIs against `iri` as they're written. Without a
+    /// base they're written as they are, relative to wherever the output
+    /// ends up.
+    pub fn base(mut self, iri: Iri) -> Self {
+        self.base = Some(iri);
+        self
+    }
+
+    fn write_triples(&self, f: &mut fmt::Formatter, triples: &Triples, indent: &str)
+        -> fmt::Result
+    {
+        // Subjects and their predicates in the order they first appear,
+        // along with where each one is so grouping doesn't search them.
+        let mut subjects: Vec<(&Subject, Predicates)> = Vec::new();
+        let mut subject_indices = HashMap::new();
+        let mut predicate_indices = HashMap::new();
+
+        for triple in triples.iter() {
+            let subject = *subject_indices.entry(&triple.subject).or_insert_with(|| {
+                subjects.push((&triple.subject, Vec::new()));
+                subjects.len() - 1
+            });
+            let predicates = &mut subjects[subject].1;
+            let predicate = *predicate_indices.entry((subject, &triple.predicate)).or_insert_with(|| {
+                predicates.push((&triple.predicate, Vec::new()));
+                predicates.len() - 1
+            });
+
+            predicates[predicate].1.push(&triple.object);
+        }
+
+        for (index, (subject, predicates)) in subjects.into_iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", indent)?;
+            self.write_subject(f, subject)?;
+
+            for (index, (predicate, objects)) in predicates.into_iter().enumerate() {
+                if index == 0 {
+                    f.write_str(" ")?;
+                } else {
+                    write!(f, " ;\n{}    ", indent)?;
+                }
+
+                self.write_predicate(f, predicate)?;
+
+                for (index, object) in objects.into_iter().enumerate() {
+                    f.write_str(if index == 0 { " " } else { ", " })?;
+                    self.write_object(f, object)?;
+                }
+            }
+
+            writeln!(f, " .")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_graph_name(&self, f: &mut fmt::Formatter, name: &GraphName) -> fmt::Result {
+        match *name {
+            GraphName::Iri(ref iri) => self.write_iri(f, iri),
+            GraphName::BlankNode(ref node) => write!(f, "{}", node),
+        }
+    }
+
+    fn write_subject(&self, f: &mut fmt::Formatter, subject: &Subject) -> fmt::Result {
+        match *subject {
+            Subject::Iri(ref iri) => self.write_iri(f, iri),
+            Subject::BlankNode(ref node) => write!(f, "{}", node),
+            Subject::Triple(ref triple) => self.write_quoted_triple(f, triple),
+            Subject::RelativeIri(ref relative) => self.write_relative_iri(f, relative),
+        }
+    }
+
+    fn write_predicate(&self, f: &mut fmt::Formatter, predicate: &Iri) -> fmt::Result {
+        if *predicate == rdf!("type") {
+            f.write_str("a")
+        } else {
+            self.write_iri(f, predicate)
+        }
+    }
+
+    fn write_object(&self, f: &mut fmt::Formatter, object: &Object) -> fmt::Result {
+        match *object {
+            Object::Iri(ref iri) => self.write_iri(f, iri),
+            Object::BlankNode(ref node) => write!(f, "{}", node),
+            Object::Literal(ref literal) => self.write_literal(f, literal),
+            Object::Triple(ref triple) => self.write_quoted_triple(f, triple),
+            Object::RelativeIri(ref relative) => self.write_relative_iri(f, relative),
+        }
+    }
+
+    fn write_quoted_triple(&self, f: &mut fmt::Formatter, triple: &Triple) -> fmt::Result {
+        f.write_str("<< ")?;
+        self.write_subject(f, &triple.subject)?;
+        f.write_str(" ")?;
+        self.write_predicate(f, &triple.predicate)?;
+        f.write_str(" ")?;
+        self.write_object(f, &triple.object)?;
+        f.write_str(" >>")
+    }
+
+    fn write_literal(&self, f: &mut fmt::Formatter, literal: &Literal) -> fmt::Result {
+        let value = &*literal.value;
+        let bare = (literal.iri == xsd!("integer") && is_valid_xsd_integer(value)) ||
+            (literal.iri == xsd!("decimal") && is_turtle_decimal(value)) ||
+            (literal.iri == xsd!("double") && is_turtle_double(value)) ||
+            (literal.iri == xsd!("boolean") && (value == "true" || value == "false"));
+
+        if bare {
+            return f.write_str(value)
+        }
+
+        literal.fmt_value(f)?;
+
+        if let Some(tag) = literal.tag() {
+            write!(f, "@{}", tag)
+        } else if literal.iri != xsd!("string") {
+            f.write_str("^^")?;
+            self.write_iri(f, &literal.iri)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_relative_iri(&self, f: &mut fmt::Formatter, relative: &RelativeIri) -> fmt::Result {
+        match self.base.as_ref().and_then(|base| relative.resolve(base).ok()) {
+            Some(iri) => self.write_iri(f, &iri),
+            None => write!(f, "{}", relative),
+        }
+    }
+
+    /// Writes `iri` as a prefixed name using the longest matching namespace,
+    /// or in full if none match.
+    fn write_iri(&self, f: &mut fmt::Formatter, iri: &Iri) -> fmt::Result {
+        let name = self.prefixs.iter()
+            .filter_map(|(prefix, namespace)| {
+                let local = iri.as_str().strip_prefix(namespace.as_str())?;
+
+                if is_local_name(local) {
+                    Some((prefix, local))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|&(_, local)| local.len());
+
+        match name {
+            Some((prefix, local)) => write!(f, "{}:{}", prefix, local),
+            None => write!(f, "{}", iri),
+        }
+    }
+}
+
+impl<'a> fmt::Display for TriGWriter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (prefix, iri) in &self.prefixs {
+            writeln!(f, "@prefix {}: {} .", prefix, iri)?;
+        }
+
+        let mut first = self.prefixs.is_empty();
+
+        if !self.dataset.default_graph.is_empty() {
+            if !first {
+                writeln!(f)?;
+            }
+
+            self.write_triples(f, &self.dataset.default_graph, "")?;
+            first = false;
+        }
+
+        for (name, triples) in &self.dataset.named_graphs {
+            if !first {
+                writeln!(f)?;
+            }
+
+            self.write_graph_name(f, name)?;
+            writeln!(f, " {{")?;
+            self.write_triples(f, triples, "    ")?;
+            writeln!(f, "}}")?;
+            first = false;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `local` can be written after a prefix without any escapes. This
+/// is stricter than Turtle's `PN_LOCAL`.
+fn is_local_name(local: &str) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    !local.starts_with('-') && local.chars().all(valid)
+}
@@ -2,7 +2,7 @@
 
 use std::fmt;
 use std::ops::{Deref, DerefMut};
-use std::iter::IntoIterator;
+use std::iter::{FromIterator, IntoIterator};
 use std::collections::HashMap;
 
 use itertools::Itertools;
@@ -89,6 +89,11 @@ impl<'a> TripleSearcher<'a> {
 }
 
 impl Triples {
+    /// Resolves the relative IRIs in every triple against `base`. See
+    /// `Triple::resolve`.
+    pub fn resolve(self, base: &Iri) -> Result<Self, url::ParseError> {
+        self.into_iter().map(|triple| triple.resolve(base)).collect()
+    }
 
     /// Determines if two graphs are isomorphic.
     pub fn is_isomorphic(&mut self, other: &mut Self) -> bool {
@@ -157,7 +162,7 @@ impl Triples {
 
                 for triple in group {
                     triple.predicate.to_string().hash(&mut hash);
-                    triple.object.to_string().hash(&mut hash);
+                    unlabelled(&triple.object).to_string().hash(&mut hash);
                 }
 
                 hashed.insert(key.into_blank_node().unwrap(), format!("{:x}", hash.finish()));
@@ -185,6 +190,16 @@ impl Triples {
                     }
                 }
 
+                // Blank nodes that only reach each other, such as a cycle,
+                // never get hashed, so they're all treated alike.
+                if items.iter().all(|(k, _)| hashed.contains_key(k)) {
+                    for triple in &self.0 {
+                        if let Subject::BlankNode(ref node) = triple.subject {
+                            hashed.entry(node.clone()).or_insert_with(|| String::from("cyclic"));
+                        }
+                    }
+                }
+
                 for (k, v) in items {
                     hashed.insert(k, v);
                 }
@@ -192,19 +207,44 @@ impl Triples {
         }
 
         for triple in &mut self.0 {
-            if triple.subject.is_blank_node() {
-                let node = triple.subject.clone().into_blank_node().unwrap();
-                triple.subject = Subject::BlankNode(BlankNode(hashed[&node].clone()));
-            }
-
-            if triple.object.is_blank_node() {
-                let node = triple.object.clone().into_blank_node().unwrap();
-                triple.object = Object::BlankNode(BlankNode(hashed[&node].clone()));
-            }
+            relabel(triple, &hashed);
         }
     }
 }
 
+/// Replaces the blank nodes in `triple`, including those in quoted triples,
+/// with their hashes. Blank nodes that are only used inside quoted triples
+/// have no hash, so they're all treated alike.
+fn relabel(triple: &mut Triple, hashed: &HashMap<BlankNode, String>) {
+    let label = |node: &BlankNode| {
+        BlankNode(hashed.get(node).cloned().unwrap_or_else(|| String::from("quoted")))
+    };
+
+    match triple.subject {
+        Subject::BlankNode(ref mut node) => *node = label(node),
+        Subject::Triple(ref mut quoted) => relabel(quoted, hashed),
+        Subject::Iri(_) | Subject::RelativeIri(_) => {}
+    }
+
+    match triple.object {
+        Object::BlankNode(ref mut node) => *node = label(node),
+        Object::Triple(ref mut quoted) => relabel(quoted, hashed),
+        Object::Iri(_) | Object::Literal(_) | Object::RelativeIri(_) => {}
+    }
+}
+
+/// `object` with the labels of any blank nodes in quoted triples removed,
+/// so it can be hashed without depending on them.
+fn unlabelled(object: &Object) -> Object {
+    let mut object = object.clone();
+
+    if let Object::Triple(ref mut quoted) = object {
+        relabel(quoted, &HashMap::new());
+    }
+
+    object
+}
+
 
 impl IntoIterator for Triples {
     type Item = Triple;
@@ -215,6 +255,18 @@ impl IntoIterator for Triples {
     }
 }
 
+impl FromIterator<Triple> for Triples {
+    fn from_iter<I: IntoIterator<Item = Triple>>(iter: I) -> Self {
+        Triples(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Triple> for Triples {
+    fn extend<I: IntoIterator<Item = Triple>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
 impl Deref for Triples {
     type Target = Vec<Triple>;
 
@@ -233,7 +285,7 @@ impl fmt::Display for Triples {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
         for triple in &self.0 {
-            write!(f, "{}\n", triple)?;
+            writeln!(f, "{}", triple)?;
         }
 
         Ok(())
@@ -241,7 +293,7 @@ impl fmt::Display for Triples {
 }
 
 /// A single triple containing a subject, predicate, and object.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Triple {
     /// The subject of the triple.
     pub subject: Subject,
@@ -260,6 +312,53 @@ impl Triple {
             object
         }
     }
+
+    /// Resolves any relative IRIs in the triple against `base`, such as
+    /// when merging triples parsed without a base into another graph.
+    pub fn resolve(self, base: &Iri) -> Result<Self, url::ParseError> {
+        Ok(Triple {
+            subject: self.subject.resolve(base)?,
+            predicate: self.predicate,
+            object: self.object.resolve(base)?,
+        })
+    }
+
+    /// Whether the triple has any relative IRIs, including in quoted
+    /// triples.
+    pub fn is_relative(&self) -> bool {
+        let subject = match self.subject {
+            Subject::RelativeIri(_) => true,
+            Subject::Triple(ref quoted) => quoted.is_relative(),
+            Subject::Iri(_) | Subject::BlankNode(_) => false,
+        };
+
+        subject || match self.object {
+            Object::RelativeIri(_) => true,
+            Object::Triple(ref quoted) => quoted.is_relative(),
+            Object::Iri(_) | Object::BlankNode(_) | Object::Literal(_) => false,
+        }
+    }
+
+    /// Replaces every blank node in the triple, including those in quoted
+    /// triples, with what `map` returns for it.
+    pub(crate) fn map_blank_nodes<F: FnMut(&BlankNode) -> BlankNode>(&mut self, map: &mut F) {
+        match self.subject {
+            Subject::BlankNode(ref mut node) => *node = map(node),
+            Subject::Triple(ref mut quoted) => quoted.map_blank_nodes(map),
+            Subject::Iri(_) | Subject::RelativeIri(_) => {}
+        }
+
+        match self.object {
+            Object::BlankNode(ref mut node) => *node = map(node),
+            Object::Triple(ref mut quoted) => quoted.map_blank_nodes(map),
+            Object::Iri(_) | Object::Literal(_) | Object::RelativeIri(_) => {}
+        }
+    }
+
+    /// Writes the triple as a quoted triple term.
+    pub(crate) fn fmt_quoted(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<< {} {} {} >>", self.subject, self.predicate, self.object)
+    }
 }
 
 impl fmt::Display for Triple {